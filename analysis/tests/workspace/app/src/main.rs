@@ -0,0 +1,20 @@
+use workspace_lib::Counter;
+
+unsafe fn incr(p: *mut i32) {
+    *p += 1;
+}
+
+unsafe fn get(c: *const Counter) -> i32 {
+    *(*c).count
+}
+
+fn main() {
+    let mut x = 0;
+    unsafe {
+        workspace_lib::set(&mut x, 1);
+        incr(&mut x);
+        let mut c = Counter { count: &mut x };
+        workspace_lib::bump(&mut c);
+        std::process::exit(get(&c) - 3);
+    }
+}
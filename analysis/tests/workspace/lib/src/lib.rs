@@ -0,0 +1,11 @@
+pub struct Counter {
+    pub count: *mut i32,
+}
+
+pub unsafe fn set(p: *mut i32, x: i32) {
+    *p = x;
+}
+
+pub unsafe fn bump(c: *mut Counter) {
+    *(*c).count += 1;
+}
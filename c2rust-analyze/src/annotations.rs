@@ -0,0 +1,447 @@
+//! User annotations that control the analysis of particular items.
+//!
+//! Functions, ADTs, fields, statics, and individual function parameters can be marked as fixed,
+//! which keeps all pointers in their types as they are.  Fixed functions are also left out of
+//! rewriting entirely.  Pointers in function signatures, fields, and statics can also be given
+//! explicit permissions.  Both are done with tool attributes in the source:
+//!
+//! ```ignore
+//! #![feature(register_tool)]
+//! #![register_tool(c2rust_analyze)]
+//!
+//! #[c2rust_analyze::fixed]
+//! pub unsafe extern "C" fn callback(p: *mut u8) {}
+//!
+//! #[c2rust_analyze::perms(dest = "READ | WRITE | OFFSET_ADD", r#return = "READ")]
+//! pub unsafe fn fill(dest: *mut u8, #[c2rust_analyze::fixed] src: *const u8) -> *const u8 {}
+//!
+//! pub struct Buffer {
+//!     #[c2rust_analyze::perms("READ | WRITE | OFFSET_ADD")]
+//!     data: *mut u8,
+//! }
+//! ```
+//!
+//! or with entries in the TOML file named by the `C2RUST_ANALYZE_CONFIG` environment variable,
+//! which refer to items by their paths within the crate, optionally prefixed by the crate name:
+//!
+//! ```toml
+//! fixed = ["callback", "fill::src"]
+//!
+//! [perms]
+//! "fill::dest" = "READ | WRITE | OFFSET_ADD"
+//! "fill::return" = "READ"
+//! "Buffer::data" = "READ | WRITE | OFFSET_ADD"
+//! ```
+//!
+//! Annotated permissions replace the initial permissions of the outermost pointer in the type.
+//! This can grant permissions the analysis doesn't see being used, such as `WRITE` on a pointer
+//! that's only written by C code, or withhold `UNIQUE` or `NON_NULL`.  The analysis still adds
+//! any permissions the code requires, and still removes `UNIQUE` and `NON_NULL` where needed.
+
+use crate::cargo_wrapper;
+use crate::context::{GlobalAnalysisCtxt, LTy, PermissionSet};
+use rustc_ast::ast::{AttrKind, Attribute, LitKind, NestedMetaItem};
+use rustc_hir::def::DefKind;
+use rustc_hir::def_id::{DefId, LocalDefId, LOCAL_CRATE};
+use rustc_middle::ty::{Ty, TyCtxt};
+use rustc_span::{Span, Symbol};
+use serde::Deserialize;
+use std::collections::{HashMap, HashSet};
+use std::fs;
+
+/// The contents of the `C2RUST_ANALYZE_CONFIG` file.
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Config {
+    /// Paths of items to mark as fixed.
+    #[serde(default)]
+    fixed: Vec<String>,
+    /// Permissions for pointers, keyed by path.
+    #[serde(default)]
+    perms: HashMap<String, String>,
+}
+
+impl Config {
+    pub fn read(path: &str) -> Result<Config, String> {
+        let s = fs::read_to_string(path).map_err(|e| e.to_string())?;
+        toml::from_str(&s).map_err(|e| e.to_string())
+    }
+}
+
+/// Something an annotation can be attached to.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum Target {
+    /// A function, ADT, field, or static.
+    Def(DefId),
+    /// The parameter of a function at the given index.
+    Param(DefId, usize),
+    /// The return type of a function.
+    Return(DefId),
+}
+
+impl Target {
+    /// Get the type of this target, if it has one.  Functions and ADTs don't.
+    fn ty(self, tcx: TyCtxt) -> Option<Ty> {
+        match self {
+            Target::Def(did) => match tcx.def_kind(did) {
+                DefKind::Field | DefKind::Static(_) => Some(tcx.type_of(did)),
+                _ => None,
+            },
+            Target::Param(did, i) => Some(tcx.fn_sig(did).skip_binder().inputs()[i]),
+            Target::Return(did) => Some(tcx.fn_sig(did).skip_binder().output()),
+        }
+    }
+
+    fn describe(self, tcx: TyCtxt) -> String {
+        match self {
+            Target::Def(did) => format!("`{}`", tcx.def_path_str(did)),
+            Target::Param(did, i) => format!(
+                "parameter `{}` of `{}`",
+                tcx.fn_arg_names(did)[i],
+                tcx.def_path_str(did)
+            ),
+            Target::Return(did) => format!("return value of `{}`", tcx.def_path_str(did)),
+        }
+    }
+
+    /// Get the labeled type of this target, if it has one.  Functions and ADTs don't.
+    pub fn lty<'tcx>(self, gacx: &GlobalAnalysisCtxt<'tcx>) -> Option<LTy<'tcx>> {
+        match self {
+            Target::Def(did) => match gacx.tcx.def_kind(did) {
+                DefKind::Field => gacx.field_ltys.get(&did).copied(),
+                DefKind::Static(_) => gacx.static_tys.get(&did).copied(),
+                _ => None,
+            },
+            Target::Param(did, i) => Some(gacx.fn_sigs.get(&did)?.inputs[i]),
+            Target::Return(did) => Some(gacx.fn_sigs.get(&did)?.output),
+        }
+    }
+}
+
+/// All annotations in the crate being analyzed, from both attributes and the config file.
+#[derive(Clone, Debug, Default)]
+pub struct Annotations {
+    fixed: HashSet<Target>,
+    perms: HashMap<Target, PermissionSet>,
+}
+
+impl Annotations {
+    /// Collect annotations from the attributes in the crate and from `config`.  Invalid
+    /// annotations are reported as errors, and abort compilation.
+    pub fn collect(tcx: TyCtxt, config: &Config) -> Annotations {
+        let mut annots = Annotations::default();
+        for ldid in tcx.hir_crate_items(()).definitions() {
+            annots.collect_attrs(tcx, ldid);
+        }
+        annots.collect_config(tcx, config);
+        tcx.sess.abort_if_errors();
+        annots
+    }
+
+    /// Returns `true` if the whole function, ADT, field, or static `did` was marked as fixed.
+    pub fn is_fixed(&self, did: DefId) -> bool {
+        self.fixed.contains(&Target::Def(did))
+    }
+
+    pub fn fixed(&self) -> impl Iterator<Item = Target> + '_ {
+        self.fixed.iter().copied()
+    }
+
+    pub fn perms(&self) -> impl Iterator<Item = (Target, PermissionSet)> + '_ {
+        self.perms.iter().map(|(&target, &perms)| (target, perms))
+    }
+
+    fn collect_attrs(&mut self, tcx: TyCtxt, ldid: LocalDefId) {
+        let did = ldid.to_def_id();
+        let def_kind = tcx.def_kind(did);
+        // Fields are handled along with their ADT below.
+        if def_kind == DefKind::Field {
+            return;
+        }
+        for attr in tcx.get_attrs_unchecked(did) {
+            self.add_attr(tcx, Target::Def(did), attr);
+        }
+
+        match def_kind {
+            DefKind::Struct | DefKind::Enum | DefKind::Union => {
+                for field in tcx.adt_def(did).all_fields() {
+                    for attr in tcx.get_attrs_unchecked(field.did) {
+                        self.add_attr(tcx, Target::Def(field.did), attr);
+                    }
+                }
+            }
+
+            // Attributes on individual parameters are only accessible through the HIR body.
+            DefKind::Fn | DefKind::AssocFn => {
+                let body_id = match tcx.hir().maybe_body_owned_by(ldid) {
+                    Some(x) => x,
+                    None => return,
+                };
+                for (i, param) in tcx.hir().body(body_id).params.iter().enumerate() {
+                    for attr in tcx.hir().attrs(param.hir_id) {
+                        self.add_attr(tcx, Target::Param(did, i), attr);
+                    }
+                }
+            }
+
+            _ => {}
+        }
+    }
+
+    fn add_attr(&mut self, tcx: TyCtxt, target: Target, attr: &Attribute) {
+        let name = match tool_attr_name(attr) {
+            Some(x) => x,
+            None => return,
+        };
+        match name.as_str() {
+            "fixed" => {
+                if !attr.is_word() {
+                    tcx.sess
+                        .span_err(attr.span, "`c2rust_analyze::fixed` takes no arguments");
+                    return;
+                }
+                self.add_fixed(tcx, target, Some(attr.span));
+            }
+            "perms" => {
+                let items = match attr.meta_item_list() {
+                    Some(x) => x,
+                    None => {
+                        tcx.sess.span_err(
+                            attr.span,
+                            "expected `c2rust_analyze::perms(\"...\")` or \
+                            `c2rust_analyze::perms(name = \"...\", ...)`",
+                        );
+                        return;
+                    }
+                };
+                for item in &items {
+                    self.add_perms_item(tcx, target, item);
+                }
+            }
+            _ => {
+                tcx.sess.span_err(
+                    attr.span,
+                    &format!("unknown attribute `c2rust_analyze::{name}`"),
+                );
+            }
+        }
+    }
+
+    /// Handle one item in the list of a `perms` attribute.  This is either a string, which gives
+    /// the permissions of the annotated field, static, or parameter, or `name = "..."`, which
+    /// gives the permissions of a parameter or the return value (named `r#return`) of the
+    /// annotated function.
+    fn add_perms_item(&mut self, tcx: TyCtxt, target: Target, item: &NestedMetaItem) {
+        let span = item.span();
+        let (target, perms_sym) = match *item {
+            NestedMetaItem::Literal(ref lit) => match lit.kind {
+                LitKind::Str(sym, _) => (target, sym),
+                _ => {
+                    tcx.sess.span_err(span, "expected a string");
+                    return;
+                }
+            },
+            NestedMetaItem::MetaItem(ref meta) => {
+                let did = match target {
+                    Target::Def(did)
+                        if matches!(tcx.def_kind(did), DefKind::Fn | DefKind::AssocFn) =>
+                    {
+                        did
+                    }
+                    _ => {
+                        tcx.sess.span_err(
+                            span,
+                            "named permissions are only allowed on functions; \
+                            use `c2rust_analyze::perms(\"...\")` here",
+                        );
+                        return;
+                    }
+                };
+                let name = meta.name_or_empty();
+                let target = match fn_param_target(tcx, did, name) {
+                    Some(x) => x,
+                    None => {
+                        tcx.sess
+                            .span_err(span, &format!("function has no parameter named `{name}`"));
+                        return;
+                    }
+                };
+                match meta.value_str() {
+                    Some(sym) => (target, sym),
+                    None => {
+                        tcx.sess.span_err(span, "expected `name = \"...\"`");
+                        return;
+                    }
+                }
+            }
+        };
+        match perms_sym.as_str().parse() {
+            Ok(perms) => self.add_perms(tcx, target, perms, Some(span)),
+            Err(e) => {
+                tcx.sess.span_err(span, &e);
+            }
+        }
+    }
+
+    fn collect_config(&mut self, tcx: TyCtxt, config: &Config) {
+        if config.fixed.is_empty() && config.perms.is_empty() {
+            return;
+        }
+
+        // Map the path of every item that can be annotated to its `Target`.
+        let mut paths = HashMap::new();
+        for ldid in tcx.hir_crate_items(()).definitions() {
+            let did = ldid.to_def_id();
+            match tcx.def_kind(did) {
+                DefKind::Fn | DefKind::AssocFn => {
+                    let path = tcx.def_path_str(did);
+                    for (i, ident) in tcx.fn_arg_names(did).iter().enumerate() {
+                        paths.insert(format!("{path}::{ident}"), Target::Param(did, i));
+                    }
+                    paths.insert(format!("{path}::return"), Target::Return(did));
+                    paths.insert(path, Target::Def(did));
+                }
+                DefKind::Struct | DefKind::Enum | DefKind::Union => {
+                    for field in tcx.adt_def(did).all_fields() {
+                        paths.insert(tcx.def_path_str(field.did), Target::Def(field.did));
+                    }
+                    paths.insert(tcx.def_path_str(did), Target::Def(did));
+                }
+                DefKind::Static(_) => {
+                    paths.insert(tcx.def_path_str(did), Target::Def(did));
+                }
+                _ => {}
+            }
+        }
+
+        // Paths may also start with the crate name, to tell apart items of different crates when
+        // analyzing a whole `cargo` workspace.  A path that starts with the name of another crate
+        // of the workspace belongs to that crate, so it's not an error if it isn't found here.
+        let crate_name = tcx.crate_name(LOCAL_CRATE).to_string();
+        let crate_prefix = format!("{crate_name}::");
+        let other_crates = cargo_wrapper::workspace_crate_names()
+            .into_iter()
+            .filter(|name| *name != crate_name)
+            .collect::<HashSet<_>>();
+        let lookup = |path: &str| {
+            let path = path.strip_prefix(&crate_prefix).unwrap_or(path);
+            let target = paths.get(path).copied();
+            let in_other_crate = path
+                .split_once("::")
+                .map_or(false, |(krate, _)| other_crates.contains(krate));
+            if target.is_none() && !in_other_crate {
+                tcx.sess.err(&format!(
+                    "C2RUST_ANALYZE_CONFIG: no item found at path `{path}`"
+                ));
+            }
+            target
+        };
+        for path in &config.fixed {
+            if let Some(target) = lookup(path) {
+                self.add_fixed(tcx, target, None);
+            }
+        }
+        for (path, perms) in &config.perms {
+            let target = match lookup(path) {
+                Some(x) => x,
+                None => continue,
+            };
+            match perms.parse() {
+                Ok(perms) => self.add_perms(tcx, target, perms, None),
+                Err(e) => {
+                    tcx.sess.err(&format!(
+                        "C2RUST_ANALYZE_CONFIG: bad permissions for `{path}`: {e}"
+                    ));
+                }
+            }
+        }
+    }
+
+    fn add_fixed(&mut self, tcx: TyCtxt, target: Target, span: Option<Span>) {
+        let ok = match target {
+            Target::Def(did) => matches!(
+                tcx.def_kind(did),
+                DefKind::Fn
+                    | DefKind::AssocFn
+                    | DefKind::Struct
+                    | DefKind::Enum
+                    | DefKind::Union
+                    | DefKind::Field
+                    | DefKind::Static(_)
+            ),
+            Target::Param(..) | Target::Return(_) => true,
+        };
+        if !ok {
+            error(
+                tcx,
+                span,
+                format!(
+                    "can't mark {} as fixed; only functions, parameters, ADTs, fields, and \
+                    statics can be fixed",
+                    target.describe(tcx)
+                ),
+            );
+            return;
+        }
+        self.fixed.insert(target);
+    }
+
+    fn add_perms(&mut self, tcx: TyCtxt, target: Target, perms: PermissionSet, span: Option<Span>) {
+        if !target.ty(tcx).map_or(false, |ty| ty.is_any_ptr()) {
+            error(
+                tcx,
+                span,
+                format!(
+                    "can't set permissions of {}, which is not a pointer",
+                    target.describe(tcx)
+                ),
+            );
+            return;
+        }
+        if let Some(old) = self.perms.insert(target, perms) {
+            if old != perms {
+                error(
+                    tcx,
+                    span,
+                    format!(
+                        "conflicting permissions for {}: {:?} and {:?}",
+                        target.describe(tcx),
+                        old,
+                        perms
+                    ),
+                );
+            }
+        }
+    }
+}
+
+/// If `attr` is `#[c2rust_analyze::name]` or `#[c2rust_analyze::name(...)]`, returns `name`.
+fn tool_attr_name(attr: &Attribute) -> Option<Symbol> {
+    let path = match attr.kind {
+        AttrKind::Normal(ref item, _) => &item.path,
+        AttrKind::DocComment(..) => return None,
+    };
+    match &path.segments[..] {
+        [a, b] if a.ident.name.as_str() == "c2rust_analyze" => Some(b.ident.name),
+        _ => None,
+    }
+}
+
+/// Find the parameter of function `did` named `name`, or its return value if `name` is `return`.
+fn fn_param_target(tcx: TyCtxt, did: DefId, name: Symbol) -> Option<Target> {
+    if name.as_str() == "return" {
+        return Some(Target::Return(did));
+    }
+    let i = tcx
+        .fn_arg_names(did)
+        .iter()
+        .position(|ident| ident.name == name)?;
+    Some(Target::Param(did, i))
+}
+
+fn error(tcx: TyCtxt, span: Option<Span>, msg: String) {
+    match span {
+        Some(span) => tcx.sess.span_err(span, &msg),
+        None => tcx.sess.err(&msg),
+    };
+}
@@ -54,9 +54,10 @@ impl<'tcx> TypeChecker<'tcx, '_> {
                 field_metadata.origin_args,
                 &mut |flty| match flty.kind() {
                     TyKind::Ref(..) | TyKind::RawPtr(..) => {
+                        // Pointers in `fn` ptr types have no origin args.
                         let origin_arg = {
-                            assert!(flty.label.len() == 1);
-                            Some(flty.label[0])
+                            assert!(flty.label.len() <= 1);
+                            flty.label.first().copied()
                         };
 
                         let origin = if matches!(origin_arg, Some(OriginArg::Actual(region)) if matches!(region.kind(), RegionKind::ReStatic)) {
@@ -413,6 +414,24 @@ impl<'tcx> TypeChecker<'tcx, '_> {
                     }
                     expect_ty
                 }
+                AggregateKind::Adt(adt_did, variant_idx, ..)
+                    if !self.acx.gacx.adt_metadata.table.contains_key(&adt_did) =>
+                {
+                    // An ADT from another crate, such as `Option`.  Its fields have no metadata,
+                    // so only fields whose types come from the type arguments (like the payload
+                    // of `Some`) carry any origins.
+                    let adt_def = tcx.adt_def(adt_did);
+                    for (field, op) in adt_def.variant(variant_idx).fields.iter().zip(ops) {
+                        let field_ty = tcx.type_of(field.did);
+                        let field_lty = self.ltcx.subst(
+                            self.ltcx.label(field_ty, &mut |_| Label::default()),
+                            expect_ty.args,
+                        );
+                        let op_lty = self.visit_operand(op);
+                        self.do_assign(field_lty, op_lty);
+                    }
+                    expect_ty
+                }
                 AggregateKind::Adt(adt_did, ..) => {
                     /*
                         Generic types are not yet supported because of situations such as the
@@ -559,6 +578,11 @@ impl<'tcx> TypeChecker<'tcx, '_> {
                         let rv_lty = self.visit_operand(&args[0]);
                         self.do_assign(pl_lty, rv_lty);
                     }
+                    Callee::PtrOffsetFrom { .. } => {
+                        for arg in args {
+                            let _ = self.visit_operand(arg);
+                        }
+                    }
                     Callee::SliceAsPtr { .. } => {
                         // TODO: handle this like a cast
                     }
@@ -601,6 +625,17 @@ impl<'tcx> TypeChecker<'tcx, '_> {
                             self.visit_operand(p)
                         });
                     }
+                    Callee::Null { .. } => {
+                        let _pl_lty = self.visit_place(destination);
+                    }
+                    Callee::UnwrapFnPtr { .. } => {
+                        // We handle this like an assignment from the `Option`'s payload.
+                        let pl_lty = self.visit_place(destination);
+                        let rv_lty = assert_matches!(&args[..], [opt, ..] => {
+                            self.visit_operand(opt)
+                        });
+                        self.do_assign(pl_lty, rv_lty.args[0]);
+                    }
                 }
             }
             // TODO(spernsteiner): handle other `TerminatorKind`s
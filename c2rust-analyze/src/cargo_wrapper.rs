@@ -0,0 +1,210 @@
+//! Running `c2rust-analyze` over a `cargo` workspace.
+//!
+//! `c2rust-analyze -- <cargo args>` (or `c2rust analyze -- <cargo args>`) runs `cargo` with
+//! `$RUSTC_WRAPPER` set to `c2rust-analyze` itself, the same way `c2rust-instrument` does.  Each
+//! `rustc` invocation for a primary package (a workspace member selected by the `cargo` command)
+//! then runs the analysis before compiling the crate as usual, while dependencies and build
+//! scripts are compiled normally.  Since `cargo` compiles crates in dependency order, every
+//! crate is analyzed with its dependencies already built, and with their summaries (see
+//! [`crate::summary`]) available in `analyze.target/summaries` unless `$C2RUST_ANALYZE_SUMMARIES`
+//! says otherwise.
+
+use rustc_driver::{RunCompiler, TimePassesCallbacks};
+use rustc_session::config::CrateType;
+use std::env;
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
+use std::process::{self, Command};
+
+const RUSTC_WRAPPER_VAR: &str = "RUSTC_WRAPPER";
+const RUST_SYSROOT_VAR: &str = "RUST_SYSROOT";
+const WORKSPACE_CRATES_VAR: &str = "C2RUST_ANALYZE_WORKSPACE_CRATES";
+
+/// Check if we were invoked by `cargo` as a `rustc` wrapper, rather than directly by the user.
+pub fn is_rustc_wrapper() -> bool {
+    let own_exe = env::current_exe().expect("failed to find the `c2rust-analyze` executable");
+    env::var_os(RUSTC_WRAPPER_VAR).as_deref() == Some(own_exe.as_os_str())
+}
+
+/// The crate names of all targets of all workspace members, as passed to the `rustc` wrappers by
+/// [`cargo_wrapper`].  This is empty when not running as a `rustc` wrapper.
+pub fn workspace_crate_names() -> Vec<String> {
+    match env::var(WORKSPACE_CRATES_VAR) {
+        Ok(names) => names.split(',').map(String::from).collect(),
+        Err(_) => Vec::new(),
+    }
+}
+
+/// Check if the current [`rustc_wrapper`] invocation is for a primary `cargo` package, as
+/// determined by `$CARGO_PRIMARY_PACKAGE`.
+fn is_primary_package() -> bool {
+    env::var("CARGO_PRIMARY_PACKAGE").is_ok()
+}
+
+/// Detect if the current [`rustc_wrapper`] invocation is compiling a build script.  Build scripts
+/// are compiled as `--crate-type bin`, but unlike normal binaries, `cargo` doesn't set
+/// `$CARGO_BIN_NAME` for them.
+fn is_build_script(rustc_args: &[String]) -> bool {
+    if env::var_os("CARGO_BIN_NAME").is_some() {
+        return false;
+    }
+    let args = rustc_driver::args::arg_expand_all(rustc_args);
+    let matches = match rustc_driver::handle_options(&args) {
+        Some(x) => x,
+        None => return false,
+    };
+    let session_options = rustc_session::config::build_session_options(&matches);
+    session_options.crate_types.contains(&CrateType::Executable)
+}
+
+/// Run as a `rustc` wrapper.  `rustc_args` starts with the path of the real `rustc`, followed by
+/// its arguments.
+pub fn rustc_wrapper(rustc_args: &[String]) -> rustc_interface::interface::Result<()> {
+    let mut rustc_args = rustc_args.to_owned();
+    let should_analyze = is_primary_package() && !is_build_script(&rustc_args);
+    let sysroot = env::var(RUST_SYSROOT_VAR).unwrap_or_else(|_| {
+        panic!("the `cargo` wrapper should've set `${RUST_SYSROOT_VAR}` for the `rustc` wrapper")
+    });
+    rustc_args.extend(["--sysroot".into(), sysroot]);
+    if should_analyze {
+        RunCompiler::new(&rustc_args, &mut crate::AnalysisCallbacks).run()
+    } else {
+        RunCompiler::new(&rustc_args, &mut TimePassesCallbacks::default()).run()
+    }
+}
+
+/// Set `$RUSTUP_TOOLCHAIN` to the toolchain channel specified in `rust-toolchain.toml`, so the
+/// crates are compiled by the same `rustc` that we link against.
+fn set_rust_toolchain() {
+    let toml = include_str!("../rust-toolchain.toml");
+    let doc = toml
+        .parse::<toml::Value>()
+        .expect("failed to parse rust-toolchain.toml");
+    if let Some(channel) = doc["toolchain"]
+        .get("channel")
+        .and_then(|channel| channel.as_str())
+    {
+        env::set_var("RUSTUP_TOOLCHAIN", channel);
+    }
+}
+
+/// Resolve the sysroot of the current toolchain using `rustc --print sysroot`.  As the `rustc`
+/// wrappers call `rustc_driver` directly instead of running the real `rustc`, they must pass the
+/// sysroot explicitly.
+fn resolve_sysroot() -> PathBuf {
+    let rustc = env::var_os("RUSTC").unwrap_or_else(|| "rustc".into());
+    let output = Command::new(rustc)
+        .args(["--print", "sysroot"])
+        .output()
+        .expect("could not invoke `rustc` to find rust sysroot");
+    let path = String::from_utf8(output.stdout).expect("`rustc --print sysroot` is not UTF-8");
+    let path = PathBuf::from(path.trim());
+    assert!(
+        path.is_dir(),
+        "invalid sysroot (not a dir): {}",
+        path.display()
+    );
+    path
+}
+
+/// Find the value of `--manifest-path` in the `cargo` args, if there is one.
+fn manifest_path(cargo_args: &[String]) -> Option<&Path> {
+    let mut args = cargo_args.iter().take_while(|&arg| arg != "--");
+    while let Some(arg) = args.next() {
+        if arg == "--manifest-path" {
+            return args.next().map(Path::new);
+        }
+        if let Some(path) = arg.strip_prefix("--manifest-path=") {
+            return Some(Path::new(path));
+        }
+    }
+    None
+}
+
+/// Find the crate names of all targets of the workspace members using `cargo metadata`.
+fn find_workspace_crate_names(cargo: &OsString, manifest_path: Option<&Path>) -> Vec<String> {
+    let mut cmd = Command::new(cargo);
+    cmd.args(["metadata", "--no-deps", "--format-version", "1"]);
+    if let Some(path) = manifest_path {
+        cmd.arg("--manifest-path").arg(path);
+    }
+    let output = cmd
+        .output()
+        .unwrap_or_else(|e| panic!("failed to run {:?}: {}", cmd, e));
+    if !output.status.success() {
+        panic!(
+            "{:?} failed with status {}:\n{}",
+            cmd,
+            output.status,
+            String::from_utf8_lossy(&output.stderr)
+        );
+    }
+    let metadata: serde_json::Value = serde_json::from_slice(&output.stdout)
+        .unwrap_or_else(|e| panic!("failed to parse the output of {:?}: {}", cmd, e));
+    let mut names = metadata["packages"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .flat_map(|package| package["targets"].as_array().into_iter().flatten())
+        .filter_map(|target| target["name"].as_str())
+        .map(|name| name.replace('-', "_"))
+        .collect::<Vec<_>>();
+    names.sort();
+    names.dedup();
+    names
+}
+
+/// Run `cargo` with `cargo_args`, using this executable as the `rustc` wrapper.  The build goes
+/// into a separate `analyze.target` directory next to the manifest, so it doesn't disturb the
+/// normal build.
+pub fn cargo_wrapper(cargo_args: &[String]) -> ! {
+    let own_exe = env::current_exe().expect("failed to find the `c2rust-analyze` executable");
+
+    set_rust_toolchain();
+    // Resolve the sysroot once here so that the `rustc` wrappers don't each have to.
+    let sysroot = resolve_sysroot();
+
+    let manifest_path = manifest_path(cargo_args);
+    let manifest_dir = manifest_path.and_then(|path| path.parent());
+    let target_dir = manifest_dir
+        .unwrap_or_else(|| Path::new("."))
+        .join("analyze.target");
+
+    let cargo = env::var_os("CARGO").unwrap_or_else(|| OsString::from("cargo"));
+    // The annotation config may name items of any workspace member, so the `rustc` wrappers need
+    // to know which path prefixes refer to other crates.
+    let workspace_crates = find_workspace_crate_names(&cargo, manifest_path).join(",");
+    let mut cmd = Command::new(cargo);
+    cmd.args(cargo_args)
+        .env(RUSTC_WRAPPER_VAR, &own_exe)
+        .env(RUST_SYSROOT_VAR, &sysroot)
+        .env(WORKSPACE_CRATES_VAR, workspace_crates)
+        .env("CARGO_TARGET_DIR", &target_dir);
+    // `cargo` runs the `rustc` wrappers in the workspace root, so make any paths that are
+    // relative to the current directory absolute.
+    let cwd = env::current_dir().expect("failed to get the current directory");
+    for var in [
+        "C2RUST_ANALYZE_CONFIG",
+        "C2RUST_ANALYZE_RESULTS_JSON",
+        "C2RUST_ANALYZE_SUMMARIES",
+    ] {
+        if let Some(path) = env::var_os(var) {
+            if path != "-" && Path::new(&path).is_relative() {
+                cmd.env(var, cwd.join(path));
+            }
+        }
+    }
+    if env::var_os("C2RUST_ANALYZE_SUMMARIES").is_none() {
+        cmd.env(
+            "C2RUST_ANALYZE_SUMMARIES",
+            cwd.join(&target_dir).join("summaries"),
+        );
+    }
+    let status = cmd
+        .status()
+        .unwrap_or_else(|e| panic!("failed to run {:?}: {}", cmd, e));
+    if !status.success() {
+        eprintln!("error ({status}) running: {cmd:?}");
+    }
+    process::exit(status.code().unwrap_or(1))
+}
@@ -1,7 +1,7 @@
 use crate::borrowck::{AdtMetadata, FieldMetadata, OriginArg, OriginParam};
 use crate::c_void_casts::CVoidCasts;
 use crate::known_fn::{all_known_fns, KnownFn};
-use crate::labeled_ty::{LabeledTy, LabeledTyCtxt};
+use crate::labeled_ty::{LabeledTy, LabeledTyCtxt, LabeledTyS};
 use crate::panic_detail::PanicDetail;
 use crate::pointer_id::{
     GlobalPointerTable, LocalPointerTable, NextGlobalPointerId, NextLocalPointerId, PointerTable,
@@ -37,6 +37,7 @@ use rustc_type_ir::RegionKind::{ReEarlyBound, ReStatic};
 use std::collections::{HashMap, HashSet};
 use std::fmt::Debug;
 use std::ops::Index;
+use std::str::FromStr;
 
 bitflags! {
     /// Permissions are created such that we allow dropping permissions in any assignment.
@@ -115,7 +116,7 @@ bitflags! {
         /// Casts/transitions from non-[`NON_NULL`] to [`NON_NULL`] will become [`_.unwrap()`].
         ///
         /// [`_.is_null()`] on a [`NON_NULL`] pointer will become [`false`].\
-        /// [`_.is_null()`] on a non-[`NON_NULL`] pointer will become [`_.is_some()`].
+        /// [`_.is_null()`] on a non-[`NON_NULL`] pointer will become [`_.is_none()`].
         ///
         /// Constant null pointers, like those produced by:
         /// * [`core::ptr::null`]
@@ -133,8 +134,16 @@ bitflags! {
         /// [`Some(_)`]: Some
         /// [`_.unwrap()`]: Option::unwrap
         /// [`_.is_null()`]: core::ptr::is_null
-        /// [`_.is_some()`]: Option::is_some
+        /// [`_.is_none()`]: Option::is_none
         const NON_NULL = 0x0080;
+
+        /// The values accessible through this pointer form a NUL-terminated C string, which is
+        /// read up to and including the NUL.  This is set for pointers passed to libc string
+        /// functions like `strlen`, `%s` arguments of `printf`, and pointers that are compared
+        /// against `'\0'` after being dereferenced.
+        ///
+        /// A `NUL_TERMINATED` pointer that is never offset or written through becomes `&CStr`.
+        const NUL_TERMINATED = 0x0100;
     }
 }
 
@@ -154,7 +163,50 @@ impl PermissionSet {
     /// The permissions for a (byte-)string literal.
     //
     // `.union` is used here since it's a `const fn`, unlike `BitOr::bitor`.
-    pub const STRING_LITERAL: Self = Self::union_all([Self::READ, Self::OFFSET_ADD]);
+    pub const STRING_LITERAL: Self =
+        Self::union_all([Self::READ, Self::OFFSET_ADD, Self::NON_NULL]);
+
+    /// The name of each individual permission, as accepted by `from_str`.
+    pub const NAMES: [(&'static str, Self); 9] = [
+        ("READ", Self::READ),
+        ("WRITE", Self::WRITE),
+        ("UNIQUE", Self::UNIQUE),
+        ("LINEAR", Self::LINEAR),
+        ("OFFSET_ADD", Self::OFFSET_ADD),
+        ("OFFSET_SUB", Self::OFFSET_SUB),
+        ("FREE", Self::FREE),
+        ("NON_NULL", Self::NON_NULL),
+        ("NUL_TERMINATED", Self::NUL_TERMINATED),
+    ];
+
+    /// The names of the permissions in this set.
+    pub fn names(self) -> impl Iterator<Item = &'static str> {
+        Self::NAMES
+            .into_iter()
+            .filter(move |&(_, perm)| self.contains(perm))
+            .map(|(name, _)| name)
+    }
+}
+
+impl FromStr for PermissionSet {
+    type Err = String;
+
+    /// Parse a `|`-separated list of permission names, such as `READ | WRITE`.  The empty string
+    /// parses as the empty set.
+    fn from_str(s: &str) -> Result<Self, String> {
+        if s.trim().is_empty() {
+            return Ok(Self::empty());
+        }
+        s.split('|').try_fold(Self::empty(), |perms, name| {
+            let name = name.trim();
+            let perm = Self::NAMES
+                .iter()
+                .find(|&&(n, _)| n == name)
+                .map(|&(_, perm)| perm)
+                .ok_or_else(|| format!("unknown permission `{name}`"))?;
+            Ok(perms | perm)
+        })
+    }
 }
 
 bitflags! {
@@ -173,6 +225,49 @@ bitflags! {
         /// cross an FFI boundary, and for arguments and return values of functions we can't
         /// rewrite.
         const FIXED = 0x0002;
+
+        /// This pointer always points to the start of a heap allocation that can be rewritten to
+        /// `Box`, so it can take ownership of the allocation.  This is computed from the flow of
+        /// pointer values, after permissions are final; see `dataflow::heap_pointers`.
+        const HEAP = 0x0004;
+    }
+}
+
+impl FlagSet {
+    /// The name of each individual flag, as accepted by `from_str`.
+    pub const NAMES: [(&'static str, Self); 3] = [
+        ("CELL", Self::CELL),
+        ("FIXED", Self::FIXED),
+        ("HEAP", Self::HEAP),
+    ];
+
+    /// The names of the flags in this set.
+    pub fn names(self) -> impl Iterator<Item = &'static str> {
+        Self::NAMES
+            .into_iter()
+            .filter(move |&(_, flag)| self.contains(flag))
+            .map(|(name, _)| name)
+    }
+}
+
+impl FromStr for FlagSet {
+    type Err = String;
+
+    /// Parse a `|`-separated list of flag names, such as `CELL | FIXED`.  The empty string parses
+    /// as the empty set.
+    fn from_str(s: &str) -> Result<Self, String> {
+        if s.trim().is_empty() {
+            return Ok(Self::empty());
+        }
+        s.split('|').try_fold(Self::empty(), |flags, name| {
+            let name = name.trim();
+            let flag = Self::NAMES
+                .iter()
+                .find(|&&(n, _)| n == name)
+                .map(|&(_, flag)| flag)
+                .ok_or_else(|| format!("unknown flag `{name}`"))?;
+            Ok(flags | flag)
+        })
     }
 }
 
@@ -229,9 +324,11 @@ impl<'tcx> Debug for AdtMetadataTable<'tcx> {
             let args: Vec<String> = lty.args.iter().map(|t| fmt_string(t)).collect();
             use rustc_type_ir::TyKind::*;
             match lty.kind() {
-                Ref(..) | RawPtr(..) => {
-                    format!("&{:?} {:}", lty.label[0], args[0])
-                }
+                Ref(..) | RawPtr(..) => match lty.label.first() {
+                    Some(origin) => format!("&{:?} {:}", origin, args[0]),
+                    // Pointers in `fn` ptr types have no origin.
+                    None => format!("&{:}", args[0]),
+                },
                 Adt(adt, _) => {
                     let mut s = format!("{adt:?}");
                     let params = lty
@@ -327,6 +424,10 @@ pub struct GlobalAnalysisCtxt<'tcx> {
 
     pub adt_metadata: AdtMetadataTable<'tcx>,
 
+    /// Region metadata for ADTs defined in other crates, as loaded from their summaries.  These
+    /// entries are copied into every `adt_metadata` table we construct.
+    pub extern_adt_metadata: HashMap<DefId, AdtMetadata<'tcx>>,
+
     pub fn_origins: FnOriginMap<'tcx>,
 
     pub foreign_mentioned_tys: HashSet<DefId>,
@@ -373,6 +474,9 @@ impl<'a, 'tcx> AnalysisCtxt<'_, 'tcx> {
             // and then removed later if it can't apply.
             // We don't care about `UNIQUE` for const refs, so just unset it here.
             actual_perms.set(PermissionSet::UNIQUE, false);
+            // `NUL_TERMINATED` flows back from uses of the literal as a C string.  Whether the
+            // literal actually ends in a NUL is up to the source program.
+            actual_perms.set(PermissionSet::NUL_TERMINATED, false);
             assert_eq!(expected_perms, actual_perms);
         }
     }
@@ -429,10 +533,23 @@ fn fn_origin_args_params<'tcx>(
         let sig = tcx.erase_late_bound_regions(tcx.fn_sig(fn_did));
         let ltcx = LabeledTyCtxt::<'tcx, &[OriginArg<'tcx>]>::new(tcx);
         let mut next_hypo_origin_id = 0;
+        // Pointers inside `fn` ptr types get no origins (see `fn_ptr_arg_ptrs`).  `label` visits
+        // the nodes in preorder, so these are among the nodes right after each `fn` ptr.
+        let mut fn_ptr_nodes_left = 0;
         let mut origin_lty = |ty: Ty<'tcx>| {
             ltcx.label(ty, &mut |ty| {
+                let in_fn_ptr = fn_ptr_nodes_left > 0;
+                if in_fn_ptr {
+                    fn_ptr_nodes_left -= 1;
+                    if matches!(ty.kind(), TyKind::RawPtr(_) | TyKind::Ref(..)) {
+                        return &[];
+                    }
+                }
                 let mut origin_args = vec![];
                 match ty.kind() {
+                    TyKind::FnPtr(_) if !in_fn_ptr => {
+                        fn_ptr_nodes_left = ltcx.label(ty, &mut |_| &[]).iter().count() - 1;
+                    }
                     TyKind::RawPtr(_ty) => {
                         origin_args.push(OriginArg::Hypothetical(next_hypo_origin_id));
                         origin_params.push(OriginParam::Hypothetical(next_hypo_origin_id));
@@ -491,11 +608,26 @@ fn fn_origin_args_params<'tcx>(
     FnOriginMap { fn_info }
 }
 
+/// Collect the pointers of `lty` that are nested inside the argument or return types of a `fn` ptr
+/// type.  These get no origins: references in `fn` ptr types are left elided, which makes the `fn`
+/// ptr type higher-ranked over their lifetimes, just like the `fn`s it can point to.  ADTs in `fn`
+/// ptr types still get their usual origin args.
+fn fn_ptr_arg_ptrs<L>(lty: LabeledTy<'_, L>) -> HashSet<*const LabeledTyS<'_, L>> {
+    lty.iter()
+        .filter(|lty| matches!(lty.kind(), TyKind::FnPtr(_)))
+        .flat_map(|lty| lty.args.iter().flat_map(|arg| arg.iter()))
+        .filter(|lty| matches!(lty.kind(), TyKind::RawPtr(_) | TyKind::Ref(..)))
+        .map(|lty| lty as *const _)
+        .collect()
+}
+
 /// Build the `AdtMetadataTable`, which records the region parameters to be added to each ADT.  A
 /// region parameter will be created for each raw pointer where `needs_region(lty)` returns `true`.
+/// The metadata of ADTs from other crates is taken from `extern_adt_metadata` as is.
 fn construct_adt_metadata<'tcx>(
     tcx: TyCtxt<'tcx>,
     field_ltys: &HashMap<DefId, LTy<'tcx>>,
+    extern_adt_metadata: &HashMap<DefId, AdtMetadata<'tcx>>,
     mut needs_region: impl FnMut(LTy<'tcx>) -> bool,
 ) -> AdtMetadataTable<'tcx> {
     let struct_dids: Vec<_> = tcx
@@ -513,10 +645,22 @@ fn construct_adt_metadata<'tcx>(
         .collect();
 
     let mut adt_metadata_table = AdtMetadataTable {
-        table: HashMap::new(),
+        table: extern_adt_metadata.clone(),
         struct_dids,
     };
 
+    // Hypothetical origins of extern ADTs are bubbled up into local ADTs that contain them, so
+    // local hypothetical origins are numbered after the extern ones to keep them distinct.
+    let first_hypo_origin_id = extern_adt_metadata
+        .values()
+        .flat_map(|adt| adt.lifetime_params.iter())
+        .filter_map(|param| match *param {
+            OriginParam::Hypothetical(h) => Some(h + 1),
+            OriginParam::Actual(_) => None,
+        })
+        .max()
+        .unwrap_or(0);
+
     // Gather existing lifetime parameters for each struct
     for struct_did in &adt_metadata_table.struct_dids {
         let struct_ty = tcx.type_of(struct_did);
@@ -562,7 +706,7 @@ fn construct_adt_metadata<'tcx>(
 
         eprintln!("---- running fixed point struct field analysis iteration #{loop_count:?} ----");
         let old_adt_metadata = adt_metadata_table.table.clone();
-        let mut next_hypo_origin_id = 0;
+        let mut next_hypo_origin_id = first_hypo_origin_id;
 
         // for each struct, gather lifetime information (actual and hypothetical)
         for struct_did in &adt_metadata_table.struct_dids {
@@ -573,7 +717,11 @@ fn construct_adt_metadata<'tcx>(
                     .get(&field.did)
                     .unwrap_or_else(|| panic!("missing field_ltys entry for {:?}", field.did));
                 eprintln!("\t{adt_def:?}.{:}", field.name);
+                let fn_ptr_arg_ptrs = fn_ptr_arg_ptrs(field_lty);
                 let field_origin_args = ltcx.relabel(field_lty, &mut |lty| {
+                    if fn_ptr_arg_ptrs.contains(&(lty as *const _)) {
+                        return &[];
+                    }
                     let mut field_origin_args = IndexSet::new();
                     match lty.kind() {
                         TyKind::RawPtr(ty) => {
@@ -694,6 +842,7 @@ impl<'tcx> GlobalAnalysisCtxt<'tcx> {
             static_tys: HashMap::new(),
             addr_of_static: HashMap::new(),
             adt_metadata: AdtMetadataTable::default(),
+            extern_adt_metadata: HashMap::new(),
             fn_origins: FnOriginMap::default(),
             foreign_mentioned_tys: HashSet::new(),
         }
@@ -708,7 +857,12 @@ impl<'tcx> GlobalAnalysisCtxt<'tcx> {
     }
 
     pub fn construct_region_metadata_filtered(&mut self, filter: impl FnMut(LTy<'tcx>) -> bool) {
-        self.adt_metadata = construct_adt_metadata(self.tcx, &self.field_ltys, filter);
+        self.adt_metadata = construct_adt_metadata(
+            self.tcx,
+            &self.field_ltys,
+            &self.extern_adt_metadata,
+            filter,
+        );
         self.fn_origins = fn_origin_args_params(self.tcx, &self.adt_metadata);
     }
 
@@ -755,6 +909,7 @@ impl<'tcx> GlobalAnalysisCtxt<'tcx> {
             ref mut static_tys,
             ref mut addr_of_static,
             adt_metadata: _,
+            extern_adt_metadata: _,
             fn_origins: _,
             foreign_mentioned_tys: _,
         } = *self;
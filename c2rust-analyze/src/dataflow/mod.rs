@@ -1,3 +1,4 @@
+use std::collections::{HashMap, HashSet};
 use std::mem;
 
 use crate::context::{AnalysisCtxt, Assignment, FlagSet, PermissionSet, PointerId};
@@ -22,6 +23,9 @@ enum Constraint {
 #[derive(Clone, Debug, Default)]
 pub struct DataflowConstraints {
     constraints: Vec<Constraint>,
+    /// Pointers that receive the result of an allocation that can be rewritten to `Box`.  These
+    /// are the starting points for [`PointerFlow::heap_pointers`].
+    alloc_ptrs: Vec<PointerId>,
 }
 
 impl DataflowConstraints {
@@ -38,11 +42,14 @@ impl DataflowConstraints {
         self.constraints.push(Constraint::AllPerms(ptr, perms));
     }
 
-    #[allow(dead_code)]
-    fn _add_no_perms(&mut self, ptr: PointerId, perms: PermissionSet) {
+    fn add_no_perms(&mut self, ptr: PointerId, perms: PermissionSet) {
         self.constraints.push(Constraint::NoPerms(ptr, perms));
     }
 
+    fn add_alloc(&mut self, ptr: PointerId) {
+        self.alloc_ptrs.push(ptr);
+    }
+
     /// Update the pointer permissions in `hypothesis` to satisfy these constraints.
     pub fn propagate(&self, hypothesis: &mut PointerTableMut<PermissionSet>) -> bool {
         eprintln!("=== propagating ===");
@@ -84,7 +91,7 @@ impl DataflowConstraints {
                 // Permissions that should be propagated "down": if the superset (`b`)
                 // doesn't have it, then the subset (`a`) should have it removed.
                 #[allow(bad_style)]
-                let PROPAGATE_DOWN = PermissionSet::UNIQUE;
+                let PROPAGATE_DOWN = PermissionSet::UNIQUE | PermissionSet::NON_NULL;
                 // Permissions that should be propagated "up": if the subset (`a`) has it,
                 // then the superset (`b`) should be given it.
                 #[allow(bad_style)]
@@ -92,12 +99,21 @@ impl DataflowConstraints {
                     | PermissionSet::WRITE
                     | PermissionSet::OFFSET_ADD
                     | PermissionSet::OFFSET_SUB
-                    | PermissionSet::FREE;
+                    | PermissionSet::FREE
+                    | PermissionSet::NUL_TERMINATED;
 
-                (
-                    old_a & !(!old_b & (PROPAGATE_DOWN & !except)),
-                    old_b | (old_a & (PROPAGATE_UP & !except)),
-                )
+                // Permissions that should be propagated "down" to owning pointers: if the subset
+                // (`a`) is freed, it takes ownership of the whole allocation, so it needs the same
+                // `OFFSET` permissions as the superset (`b`).  Otherwise we would need to convert
+                // a `Box<[T]>` into a `Box<T>`.
+                #[allow(bad_style)]
+                let PROPAGATE_DOWN_OWNED = PermissionSet::OFFSET_ADD | PermissionSet::OFFSET_SUB;
+
+                let mut new_a = old_a & !(!old_b & (PROPAGATE_DOWN & !except));
+                if old_a.contains(PermissionSet::FREE) {
+                    new_a |= old_b & (PROPAGATE_DOWN_OWNED & !except);
+                }
+                (new_a, old_b | (old_a & (PROPAGATE_UP & !except)))
             }
 
             fn all_perms(
@@ -305,6 +321,110 @@ impl DataflowConstraints {
         for c in &mut self.constraints {
             c.remap_pointers(map.borrow());
         }
+        for ptr in &mut self.alloc_ptrs {
+            *ptr = map[*ptr];
+        }
+    }
+}
+
+/// A pointer in [`PointerFlow`].  Local `PointerId`s are only unique within a function, so
+/// they're paired with the index of their function; global ones are paired with `None`.
+pub type FlowNode = (Option<usize>, PointerId);
+
+/// The flow of pointer values between the pointers of several functions, as given by their
+/// `Subset` constraints.  Values flow from the superset to the subset of each constraint.
+pub struct PointerFlow {
+    sources: HashMap<FlowNode, Vec<FlowNode>>,
+    sinks: HashMap<FlowNode, Vec<FlowNode>>,
+    allocs: Vec<FlowNode>,
+}
+
+impl PointerFlow {
+    /// Build the flow graph for `fns`.  Local pointers of `fns[i]` are identified by `Some(i)`.
+    pub fn new(fns: &[&DataflowConstraints]) -> PointerFlow {
+        let node = |i: usize, ptr: PointerId| (if ptr.is_global() { None } else { Some(i) }, ptr);
+
+        let mut flow = PointerFlow {
+            sources: HashMap::new(),
+            sinks: HashMap::new(),
+            allocs: Vec::new(),
+        };
+        for (i, dataflow) in fns.iter().enumerate() {
+            for c in &dataflow.constraints {
+                match *c {
+                    Constraint::Subset(a, b) | Constraint::SubsetExcept(a, b, _) => {
+                        if a.is_none() || b.is_none() {
+                            continue;
+                        }
+                        flow.sources.entry(node(i, a)).or_default().push(node(i, b));
+                        flow.sinks.entry(node(i, b)).or_default().push(node(i, a));
+                    }
+                    Constraint::AllPerms(..) | Constraint::NoPerms(..) => {}
+                }
+            }
+            flow.allocs
+                .extend(dataflow.alloc_ptrs.iter().map(|&ptr| node(i, ptr)));
+        }
+        flow
+    }
+
+    /// Find the pointers that always point to an allocation recorded with `add_alloc`, and so can
+    /// own it as a `Box`.  `global_roots` lists global pointers that are known to own their
+    /// allocations, such as those summarized by other crates.
+    ///
+    /// A pointer owns its allocation only if every value that can flow into it does.  A pointer
+    /// with no incoming values at all, such as a parameter of a function with no callers in this
+    /// crate, may come from anywhere, so it doesn't own its allocation unless it's allocated
+    /// itself.
+    pub fn heap_pointers(
+        &self,
+        global_roots: impl IntoIterator<Item = PointerId>,
+    ) -> HashSet<FlowNode> {
+        // Start from every pointer that an allocation can flow into, and remove the ones with a
+        // source that doesn't own its allocation until nothing changes.  Pointers that only get
+        // their values from each other, as for `p = p.offset(1)`, are kept only if an allocation
+        // flows into them as well.
+        let roots = global_roots
+            .into_iter()
+            .map(|ptr| (None, ptr))
+            .chain(self.allocs.iter().copied());
+        let mut heap = self.reachable(roots, |_| true);
+        loop {
+            let removed = heap
+                .iter()
+                .filter(|&n| {
+                    self.sources
+                        .get(n)
+                        .map_or(false, |srcs| srcs.iter().any(|src| !heap.contains(src)))
+                })
+                .copied()
+                .collect::<Vec<_>>();
+            if removed.is_empty() {
+                break;
+            }
+            for n in removed {
+                heap.remove(&n);
+            }
+        }
+        heap
+    }
+
+    /// Find the pointers that values of `roots` can flow into, following only the pointers for
+    /// which `filter` returns `true`.  The `roots` themselves are always included.
+    pub fn reachable(
+        &self,
+        roots: impl IntoIterator<Item = FlowNode>,
+        filter: impl Fn(FlowNode) -> bool,
+    ) -> HashSet<FlowNode> {
+        let mut seen = HashSet::new();
+        let mut queue = roots.into_iter().collect::<Vec<_>>();
+        while let Some(n) = queue.pop() {
+            if seen.insert(n) {
+                let sinks = self.sinks.get(&n).into_iter().flatten().copied();
+                queue.extend(sinks.filter(|&sink| filter(sink)));
+            }
+        }
+        seen
     }
 }
 
@@ -2,9 +2,11 @@ use super::DataflowConstraints;
 use crate::c_void_casts::{is_c_void_ptr, CVoidCastDirection};
 use crate::context::{AnalysisCtxt, LTy, PermissionSet, PointerId};
 use crate::panic_detail;
+use crate::type_desc::is_c_char;
 use crate::util::{
-    describe_rvalue, is_null_const, is_transmutable_ptr_cast, ty_callee, Callee, RvalueDesc,
-    UnknownDefCallee,
+    c_char_load_pointer, describe_rvalue, implements_default, is_nonnegative_offset, is_null_const,
+    is_transmutable_ptr_cast, is_zero_operand, printf_format_arg, printf_string_args,
+    string_literal_bytes, ty_callee, Callee, RvalueDesc, UnknownDefCallee,
 };
 use assert_matches::assert_matches;
 use rustc_hir::def_id::DefId;
@@ -118,6 +120,9 @@ impl<'tcx> TypeChecker<'tcx, '_> {
                 if !op.constant().copied().map(is_null_const).unwrap_or(false) {
                     panic!("Creating non-null pointers from exposed addresses not supported");
                 }
+                // The result is a null pointer.
+                self.constraints
+                    .add_no_perms(to_lty.label, PermissionSet::NON_NULL);
             }
             CastKind::PointerExposeAddress => {
                 // Allow, as [`CastKind::PointerFromExposedAddress`] is the dangerous one,
@@ -131,12 +136,18 @@ impl<'tcx> TypeChecker<'tcx, '_> {
                 // ([PointerCast::MutToConstPointer`] doesn't allow changing types),
                 // which we need to check for safe transmutability,
                 // and which are (currently) covered in [`CastKind::Misc`].
-                // That's why there's a `match` here that does nothing;
+                // That's why there's a `match` here that does almost nothing;
                 // it ensures if [`PointerCast`] is changed in a future `rustc` version,
                 // this won't compile until we've checked that this reasoning is still accurate.
+                // The exception is `fn` ptrs, whose argument and return types must agree with
+                // those of every `fn` they may point to.
                 match ptr_cast {
-                    PointerCast::ReifyFnPointer => {}
-                    PointerCast::UnsafeFnPointer => {}
+                    PointerCast::ReifyFnPointer => self.do_unify_fn_ptr(to_lty, from_ty),
+                    PointerCast::UnsafeFnPointer => {
+                        for (&to_arg_lty, &from_arg_lty) in to_lty.args.iter().zip(from_lty.args) {
+                            self.do_unify(to_arg_lty, from_arg_lty);
+                        }
+                    }
                     PointerCast::ClosureFnPointer(_) => {}
                     PointerCast::MutToConstPointer => {}
                     PointerCast::ArrayToPointer => {}
@@ -221,6 +232,38 @@ impl<'tcx> TypeChecker<'tcx, '_> {
                 self.visit_cast(cast_kind, op, rvalue_lty);
             }
             Rvalue::BinaryOp(BinOp::Offset, _) => todo!("visit_rvalue BinOp::Offset"),
+            Rvalue::BinaryOp(BinOp::Lt | BinOp::Le | BinOp::Gt | BinOp::Ge, ref ops) => {
+                self.visit_operand(&ops.0);
+                self.visit_operand(&ops.1);
+                // Ordering comparisons between pointers are only meaningful for pointers into the
+                // same array.  Such pointers are rewritten to `OffsetPtr`, which compares by
+                // address rather than by contents like a slice would.
+                let perms = PermissionSet::OFFSET_ADD | PermissionSet::OFFSET_SUB;
+                for op in [&ops.0, &ops.1] {
+                    let ptr = self.acx.type_of(op).label;
+                    if !ptr.is_none() {
+                        self.constraints.add_all_perms(ptr, perms);
+                    }
+                }
+            }
+            Rvalue::BinaryOp(BinOp::Eq | BinOp::Ne, ref ops) => {
+                self.visit_operand(&ops.0);
+                self.visit_operand(&ops.1);
+                // Comparing a character read through a pointer against `'\0'` is how C code finds
+                // the end of a string, so the pointer must point to a NUL-terminated string.
+                for (a, b) in [(&ops.0, &ops.1), (&ops.1, &ops.0)] {
+                    if !is_zero_operand(self.mir, b) {
+                        continue;
+                    }
+                    if let Some(ptr_pl) = c_char_load_pointer(self.acx.tcx(), self.mir, a) {
+                        let ptr = self.acx.type_of(ptr_pl).label;
+                        if !ptr.is_none() {
+                            self.constraints
+                                .add_all_perms(ptr, PermissionSet::NUL_TERMINATED);
+                        }
+                    }
+                }
+            }
             Rvalue::BinaryOp(_, ref ops) => {
                 self.visit_operand(&ops.0);
                 self.visit_operand(&ops.1);
@@ -251,12 +294,23 @@ impl<'tcx> TypeChecker<'tcx, '_> {
                             self.do_assign(elem_lty, op_lty);
                         }
                     }
-                    AggregateKind::Adt(adt_did, ..) => {
+                    AggregateKind::Adt(adt_did, variant_idx, ..) => {
                         let base_adt_def = self.acx.tcx().adt_def(adt_did);
-                        let fields = &base_adt_def.non_enum_variant().fields;
+                        let fields = &base_adt_def.variant(variant_idx).fields;
                         for (field, op) in fields.iter().zip(ops.iter()) {
                             let op_lty = self.acx.type_of(op);
-                            let unresolved_field_lty = self.acx.gacx.field_ltys[&field.did];
+                            // Fields of other crates' ADTs, such as the payload of `Some`, aren't
+                            // labeled, but their pointers can still come from the type arguments.
+                            let unresolved_field_lty =
+                                match self.acx.gacx.field_ltys.get(&field.did) {
+                                    Some(&lty) => lty,
+                                    None => self
+                                        .acx
+                                        .lcx()
+                                        .label(self.acx.tcx().type_of(field.did), &mut |_| {
+                                            PointerId::NONE
+                                        }),
+                                };
                             // resolve the generic type arguments in `field_lty` by referencing the `Ty` of `op`
                             let resolved_field_lty =
                                 self.acx.lcx().subst(unresolved_field_lty, rvalue_lty.args);
@@ -367,6 +421,31 @@ impl<'tcx> TypeChecker<'tcx, '_> {
         }
     }
 
+    /// Unify the [`LFnSig`](crate::context::LFnSig) of the `fn` item `fn_def_ty` with
+    /// `fn_ptr_lty`, the type of a `fn` ptr it's converted to.
+    ///
+    /// Calls through a `fn` ptr use its labeled type in place of the callee's signature, so the
+    /// pointers in the signature of every `fn` whose address is taken must be rewritten the same
+    /// way as the corresponding pointers in the `fn` ptr types it flows into.  Since all the `fn`s
+    /// stored in the same place are unified with its type, they're all rewritten consistently.
+    fn do_unify_fn_ptr(&mut self, fn_ptr_lty: LTy<'tcx>, fn_def_ty: Ty<'tcx>) {
+        let def_id = match *fn_def_ty.kind() {
+            TyKind::FnDef(def_id, _) => def_id,
+            _ => panic!("expected a fn item type, but got {fn_def_ty:?}"),
+        };
+        let sig = match self.acx.gacx.fn_sigs.get(&def_id) {
+            Some(sig) => sig,
+            None => {
+                ::log::error!("TODO: taking the address of unknown fn {def_id:?}");
+                return;
+            }
+        };
+        assert_eq!(sig.inputs.len() + 1, fn_ptr_lty.args.len());
+        for (sig_lty, &arg_lty) in sig.inputs_and_output().zip(fn_ptr_lty.args) {
+            self.do_unify(sig_lty, arg_lty);
+        }
+    }
+
     pub fn visit_statement(&mut self, stmt: &Statement<'tcx>, loc: Location) {
         eprintln!("visit_statement({:?})", stmt);
         if self.acx.c_void_casts.should_skip_stmt(loc) {
@@ -404,7 +483,6 @@ impl<'tcx> TypeChecker<'tcx, '_> {
 
     pub fn visit_terminator(&mut self, term: &Terminator<'tcx>, loc: Location) {
         eprintln!("visit_terminator({:?})", term.kind);
-        let tcx = self.acx.tcx();
         let _g = panic_detail::set_current_span(term.source_info.span);
         // TODO(spernsteiner): other `TerminatorKind`s will be handled in the future
         #[allow(clippy::single_match)]
@@ -416,7 +494,6 @@ impl<'tcx> TypeChecker<'tcx, '_> {
                 target: _,
                 ..
             } => {
-                let func = func.ty(self.mir, tcx);
                 self.visit_call(loc, func, args, destination);
             }
             // TODO(spernsteiner): handle other `TerminatorKind`s
@@ -424,15 +501,39 @@ impl<'tcx> TypeChecker<'tcx, '_> {
         }
     }
 
+    /// Visit a call to a `printf`-style function.  Arguments printed with `%s` must point to
+    /// NUL-terminated strings.
+    fn visit_printf_call(&mut self, format_arg: usize, args: &[Operand<'tcx>]) {
+        for arg in args {
+            self.visit_operand(arg);
+        }
+        let format = match string_literal_bytes(self.acx.tcx(), self.mir, &args[format_arg]) {
+            Some(x) => x,
+            None => return,
+        };
+        for i in printf_string_args(&format) {
+            let arg = match args.get(format_arg + 1 + i) {
+                Some(x) => x,
+                None => break,
+            };
+            let arg_lty = self.acx.type_of(arg);
+            let is_c_string = matches!(*arg_lty.ty.kind(), TyKind::RawPtr(tm) if is_c_char(tm.ty));
+            if is_c_string && !arg_lty.label.is_none() {
+                let perms = PermissionSet::READ | PermissionSet::NUL_TERMINATED;
+                self.constraints.add_all_perms(arg_lty.label, perms);
+            }
+        }
+    }
+
     pub fn visit_call(
         &mut self,
         loc: Location,
-        func: Ty<'tcx>,
+        func: &Operand<'tcx>,
         args: &[Operand<'tcx>],
         destination: Place<'tcx>,
     ) {
         let tcx = self.acx.tcx();
-        let callee = ty_callee(tcx, func);
+        let callee = ty_callee(tcx, func.ty(self.mir, tcx));
         eprintln!("callee = {callee:?}");
         match callee {
             Callee::Trivial => {}
@@ -448,9 +549,41 @@ impl<'tcx> TypeChecker<'tcx, '_> {
                 // As this is actually a known `fn`, we can treat it as a normal local call.
                 self.visit_local_call(def_id, substs, args, destination);
             }
+            Callee::UnknownDef(UnknownDefCallee::Direct {
+                ty: _,
+                def_id,
+                substs,
+                is_foreign: false,
+            }) if self.acx.gacx.fn_sigs.contains_key(&def_id) => {
+                // A function in another crate, whose signature we loaded from that crate's
+                // summary.  This works just like a local call.
+                self.visit_local_call(def_id, substs, args, destination);
+            }
+            Callee::UnknownDef(UnknownDefCallee::Direct {
+                ty: _,
+                def_id,
+                substs: _,
+                is_foreign: true,
+            }) if printf_format_arg(tcx.item_name(def_id).as_str()).is_some() => {
+                let format_arg = printf_format_arg(tcx.item_name(def_id).as_str()).unwrap();
+                self.visit_printf_call(format_arg, args);
+            }
+            Callee::UnknownDef(UnknownDefCallee::Indirect { .. }) => {
+                self.visit_operand(func);
+                let func_lty = self.acx.type_of(func);
+                self.visit_fn_ptr_call(func_lty, args, destination);
+            }
             Callee::UnknownDef(_) => {
                 log::error!("TODO: visit Callee::{callee:?}");
             }
+            Callee::UnwrapFnPtr { .. } => {
+                // This is like an assignment from the `Option`'s payload.
+                self.visit_place(destination, Mutability::Mut);
+                let pl_lty = self.acx.type_of(destination);
+                self.visit_operand(&args[0]);
+                let rv_lty = self.acx.type_of(&args[0]);
+                self.do_assign(pl_lty, rv_lty.args[0]);
+            }
             Callee::PtrOffset { .. } => {
                 // We handle this like a pointer assignment.
                 self.visit_place(destination, Mutability::Mut);
@@ -458,11 +591,29 @@ impl<'tcx> TypeChecker<'tcx, '_> {
                 assert!(args.len() == 2);
                 self.visit_operand(&args[0]);
                 let rv_lty = self.acx.type_of(&args[0]);
-                self.do_assign(pl_lty, rv_lty);
-                let perms = PermissionSet::OFFSET_ADD | PermissionSet::OFFSET_SUB;
+                // The result of `offset` is never null, even if its input is nullable.
+                self.do_assign_except(pl_lty, rv_lty, PermissionSet::NON_NULL);
+                // Only a possibly-negative offset requires stepping backward.
+                let perms = if is_nonnegative_offset(self.acx.tcx(), self.mir, &args[1]) {
+                    PermissionSet::OFFSET_ADD
+                } else {
+                    PermissionSet::OFFSET_ADD | PermissionSet::OFFSET_SUB
+                };
                 self.constraints.add_all_perms(rv_lty.label, perms);
             }
 
+            Callee::PtrOffsetFrom { .. } => {
+                // `p.offset_from(q)` measures the distance between two pointers into the same
+                // array, which may be in either direction.
+                assert!(args.len() == 2);
+                let perms = PermissionSet::OFFSET_ADD | PermissionSet::OFFSET_SUB;
+                for arg in args {
+                    self.visit_operand(arg);
+                    let arg_lty = self.acx.type_of(arg);
+                    self.constraints.add_all_perms(arg_lty.label, perms);
+                }
+            }
+
             Callee::SliceAsPtr { elem_ty, .. } => {
                 // We handle this like an assignment, but with some adjustments due to the
                 // difference in input and output types.
@@ -494,6 +645,18 @@ impl<'tcx> TypeChecker<'tcx, '_> {
                     destination,
                 );
                 self.visit_place(out_ptr, Mutability::Mut);
+
+                // `malloc` and `calloc` return null on failure.
+                let pl_lty = self.acx.type_of(out_ptr);
+                self.constraints
+                    .add_no_perms(pl_lty.label, PermissionSet::NON_NULL);
+
+                // The allocation can only be rewritten to a `Box` if there's a default value to
+                // fill it with.
+                let pointee_ty = pl_lty.args[0].ty;
+                if implements_default(self.acx.tcx(), self.mir.source.def_id(), pointee_ty) {
+                    self.constraints.add_alloc(pl_lty.label);
+                }
             }
             Callee::Realloc => {
                 let out_ptr = self.acx.c_void_casts.get_adjusted_place_or_default_to(
@@ -519,6 +682,10 @@ impl<'tcx> TypeChecker<'tcx, '_> {
                 let perms = PermissionSet::FREE;
                 self.constraints.add_all_perms(rv_lty.label, perms);
 
+                // `realloc` returns null on failure
+                self.constraints
+                    .add_no_perms(pl_lty.label, PermissionSet::NON_NULL);
+
                 // unify inner-most pointer types
                 self.do_equivalence_nested(pl_lty, rv_lty);
             }
@@ -615,6 +782,13 @@ impl<'tcx> TypeChecker<'tcx, '_> {
                 assert!(args.len() == 1);
                 self.visit_operand(&args[0]);
             }
+            Callee::Null { .. } => {
+                assert!(args.is_empty());
+                self.visit_place(destination, Mutability::Mut);
+                let pl_lty = self.acx.type_of(destination);
+                self.constraints
+                    .add_no_perms(pl_lty.label, PermissionSet::NON_NULL);
+            }
         }
     }
 
@@ -635,17 +809,42 @@ impl<'tcx> TypeChecker<'tcx, '_> {
             todo!("call to generic function {def_id:?} {substs:?}");
         }
 
-        // Process pseudo-assignments from `args` to the types declared in `sig`.
-        for (arg_op, &input_lty) in args.iter().zip(sig.inputs.iter()) {
+        self.visit_call_with_sig(sig.inputs, sig.output, args, dest);
+    }
+
+    /// Visit a call through a `fn` ptr of type `func_lty`.  The `fn` ptr's argument and return
+    /// types are used like the signature of a [`Callee::LocalDef`].  See `do_unify_fn_ptr` for
+    /// how they're connected to the `fn`s it may point to.
+    fn visit_fn_ptr_call(
+        &mut self,
+        func_lty: LTy<'tcx>,
+        args: &[Operand<'tcx>],
+        dest: Place<'tcx>,
+    ) {
+        let (&output_lty, input_ltys) = func_lty
+            .args
+            .split_last()
+            .expect("fn ptr type should have a return type");
+        self.visit_call_with_sig(input_ltys, output_lty, args, dest);
+    }
+
+    fn visit_call_with_sig(
+        &mut self,
+        input_ltys: &[LTy<'tcx>],
+        output_lty: LTy<'tcx>,
+        args: &[Operand<'tcx>],
+        dest: Place<'tcx>,
+    ) {
+        // Process pseudo-assignments from `args` to the types declared in the signature.
+        for (arg_op, &input_lty) in args.iter().zip(input_ltys.iter()) {
             self.visit_operand(arg_op);
             let arg_lty = self.acx.type_of(arg_op);
             self.do_assign(input_lty, arg_lty);
         }
 
-        // Process a pseudo-assignment from the return type declared in `sig` to `dest`.
+        // Process a pseudo-assignment from the declared return type to `dest`.
         self.visit_place(dest, Mutability::Mut);
         let dest_lty = self.acx.type_of(dest);
-        let output_lty = sig.output;
         self.do_assign(dest_lty, output_lty);
     }
 }
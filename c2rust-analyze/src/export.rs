@@ -0,0 +1,281 @@
+//! Machine-readable export of the final analysis results.
+//!
+//! Setting `C2RUST_ANALYZE_RESULTS_JSON` to a path makes `c2rust-analyze` write the final
+//! labeling of every function, `static`, and field to that path as JSON (use `-` to write it to
+//! stdout instead, or a directory to write it to `<crate name>.analysis.json` there).  The output
+//! has this shape:
+//!
+//! ```json
+//! {
+//!   "crate": "foo",
+//!   "functions": [
+//!     {
+//!       "name": "m::f",
+//!       "span": { "file": "src/m.rs", "lo_line": 3, "lo_col": 1, "hi_line": 7, "hi_col": 2 },
+//!       "error": null,
+//!       "locals": [
+//!         {
+//!           "name": "_1",
+//!           "span": { ... },
+//!           "addr_of": { "equiv_class": "l0", "perms": ["UNIQUE", "NON_NULL"], "flags": [] },
+//!           "pointers": [
+//!             {
+//!               "equiv_class": "g2",
+//!               "perms": ["READ", "WRITE", "UNIQUE", "NON_NULL"],
+//!               "flags": [],
+//!               "ty": "*mut i32",
+//!               "rewritten_ty": "&mut i32"
+//!             }
+//!           ]
+//!         }
+//!       ]
+//!     }
+//!   ],
+//!   "statics": [ ... ],
+//!   "fields": [ ... ]
+//! }
+//! ```
+//!
+//! `pointers` lists every pointer appearing in the variable's type, outermost first.  All
+//! pointers in the same equivalence class share an `equiv_class`; classes starting with `g` are
+//! global, while those starting with `l` are local to the enclosing function.  For a function
+//! whose analysis failed, `locals` is empty and `error` describes the failure.
+
+use crate::context::{
+    AnalysisCtxt, Assignment, FlagSet, GlobalAnalysisCtxt, GlobalAssignment, LTy, PermissionSet,
+    PointerId,
+};
+use crate::panic_detail::PanicDetail;
+use crate::rewrite;
+use rustc_hir::def_id::{DefId, LOCAL_CRATE};
+use rustc_middle::mir::Body;
+use rustc_middle::ty::TyCtxt;
+use rustc_span::Span;
+use serde::Serialize;
+use std::fs;
+use std::io::{self, Write};
+use std::ops::Index;
+use std::path::PathBuf;
+
+#[derive(Serialize)]
+pub struct Results {
+    #[serde(rename = "crate")]
+    crate_name: String,
+    functions: Vec<FnResults>,
+    statics: Vec<VarResults>,
+    fields: Vec<VarResults>,
+}
+
+#[derive(Serialize)]
+struct FnResults {
+    name: String,
+    span: JsonSpan,
+    error: Option<ErrorResults>,
+    locals: Vec<VarResults>,
+}
+
+#[derive(Serialize)]
+struct ErrorResults {
+    /// The one-line summary produced by [`PanicDetail::to_string_short`].
+    summary: String,
+    /// The source location being analyzed when the error occurred, if known.
+    span: Option<JsonSpan>,
+}
+
+#[derive(Serialize)]
+struct VarResults {
+    name: String,
+    span: JsonSpan,
+    /// The pointer representing the address of this variable, if it has one.  Fields don't.
+    addr_of: Option<PointerResults>,
+    pointers: Vec<TyPointerResults>,
+}
+
+#[derive(Serialize)]
+struct PointerResults {
+    equiv_class: String,
+    perms: Vec<&'static str>,
+    flags: Vec<&'static str>,
+}
+
+#[derive(Serialize)]
+struct TyPointerResults {
+    #[serde(flatten)]
+    ptr: PointerResults,
+    /// The original pointer type.
+    ty: String,
+    /// The type the pointer will be rewritten to.
+    rewritten_ty: String,
+}
+
+#[derive(Serialize)]
+struct JsonSpan {
+    file: String,
+    /// 1-based line and column of the start and end of the span.
+    lo_line: usize,
+    lo_col: usize,
+    hi_line: usize,
+    hi_col: usize,
+}
+
+impl JsonSpan {
+    fn new(tcx: TyCtxt, span: Span) -> JsonSpan {
+        let source_map = tcx.sess.source_map();
+        let lo = source_map.lookup_char_pos(span.lo());
+        let hi = source_map.lookup_char_pos(span.hi());
+        JsonSpan {
+            file: lo.file.name.prefer_local().to_string(),
+            lo_line: lo.line,
+            lo_col: lo.col.0 + 1,
+            hi_line: hi.line,
+            hi_col: hi.col.0 + 1,
+        }
+    }
+}
+
+impl PointerResults {
+    fn new(ptr: PointerId, perms: PermissionSet, flags: FlagSet) -> PointerResults {
+        PointerResults {
+            equiv_class: ptr.to_string(),
+            perms: perms.names().collect(),
+            flags: flags.names().collect(),
+        }
+    }
+}
+
+impl VarResults {
+    fn new<'tcx>(
+        gacx: &GlobalAnalysisCtxt<'tcx>,
+        name: String,
+        span: Span,
+        addr_of_ptr: PointerId,
+        lty: LTy<'tcx>,
+        perms: &impl Index<PointerId, Output = PermissionSet>,
+        flags: &impl Index<PointerId, Output = FlagSet>,
+    ) -> VarResults {
+        let addr_of = if addr_of_ptr.is_none() {
+            None
+        } else {
+            Some(PointerResults::new(
+                addr_of_ptr,
+                perms[addr_of_ptr],
+                flags[addr_of_ptr],
+            ))
+        };
+        let pointers = lty
+            .iter()
+            .filter(|lty| !lty.label.is_none())
+            .map(|lty| TyPointerResults {
+                ptr: PointerResults::new(lty.label, perms[lty.label], flags[lty.label]),
+                ty: format!("{:?}", lty.ty),
+                rewritten_ty: format!("{:?}", rewrite::rewritten_ty(perms, flags, lty, gacx)),
+            })
+            .collect();
+        VarResults {
+            name,
+            span: JsonSpan::new(gacx.tcx, span),
+            addr_of,
+            pointers,
+        }
+    }
+}
+
+impl Results {
+    pub fn new(tcx: TyCtxt) -> Results {
+        Results {
+            crate_name: tcx.crate_name(LOCAL_CRATE).to_string(),
+            functions: Vec::new(),
+            statics: Vec::new(),
+            fields: Vec::new(),
+        }
+    }
+
+    /// Record the final labeling of every local of a successfully analyzed function.
+    pub fn add_fn<'tcx>(
+        &mut self,
+        acx: &AnalysisCtxt<'_, 'tcx>,
+        asn: &Assignment,
+        mir: &Body<'tcx>,
+    ) {
+        let tcx = acx.tcx();
+        let did = mir.source.def_id();
+        let locals = mir
+            .local_decls
+            .iter_enumerated()
+            .map(|(local, decl)| {
+                VarResults::new(
+                    acx.gacx,
+                    format!("{:?}", local),
+                    decl.source_info.span,
+                    acx.addr_of_local[local],
+                    acx.local_tys[local],
+                    &asn.perms(),
+                    &asn.flags(),
+                )
+            })
+            .collect();
+        self.functions.push(FnResults {
+            name: tcx.def_path_str(did),
+            span: JsonSpan::new(tcx, tcx.def_span(did)),
+            error: None,
+            locals,
+        });
+    }
+
+    /// Record a function whose analysis failed.
+    pub fn add_failed_fn(&mut self, tcx: TyCtxt, did: DefId, detail: &PanicDetail) {
+        let span = detail.span();
+        self.functions.push(FnResults {
+            name: tcx.def_path_str(did),
+            span: JsonSpan::new(tcx, tcx.def_span(did)),
+            error: Some(ErrorResults {
+                summary: detail.to_string_short(),
+                span: (!span.is_dummy()).then(|| JsonSpan::new(tcx, span)),
+            }),
+            locals: Vec::new(),
+        });
+    }
+
+    pub fn add_static(&mut self, gacx: &GlobalAnalysisCtxt, gasn: &GlobalAssignment, did: DefId) {
+        let tcx = gacx.tcx;
+        self.statics.push(VarResults::new(
+            gacx,
+            tcx.def_path_str(did),
+            tcx.def_span(did),
+            gacx.addr_of_static[&did],
+            gacx.static_tys[&did],
+            &gasn.perms,
+            &gasn.flags,
+        ));
+    }
+
+    pub fn add_field(&mut self, gacx: &GlobalAnalysisCtxt, gasn: &GlobalAssignment, did: DefId) {
+        let tcx = gacx.tcx;
+        self.fields.push(VarResults::new(
+            gacx,
+            tcx.def_path_str(did),
+            tcx.def_span(did),
+            PointerId::NONE,
+            gacx.field_ltys[&did],
+            &gasn.perms,
+            &gasn.flags,
+        ));
+    }
+
+    /// Write the results to `path`, or to stdout if `path` is `-`.  If `path` is a directory, the
+    /// results are written to `<crate name>.analysis.json` inside it, which is useful when
+    /// analyzing several crates at once.
+    pub fn write(&self, path: &str) -> io::Result<()> {
+        let json = serde_json::to_string_pretty(self)
+            .map_err(|e| io::Error::new(io::ErrorKind::Other, e))?;
+        if path == "-" {
+            return writeln!(io::stdout(), "{}", json);
+        }
+        let mut path = PathBuf::from(path);
+        if path.is_dir() {
+            path.push(format!("{}.analysis.json", self.crate_name));
+        }
+        eprintln!("writing analysis results to {:?}", path);
+        fs::write(path, json)
+    }
+}
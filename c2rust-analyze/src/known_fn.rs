@@ -262,7 +262,13 @@ impl KnownFn {
 /// we annotate any argument with [`NON_NULL`] if `NULL` might cause UB,
 /// and we annotate returns with [`NON_NULL`] only if it is strictly guaranteed to be non-`NULL`.
 ///
+/// String arguments that are only read up to their NUL terminator
+/// are annotated with [`NUL_TERMINATED`] instead of [`OFFSET_ADD`],
+/// so that they can be rewritten to `&CStr`.
+///
 /// [`NON_NULL`]: PermissionSet::NON_NULL
+/// [`NUL_TERMINATED`]: PermissionSet::NUL_TERMINATED
+/// [`OFFSET_ADD`]: PermissionSet::OFFSET_ADD
 macro_rules! known_fns {
     {
         mod $module:ident {
@@ -631,7 +637,7 @@ pub const fn all_known_fns() -> &'static [KnownFn] {
             ) -> c_uint;
 
             fn atoi(
-                s: *const c_char: [READ | OFFSET_ADD | NON_NULL],
+                s: *const c_char: [READ | NON_NULL | NUL_TERMINATED],
             ) -> c_int;
 
             fn bind(
@@ -764,7 +770,7 @@ pub const fn all_known_fns() -> &'static [KnownFn] {
             // ) -> c_int;
 
             fn fputs(
-                s: *const c_char: [READ | OFFSET_ADD | NON_NULL],
+                s: *const c_char: [READ | NON_NULL | NUL_TERMINATED],
                 stream: *mut FILE: [READ | WRITE | NON_NULL],
             ) -> c_int;
 
@@ -817,7 +823,7 @@ pub const fn all_known_fns() -> &'static [KnownFn] {
             ) -> c_int;
 
             fn getenv(
-                s: *const c_char: [READ | OFFSET_ADD | NON_NULL],
+                s: *const c_char: [READ | NON_NULL | NUL_TERMINATED],
                 // Not `WRITE` even though it's `*mut` since future calls may overwrite the returned memory,
                 // as it may be statically allocated and reused.  It is not meant to be modified.
             ) -> *mut c_char: [READ | OFFSET_ADD];
@@ -1087,7 +1093,7 @@ pub const fn all_known_fns() -> &'static [KnownFn] {
             ) -> ();
 
             fn perror(
-                s: *const c_char: [READ | OFFSET_ADD],
+                s: *const c_char: [READ | NUL_TERMINATED],
             ) -> ();
 
             fn pipe(
@@ -1126,7 +1132,7 @@ pub const fn all_known_fns() -> &'static [KnownFn] {
             // ) -> c_int;
 
             fn puts(
-                s: *const c_char: [READ | OFFSET_ADD | NON_NULL],
+                s: *const c_char: [READ | NON_NULL | NUL_TERMINATED],
             ) -> c_int;
 
             fn pwrite(
@@ -1318,17 +1324,17 @@ pub const fn all_known_fns() -> &'static [KnownFn] {
             ) -> *mut c_char: [READ | WRITE | OFFSET_ADD | OFFSET_SUB];
 
             fn strcmp(
-                cs: *const c_char: [READ | OFFSET_ADD | NON_NULL],
-                ct: *const c_char: [READ | OFFSET_ADD | NON_NULL],
+                cs: *const c_char: [READ | NON_NULL | NUL_TERMINATED],
+                ct: *const c_char: [READ | NON_NULL | NUL_TERMINATED],
             ) -> c_int;
 
             fn strcspn(
-                cs: *const c_char: [READ | OFFSET_ADD | NON_NULL],
-                ct: *const c_char: [READ | OFFSET_ADD | NON_NULL],
+                cs: *const c_char: [READ | NON_NULL | NUL_TERMINATED],
+                ct: *const c_char: [READ | NON_NULL | NUL_TERMINATED],
             ) -> size_t;
 
             fn strdup(
-                cs: *const c_char: [READ | OFFSET_ADD | NON_NULL],
+                cs: *const c_char: [READ | NON_NULL | NUL_TERMINATED],
             ) -> *mut c_char: [READ | WRITE | OFFSET_ADD | FREE];
 
             fn strerror_r(
@@ -1345,7 +1351,7 @@ pub const fn all_known_fns() -> &'static [KnownFn] {
             ) -> size_t;
 
             fn strlen(
-                cs: *const c_char: [READ | OFFSET_ADD | NON_NULL],
+                cs: *const c_char: [READ | NON_NULL | NUL_TERMINATED],
             ) -> size_t;
 
             fn strncasecmp(
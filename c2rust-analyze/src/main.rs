@@ -8,31 +8,36 @@ extern crate rustc_data_structures;
 extern crate rustc_driver;
 extern crate rustc_hir;
 extern crate rustc_index;
+extern crate rustc_infer;
 extern crate rustc_interface;
+extern crate rustc_metadata;
 extern crate rustc_middle;
 extern crate rustc_mir_build;
 extern crate rustc_session;
 extern crate rustc_span;
 extern crate rustc_target;
+extern crate rustc_trait_selection;
 extern crate rustc_type_ir;
 
+use crate::annotations::{Annotations, Config, Target};
 use crate::context::{
     AnalysisCtxt, AnalysisCtxtData, FlagSet, GlobalAnalysisCtxt, GlobalAssignment, LFnSig, LTy,
     LTyCtxt, LocalAssignment, PermissionSet, PointerId, PointerInfo,
 };
-use crate::dataflow::DataflowConstraints;
+use crate::dataflow::{DataflowConstraints, FlowNode};
 use crate::equiv::{GlobalEquivSet, LocalEquivSet};
 use crate::labeled_ty::LabeledTyCtxt;
 use crate::log::init_logger;
 use crate::panic_detail::PanicDetail;
 use crate::pointee_type::PointeeTypes;
 use crate::pointer_id::{GlobalPointerTable, LocalPointerTable, PointerTable};
+use crate::summary::{CrateSummary, Summaries};
 use crate::type_desc::Ownership;
 use crate::util::{Callee, TestAttr};
 use ::log::warn;
 use context::AdtMetadataTable;
 use rustc_hir::def::DefKind;
-use rustc_hir::def_id::{CrateNum, DefId, DefIndex, LocalDefId};
+use rustc_hir::def_id::{DefId, LocalDefId};
 use rustc_index::vec::IndexVec;
 use rustc_middle::mir::visit::Visitor;
 use rustc_middle::mir::{
@@ -44,20 +49,22 @@ use rustc_span::Span;
 use std::collections::{HashMap, HashSet};
 use std::env;
 use std::fmt::{Debug, Display, Write as _};
-use std::fs::File;
-use std::io::{self, BufRead, BufReader};
 use std::iter;
 use std::ops::{Deref, DerefMut, Index};
 use std::panic::AssertUnwindSafe;
-use std::str::FromStr;
+use std::path::PathBuf;
+use std::process;
 
 use c2rust_pdg::graph::Graphs;
 
+mod annotations;
 mod borrowck;
 mod c_void_casts;
+mod cargo_wrapper;
 mod context;
 mod dataflow;
 mod equiv;
+mod export;
 mod known_fn;
 mod labeled_ty;
 mod log;
@@ -65,6 +72,7 @@ mod panic_detail;
 mod pointee_type;
 mod pointer_id;
 mod rewrite;
+mod summary;
 mod trivial;
 mod type_desc;
 mod util;
@@ -394,73 +402,29 @@ fn mark_foreign_fixed<'tcx>(
     }
 }
 
-fn parse_def_id(s: &str) -> Result<DefId, String> {
-    // DefId debug output looks like `DefId(0:1 ~ alias1[0dc4]::{use#0})`.  The ` ~ name` part may
-    // be omitted if the name/DefPath info is not available at the point in the compiler where the
-    // `DefId` was printed.
-    let orig_s = s;
-    let s = s
-        .strip_prefix("DefId(")
-        .ok_or("does not start with `DefId(`")?;
-    let s = s.strip_suffix(")").ok_or("does not end with `)`")?;
-    let s = match s.find(" ~ ") {
-        Some(i) => &s[..i],
-        None => s,
-    };
-    let i = s
-        .find(':')
-        .ok_or("does not contain `:` in `CrateNum:DefIndex` part")?;
-
-    let krate_str = &s[..i];
-    let index_str = &s[i + 1..];
-    let krate = u32::from_str(krate_str).map_err(|_| "failed to parse CrateNum")?;
-    let index = u32::from_str(index_str).map_err(|_| "failed to parse DefIndex")?;
-    let def_id = DefId {
-        krate: CrateNum::from_u32(krate),
-        index: DefIndex::from_u32(index),
-    };
-
-    let rendered = format!("{:?}", def_id);
-    if &rendered != s {
-        return Err(format!(
-            "path mismatch: after parsing input {}, obtained a different path {:?}",
-            orig_s, def_id
-        ));
-    }
-
-    Ok(def_id)
-}
-
-fn read_fixed_defs_list(path: &str) -> io::Result<HashSet<DefId>> {
-    let f = BufReader::new(File::open(path)?);
-    let mut def_ids = HashSet::new();
-    for (i, line) in f.lines().enumerate() {
-        let line = line?;
-        let line = line.trim();
-        if line.len() == 0 || line.starts_with('#') {
-            continue;
-        }
-
-        let def_id = parse_def_id(&line).unwrap_or_else(|e| {
-            panic!("failed to parse {} line {}: {}", path, i + 1, e);
-        });
-        def_ids.insert(def_id);
-    }
-    Ok(def_ids)
-}
-
 fn run(tcx: TyCtxt) {
     eprintln!("all defs:");
     for ldid in tcx.hir_crate_items(()).definitions() {
         eprintln!("{:?}", ldid);
     }
 
-    // Load the list of fixed defs early, so any errors are reported immediately.
-    let fixed_defs = if let Ok(path) = env::var("C2RUST_ANALYZE_FIXED_DEFS_LIST") {
-        read_fixed_defs_list(&path).unwrap()
+    // Load the annotations and the rewrite modes early, so any errors are reported immediately.
+    let config = if let Ok(path) = env::var("C2RUST_ANALYZE_CONFIG") {
+        Config::read(&path)
+            .unwrap_or_else(|e| panic!("failed to read C2RUST_ANALYZE_CONFIG {}: {}", path, e))
+    } else {
+        Config::default()
+    };
+    let annotations = Annotations::collect(tcx, &config);
+    let rewrite_modes = if let Ok(modes) = env::var("C2RUST_ANALYZE_REWRITE_MODE") {
+        rewrite::OutputMode::parse_list(&modes)
+            .unwrap_or_else(|e| panic!("failed to parse C2RUST_ANALYZE_REWRITE_MODE: {}", e))
     } else {
-        HashSet::new()
+        vec![rewrite::OutputMode::Print]
     };
+    let results_path = env::var("C2RUST_ANALYZE_RESULTS_JSON").ok();
+    let mut results = results_path.as_ref().map(|_| export::Results::new(tcx));
+    let summaries_dir = env::var_os("C2RUST_ANALYZE_SUMMARIES").map(PathBuf::from);
 
     let mut gacx = GlobalAnalysisCtxt::new(tcx);
     let mut func_info = HashMap::new();
@@ -547,6 +511,13 @@ fn run(tcx: TyCtxt) {
         gacx.assign_pointer_to_fields(did);
     }
 
+    // Label the signatures and field types that are summarized by dependencies of this crate.
+    let summaries = match summaries_dir {
+        Some(ref dir) => Summaries::load(&mut gacx, dir)
+            .unwrap_or_else(|e| panic!("failed to load crate summaries: {}", e)),
+        None => Summaries::default(),
+    };
+
     // Compute hypothetical region data for all ADTs and functions.  This can only be done after
     // all field types are labeled.
     gacx.construct_region_metadata();
@@ -763,8 +734,10 @@ fn run(tcx: TyCtxt) {
     // don't want to rewrite those
     gacx.foreign_mentioned_tys = foreign_mentioned_tys(tcx);
 
-    let mut gasn =
-        GlobalAssignment::new(gacx.num_pointers(), PermissionSet::UNIQUE, FlagSet::empty());
+    // Initially, all pointers are assumed to be unique and non-null.  Dataflow removes `UNIQUE`
+    // and `NON_NULL` from the pointers that need it removed.
+    let default_perms = PermissionSet::UNIQUE | PermissionSet::NON_NULL;
+    let mut gasn = GlobalAssignment::new(gacx.num_pointers(), default_perms, FlagSet::empty());
     for (ptr, &info) in gacx.ptr_info().iter() {
         if should_make_fixed(info) {
             gasn.flags[ptr].insert(FlagSet::FIXED);
@@ -775,14 +748,32 @@ fn run(tcx: TyCtxt) {
 
     for (ptr, perms) in gacx.known_fn_ptr_perms() {
         let existing_perms = &mut gasn.perms[ptr];
-        existing_perms.remove(PermissionSet::UNIQUE);
+        existing_perms.remove(default_perms);
         assert_eq!(*existing_perms, PermissionSet::empty());
         *existing_perms = perms;
     }
 
+    // Other crates have already been rewritten according to their summaries, so their pointers
+    // start out with exactly the summarized permissions and flags.
+    for (ptr, perms, flags) in summaries.ptr_perms(&gacx) {
+        gasn.perms[ptr] = perms;
+        gasn.flags[ptr] = flags;
+    }
+
+    // Annotated permissions replace the initial permissions of the outermost pointer.
+    for (target, perms) in annotations.perms() {
+        let ptr = match target.lty(&gacx) {
+            Some(lty) => lty.label,
+            None => panic!("missing LTy for annotated {:?}", target),
+        };
+        if !ptr.is_none() {
+            gasn.perms[ptr] = perms;
+        }
+    }
+
     for info in func_info.values_mut() {
         let num_pointers = info.acx_data.num_pointers();
-        let mut lasn = LocalAssignment::new(num_pointers, PermissionSet::UNIQUE, FlagSet::empty());
+        let mut lasn = LocalAssignment::new(num_pointers, default_perms, FlagSet::empty());
 
         for (ptr, &info) in info.acx_data.local_ptr_info().iter() {
             if should_make_fixed(info) {
@@ -897,11 +888,41 @@ fn run(tcx: TyCtxt) {
         }
     }
 
-    // Items in the "fixed defs" list have all pointers in their types set to `FIXED`.  For
-    // testing, putting #[c2rust_analyze_test::fixed_signature] on an item has the same effect.
+    // Annotated items have all pointers in their types set to `FIXED`.
+    for target in annotations.fixed() {
+        // Fields, statics, parameters, and return values.
+        if let Some(lty) = target.lty(&gacx) {
+            make_ty_fixed(&mut gasn, lty);
+        }
+        let did = match target {
+            Target::Def(did) => did,
+            Target::Param(..) | Target::Return(_) => continue,
+        };
+        match tcx.def_kind(did) {
+            DefKind::Fn | DefKind::AssocFn => {
+                make_sig_fixed(&mut gasn, &gacx.fn_sigs[&did]);
+            }
+            // If the whole ADT is annotated, then every field is marked FIXED.
+            DefKind::Struct | DefKind::Enum | DefKind::Union => {
+                for field in tcx.adt_def(did).all_fields() {
+                    make_ty_fixed(&mut gasn, gacx.field_ltys[&field.did]);
+                }
+            }
+            DefKind::Static(_) => {
+                let ptr = gacx.addr_of_static[&did];
+                if !ptr.is_none() {
+                    gasn.flags[ptr].insert(FlagSet::FIXED);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    // For testing, putting #[c2rust_analyze_test::fixed_signature] on an item sets all pointers
+    // in its type to `FIXED`.  Unlike `#[c2rust_analyze::fixed]`, this still allows rewriting the
+    // body of a function.
     for ldid in tcx.hir_crate_items(()).definitions() {
-        let def_fixed = fixed_defs.contains(&ldid.to_def_id())
-            || util::has_test_attr(tcx, ldid, TestAttr::FixedSignature);
+        let def_fixed = util::has_test_attr(tcx, ldid, TestAttr::FixedSignature);
         match tcx.def_kind(ldid.to_def_id()) {
             DefKind::Fn | DefKind::AssocFn if def_fixed => {
                 let lsig = match gacx.fn_sigs.get(&ldid.to_def_id()) {
@@ -914,11 +935,10 @@ fn run(tcx: TyCtxt) {
             DefKind::Struct | DefKind::Enum | DefKind::Union => {
                 let adt_def = tcx.adt_def(ldid);
                 for field in adt_def.all_fields() {
-                    // Each field can be separately listed in `fixed_defs` or annotated with the
-                    // attribute to cause it to be marked FIXED.  If the whole ADT is
-                    // listed/annotated, then every field is marked FIXED.
+                    // Each field can be separately annotated with the attribute to cause it to be
+                    // marked FIXED.  If the whole ADT is annotated, then every field is marked
+                    // FIXED.
                     let field_fixed = def_fixed
-                        || fixed_defs.contains(&ldid.to_def_id())
                         || field.did.as_local().map_or(false, |ldid| {
                             util::has_test_attr(tcx, ldid, TestAttr::FixedSignature)
                         });
@@ -1045,6 +1065,61 @@ fn run(tcx: TyCtxt) {
     }
     eprintln!("reached fixpoint in {} iterations", loop_count);
 
+    // Mark the pointers that own their allocations, which will be rewritten to `Box` if they're
+    // freed.  This depends on the flow of pointer values across all functions, so it's done once
+    // the permissions are final.  Pointers that are freed but don't own their allocations keep
+    // their raw pointer types, since there's no safe type for them.
+    let dataflow_ldids = all_fn_ldids
+        .iter()
+        .copied()
+        .filter(|ldid| !gacx.fn_failed(ldid.to_def_id()))
+        .collect::<Vec<_>>();
+    let dataflows = dataflow_ldids
+        .iter()
+        .map(|ldid| &*func_info[ldid].dataflow)
+        .collect::<Vec<_>>();
+    let summarized_heap_ptrs = gasn
+        .flags
+        .iter()
+        .filter(|(_, flags)| flags.contains(FlagSet::HEAP))
+        .map(|(ptr, _)| ptr)
+        .collect::<Vec<_>>();
+    let flow = dataflow::PointerFlow::new(&dataflows);
+    let heap_ptrs = flow.heap_pointers(summarized_heap_ptrs);
+    // There's no safe way to turn a raw pointer into a slice of unknown length either, so the
+    // pointers that step through the value of such a freed pointer with offsets stay raw too.
+    let perms_of = |(i, ptr): FlowNode| match i {
+        None => gasn.perms[ptr],
+        Some(i) => func_info[&dataflow_ldids[i]].lasn.perms[ptr],
+    };
+    let global_nodes = gasn.perms.iter().map(|(ptr, _)| (None, ptr));
+    let local_nodes = dataflow_ldids.iter().enumerate().flat_map(|(i, ldid)| {
+        let lasn: &LocalAssignment = &func_info[ldid].lasn;
+        lasn.perms.iter().map(move |(ptr, _)| (Some(i), ptr))
+    });
+    let freed_raw_ptrs = global_nodes
+        .chain(local_nodes)
+        .filter(|&n| perms_of(n).contains(PermissionSet::FREE) && !heap_ptrs.contains(&n))
+        .collect::<Vec<_>>();
+    let raw_ptrs = flow.reachable(freed_raw_ptrs, |n| {
+        perms_of(n).intersects(PermissionSet::OFFSET_ADD | PermissionSet::OFFSET_SUB)
+    });
+    for (ptr, flags) in gasn.flags.iter_mut() {
+        flags.set(FlagSet::HEAP, heap_ptrs.contains(&(None, ptr)));
+        if raw_ptrs.contains(&(None, ptr)) {
+            flags.insert(FlagSet::FIXED);
+        }
+    }
+    for (i, ldid) in dataflow_ldids.iter().enumerate() {
+        let lasn: &mut LocalAssignment = &mut func_info.get_mut(ldid).unwrap().lasn;
+        for (ptr, flags) in lasn.flags.iter_mut() {
+            flags.set(FlagSet::HEAP, heap_ptrs.contains(&(Some(i), ptr)));
+            if raw_ptrs.contains(&(Some(i), ptr)) {
+                flags.insert(FlagSet::FIXED);
+            }
+        }
+    }
+
     // Do final processing on each function.
     for &ldid in &all_fn_ldids {
         if gacx.fn_failed(ldid.to_def_id()) {
@@ -1102,6 +1177,20 @@ fn run(tcx: TyCtxt) {
         }
     }
 
+    // Summarized pointers can't change either, since the crates they come from won't be rewritten
+    // again.  If this crate needs something different, we keep the summarized permissions, so that
+    // the call sites are rewritten to match the actual signatures.
+    for (ptr, perms, flags) in summaries.ptr_perms(&gacx) {
+        if gasn.perms[ptr] != perms || gasn.flags[ptr] != flags {
+            warn!(
+                "summarized permissions changed for PointerId {ptr:?}: {perms:?}, {flags:?} -> {:?}, {:?}",
+                gasn.perms[ptr], gasn.flags[ptr]
+            );
+            gasn.perms[ptr] = perms;
+            gasn.flags[ptr] = flags;
+        }
+    }
+
     // ----------------------------------
     // Generate rewrites
     // ----------------------------------
@@ -1184,7 +1273,7 @@ fn run(tcx: TyCtxt) {
                 if util::has_test_attr(tcx, ldid, TestAttr::SkipRewrite) {
                     return;
                 }
-                if fixed_defs.contains(&ldid.to_def_id()) {
+                if annotations.is_fixed(ldid.to_def_id()) {
                     return;
                 }
 
@@ -1248,7 +1337,7 @@ fn run(tcx: TyCtxt) {
     // Generate rewrites for statics
     let mut static_rewrites = Vec::new();
     for (&def_id, &ptr) in gacx.addr_of_static.iter() {
-        if fixed_defs.contains(&def_id) {
+        if annotations.is_fixed(def_id) {
             continue;
         }
         static_rewrites.extend(rewrite::gen_static_rewrites(tcx, &gasn, def_id, ptr));
@@ -1274,11 +1363,14 @@ fn run(tcx: TyCtxt) {
     // Generate rewrites for ADTs
     let mut adt_reports = HashMap::<DefId, String>::new();
     for &def_id in gacx.adt_metadata.table.keys() {
+        if !def_id.is_local() {
+            continue;
+        }
         if gacx.foreign_mentioned_tys.contains(&def_id) {
             eprintln!("Avoiding rewrite for foreign-mentioned type: {def_id:?}");
             continue;
         }
-        if fixed_defs.contains(&def_id) {
+        if annotations.is_fixed(def_id) {
             continue;
         }
 
@@ -1306,16 +1398,19 @@ fn run(tcx: TyCtxt) {
     // filter based on membership in `func_info`, which contains an entry for each ID in
     // `all_fn_ldids`.
     for ldid in tcx.hir().body_owners() {
+        if let Some(detail) = gacx.fns_failed.get(&ldid.to_def_id()) {
+            if let Some(ref mut results) = results {
+                results.add_failed_fn(tcx, ldid.to_def_id(), detail);
+            }
+            continue;
+        }
+
         // Skip any body owners that aren't present in `func_info`, and also get the info itself.
         let info = match func_info.get_mut(&ldid) {
             Some(x) => x,
             None => continue,
         };
 
-        if gacx.fn_failed(ldid.to_def_id()) {
-            continue;
-        }
-
         let ldid_const = WithOptConstParam::unknown(ldid);
         let name = tcx.item_name(ldid.to_def_id());
         let mir = tcx.mir_built(ldid_const);
@@ -1323,6 +1418,10 @@ fn run(tcx: TyCtxt) {
         let acx = gacx.function_context_with_data(&mir, info.acx_data.take());
         let asn = gasn.and(&mut info.lasn);
 
+        if let Some(ref mut results) = results {
+            results.add_fn(&acx, &asn, &mir);
+        }
+
         // Print labeling and rewrites for the current function.
 
         eprintln!("\nfinal labeling for {:?}:", name);
@@ -1367,6 +1466,9 @@ fn run(tcx: TyCtxt) {
             &gasn.perms,
             &gasn.flags,
         );
+        if let Some(ref mut results) = results {
+            results.add_static(&gacx, &gasn, did);
+        }
     }
     eprintln!("\n{statics_report}");
 
@@ -1375,6 +1477,9 @@ fn run(tcx: TyCtxt) {
     let mut field_dids = gacx.field_ltys.keys().cloned().collect::<Vec<_>>();
     field_dids.sort();
     for did in field_dids {
+        if !did.is_local() {
+            continue;
+        }
         let field_lty = gacx.field_ltys[&did];
         let name = tcx.item_name(did);
         let pid = field_lty.label;
@@ -1383,6 +1488,9 @@ fn run(tcx: TyCtxt) {
             let ty_flags = gasn.flags[pid];
             eprintln!("{name:}: ({pid}) perms = {ty_perms:?}, flags = {ty_flags:?}");
         }
+        if let Some(ref mut results) = results {
+            results.add_field(&gacx, &gasn, did);
+        }
     }
 
     let mut adt_dids = gacx.adt_metadata.table.keys().cloned().collect::<Vec<_>>();
@@ -1393,12 +1501,26 @@ fn run(tcx: TyCtxt) {
         }
     }
 
+    if let (Some(results), Some(path)) = (results, results_path) {
+        results
+            .write(&path)
+            .unwrap_or_else(|e| panic!("failed to write analysis results to {}: {}", path, e));
+    }
+
+    if let Some(ref dir) = summaries_dir {
+        let fn_dids = all_fn_ldids.iter().map(|ldid| ldid.to_def_id());
+        CrateSummary::new(&gacx, &gasn, fn_dids)
+            .write(tcx, dir)
+            .unwrap_or_else(|e| panic!("failed to write crate summary to {:?}: {}", dir, e));
+    }
+
     // ----------------------------------
     // Apply rewrites
     // ----------------------------------
 
     // Apply rewrite to all functions at once.
-    rewrite::apply_rewrites(tcx, all_rewrites);
+    rewrite::apply_rewrites(tcx, all_rewrites, &rewrite_modes)
+        .unwrap_or_else(|e| panic!("failed to output rewrites: {}", e));
 
     // ----------------------------------
     // Report caught panics
@@ -1744,6 +1866,30 @@ impl rustc_driver::Callbacks for AnalysisCallbacks {
     }
 }
 
+/// Remove any leading `--rewrite-mode <modes>` (or `--rewrite-mode=<modes>`) options from `args`,
+/// storing their value in `$C2RUST_ANALYZE_REWRITE_MODE`.  The analysis reads the modes from
+/// there, which also passes them on to the `rustc` wrappers when running under `cargo`.  The
+/// variable can also be set directly, but the option takes precedence.
+fn take_rewrite_mode_arg(args: &mut Vec<String>) {
+    while let Some(arg) = args.get(1) {
+        let modes = if let Some(modes) = arg.strip_prefix("--rewrite-mode=") {
+            let modes = modes.to_owned();
+            args.remove(1);
+            modes
+        } else if arg == "--rewrite-mode" && args.len() > 2 {
+            args.remove(1);
+            args.remove(1)
+        } else {
+            break;
+        };
+        if let Err(e) = rewrite::OutputMode::parse_list(&modes) {
+            eprintln!("error: invalid --rewrite-mode `{modes}`: {e}");
+            process::exit(1);
+        }
+        env::set_var("C2RUST_ANALYZE_REWRITE_MODE", modes);
+    }
+}
+
 fn main() -> rustc_interface::interface::Result<()> {
     init_logger();
 
@@ -1752,7 +1898,15 @@ fn main() -> rustc_interface::interface::Result<()> {
         panic_detail::set_hook();
     }
 
-    let args = env::args().collect::<Vec<_>>();
+    let mut args = env::args().collect::<Vec<_>>();
+
+    if cargo_wrapper::is_rustc_wrapper() {
+        return cargo_wrapper::rustc_wrapper(&args[1..]);
+    }
+    take_rewrite_mode_arg(&mut args);
+    if args.get(1).map(String::as_str) == Some("--") {
+        cargo_wrapper::cargo_wrapper(&args[2..]);
+    }
 
     rustc_driver::RunCompiler::new(&args, &mut AnalysisCallbacks).run()
 }
@@ -33,6 +33,11 @@ impl PanicDetail {
         self.backtrace.is_some()
     }
 
+    /// The source location being analyzed when the panic occurred, or `DUMMY_SP` if unknown.
+    pub fn span(&self) -> Span {
+        self.span
+    }
+
     /// Return a short (usually one-line) description of this panic.
     pub fn to_string_short(&self) -> String {
         let loc_str = self
@@ -1,13 +1,17 @@
 use super::constraint_set::{CTy, ConstraintSet};
 use crate::context::{AnalysisCtxt, LTy, PointerId};
 use crate::panic_detail;
-use crate::util::{describe_rvalue, ty_callee, Callee, RvalueDesc, UnknownDefCallee};
+use crate::util::{
+    describe_rvalue, printf_format_arg, ty_callee, Callee, RvalueDesc, UnknownDefCallee,
+};
 use log::*;
+use rustc_hir::def_id::DefId;
 use rustc_middle::mir::{
-    BinOp, Body, Location, Operand, Place, PlaceRef, ProjectionElem, Rvalue, Statement,
+    BinOp, Body, CastKind, Location, Operand, Place, PlaceRef, ProjectionElem, Rvalue, Statement,
     StatementKind, Terminator, TerminatorKind,
 };
-use rustc_middle::ty::{Ty, TyKind};
+use rustc_middle::ty::adjustment::PointerCast;
+use rustc_middle::ty::{SubstsRef, Ty, TyKind};
 
 struct TypeChecker<'tcx, 'a> {
     acx: &'a AnalysisCtxt<'a, 'tcx>,
@@ -122,11 +126,15 @@ impl<'tcx> TypeChecker<'tcx, '_> {
             Rvalue::Len(pl) => {
                 self.visit_place(pl);
             }
-            Rvalue::Cast(_kind, ref op, _ty) => {
+            Rvalue::Cast(kind, ref op, _ty) => {
                 self.visit_operand(op);
 
                 let op_lty = self.acx.type_of(op);
                 self.assign(lty.label, op_lty.label);
+
+                if kind == CastKind::Pointer(PointerCast::ReifyFnPointer) {
+                    self.visit_reify_fn_ptr(op_lty.ty, lty);
+                }
             }
             Rvalue::BinaryOp(bin_op, ref ops) | Rvalue::CheckedBinaryOp(bin_op, ref ops) => {
                 assert_ne!(bin_op, BinOp::Offset, "BinOp::Offset special case NYI");
@@ -194,7 +202,6 @@ impl<'tcx> TypeChecker<'tcx, '_> {
             term.source_info.span
         );
         let _g = panic_detail::set_current_span(term.source_info.span);
-        let tcx = self.acx.tcx();
 
         match term.kind {
             TerminatorKind::Call {
@@ -209,39 +216,47 @@ impl<'tcx> TypeChecker<'tcx, '_> {
                 }
                 let dest_lty = self.visit_place(destination);
 
-                let func = func.ty(self.mir, tcx);
                 self.visit_call(func, args, dest_lty);
             }
             _ => (),
         }
     }
 
-    pub fn visit_call(&mut self, func: Ty<'tcx>, args: &[Operand<'tcx>], dest_lty: LTy<'tcx>) {
+    /// Connect the signature of the `fn` item `fn_def_ty` to `fn_ptr_lty`, the type of a `fn` ptr
+    /// it's converted to.  Arguments passed through the `fn` ptr flow into the `fn`'s arguments,
+    /// and its return value flows back out.
+    fn visit_reify_fn_ptr(&mut self, fn_def_ty: Ty<'tcx>, fn_ptr_lty: LTy<'tcx>) {
+        let def_id = match *fn_def_ty.kind() {
+            TyKind::FnDef(def_id, _) => def_id,
+            _ => panic!("expected a fn item type, but got {fn_def_ty:?}"),
+        };
+        let sig = match self.acx.gacx.fn_sigs.get(&def_id) {
+            Some(sig) => sig,
+            None => return,
+        };
+        let (&output_lty, input_ltys) = fn_ptr_lty
+            .args
+            .split_last()
+            .expect("fn ptr type should have a return type");
+        for (&sig_lty, &arg_lty) in sig.inputs.iter().zip(input_ltys) {
+            self.assign(sig_lty.label, arg_lty.label);
+        }
+        self.assign(output_lty.label, sig.output.label);
+    }
+
+    pub fn visit_call(
+        &mut self,
+        func: &Operand<'tcx>,
+        args: &[Operand<'tcx>],
+        dest_lty: LTy<'tcx>,
+    ) {
         let tcx = self.acx.tcx();
-        let callee = ty_callee(tcx, func);
+        let callee = ty_callee(tcx, func.ty(self.mir, tcx));
         eprintln!("callee = {callee:?}");
         match callee {
             Callee::Trivial => {}
             Callee::LocalDef { def_id, substs } => {
-                let sig = self
-                    .acx
-                    .gacx
-                    .fn_sigs
-                    .get(&def_id)
-                    .unwrap_or_else(|| panic!("LFnSig not found for {def_id:?}"));
-                if substs.non_erasable_generics().next().is_some() {
-                    todo!("call to generic function {def_id:?} {substs:?}");
-                }
-
-                // Process pseudo-assignments from `args` to the types declared in `sig`.
-                for (arg_op, &input_lty) in args.iter().zip(sig.inputs.iter()) {
-                    let arg_lty = self.acx.type_of(arg_op);
-                    self.assign(input_lty.label, arg_lty.label);
-                }
-
-                // Process a pseudo-assignment from the return type declared in `sig` to `dest`.
-                let output_lty = sig.output;
-                self.assign(dest_lty.label, output_lty.label);
+                self.visit_local_call(def_id, substs, args, dest_lty);
             }
             Callee::UnknownDef(UnknownDefCallee::Direct {
                 ty: _,
@@ -252,9 +267,41 @@ impl<'tcx> TypeChecker<'tcx, '_> {
                 // TODO: no good handling for this currently - might need to expand KnownFn to
                 // include information about expected/required pointee types
             }
+            Callee::UnknownDef(UnknownDefCallee::Direct {
+                ty: _,
+                def_id,
+                substs: _,
+                is_foreign: true,
+            }) if printf_format_arg(tcx.item_name(def_id).as_str()).is_some() => {
+                // `printf`-style functions only read their arguments, so they place no
+                // constraints on pointee types.
+            }
+            Callee::UnknownDef(UnknownDefCallee::Direct {
+                ty: _,
+                def_id,
+                substs,
+                is_foreign: false,
+            }) if self.acx.gacx.fn_sigs.contains_key(&def_id) => {
+                // A function in another crate, whose signature we loaded from that crate's
+                // summary.  This works just like a local call.
+                self.visit_local_call(def_id, substs, args, dest_lty);
+            }
+            Callee::UnknownDef(UnknownDefCallee::Indirect { .. }) => {
+                // A call through a `fn` ptr works like a local call, using the `fn` ptr type's
+                // arguments as the signature.
+                let func_lty = self.acx.type_of(func);
+                let (&output_lty, input_ltys) = func_lty
+                    .args
+                    .split_last()
+                    .expect("fn ptr type should have a return type");
+                self.visit_call_with_sig(input_ltys, output_lty, args, dest_lty);
+            }
             Callee::UnknownDef(_) => {
                 log::error!("TODO: visit Callee::{callee:?}");
             }
+            Callee::UnwrapFnPtr { .. } => {
+                // No constraints.  The `fn` ptr itself has no pointee type.
+            }
 
             Callee::PtrOffset { .. } => {
                 // Normal uses of `offset` don't change the pointee type but only step
@@ -271,6 +318,11 @@ impl<'tcx> TypeChecker<'tcx, '_> {
                 self.assign(dest_lty.label, arg_lty.label);
             }
 
+            Callee::PtrOffsetFrom { .. } => {
+                // `offset_from` only compares addresses, so it places no constraints on the
+                // pointee types.
+            }
+
             Callee::SliceAsPtr { .. } => {
                 // The input is a `Ref`, so its underlying type is known precisely.
                 assert_eq!(args.len(), 1);
@@ -331,8 +383,48 @@ impl<'tcx> TypeChecker<'tcx, '_> {
             Callee::IsNull => {
                 // No constraints.
             }
+            Callee::Null { .. } => {
+                // No constraints.  A null pointer can be given any pointee type.
+            }
         }
     }
+
+    fn visit_local_call(
+        &mut self,
+        def_id: DefId,
+        substs: SubstsRef<'tcx>,
+        args: &[Operand<'tcx>],
+        dest_lty: LTy<'tcx>,
+    ) {
+        let sig = self
+            .acx
+            .gacx
+            .fn_sigs
+            .get(&def_id)
+            .unwrap_or_else(|| panic!("LFnSig not found for {def_id:?}"));
+        if substs.non_erasable_generics().next().is_some() {
+            todo!("call to generic function {def_id:?} {substs:?}");
+        }
+
+        self.visit_call_with_sig(sig.inputs, sig.output, args, dest_lty);
+    }
+
+    fn visit_call_with_sig(
+        &mut self,
+        input_ltys: &[LTy<'tcx>],
+        output_lty: LTy<'tcx>,
+        args: &[Operand<'tcx>],
+        dest_lty: LTy<'tcx>,
+    ) {
+        // Process pseudo-assignments from `args` to the types declared in the signature.
+        for (arg_op, &input_lty) in args.iter().zip(input_ltys.iter()) {
+            let arg_lty = self.acx.type_of(arg_op);
+            self.assign(input_lty.label, arg_lty.label);
+        }
+
+        // Process a pseudo-assignment from the declared return type to `dest`.
+        self.assign(dest_lty.label, output_lty.label);
+    }
 }
 
 pub fn visit<'tcx>(acx: &AnalysisCtxt<'_, 'tcx>, mir: &Body<'tcx>) -> ConstraintSet<'tcx> {
@@ -1,9 +1,9 @@
 use crate::rewrite::Rewrite;
+use rustc_data_structures::sync::Lrc;
 use rustc_hir::Mutability;
-use rustc_span::source_map::{FileName, SourceMap};
+use rustc_span::source_map::SourceMap;
 use rustc_span::{BytePos, SourceFile, Span, SyntaxContext};
 use std::cmp::Reverse;
-use std::collections::HashMap;
 use std::convert::Infallible;
 use std::fmt;
 use std::mem;
@@ -320,6 +320,15 @@ impl<S: Sink> Emitter<'_, S> {
                 slf.emit_str(" as ")?;
                 slf.emit_str(ty)
             }),
+            // Binary operators all bind more loosely than `as` and the unary and postfix
+            // operators, so we parenthesize in any context other than the top level.  Nested
+            // binary operators are always parenthesized, so we don't need to track their relative
+            // precedence.
+            Rewrite::Binary(ref lhs, ref op, ref rhs) => self.emit_parenthesized(prec > 0, |slf| {
+                slf.emit(lhs, 1)?;
+                slf.emit_fmt(format_args!(" {op} "))?;
+                slf.emit(rhs, 1)
+            }),
             Rewrite::LitZero => self.emit_str("0"),
 
             Rewrite::Print(ref s) => self.emit_str(s),
@@ -346,7 +355,7 @@ impl<S: Sink> Emitter<'_, S> {
                 })
             }
             Rewrite::MethodCall(ref method, ref receiver_rw, ref arg_rws) => {
-                self.emit(receiver_rw, 0)?;
+                self.emit(receiver_rw, 3)?;
                 self.emit_str(".")?;
                 self.emit_str(method)?;
                 self.emit_parenthesized(true, |slf| {
@@ -359,6 +368,10 @@ impl<S: Sink> Emitter<'_, S> {
                     Ok(())
                 })
             }
+            Rewrite::Closure1(ref name, ref body_rw) => self.emit_parenthesized(prec > 0, |slf| {
+                slf.emit_fmt(format_args!("|{name}| "))?;
+                slf.emit(body_rw, 0)
+            }),
             Rewrite::TyPtr(ref rw, mutbl) => {
                 match mutbl {
                     Mutability::Not => self.emit_str("*const ")?,
@@ -532,12 +545,20 @@ impl<'a, F: FnMut(&str)> Sink for RewriteTreeSink<'a, F> {
     }
 }
 
-/// Apply rewrites `rws` to the source files covered by their `Span`s.  Returns a map giving the
-/// rewritten source code for each file that contains at least one rewritten `Span`.
-pub fn apply_rewrites(
-    source_map: &SourceMap,
-    rws: Vec<(Span, Rewrite)>,
-) -> HashMap<FileName, String> {
+/// The result of applying rewrites to a single source file.
+pub struct RewrittenFile {
+    pub file: Lrc<SourceFile>,
+    /// The rewritten source code of the file.
+    pub new_src: String,
+    /// The outermost rewritten `Span`s of the file, in order, along with their new text.  All
+    /// other rewrites are nested within one of these.
+    pub edits: Vec<(Span, String)>,
+}
+
+/// Apply rewrites `rws` to the source files covered by their `Span`s.  Returns the rewritten
+/// source code for each file that contains at least one rewritten `Span`, in the order the files
+/// appear in the `SourceMap`.
+pub fn apply_rewrites(source_map: &SourceMap, rws: Vec<(Span, Rewrite)>) -> Vec<RewrittenFile> {
     let (rts, errs) = RewriteTree::build(rws);
     for (span, rw, err) in errs {
         eprintln!(
@@ -546,7 +567,7 @@ pub fn apply_rewrites(
         );
     }
 
-    let mut new_src = HashMap::new();
+    let mut new_src = Vec::new();
     let mut rts = &rts as &[RewriteTree<Span>];
     while !rts.is_empty() {
         let file = source_map.lookup_source_file(rts[0].span.lo());
@@ -564,7 +585,22 @@ pub fn apply_rewrites(
         let file_span = Span::new(file.start_pos, file.end_pos, SyntaxContext::root(), None);
         sink.emit_span_with_rewrites(file_span, file_rts).unwrap();
 
-        new_src.insert(file.name.clone(), buf);
+        let edits = file_rts
+            .iter()
+            .map(|rt| {
+                let mut text = String::new();
+                let mut emit = |s: &str| text.push_str(s);
+                let mut sink = RewriteTreeSink::new(&file, &mut emit);
+                sink.with_rt(rt, |slf| emit_rewrite(slf, &rt.rw)).unwrap();
+                (rt.span, text)
+            })
+            .collect();
+
+        new_src.push(RewrittenFile {
+            file,
+            new_src: buf,
+            edits,
+        });
     }
 
     new_src
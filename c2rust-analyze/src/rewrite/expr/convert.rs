@@ -169,6 +169,93 @@ impl<'tcx> Visitor<'tcx> for ConvertVisitor<'tcx> {
                     Rewrite::MethodCall("set".to_string(), Box::new(lhs), vec![rhs])
                 }
 
+                mir_op::RewriteKind::NullToNone => {
+                    // `ptr::null()` or `0 as *const T` to `None`
+                    assert!(matches!(hir_rw, Rewrite::Identity));
+                    Rewrite::Text("None".to_string())
+                }
+
+                mir_op::RewriteKind::IsNullToIsNone => {
+                    // `p.is_null()` to `p.is_none()`
+                    assert!(matches!(hir_rw, Rewrite::Identity));
+                    Rewrite::MethodCall(
+                        "is_none".to_string(),
+                        Box::new(self.get_subexpr(ex, 0)),
+                        vec![],
+                    )
+                }
+
+                mir_op::RewriteKind::IsNullToConstFalse => {
+                    // `p.is_null()` to `false`
+                    assert!(matches!(hir_rw, Rewrite::Identity));
+                    Rewrite::Text("false".to_string())
+                }
+
+                mir_op::RewriteKind::AllocBox => {
+                    // `malloc(size)` to `Box::new(Default::default())`
+                    assert!(matches!(hir_rw, Rewrite::Identity));
+                    let default = Rewrite::Call("Default::default".to_string(), vec![]);
+                    Rewrite::Call("Box::new".to_string(), vec![default])
+                }
+
+                mir_op::RewriteKind::MallocBoxSlice { elem_ty } => {
+                    // `malloc(size)` to a `Box<[T]>` of `size / size_of::<T>()` elements
+                    assert!(matches!(hir_rw, Rewrite::Identity));
+                    let size = Rewrite::Cast(Box::new(self.get_subexpr(ex, 0)), "usize".to_owned());
+                    let elem_size =
+                        Rewrite::Call(format!("std::mem::size_of::<{}>", elem_ty), vec![]);
+                    let len = Rewrite::Binary(Box::new(size), "/".to_owned(), Box::new(elem_size));
+                    box_slice_of_defaults(len)
+                }
+
+                mir_op::RewriteKind::CallocBoxSlice => {
+                    // `calloc(n, size)` to a `Box<[T]>` of `n` elements
+                    assert!(matches!(hir_rw, Rewrite::Identity));
+                    let len = Rewrite::Cast(Box::new(self.get_subexpr(ex, 0)), "usize".to_owned());
+                    box_slice_of_defaults(len)
+                }
+
+                mir_op::RewriteKind::FreeToDrop => {
+                    // `free(p as *mut c_void)` to `drop(p)`
+                    assert!(matches!(hir_rw, Rewrite::Identity));
+                    let arg = assert_matches!(ex.kind, ExprKind::Call(_, args) => &args[0]);
+                    let ptr = match arg.kind {
+                        ExprKind::Cast(..) => self.get_subexpr(arg, 0),
+                        _ => self.get_subexpr(ex, 0),
+                    };
+                    Rewrite::Call("drop".to_string(), vec![ptr])
+                }
+
+                mir_op::RewriteKind::StrlenToLen { ref ret_ty } => {
+                    // `strlen(s)` to `s.to_bytes().len() as T`
+                    assert!(matches!(hir_rw, Rewrite::Identity));
+                    let bytes = Rewrite::MethodCall(
+                        "to_bytes".to_string(),
+                        Box::new(self.get_subexpr(ex, 0)),
+                        vec![],
+                    );
+                    let len = Rewrite::MethodCall("len".to_string(), Box::new(bytes), vec![]);
+                    Rewrite::Cast(Box::new(len), ret_ty.clone())
+                }
+
+                mir_op::RewriteKind::StrcmpToCmp { ref ret_ty } => {
+                    // `strcmp(s1, s2)` to `s1.cmp(s2) as T`.  `Ordering` converts to -1, 0, or 1,
+                    // which has the same sign as the result of `strcmp`.
+                    assert!(matches!(hir_rw, Rewrite::Identity));
+                    let cmp = Rewrite::MethodCall(
+                        "cmp".to_string(),
+                        Box::new(self.get_subexpr(ex, 0)),
+                        vec![self.get_subexpr(ex, 1)],
+                    );
+                    Rewrite::Cast(Box::new(cmp), ret_ty.clone())
+                }
+
+                mir_op::RewriteKind::RemoveCast => {
+                    // `e as T` to `e`
+                    assert!(matches!(hir_rw, Rewrite::Identity));
+                    self.get_subexpr(ex, 0)
+                }
+
                 _ => convert_cast_rewrite(rw, hir_rw),
             }
         };
@@ -335,6 +422,102 @@ pub fn convert_cast_rewrite(kind: &mir_op::RewriteKind, hir_rw: Rewrite) -> Rewr
             Rewrite::Cast(Box::new(hir_rw), format!("*const std::cell::Cell<{}>", ty))
         }
 
+        mir_op::RewriteKind::OptionSome => {
+            // `p` to `Some(p)`
+            Rewrite::Call("Some".to_string(), vec![hir_rw])
+        }
+        mir_op::RewriteKind::OptionUnwrap => {
+            // `p` to `p.unwrap()`
+            Rewrite::MethodCall("unwrap".to_string(), Box::new(hir_rw), vec![])
+        }
+        mir_op::RewriteKind::OptionDowngrade { mutbl } => {
+            // `p` to `p.as_deref()` or `p.as_deref_mut()`
+            let method = if mutbl { "as_deref_mut" } else { "as_deref" };
+            Rewrite::MethodCall(method.to_string(), Box::new(hir_rw), vec![])
+        }
+        mir_op::RewriteKind::OptionMap { ref inner } => {
+            // `p` to `p.map(|ptr| ...)`, applying `inner` to `ptr`
+            let mut body = Rewrite::Text("ptr".to_string());
+            for kind in inner {
+                body = convert_cast_rewrite(kind, body);
+            }
+            let closure = Rewrite::Closure1("ptr".to_string(), Box::new(body));
+            Rewrite::MethodCall("map".to_string(), Box::new(hir_rw), vec![closure])
+        }
+        mir_op::RewriteKind::OptionUnwrapOrNull { mutbl } => {
+            // `p` to `p.unwrap_or(core::ptr::null())`
+            let null_fn = if mutbl {
+                "core::ptr::null_mut"
+            } else {
+                "core::ptr::null"
+            };
+            let null = Rewrite::Call(null_fn.to_string(), vec![]);
+            Rewrite::MethodCall("unwrap_or".to_string(), Box::new(hir_rw), vec![null])
+        }
+        mir_op::RewriteKind::BoxToRef { mutbl } => {
+            // `p` to `&*p` or `&mut *p`
+            let place = Rewrite::Deref(Box::new(hir_rw));
+            Rewrite::Ref(Box::new(place), mutbl_from_bool(mutbl))
+        }
+        mir_op::RewriteKind::UnsafeCastRawToOptionRef { mutbl } => {
+            // `p` to `p.as_ref()` or `p.as_mut()`
+            let method = if mutbl { "as_mut" } else { "as_ref" };
+            Rewrite::MethodCall(method.to_string(), Box::new(hir_rw), vec![])
+        }
+        mir_op::RewriteKind::OptionAsRef { mutbl } => {
+            // `p` to `p.as_ref()` or `p.as_mut()`
+            let method = if mutbl { "as_mut" } else { "as_ref" };
+            Rewrite::MethodCall(method.to_string(), Box::new(hir_rw), vec![])
+        }
+
+        mir_op::RewriteKind::SliceToOffsetPtr { mutbl } => {
+            // `s` to `OffsetPtr::new(s)` or `OffsetPtrMut::new(s)`
+            let func = if mutbl {
+                "c2rust_support::OffsetPtrMut::new"
+            } else {
+                "c2rust_support::OffsetPtr::new"
+            };
+            Rewrite::Call(func.to_string(), vec![hir_rw])
+        }
+        mir_op::RewriteKind::OffsetPtrToSlice { mutbl } => {
+            // `p` to `p.as_slice()` or `p.as_mut_slice()`
+            let method = if mutbl { "as_mut_slice" } else { "as_slice" };
+            Rewrite::MethodCall(method.to_string(), Box::new(hir_rw), vec![])
+        }
+        mir_op::RewriteKind::OffsetPtrGet { mutbl } => {
+            // `p` to `p.get()` or `p.get_mut()`
+            let method = if mutbl { "get_mut" } else { "get" };
+            Rewrite::MethodCall(method.to_string(), Box::new(hir_rw), vec![])
+        }
+        mir_op::RewriteKind::OffsetPtrMutToImm => {
+            // `p` to `p.as_imm()`
+            Rewrite::MethodCall("as_imm".to_string(), Box::new(hir_rw), vec![])
+        }
+        mir_op::RewriteKind::OffsetPtrReborrow => {
+            // `p` to `p.reborrow()`
+            Rewrite::MethodCall("reborrow".to_string(), Box::new(hir_rw), vec![])
+        }
+
+        mir_op::RewriteKind::CStrToChars { ref elem_ty } => {
+            // `s` to `cstr_to_chars::<T>(s)`
+            let func = format!("c2rust_support::cstr_to_chars::<{}>", elem_ty);
+            Rewrite::Call(func, vec![hir_rw])
+        }
+        mir_op::RewriteKind::CStrFromChars => {
+            // `s` to `cstr_from_chars(s)`
+            Rewrite::Call("c2rust_support::cstr_from_chars".to_string(), vec![hir_rw])
+        }
+        mir_op::RewriteKind::UnsafeCastRawToCStr => {
+            // `p` to `CStr::from_ptr(p as *const _)`
+            let ptr = Rewrite::Cast(Box::new(hir_rw), "*const _".to_string());
+            Rewrite::Call("core::ffi::CStr::from_ptr".to_string(), vec![ptr])
+        }
+        mir_op::RewriteKind::CStrAsPtr { ref elem_ty } => {
+            // `s` to `s.as_ptr() as *const T`
+            let ptr = Rewrite::MethodCall("as_ptr".to_string(), Box::new(hir_rw), vec![]);
+            Rewrite::Cast(Box::new(ptr), format!("*const {}", elem_ty))
+        }
+
         _ => panic!(
             "rewrite {:?} is not supported by convert_cast_rewrite",
             kind
@@ -342,6 +525,15 @@ pub fn convert_cast_rewrite(kind: &mir_op::RewriteKind, hir_rw: Rewrite) -> Rewr
     }
 }
 
+/// Build `std::iter::repeat_with(Default::default).take(len).collect::<Box<[_]>>()`, a boxed
+/// slice of `len` default values.
+fn box_slice_of_defaults(len: Rewrite) -> Rewrite {
+    let default = Rewrite::Text("Default::default".to_string());
+    let iter = Rewrite::Call("std::iter::repeat_with".to_string(), vec![default]);
+    let iter = Rewrite::MethodCall("take".to_string(), Box::new(iter), vec![len]);
+    Rewrite::MethodCall("collect::<Box<[_]>>".to_string(), Box::new(iter), vec![])
+}
+
 /// Convert the MIR rewrites attached to each HIR node into `Span`-based `rewrite::Rewrite`s.
 pub fn convert_rewrites(
     tcx: TyCtxt,
@@ -7,16 +7,18 @@
 //! all adjustments, as this would make even non-rewritten code extremely verbose, so we try to
 //! materialize adjustments only on code that's subject to some rewrite.
 
+use crate::c_void_casts::{self, is_c_void_ptr, CVoidCastDirection};
 use crate::context::{AnalysisCtxt, Assignment, FlagSet, LTy, PermissionSet};
 use crate::panic_detail;
 use crate::pointer_id::{PointerId, PointerTable};
 use crate::type_desc::{self, Ownership, Quantity, TypeDesc};
-use crate::util::{ty_callee, Callee};
+use crate::util::{ty_callee, Callee, UnknownDefCallee};
 use log::*;
 use rustc_ast::Mutability;
+use rustc_hir::def_id::DefId;
 use rustc_middle::mir::{
-    BasicBlock, Body, Location, Operand, Place, Rvalue, Statement, StatementKind, Terminator,
-    TerminatorKind,
+    BasicBlock, BinOp, Body, CastKind, Location, Operand, Place, ProjectionElem, Rvalue, Statement,
+    StatementKind, Terminator, TerminatorKind,
 };
 use rustc_middle::ty::print::FmtPrinter;
 use rustc_middle::ty::print::Print;
@@ -79,6 +81,75 @@ pub enum RewriteKind {
     CellFromMut,
     /// `x` to `x.as_ptr()`
     AsPtr,
+
+    /// Replace a null pointer constant, such as `0 as *const T` or `ptr::null()`, with `None`.
+    NullToNone,
+    /// Replace `ptr.is_null()` with `ptr.is_none()`.
+    IsNullToIsNone,
+    /// Replace `ptr.is_null()` with `false`, for a pointer that is never null.
+    IsNullToConstFalse,
+    /// Wrap `ptr` in `Some(ptr)`.
+    OptionSome,
+    /// Replace `ptr` with `ptr.unwrap()`.
+    OptionUnwrap,
+    /// Replace `ptr` with `ptr.as_deref()` or `ptr.as_deref_mut()`.  This is used to reborrow the
+    /// pointer in an `Option<&mut T>` rather than moving it out.
+    OptionDowngrade { mutbl: bool },
+    /// Apply the casts in `inner` to the pointer inside an `Option`, as in
+    /// `ptr.map(|ptr| &*ptr)`.
+    OptionMap { inner: Vec<RewriteKind> },
+    /// Replace `ptr` with `ptr.unwrap_or(core::ptr::null())` or
+    /// `ptr.unwrap_or(core::ptr::null_mut())`, converting `Option<*const T>` to `*const T`.
+    OptionUnwrapOrNull { mutbl: bool },
+    /// Cast `*const T` to `Option<&T>` or `*mut T` to `Option<&mut T>`.
+    UnsafeCastRawToOptionRef { mutbl: bool },
+
+    /// Replace `malloc(size)` or `calloc(n, size)` with `Box::new(Default::default())`.
+    AllocBox,
+    /// Replace `malloc(size)` with a `Box<[T]>` of `size / size_of::<T>()` default elements.
+    MallocBoxSlice { elem_ty: String },
+    /// Replace `calloc(n, size)` with a `Box<[T]>` of `n` default elements.
+    CallocBoxSlice,
+    /// Replace `free(p as *mut c_void)` with `drop(p)`.
+    FreeToDrop,
+    /// Replace `e as T` with `e`.  Used to remove `void*` casts around allocations that are
+    /// rewritten to `Box`.
+    RemoveCast,
+    /// Borrow the contents of a `Box`: `&*p` or `&mut *p`.
+    BoxToRef { mutbl: bool },
+
+    /// Convert a slice to an `OffsetPtr` pointing at its first element:
+    /// `c2rust_support::OffsetPtr::new(s)` or `c2rust_support::OffsetPtrMut::new(s)`.
+    SliceToOffsetPtr { mutbl: bool },
+    /// Replace `p` with `p.as_slice()` or `p.as_mut_slice()`, converting an `OffsetPtr` to the
+    /// slice starting at its current position.
+    OffsetPtrToSlice { mutbl: bool },
+    /// Replace `p` with `p.get()` or `p.get_mut()`, converting an `OffsetPtr` to a reference to
+    /// the element it points to.
+    OffsetPtrGet { mutbl: bool },
+    /// Replace `p` with `p.as_imm()`, converting `OffsetPtrMut` to `OffsetPtr`.
+    OffsetPtrMutToImm,
+    /// Replace `p` with `p.reborrow()`.  `OffsetPtrMut` isn't `Copy`, so this is used to avoid
+    /// moving out of `p` when offsetting it.
+    OffsetPtrReborrow,
+    /// Replace `p` with `p.as_ref()` or `p.as_mut()`.  This is used to borrow the pointer in an
+    /// `Option<OffsetPtrMut>`, which has no `as_deref_mut`.
+    OptionAsRef { mutbl: bool },
+
+    /// Convert a `&CStr` to the slice of its characters, including the NUL terminator:
+    /// `c2rust_support::cstr_to_chars::<T>(s)`.
+    CStrToChars { elem_ty: String },
+    /// Convert a slice of characters to the `&CStr` that ends at its first NUL:
+    /// `c2rust_support::cstr_from_chars(s)`.
+    CStrFromChars,
+    /// Cast `*const c_char` to `&CStr`: `core::ffi::CStr::from_ptr(p as *const _)`.
+    UnsafeCastRawToCStr,
+    /// Convert a `&CStr` back to a raw pointer to its first character: `s.as_ptr() as *const T`.
+    CStrAsPtr { elem_ty: String },
+    /// Replace `strlen(s)` with `s.to_bytes().len() as T`, where `s` is a `&CStr`.
+    StrlenToLen { ret_ty: String },
+    /// Replace `strcmp(s1, s2)` with `s1.cmp(s2) as T`, where `s1` and `s2` are `&CStr`s.
+    StrcmpToCmp { ret_ty: String },
 }
 
 #[derive(Clone, PartialEq, Eq, Debug)]
@@ -147,13 +218,11 @@ impl<'a, 'tcx> ExprRewriteVisitor<'a, 'tcx> {
         self.enter(SubLoc::RvaluePlace(i), f)
     }
 
-    #[allow(dead_code)]
-    fn _enter_operand_place<F: FnOnce(&mut Self) -> R, R>(&mut self, f: F) -> R {
+    fn enter_operand_place<F: FnOnce(&mut Self) -> R, R>(&mut self, f: F) -> R {
         self.enter(SubLoc::OperandPlace, f)
     }
 
-    #[allow(dead_code)]
-    fn _enter_place_pointer<F: FnOnce(&mut Self) -> R, R>(&mut self, i: usize, f: F) -> R {
+    fn enter_place_pointer<F: FnOnce(&mut Self) -> R, R>(&mut self, i: usize, f: F) -> R {
         self.enter(SubLoc::PlacePointer(i), f)
     }
 
@@ -170,9 +239,20 @@ impl<'a, 'tcx> ExprRewriteVisitor<'a, 'tcx> {
                     // This is a cast to or from `void*` associated with a `malloc`, `free`, or
                     // other libc call.
                     //
-                    // TODO: we should probably emit a rewrite here to remove the cast; then when
-                    // we implement rewriting of the actual call, it won't need to deal with
-                    // additional rewrites at a separate location from the call itself.
+                    // An allocation that is rewritten to `Box` produces the right type already, so
+                    // its cast from `void*` is removed.  Casts to `void*` are handled as part of
+                    // the call, as in `free`.
+                    let pl_lty = self.acx.type_of(pl);
+                    let is_alloc_cast = !is_c_void_ptr(self.acx.tcx(), pl_lty.ty)
+                        && c_void_casts::source_place(rv)
+                            .map_or(false, |src| self.is_alloc_result(src));
+                    if is_alloc_cast {
+                        if let Some(desc) = self.lty_desc(pl_lty) {
+                            if desc.own == Ownership::Box {
+                                self.enter_rvalue(|v| v.emit(RewriteKind::RemoveCast));
+                            }
+                        }
+                    }
                     return;
                 }
 
@@ -265,7 +345,7 @@ impl<'a, 'tcx> ExprRewriteVisitor<'a, 'tcx> {
                 ref func,
                 ref args,
                 destination,
-                target: _,
+                target,
                 ..
             } => {
                 let func_ty = func.ty(self.mir, tcx);
@@ -274,36 +354,92 @@ impl<'a, 'tcx> ExprRewriteVisitor<'a, 'tcx> {
                 // Special cases for particular functions.
                 match ty_callee(tcx, func_ty) {
                     Callee::PtrOffset { .. } => {
-                        self.visit_ptr_offset(&args[0], pl_ty);
+                        self.visit_ptr_offset(&args[0], pl_ty, destination, target);
+                    }
+                    Callee::PtrOffsetFrom { .. } => {
+                        self.visit_ptr_offset_from(args);
                     }
                     Callee::SliceAsPtr { elem_ty, .. } => {
                         self.visit_slice_as_ptr(elem_ty, &args[0], pl_ty);
                     }
 
-                    Callee::LocalDef { def_id, substs: _ } => {
-                        // TODO: handle substs (if nonempty)
-                        if let Some(lsig) = self.acx.gacx.fn_sigs.get(&def_id) {
-                            self.enter_rvalue(|v| {
-                                for (i, op) in args.iter().enumerate() {
-                                    if let Some(&lty) = lsig.inputs.get(i) {
-                                        v.enter_call_arg(i, |v| v.visit_operand(op, Some(lty)));
-                                    } else {
-                                        // This is a call to a variadic function, and we've gone
-                                        // past the end of the declared arguments.
-                                        // TODO: insert a cast to turn `op` back into its original
-                                        // declared type (i.e. upcast the chosen reference type
-                                        // back to a raw pointer)
-                                        continue;
-                                    }
-                                }
+                    Callee::IsNull => {
+                        self.visit_is_null(&args[0]);
+                    }
 
-                                if !pl_ty.label.is_none() {
-                                    v.emit_cast_lty_lty(lsig.output, pl_ty);
+                    callee @ (Callee::Malloc | Callee::Calloc) => {
+                        let out_ptr = self.acx.c_void_casts.get_adjusted_place_or_default_to(
+                            loc,
+                            CVoidCastDirection::From,
+                            destination,
+                        );
+                        let out_lty = self.acx.type_of(out_ptr);
+                        if let Some(desc) = self.lty_desc(out_lty) {
+                            if desc.own == Ownership::Box {
+                                self.visit_alloc_box(callee, desc);
+                            }
+                        }
+                    }
+
+                    Callee::Free => {
+                        if let Some(in_ptr) = args[0].place() {
+                            let in_ptr = self.acx.c_void_casts.get_adjusted_place_or_default_to(
+                                loc,
+                                CVoidCastDirection::To,
+                                in_ptr,
+                            );
+                            let in_lty = self.acx.type_of(in_ptr);
+                            if let Some(desc) = self.lty_desc(in_lty) {
+                                if desc.own == Ownership::Box {
+                                    self.enter_rvalue(|v| v.emit(RewriteKind::FreeToDrop));
                                 }
-                            });
+                            }
                         }
                     }
 
+                    Callee::Realloc => {
+                        // TODO: rewrite `realloc` of `Box` pointers
+                    }
+
+                    Callee::Null { .. } => {
+                        // The result is `None` if it's rewritten to an `Option`.
+                        if self.lty_desc(pl_ty).is_some() {
+                            self.enter_rvalue(|v| v.emit(RewriteKind::NullToNone));
+                        }
+                    }
+
+                    Callee::LocalDef { def_id, substs: _ }
+                    | Callee::UnknownDef(UnknownDefCallee::Direct {
+                        def_id,
+                        is_foreign: false,
+                        ..
+                    }) => {
+                        // Functions in other crates only have an `LFnSig` if it was loaded from
+                        // the crate's summary.
+                        // TODO: handle substs (if nonempty)
+                        if let Some(lsig) = self.acx.gacx.fn_sigs.get(&def_id) {
+                            self.visit_call_with_sig(lsig.inputs, lsig.output, args, pl_ty);
+                        }
+                    }
+
+                    Callee::UnknownDef(UnknownDefCallee::Indirect { .. }) => {
+                        // The `fn` ptr's type takes the place of the callee's signature.
+                        let func_lty = self.acx.type_of(func);
+                        let (&output_lty, input_ltys) = func_lty
+                            .args
+                            .split_last()
+                            .expect("fn ptr type should have a return type");
+                        self.visit_call_with_sig(input_ltys, output_lty, args, pl_ty);
+                    }
+
+                    Callee::UnknownDef(UnknownDefCallee::Direct {
+                        def_id,
+                        is_foreign: true,
+                        ..
+                    }) => {
+                        self.visit_foreign_call(def_id, args, pl_ty);
+                    }
+
                     _ => {}
                 }
             }
@@ -316,6 +452,35 @@ impl<'a, 'tcx> ExprRewriteVisitor<'a, 'tcx> {
         }
     }
 
+    /// Visit the arguments of a call to a function with the given argument and return types,
+    /// emitting casts to convert each argument to the declared type and the return value to the
+    /// type of the destination `pl_ty`.
+    fn visit_call_with_sig(
+        &mut self,
+        input_ltys: &[LTy<'tcx>],
+        output_lty: LTy<'tcx>,
+        args: &[Operand<'tcx>],
+        pl_ty: LTy<'tcx>,
+    ) {
+        self.enter_rvalue(|v| {
+            for (i, op) in args.iter().enumerate() {
+                if let Some(&lty) = input_ltys.get(i) {
+                    v.enter_call_arg(i, |v| v.visit_operand(op, Some(lty)));
+                } else {
+                    // This is a call to a variadic function, and we've gone past the end of the
+                    // declared arguments.
+                    // TODO: insert a cast to turn `op` back into its original declared type (i.e.
+                    // upcast the chosen reference type back to a raw pointer)
+                    continue;
+                }
+            }
+
+            if !pl_ty.label.is_none() {
+                v.emit_cast_lty_lty(output_lty, pl_ty);
+            }
+        });
+    }
+
     /// Visit an `Rvalue`.  If `expect_ty` is `Some`, also emit whatever casts are necessary to
     /// make the `Rvalue` produce a value of type `expect_ty`.
     fn visit_rvalue(&mut self, rv: &Rvalue<'tcx>, expect_ty: Option<LTy<'tcx>>) {
@@ -354,12 +519,33 @@ impl<'a, 'tcx> ExprRewriteVisitor<'a, 'tcx> {
             Rvalue::Len(pl) => {
                 self.enter_rvalue_place(0, |v| v.visit_place(pl));
             }
-            Rvalue::Cast(_kind, ref op, _ty) => {
+            Rvalue::Cast(kind, ref op, _ty) => {
                 self.enter_rvalue_operand(0, |v| v.visit_operand(op, None));
+                // `0 as *const T` becomes `None` if it's rewritten to an `Option`.  Type checking
+                // already ensures that the operand of such a cast is the constant `0`.
+                if kind == CastKind::PointerFromExposedAddress
+                    && expect_ty.and_then(|lty| self.lty_desc(lty)).is_some()
+                {
+                    self.emit(RewriteKind::NullToNone);
+                }
             }
-            Rvalue::BinaryOp(_bop, ref ops) => {
-                self.enter_rvalue_operand(0, |v| v.visit_operand(&ops.0, None));
-                self.enter_rvalue_operand(1, |v| v.visit_operand(&ops.1, None));
+            Rvalue::BinaryOp(bop, ref ops) => {
+                let expect_descs = match bop {
+                    BinOp::Eq | BinOp::Ne | BinOp::Lt | BinOp::Le | BinOp::Gt | BinOp::Ge => {
+                        self.ptr_comparison_descs(ops)
+                    }
+                    _ => None,
+                };
+                match expect_descs {
+                    Some((desc0, desc1)) => {
+                        self.enter_rvalue_operand(0, |v| v.visit_operand_desc(&ops.0, desc0));
+                        self.enter_rvalue_operand(1, |v| v.visit_operand_desc(&ops.1, desc1));
+                    }
+                    None => {
+                        self.enter_rvalue_operand(0, |v| v.visit_operand(&ops.0, None));
+                        self.enter_rvalue_operand(1, |v| v.visit_operand(&ops.1, None));
+                    }
+                }
             }
             Rvalue::CheckedBinaryOp(_bop, ref ops) => {
                 self.enter_rvalue_operand(0, |v| v.visit_operand(&ops.0, None));
@@ -391,7 +577,7 @@ impl<'a, 'tcx> ExprRewriteVisitor<'a, 'tcx> {
     fn visit_operand(&mut self, op: &Operand<'tcx>, expect_ty: Option<LTy<'tcx>>) {
         match *op {
             Operand::Copy(pl) | Operand::Move(pl) => {
-                self.visit_place(pl);
+                self.enter_operand_place(|v| v.visit_place(pl));
 
                 if let Some(expect_ty) = expect_ty {
                     let ptr_lty = self.acx.type_of(pl);
@@ -408,7 +594,7 @@ impl<'a, 'tcx> ExprRewriteVisitor<'a, 'tcx> {
     fn visit_operand_desc(&mut self, op: &Operand<'tcx>, expect_desc: TypeDesc<'tcx>) {
         match *op {
             Operand::Copy(pl) | Operand::Move(pl) => {
-                self.visit_place(pl);
+                self.enter_operand_place(|v| v.visit_place(pl));
 
                 let ptr_lty = self.acx.type_of(pl);
                 if !ptr_lty.label.is_none() {
@@ -419,33 +605,134 @@ impl<'a, 'tcx> ExprRewriteVisitor<'a, 'tcx> {
         }
     }
 
-    fn visit_place(&mut self, _pl: Place<'tcx>) {
-        // TODO: walk over `pl` to handle all derefs (casts, `*x` -> `(*x).get()`)
+    fn visit_place(&mut self, pl: Place<'tcx>) {
+        // TODO: handle `Cell` derefs here too (`*x` -> `(*x).get()`)
+        let mut lty = self.acx.type_of(pl.local);
+        let mut num_derefs = 0;
+        for proj in pl.as_ref().projection {
+            if let ProjectionElem::Deref = proj {
+                // A nullable pointer must be unwrapped, and an `OffsetPtr` or `CStr` must be
+                // converted to a reference, before it can be dereferenced.  References to arrays
+                // and slices, such as the temporary in `(&mut arr).as_mut_ptr()`, keep their
+                // type, so they are dereferenced as is.
+                if let Some(desc) = self.lty_desc(lty) {
+                    let to_single = matches!(desc.qty, Quantity::OffsetPtr | Quantity::CStr)
+                        && !matches!(
+                            desc.pointee_ty.kind(),
+                            TyKind::Array(..) | TyKind::Slice(..)
+                        );
+                    if desc.option || to_single {
+                        // Dereferencing an `Option<Box<T>>` borrows the `Box` rather than moving
+                        // out of it.
+                        let own = match desc.own {
+                            Ownership::Box
+                                if desc.option
+                                    && self.perms[lty.label].contains(PermissionSet::WRITE) =>
+                            {
+                                Ownership::Mut
+                            }
+                            Ownership::Box if desc.option => Ownership::Imm,
+                            own => own,
+                        };
+                        let qty = if to_single {
+                            Quantity::Single
+                        } else {
+                            desc.qty
+                        };
+                        let deref_desc = TypeDesc {
+                            own,
+                            qty,
+                            option: false,
+                            ..desc
+                        };
+                        self.enter_place_pointer(num_derefs, |v| {
+                            v.emit_cast_desc_desc(desc, deref_desc)
+                        });
+                    }
+                }
+                num_derefs += 1;
+            }
+            lty = self.acx.projection_lty(lty, proj);
+        }
+    }
+
+    /// Get the `TypeDesc` of the rewritten type of `lty`, or `None` if `lty` is not a pointer or
+    /// is a `FIXED` pointer, which is not rewritten.
+    fn lty_desc(&self, lty: LTy<'tcx>) -> Option<TypeDesc<'tcx>> {
+        let ptr = lty.label;
+        if ptr.is_none() || self.flags[ptr].contains(FlagSet::FIXED) {
+            return None;
+        }
+        Some(type_desc::perms_to_desc(
+            lty.ty,
+            self.perms[ptr],
+            self.flags[ptr],
+        ))
     }
 
-    fn visit_ptr_offset(&mut self, op: &Operand<'tcx>, result_ty: LTy<'tcx>) {
+    fn visit_ptr_offset(
+        &mut self,
+        op: &Operand<'tcx>,
+        result_ty: LTy<'tcx>,
+        dest: Place<'tcx>,
+        target: Option<BasicBlock>,
+    ) {
         // Compute the expected type for the argument, and emit a cast if needed.
         let result_ptr = result_ty.label;
+        if self.flags[result_ptr].contains(FlagSet::FIXED) {
+            // A `FIXED` result keeps its raw pointer type, so the call is kept as is, and only
+            // the argument needs to be cast back to that type.
+            self.enter_rvalue(|v| v.enter_call_arg(0, |v| v.visit_operand(op, Some(result_ty))));
+            return;
+        }
         let result_desc =
             type_desc::perms_to_desc(result_ty.ty, self.perms[result_ptr], self.flags[result_ptr]);
 
+        // `OffsetPtr::offset` has the same signature as `<*const T>::offset`, so if either side
+        // is an `OffsetPtr`, we convert the argument to an `OffsetPtr` and keep the call as is.
+        let arg_desc = self.lty_desc(self.acx.type_of(op));
+        let use_offset_ptr = matches!(
+            result_desc.own,
+            Ownership::Imm | Ownership::Cell | Ownership::Mut
+        ) && (result_desc.qty == Quantity::OffsetPtr
+            || arg_desc.map_or(false, |desc| desc.qty == Quantity::OffsetPtr));
+
         let arg_expect_desc = TypeDesc {
             own: result_desc.own,
             qty: match result_desc.qty {
+                _ if use_offset_ptr => Quantity::OffsetPtr,
                 Quantity::Single => Quantity::Slice,
                 Quantity::Slice => Quantity::Slice,
-                Quantity::OffsetPtr => Quantity::OffsetPtr,
+                // The result is converted back to a `CStr` by the cast below.
+                Quantity::CStr => Quantity::Slice,
+                Quantity::OffsetPtr => unreachable!("OffsetPtr result requires use_offset_ptr"),
                 Quantity::Array => unreachable!("perms_to_desc should not return Quantity::Array"),
             },
+            // The pointer must be non-null to be offset.
+            option: false,
             pointee_ty: result_desc.pointee_ty,
         };
 
         self.enter_rvalue(|v| {
-            v.enter_call_arg(0, |v| v.visit_operand_desc(op, arg_expect_desc));
+            v.enter_call_arg(0, |v| {
+                v.visit_operand_desc(op, arg_expect_desc);
+                // `OffsetPtrMut::offset` consumes its receiver, so borrow it instead unless the
+                // result replaces the original pointer.  Casting out of an `Option` already
+                // borrows the pointer.
+                if use_offset_ptr
+                    && result_desc.own == Ownership::Mut
+                    && !arg_desc.map_or(false, |desc| desc.option)
+                    && !v.offset_replaces_arg(op, dest, target)
+                {
+                    v.emit(RewriteKind::OffsetPtrReborrow);
+                }
+            });
 
-            // Emit `OffsetSlice` for the offset itself.
-            let mutbl = matches!(result_desc.own, Ownership::Mut);
-            v.emit(RewriteKind::OffsetSlice { mutbl });
+            if !use_offset_ptr {
+                // Emit `OffsetSlice` for the offset itself.
+                let mutbl = matches!(result_desc.own, Ownership::Mut);
+                v.emit(RewriteKind::OffsetSlice { mutbl });
+            }
 
             // The `OffsetSlice` operation returns something of the same type as its input.
             // Afterward, we must cast the result to the `result_ty`/`result_desc`.
@@ -453,6 +740,215 @@ impl<'a, 'tcx> ExprRewriteVisitor<'a, 'tcx> {
         });
     }
 
+    /// Rewrite a call to a foreign function.  `strlen` and `strcmp` on `CStr`s are replaced with
+    /// their Rust equivalents.  Otherwise, the arguments are cast back to the raw pointer types
+    /// the foreign function expects.
+    fn visit_foreign_call(&mut self, def_id: DefId, args: &[Operand<'tcx>], pl_ty: LTy<'tcx>) {
+        let is_cstr = |op: &Operand<'tcx>| {
+            self.lty_desc(self.acx.type_of(op))
+                .map_or(false, |desc| desc.qty == Quantity::CStr && !desc.option)
+        };
+        let ret_ty = pl_ty.ty.to_string();
+        match self.acx.tcx().item_name(def_id).as_str() {
+            "strlen" if args.len() == 1 && is_cstr(&args[0]) => {
+                self.enter_rvalue(|v| v.emit(RewriteKind::StrlenToLen { ret_ty }));
+                return;
+            }
+            "strcmp" if args.len() == 2 && args.iter().all(is_cstr) => {
+                self.enter_rvalue(|v| v.emit(RewriteKind::StrcmpToCmp { ret_ty }));
+                return;
+            }
+            _ => {}
+        }
+
+        let lsig = match self.acx.gacx.fn_sigs.get(&def_id) {
+            Some(lsig) => lsig,
+            None => return,
+        };
+        self.enter_rvalue(|v| {
+            for (i, op) in args.iter().enumerate() {
+                if let Some(&lty) = lsig.inputs.get(i) {
+                    v.enter_call_arg(i, |v| v.visit_operand(op, Some(lty)));
+                    continue;
+                }
+                // This is a variadic argument, such as a `%s` argument of `printf`.  It's passed
+                // as the raw pointer type it had originally.
+                let op_lty = v.acx.type_of(op);
+                let mutbl = match *op_lty.ty.kind() {
+                    TyKind::RawPtr(tm) => tm.mutbl,
+                    _ => continue,
+                };
+                if let Some(desc) = v.lty_desc(op_lty) {
+                    let raw_desc = TypeDesc {
+                        own: match mutbl {
+                            Mutability::Not => Ownership::Raw,
+                            Mutability::Mut => Ownership::RawMut,
+                        },
+                        qty: Quantity::Single,
+                        option: false,
+                        pointee_ty: desc.pointee_ty,
+                    };
+                    v.enter_call_arg(i, |v| v.visit_operand_desc(op, raw_desc));
+                }
+            }
+        });
+    }
+
+    /// Check whether the result of an `offset` call is stored straight back into the pointer it
+    /// was computed from, as in `p = p.offset(1)`.  The MIR for this looks like
+    /// `_2 = copy _1; _3 = offset(move _2, 1) -> bb1; bb1: _1 = move _3`.
+    fn offset_replaces_arg(
+        &self,
+        op: &Operand<'tcx>,
+        dest: Place<'tcx>,
+        target: Option<BasicBlock>,
+    ) -> bool {
+        let (arg_local, target) = match (op.place().and_then(|pl| pl.as_local()), target) {
+            (Some(arg_local), Some(target)) => (arg_local, target),
+            _ => return false,
+        };
+
+        let bb_data = &self.mir.basic_blocks()[self.loc.block];
+        let src = bb_data
+            .statements
+            .iter()
+            .rev()
+            .find_map(|stmt| match stmt.kind {
+                StatementKind::Assign(ref x) if x.0.as_local() == Some(arg_local) => Some(&x.1),
+                _ => None,
+            });
+        let src = match src {
+            Some(&Rvalue::Use(Operand::Copy(pl) | Operand::Move(pl))) => pl,
+            Some(_) => return false,
+            None => Place::from(arg_local),
+        };
+
+        let first_assign = self.mir.basic_blocks()[target]
+            .statements
+            .iter()
+            .find_map(|stmt| match stmt.kind {
+                StatementKind::Assign(ref x) => Some(&**x),
+                _ => None,
+            });
+        matches!(
+            first_assign,
+            Some(&(pl, Rvalue::Use(Operand::Move(rv_pl)))) if pl == src && rv_pl == dest
+        )
+    }
+
+    /// Rewrite `p.offset_from(q)`.  If the pointers are rewritten, both are converted to
+    /// `OffsetPtr`, which provides its own `offset_from` method.
+    fn visit_ptr_offset_from(&mut self, args: &[Operand<'tcx>]) {
+        let descs = args
+            .iter()
+            .map(|op| self.lty_desc(self.acx.type_of(op)))
+            .collect::<Option<Vec<_>>>();
+        let descs = match descs {
+            Some(x) => x,
+            None => {
+                // TODO: handle `offset_from` between a rewritten pointer and a `FIXED` one
+                return;
+            }
+        };
+
+        self.enter_rvalue(|v| {
+            for (i, (op, desc)) in args.iter().zip(descs).enumerate() {
+                let expect_desc = TypeDesc {
+                    own: match desc.own {
+                        Ownership::Cell => Ownership::Cell,
+                        _ => Ownership::Imm,
+                    },
+                    qty: Quantity::OffsetPtr,
+                    option: false,
+                    ..desc
+                };
+                v.enter_call_arg(i, |v| v.visit_operand_desc(op, expect_desc));
+            }
+        });
+    }
+
+    /// Compute the expected types for the operands of a comparison between two pointers into an
+    /// array.  `OffsetPtr`s are compared by address, but comparing slices would compare their
+    /// contents instead, so both sides are converted to `OffsetPtr`.  Returns `None` if the
+    /// operands need no conversion.
+    fn ptr_comparison_descs(
+        &self,
+        ops: &(Operand<'tcx>, Operand<'tcx>),
+    ) -> Option<(TypeDesc<'tcx>, TypeDesc<'tcx>)> {
+        let desc0 = self.lty_desc(self.acx.type_of(&ops.0))?;
+        let desc1 = self.lty_desc(self.acx.type_of(&ops.1))?;
+        let is_array_ptr =
+            |desc: TypeDesc| matches!(desc.qty, Quantity::Slice | Quantity::OffsetPtr);
+        if !is_array_ptr(desc0) || !is_array_ptr(desc1) || desc0.option != desc1.option {
+            return None;
+        }
+
+        let expect_desc = |desc: TypeDesc<'tcx>| TypeDesc {
+            own: match desc.own {
+                Ownership::Cell => Ownership::Cell,
+                _ => Ownership::Imm,
+            },
+            qty: Quantity::OffsetPtr,
+            ..desc
+        };
+        Some((expect_desc(desc0), expect_desc(desc1)))
+    }
+
+    /// Check whether `pl` is the destination of a call to `malloc` or `calloc`, which
+    /// `visit_alloc_box` can rewrite.
+    fn is_alloc_result(&self, pl: Place<'tcx>) -> bool {
+        self.mir
+            .basic_blocks()
+            .iter()
+            .any(|bb_data| match bb_data.terminator().kind {
+                TerminatorKind::Call {
+                    ref func,
+                    destination,
+                    ..
+                } if destination == pl => matches!(
+                    ty_callee(self.acx.tcx(), func.ty(self.mir, self.acx.tcx())),
+                    Callee::Malloc | Callee::Calloc
+                ),
+                _ => false,
+            })
+    }
+
+    /// Rewrite a call to `malloc` or `calloc` whose result is converted to `Box`.  `desc`
+    /// describes the rewritten type of the result.
+    fn visit_alloc_box(&mut self, callee: Callee<'tcx>, desc: TypeDesc<'tcx>) {
+        self.enter_rvalue(|v| {
+            match (&callee, desc.qty) {
+                (_, Quantity::Single) => v.emit(RewriteKind::AllocBox),
+                (Callee::Malloc, _) => {
+                    let printer = FmtPrinter::new(v.acx.tcx(), Namespace::TypeNS);
+                    let elem_ty = desc.pointee_ty.print(printer).unwrap().into_buffer();
+                    v.emit(RewriteKind::MallocBoxSlice { elem_ty });
+                }
+                (Callee::Calloc, _) => v.emit(RewriteKind::CallocBoxSlice),
+                _ => unreachable!("expected malloc or calloc, but got {:?}", callee),
+            }
+            // Unlike `malloc`, the new allocation is never null.
+            if desc.option {
+                v.emit(RewriteKind::OptionSome);
+            }
+        });
+    }
+
+    fn visit_is_null(&mut self, op: &Operand<'tcx>) {
+        self.enter_rvalue(|v| {
+            v.enter_call_arg(0, |v| v.visit_operand(op, None));
+
+            let op_lty = v.acx.type_of(op);
+            if let Some(desc) = v.lty_desc(op_lty) {
+                if desc.option {
+                    v.emit(RewriteKind::IsNullToIsNone);
+                } else {
+                    v.emit(RewriteKind::IsNullToConstFalse);
+                }
+            }
+        });
+    }
+
     fn visit_slice_as_ptr(&mut self, elem_ty: Ty<'tcx>, op: &Operand<'tcx>, result_lty: LTy<'tcx>) {
         let op_lty = self.acx.type_of(op);
         let op_ptr = op_lty.label;
@@ -566,10 +1062,110 @@ where
             return;
         }
 
+        // Casts involving `Option`.  When both sides are `Option`s, the rest of the cast is
+        // applied to the pointer inside the `Option`.
+        match (from.option, to.option) {
+            (false, false) => {}
+            (true, true) => {
+                from.own = self.downgrade_option(from, to.own);
+                let inner = self.collect_cast_desc_desc(
+                    TypeDesc {
+                        option: false,
+                        ..from
+                    },
+                    TypeDesc {
+                        option: false,
+                        ..to
+                    },
+                );
+                if !inner.is_empty() {
+                    (self.emit)(RewriteKind::OptionMap { inner });
+                }
+                return;
+            }
+            (true, false) => {
+                from.own = self.downgrade_option(from, to.own);
+                if let Ownership::Raw | Ownership::RawMut = to.own {
+                    // `None` becomes a null pointer.
+                    let inner = self.collect_cast_desc_desc(
+                        TypeDesc {
+                            option: false,
+                            ..from
+                        },
+                        to,
+                    );
+                    if !inner.is_empty() {
+                        (self.emit)(RewriteKind::OptionMap { inner });
+                    }
+                    (self.emit)(RewriteKind::OptionUnwrapOrNull {
+                        mutbl: to.own == Ownership::RawMut,
+                    });
+                    return;
+                }
+                (self.emit)(RewriteKind::OptionUnwrap);
+                from.option = false;
+            }
+            (false, true) => {
+                let mut inner = self.collect_cast_desc_desc(
+                    from,
+                    TypeDesc {
+                        option: false,
+                        ..to
+                    },
+                );
+                // A null raw pointer becomes `None`, rather than a reference wrapped in `Some`.
+                if let Some(&RewriteKind::UnsafeCastRawToRef { mutbl }) = inner.last() {
+                    *inner.last_mut().unwrap() = RewriteKind::UnsafeCastRawToOptionRef { mutbl };
+                    inner.into_iter().for_each(&mut self.emit);
+                    return;
+                }
+                // Likewise for a raw C string, which is checked for null before being wrapped.
+                if let Some(&RewriteKind::UnsafeCastRawToCStr) = inner.last() {
+                    *inner.last_mut().unwrap() =
+                        RewriteKind::UnsafeCastRawToOptionRef { mutbl: false };
+                    inner.into_iter().for_each(&mut self.emit);
+                    (self.emit)(RewriteKind::OptionMap {
+                        inner: vec![
+                            RewriteKind::CastRefToRaw { mutbl: false },
+                            RewriteKind::UnsafeCastRawToCStr,
+                        ],
+                    });
+                    return;
+                }
+                inner.into_iter().for_each(&mut self.emit);
+                (self.emit)(RewriteKind::OptionSome);
+                return;
+            }
+        }
+
+        // A `&CStr` converts directly to a raw pointer to its first character.
+        if from.own == Ownership::Imm
+            && from.qty == Quantity::CStr
+            && matches!(to.own, Ownership::Raw | Ownership::RawMut)
+            && to.qty == Quantity::Single
+        {
+            let printer = FmtPrinter::new(self.tcx, Namespace::TypeNS);
+            let elem_ty = from.pointee_ty.print(printer).unwrap().into_buffer();
+            (self.emit)(RewriteKind::CStrAsPtr { elem_ty });
+            from.own = Ownership::Raw;
+            from.qty = Quantity::Single;
+        }
+
         // Early `Ownership` casts.  We do certain casts here in hopes of reaching an `Ownership`
         // on which we can safely adjust `Quantity`.
         from.own = self.cast_ownership(from, to, true);
 
+        // A raw C string converts directly to `&CStr`.
+        if matches!(from.own, Ownership::Raw | Ownership::RawMut)
+            && from.qty == Quantity::Single
+            && to.own == Ownership::Imm
+            && to.qty == Quantity::CStr
+        {
+            (self.emit)(RewriteKind::UnsafeCastRawToCStr);
+            from.own = Ownership::Imm;
+            from.qty = Quantity::CStr;
+        }
+
         // Safe casts that change `Quantity`.
         while from.qty != to.qty {
             // Mutability of `from`.  `None` here means that safe `Quantity` conversions aren't
@@ -589,12 +1185,48 @@ where
                     error!("TODO: cast Array to {:?}", to.qty);
                     from.qty = Quantity::Slice;
                 }
+                // `CStr` goes only to `Slice` directly, viewing the string as its characters.
+                (Quantity::CStr, _) => {
+                    if opt_mutbl != Some(false) {
+                        break;
+                    }
+                    let printer = FmtPrinter::new(self.tcx, Namespace::TypeNS);
+                    let elem_ty = from.pointee_ty.print(printer).unwrap().into_buffer();
+                    (self.emit)(RewriteKind::CStrToChars { elem_ty });
+                    from.qty = Quantity::Slice;
+                }
+                (Quantity::Slice, Quantity::CStr) => {
+                    if from.own != Ownership::Imm {
+                        break;
+                    }
+                    (self.emit)(RewriteKind::CStrFromChars);
+                    from.qty = Quantity::CStr;
+                }
                 // Bidirectional conversions between `Slice` and `OffsetPtr`.
-                (Quantity::Slice, Quantity::OffsetPtr) | (Quantity::OffsetPtr, Quantity::Slice) => {
-                    // Currently a no-op, since `Slice` and `OffsetPtr` are identical.
-                    from.qty = to.qty;
+                (Quantity::Slice, Quantity::OffsetPtr) => {
+                    let rw = match opt_mutbl {
+                        Some(mutbl) => RewriteKind::SliceToOffsetPtr { mutbl },
+                        None => break,
+                    };
+                    (self.emit)(rw);
+                    from.qty = Quantity::OffsetPtr;
+                }
+                (Quantity::OffsetPtr, Quantity::Slice | Quantity::CStr) => {
+                    let rw = match opt_mutbl {
+                        Some(mutbl) => RewriteKind::OffsetPtrToSlice { mutbl },
+                        None => break,
+                    };
+                    (self.emit)(rw);
+                    from.qty = Quantity::Slice;
+                }
+                (Quantity::OffsetPtr, Quantity::Single) => {
+                    let rw = match opt_mutbl {
+                        Some(mutbl) => RewriteKind::OffsetPtrGet { mutbl },
+                        None => break,
+                    };
+                    (self.emit)(rw);
+                    from.qty = Quantity::Single;
                 }
-                // `Slice` and `OffsetPtr` convert to `Single` the same way.
                 // TODO: when converting to `Ownership::Raw`/`RawMut`, use `slice.as_ptr()` to
                 // avoid panic on 0-length inputs
                 (_, Quantity::Single) => {
@@ -628,6 +1260,59 @@ where
         }
     }
 
+    /// Borrow the pointer inside an `Option<&mut T>` or `Option<Box<T>>` instead of moving it out
+    /// of the `Option`, in preparation for a cast to `to_own`.  Returns the new `Ownership` of the
+    /// pointer inside the `Option`.
+    fn downgrade_option(&mut self, from: TypeDesc<'tcx>, to_own: Ownership) -> Ownership {
+        if from.qty == Quantity::OffsetPtr {
+            // `OffsetPtrMut` doesn't implement `DerefMut`, so it must be reborrowed explicitly.
+            return match (from.own, to_own) {
+                (Ownership::Mut, Ownership::Imm | Ownership::Raw) => {
+                    (self.emit)(RewriteKind::OptionAsRef { mutbl: false });
+                    (self.emit)(RewriteKind::OptionMap {
+                        inner: vec![RewriteKind::OffsetPtrMutToImm],
+                    });
+                    Ownership::Imm
+                }
+                (Ownership::Mut, _) => {
+                    (self.emit)(RewriteKind::OptionAsRef { mutbl: true });
+                    (self.emit)(RewriteKind::OptionMap {
+                        inner: vec![RewriteKind::OffsetPtrReborrow],
+                    });
+                    Ownership::Mut
+                }
+                (own, _) => own,
+            };
+        }
+        match (from.own, to_own) {
+            // Moving a `Box` into another `Box` transfers ownership, so no borrow is needed.
+            (Ownership::Box, Ownership::Box) => Ownership::Box,
+            (Ownership::Mut | Ownership::Box, Ownership::Imm | Ownership::Raw) => {
+                (self.emit)(RewriteKind::OptionDowngrade { mutbl: false });
+                Ownership::Imm
+            }
+            (Ownership::Mut | Ownership::Box, _) => {
+                (self.emit)(RewriteKind::OptionDowngrade { mutbl: true });
+                Ownership::Mut
+            }
+            (own, _) => own,
+        }
+    }
+
+    /// Build a cast from `from` to `to`, returning the `RewriteKind`s instead of emitting them.
+    fn collect_cast_desc_desc(&self, from: TypeDesc<'tcx>, to: TypeDesc<'tcx>) -> Vec<RewriteKind> {
+        let mut rws = Vec::new();
+        let mut emit = |rk| rws.push(rk);
+        let mut builder = CastBuilder::new(
+            self.tcx,
+            self.perms,
+            self.flags,
+            &mut emit as &mut dyn FnMut(RewriteKind),
+        );
+        builder.build_cast_desc_desc(from, to);
+        rws
+    }
+
     fn cast_ownership(
         &mut self,
         from: TypeDesc<'tcx>,
@@ -655,11 +1340,11 @@ where
         match from.own {
             Ownership::Box => match to.own {
                 Ownership::Raw | Ownership::Imm => {
-                    error!("TODO: cast Box to Imm");
+                    (self.emit)(RewriteKind::BoxToRef { mutbl: false });
                     Some(Ownership::Imm)
                 }
-                Ownership::RawMut | Ownership::Mut => {
-                    error!("TODO: cast Box to Mut");
+                Ownership::RawMut | Ownership::Mut | Ownership::Cell => {
+                    (self.emit)(RewriteKind::BoxToRef { mutbl: true });
                     Some(Ownership::Mut)
                 }
                 _ => None,
@@ -672,10 +1357,16 @@ where
                 _ => None,
             },
             Ownership::Mut => match to.own {
+                Ownership::Imm | Ownership::Raw if from.qty == Quantity::OffsetPtr => {
+                    (self.emit)(RewriteKind::OffsetPtrMutToImm);
+                    Some(Ownership::Imm)
+                }
                 Ownership::Imm | Ownership::Raw => {
                     (self.emit)(RewriteKind::MutToImm);
                     Some(Ownership::Imm)
                 }
+                // TODO: convert `OffsetPtrMut<T>` to `OffsetPtr<Cell<T>>`
+                Ownership::Cell if from.qty == Quantity::OffsetPtr => None,
                 Ownership::Cell => {
                     (self.emit)(RewriteKind::CellFromMut);
                     Some(Ownership::Cell)
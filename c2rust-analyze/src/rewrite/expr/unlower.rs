@@ -240,7 +240,7 @@ impl<'a, 'tcx> UnlowerVisitor<'a, 'tcx> {
         match ex.kind {
             hir::ExprKind::Assign(pl, rv, _span) => {
                 // For `Assign`, we expect the assignment to be the whole thing.
-                let (loc, _mir_pl, mir_rv) = match self.get_sole_assign(&locs) {
+                let (loc, mir_pl, mir_rv) = match self.get_sole_assign(&locs) {
                     Some(x) => x,
                     None => {
                         warn("expected exactly one StatementKind::Assign");
@@ -254,6 +254,10 @@ impl<'a, 'tcx> UnlowerVisitor<'a, 'tcx> {
                 self.record(loc, &[], ex);
                 self.record(loc, &[SubLoc::Dest], pl);
                 self.record_desc(loc, &[SubLoc::Rvalue], rv, desc);
+                self.record_place_pointers(loc, &[SubLoc::Dest], pl, mir_pl.as_ref());
+                if self.typeck_results.expr_adjustments(rv).is_empty() {
+                    self.record_rvalue_place_pointers(loc, &[SubLoc::Rvalue], rv, mir_rv);
+                }
             }
 
             hir::ExprKind::Call(_, args) | hir::ExprKind::MethodCall(_, args, _) => {
@@ -287,6 +291,26 @@ impl<'a, 'tcx> UnlowerVisitor<'a, 'tcx> {
                 }
             }
 
+            hir::ExprKind::Binary(_, lhs, rhs) => {
+                let (loc, _mir_pl, mir_rv) = match self.get_last_assign(&locs) {
+                    Some(x @ (_, pl, _)) if is_var(pl) => x,
+                    _ => {
+                        warn("expected final Assign to store into var");
+                        return;
+                    }
+                };
+                self.record_desc(loc, &[], ex, MirOriginDesc::StoreIntoLocal);
+                self.visit_expr_rvalue(ex, loc, mir_rv, &locs[..locs.len() - 1]);
+                // Record the operands so that casts can be applied to them, as in pointer
+                // comparisons.  Checked arithmetic uses `CheckedBinaryOp` instead, and its
+                // operands are never pointers.
+                if let mir::Rvalue::BinaryOp(_, ref ops) = *mir_rv {
+                    let sub_loc = |i| [SubLoc::Rvalue, SubLoc::RvalueOperand(i)];
+                    self.record_operand(loc, &sub_loc(0), lhs, &ops.0);
+                    self.record_operand(loc, &sub_loc(1), rhs, &ops.1);
+                }
+            }
+
             _ => {
                 // For all other `ExprKind`s, we expect the last `loc` to be an assignment storing
                 // the final result into a temporary.
@@ -303,6 +327,84 @@ impl<'a, 'tcx> UnlowerVisitor<'a, 'tcx> {
         }
     }
 
+    /// Record `PlacePointer` entries for the pointers that are dereferenced in the MIR place `pl`,
+    /// which was lowered from the HIR place expression `ex`.  `sub_loc` is the location of `pl`
+    /// itself.  This stops at the first part of `ex` that doesn't correspond directly to a
+    /// projection of `pl`, such as an overloaded deref or an expression with adjustments.
+    fn record_place_pointers(
+        &mut self,
+        loc: Location,
+        sub_loc: &[SubLoc],
+        mut ex: &'tcx hir::Expr<'tcx>,
+        pl: mir::PlaceRef<'tcx>,
+    ) {
+        let mut projection = pl.projection;
+        while let Some((&outer_proj, remaining_proj)) = projection.split_last() {
+            let inner_ex = match (outer_proj, &ex.kind) {
+                (mir::PlaceElem::Deref, &hir::ExprKind::Unary(hir::UnOp::Deref, inner_ex))
+                    if !self.typeck_results.is_method_call(ex) =>
+                {
+                    inner_ex
+                }
+                (mir::PlaceElem::Field(..), &hir::ExprKind::Field(inner_ex, _)) => inner_ex,
+                (mir::PlaceElem::Index(..), &hir::ExprKind::Index(inner_ex, _)) => inner_ex,
+                _ => return,
+            };
+            if !self.typeck_results.expr_adjustments(inner_ex).is_empty() {
+                return;
+            }
+
+            if matches!(outer_proj, mir::PlaceElem::Deref) {
+                // Number of derefs, not counting the outermost one.
+                let num_inner_derefs = remaining_proj
+                    .iter()
+                    .filter(|p| matches!(p, mir::PlaceElem::Deref))
+                    .count();
+                let mut pointer_sub_loc = sub_loc.to_owned();
+                pointer_sub_loc.push(SubLoc::PlacePointer(num_inner_derefs));
+                let inner_pl = mir::PlaceRef {
+                    local: pl.local,
+                    projection: remaining_proj,
+                };
+                let desc = if is_temp_var(self.mir, inner_pl) {
+                    MirOriginDesc::LoadFromTemp
+                } else {
+                    MirOriginDesc::Expr
+                };
+                self.record_desc(loc, &pointer_sub_loc, inner_ex, desc);
+            }
+
+            ex = inner_ex;
+            projection = remaining_proj;
+        }
+    }
+
+    /// Record `PlacePointer` entries for the place used in the MIR rvalue `rv`, if it has one.
+    /// `ex` is the HIR expression that was lowered to produce `rv`, not including adjustments.
+    fn record_rvalue_place_pointers(
+        &mut self,
+        loc: Location,
+        sub_loc: &[SubLoc],
+        ex: &'tcx hir::Expr<'tcx>,
+        rv: &mir::Rvalue<'tcx>,
+    ) {
+        match *rv {
+            mir::Rvalue::Use(mir::Operand::Copy(pl) | mir::Operand::Move(pl)) => {
+                let mut sub_loc = sub_loc.to_owned();
+                sub_loc.extend([SubLoc::RvalueOperand(0), SubLoc::OperandPlace]);
+                self.record_place_pointers(loc, &sub_loc, ex, pl.as_ref());
+            }
+            mir::Rvalue::Ref(_, _, pl) | mir::Rvalue::AddressOf(_, pl) => {
+                if let hir::ExprKind::AddrOf(_, _, inner_ex) = ex.kind {
+                    let mut sub_loc = sub_loc.to_owned();
+                    sub_loc.push(SubLoc::RvaluePlace(0));
+                    self.record_place_pointers(loc, &sub_loc, inner_ex, pl.as_ref());
+                }
+            }
+            _ => {}
+        }
+    }
+
     fn visit_expr_rvalue(
         &mut self,
         ex: &'tcx hir::Expr<'tcx>,
@@ -325,6 +427,7 @@ impl<'a, 'tcx> UnlowerVisitor<'a, 'tcx> {
             vec![SubLoc::Rvalue],
         );
 
+        let mut peeled_all_adjusts = true;
         for (i, adjust) in adjusts.iter().enumerate().rev() {
             while cursor.peel_temp().is_some() {
                 // No-op.  Just loop until we've peeled all temporaries.
@@ -343,6 +446,7 @@ impl<'a, 'tcx> UnlowerVisitor<'a, 'tcx> {
                             but got {:?}",
                             cursor.cur
                         );
+                        peeled_all_adjusts = false;
                         break;
                     }
                 }
@@ -353,6 +457,7 @@ impl<'a, 'tcx> UnlowerVisitor<'a, 'tcx> {
                             on expr {ex:?}, but got {:?}",
                             cursor.cur
                         );
+                        peeled_all_adjusts = false;
                         break;
                     }
                 }
@@ -363,6 +468,7 @@ impl<'a, 'tcx> UnlowerVisitor<'a, 'tcx> {
                             on expr {ex:?}, but got {:?}",
                             cursor.cur
                         );
+                        peeled_all_adjusts = false;
                         break;
                     }
                 }
@@ -374,6 +480,7 @@ impl<'a, 'tcx> UnlowerVisitor<'a, 'tcx> {
                             but got {:?}",
                             cursor.cur
                         );
+                        peeled_all_adjusts = false;
                         break;
                     }
                 }
@@ -384,11 +491,13 @@ impl<'a, 'tcx> UnlowerVisitor<'a, 'tcx> {
                             on expr {ex:?}, but got {:?}",
                             cursor.cur
                         );
+                        peeled_all_adjusts = false;
                         break;
                     }
                 }
                 _ => {
                     warn!("unsupported adjustment {adjust:?} on expr {ex:?}");
+                    peeled_all_adjusts = false;
                     break;
                 }
             }
@@ -404,6 +513,24 @@ impl<'a, 'tcx> UnlowerVisitor<'a, 'tcx> {
         }
 
         self.record_desc(cursor.loc, &cursor.sub_loc, ex, MirOriginDesc::Expr);
+
+        // Record the pointers dereferenced by the place that `ex` evaluates, if any.
+        if peeled_all_adjusts {
+            match cursor.cur {
+                ExprMir::Rvalue(rv) => {
+                    self.record_rvalue_place_pointers(cursor.loc, &cursor.sub_loc, ex, rv);
+                }
+                ExprMir::Operand(&mir::Operand::Copy(pl) | &mir::Operand::Move(pl)) => {
+                    let mut sub_loc = cursor.sub_loc.clone();
+                    sub_loc.push(SubLoc::OperandPlace);
+                    self.record_place_pointers(cursor.loc, &sub_loc, ex, pl.as_ref());
+                }
+                ExprMir::Operand(&mir::Operand::Constant(..)) => {}
+                ExprMir::Place(pl) => {
+                    self.record_place_pointers(cursor.loc, &cursor.sub_loc, ex, pl);
+                }
+            }
+        }
     }
 
     fn visit_expr_operand(
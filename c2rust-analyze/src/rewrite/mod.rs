@@ -23,12 +23,20 @@
 //! require us to update the `Span`s mentioned in the later rewrites to account for the changes in
 //! the source code produced by the earlier ones).
 
+use rustc_hir::def_id::LOCAL_CRATE;
 use rustc_hir::Mutability;
 use rustc_middle::mir::Body;
 use rustc_middle::mir::Location;
 use rustc_middle::ty::TyCtxt;
+use rustc_span::source_map::FileName;
 use rustc_span::Span;
+use serde::Serialize;
+use similar::TextDiff;
 use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
 
 mod apply;
 mod expr;
@@ -41,7 +49,7 @@ pub use self::expr::gen_expr_rewrites;
 pub use self::shim::{gen_shim_call_rewrites, gen_shim_definition_rewrite};
 use self::span_index::SpanIndex;
 pub use self::statics::gen_static_rewrites;
-pub use self::ty::dump_rewritten_local_tys;
+pub use self::ty::{dump_rewritten_local_tys, rewritten_ty};
 pub use self::ty::{gen_adt_ty_rewrites, gen_ty_rewrites};
 
 #[derive(Clone, PartialEq, Eq, Debug)]
@@ -76,12 +84,17 @@ pub enum Rewrite<S = Span> {
     SliceTail(Box<Rewrite>, Box<Rewrite>),
     /// `e as T`
     Cast(Box<Rewrite>, String),
+    /// `a op b`, where `op` is a binary operator such as `/`.
+    Binary(Box<Rewrite>, String, Box<Rewrite>),
     /// The integer literal `0`.
     LitZero,
     // Function calls
     Call(String, Vec<Rewrite>),
     // Method calls
     MethodCall(String, Box<Rewrite>, Vec<Rewrite>),
+    /// `|x| e`, a closure with one argument named `x`.  The body can refer to the argument with
+    /// `Rewrite::Text`.
+    Closure1(String, Box<Rewrite>),
 
     // Type builders
     /// Emit a complete pretty-printed type, discarding the original annotation.
@@ -164,24 +177,163 @@ fn build_span_index(mir: &Body<'_>) -> SpanIndex<Location> {
     SpanIndex::new(span_index_items)
 }
 
-pub fn apply_rewrites(tcx: TyCtxt, rewrites: Vec<(Span, Rewrite)>) {
-    // TODO: emit new source code properly instead of just printing
-    let new_src = apply::apply_rewrites(tcx.sess.source_map(), rewrites);
-
-    for (filename, src) in new_src {
-        eprintln!("\n\n ===== BEGIN {:?} =====", filename);
-        for line in src.lines() {
-            // Omit filecheck directives from the debug output, as filecheck can get confused due
-            // to directives matching themselves (e.g. `// CHECK: foo` will match the `foo` in the
-            // line `// CHECK: foo`).
-            if let Some((pre, _post)) = line.split_once("// CHECK") {
-                eprintln!("{}// (FileCheck directive omitted)", pre);
-            } else {
-                eprintln!("{}", line);
+/// Ways of outputting the rewritten source code, selected by passing a comma-separated list of
+/// modes to `--rewrite-mode` or setting `C2RUST_ANALYZE_REWRITE_MODE` to one.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum OutputMode {
+    /// Print the rewritten files to stderr.  This is the default.
+    Print,
+    /// Overwrite the original files.
+    InPlace,
+    /// Write the rewritten version of each `foo.rs` to `foo.new`.
+    Alongside,
+    /// Print a unified diff of the changes to stdout.
+    PrintDiff,
+    /// Write the rewritten spans and their new text to `<crate name>.rewrites.json`.
+    Json,
+}
+
+impl FromStr for OutputMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<OutputMode, String> {
+        Ok(match s {
+            "print" => OutputMode::Print,
+            "inplace" => OutputMode::InPlace,
+            "alongside" => OutputMode::Alongside,
+            "diff" => OutputMode::PrintDiff,
+            "json" => OutputMode::Json,
+            _ => {
+                return Err(format!(
+                    "unknown rewrite mode {s:?}, expected one of \
+                     `print`, `inplace`, `alongside`, `diff`, or `json`"
+                ))
+            }
+        })
+    }
+}
+
+impl OutputMode {
+    /// Parse a comma-separated list of modes.
+    pub fn parse_list(s: &str) -> Result<Vec<OutputMode>, String> {
+        s.split(',').map(|mode| mode.trim().parse()).collect()
+    }
+
+    fn write_dest(self, path: &Path) -> Option<PathBuf> {
+        match self {
+            OutputMode::InPlace => Some(path.to_owned()),
+            OutputMode::Alongside => Some(path.with_extension("new")),
+            _ => None,
+        }
+    }
+}
+
+/// A single rewritten span in the `json` output mode.
+#[derive(Serialize)]
+struct JsonEdit {
+    file: String,
+    /// Byte offsets of the span within the original file.
+    lo: usize,
+    hi: usize,
+    /// 1-based line and column of the start and end of the span.
+    lo_line: usize,
+    lo_col: usize,
+    hi_line: usize,
+    hi_col: usize,
+    old_text: String,
+    new_text: String,
+}
+
+pub fn apply_rewrites(
+    tcx: TyCtxt,
+    rewrites: Vec<(Span, Rewrite)>,
+    modes: &[OutputMode],
+) -> io::Result<()> {
+    let source_map = tcx.sess.source_map();
+    let new_src = apply::apply_rewrites(source_map, rewrites);
+
+    let mut json_edits = Vec::new();
+    for rewritten in new_src {
+        let file = &rewritten.file;
+        let src = &rewritten.new_src;
+        let old_src = file
+            .src
+            .as_ref()
+            .unwrap_or_else(|| panic!("source is not available for file {:?}", file.name));
+
+        for &mode in modes {
+            match mode {
+                OutputMode::Print => print_src(&file.name, src),
+                OutputMode::InPlace | OutputMode::Alongside => {
+                    let path = match file.name {
+                        FileName::Real(ref name) => name.local_path(),
+                        _ => None,
+                    };
+                    match path.and_then(|path| mode.write_dest(path)) {
+                        Some(dest) => {
+                            eprintln!("writing rewritten {:?} to {:?}", file.name, dest);
+                            fs::write(&dest, src)?;
+                        }
+                        None => eprintln!("warning: cannot write rewritten {:?}", file.name),
+                    }
+                }
+                OutputMode::PrintDiff => {
+                    let name = file.name.prefer_local().to_string();
+                    let diff = TextDiff::from_lines(old_src.as_str(), src.as_str());
+                    print!(
+                        "{}",
+                        diff.unified_diff()
+                            .header(&format!("a/{name}"), &format!("b/{name}"))
+                    );
+                }
+                OutputMode::Json => {
+                    for (span, new_text) in &rewritten.edits {
+                        let lo = source_map.lookup_char_pos(span.lo());
+                        let hi = source_map.lookup_char_pos(span.hi());
+                        let lo_in_file = (span.lo() - file.start_pos).0 as usize;
+                        let hi_in_file = (span.hi() - file.start_pos).0 as usize;
+                        json_edits.push(JsonEdit {
+                            file: file.name.prefer_local().to_string(),
+                            lo: lo_in_file,
+                            hi: hi_in_file,
+                            lo_line: lo.line,
+                            lo_col: lo.col.0 + 1,
+                            hi_line: hi.line,
+                            hi_col: hi.col.0 + 1,
+                            old_text: old_src[lo_in_file..hi_in_file].to_owned(),
+                            new_text: new_text.clone(),
+                        });
+                    }
+                }
             }
         }
-        eprintln!(" ===== END {:?} =====", filename);
     }
+
+    if modes.contains(&OutputMode::Json) {
+        let path = format!("{}.rewrites.json", tcx.crate_name(LOCAL_CRATE));
+        eprintln!("writing rewrites to {:?}", path);
+        let json = serde_json::to_string_pretty(&json_edits)
+            .map_err(|e| io::Error::new(io::ErrorKind::Other, e))?;
+        fs::write(path, json)?;
+    }
+
+    Ok(())
+}
+
+/// Print the rewritten source of a file to stderr, for the default `print` output mode.
+fn print_src(filename: &FileName, src: &str) {
+    eprintln!("\n\n ===== BEGIN {:?} =====", filename);
+    for line in src.lines() {
+        // Omit filecheck directives from the debug output, as filecheck can get confused due
+        // to directives matching themselves (e.g. `// CHECK: foo` will match the `foo` in the
+        // line `// CHECK: foo`).
+        if let Some((pre, _post)) = line.split_once("// CHECK") {
+            eprintln!("{}// (FileCheck directive omitted)", pre);
+        } else {
+            eprintln!("{}", line);
+        }
+    }
+    eprintln!(" ===== END {:?} =====", filename);
 }
 
 #[cfg(test)]
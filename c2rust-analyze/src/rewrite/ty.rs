@@ -32,7 +32,7 @@ use rustc_middle::mir::{self, Body, LocalDecl};
 use rustc_middle::ty::print::{FmtPrinter, Print};
 use rustc_middle::ty::TyKind;
 use rustc_middle::ty::{self, AdtDef, GenericArg, GenericArgKind, List, ReErased, TyCtxt};
-use rustc_span::Span;
+use rustc_span::{sym, Span};
 
 use super::LifetimeName;
 
@@ -41,6 +41,9 @@ use super::LifetimeName;
 struct RewriteLabel<'a> {
     /// Rewrite a raw pointer, whose ownership and quantity have been inferred as indicated.
     ty_desc: Option<(Ownership, Quantity)>,
+    /// If set, the rewritten pointer is wrapped in `Option`.  This is only set along with
+    /// `ty_desc`.
+    option: bool,
     /// If set, a child or other descendant of this type requires rewriting.
     descendant_has_rewrite: bool,
     /// A lifetime rewrite for a pointer or reference.
@@ -86,24 +89,25 @@ fn create_rewrite_label<'tcx>(
     lifetime: &'tcx [OriginArg<'tcx>],
     adt_metadata: &AdtMetadataTable,
 ) -> RewriteLabel<'tcx> {
-    let ty_desc = if pointer_lty.label.is_none() {
-        None
+    let (ty_desc, option) = if pointer_lty.label.is_none() {
+        (None, false)
     } else {
         let perms = perms[pointer_lty.label];
         let flags = flags[pointer_lty.label];
         if flags.contains(FlagSet::FIXED) {
-            None
+            (None, false)
         } else {
             // TODO: if the `Ownership` and `Quantity` exactly match `lty.ty`, then `ty_desc`
             // can be `None` (no rewriting required).  This might let us avoid inlining a type
             // alias for some pointers where no actual improvement was possible.
             let desc = type_desc::perms_to_desc(pointer_lty.ty, perms, flags);
-            Some((desc.own, desc.qty))
+            (Some((desc.own, desc.qty)), desc.option)
         }
     };
 
     RewriteLabel {
         ty_desc,
+        option,
         descendant_has_rewrite: descendant_has_rewrite(args, adt_metadata),
         lifetime,
     }
@@ -331,6 +335,22 @@ fn mk_cell<'tcx>(tcx: TyCtxt<'tcx>, ty: ty::Ty<'tcx>) -> ty::Ty<'tcx> {
     tcx.mk_adt(cell_adt, substs)
 }
 
+fn mk_cstr(tcx: TyCtxt) -> ty::Ty {
+    let cstr_struct_did = tcx
+        .get_diagnostic_item(sym::CStr)
+        .expect("failed to find struct `core::ffi::CStr`");
+    tcx.mk_adt(tcx.adt_def(cstr_struct_did), List::empty())
+}
+
+fn mk_option<'tcx>(tcx: TyCtxt<'tcx>, ty: ty::Ty<'tcx>) -> ty::Ty<'tcx> {
+    let option_enum_did = tcx
+        .get_diagnostic_item(sym::Option)
+        .expect("failed to find enum `core::option::Option`");
+    let option_adt = tcx.adt_def(option_enum_did);
+    let substs = tcx.mk_substs([GenericArg::from(ty)].into_iter());
+    tcx.mk_adt(option_adt, substs)
+}
+
 /// Produce a `Ty` reflecting the rewrites indicated by the labels in `rw_lty`.
 fn mk_rewritten_ty<'tcx>(
     lcx: LabeledTyCtxt<'tcx, RewriteLabel<'tcx>>,
@@ -354,8 +374,11 @@ fn mk_rewritten_ty<'tcx>(
         ty = match qty {
             Quantity::Single => ty,
             Quantity::Slice => tcx.mk_slice(ty),
-            // TODO: This should generate `OffsetPtr<T>` rather than `&[T]`, but `OffsetPtr` is NYI
+            // `OffsetPtr` is defined in `c2rust-support`, which the crate being analyzed might not
+            // depend on, so there may be no `Ty` for it.  We approximate it with a slice, which
+            // has the same `Ownership` conversions.
             Quantity::OffsetPtr => tcx.mk_slice(ty),
+            Quantity::CStr => mk_cstr(tcx),
             Quantity::Array => panic!("can't mk_rewritten_ty with Quantity::Array"),
         };
 
@@ -369,6 +392,10 @@ fn mk_rewritten_ty<'tcx>(
             Ownership::Box => tcx.mk_box(ty),
         };
 
+        if label.option {
+            ty = mk_option(tcx, ty);
+        }
+
         ty
     })
 }
@@ -439,30 +466,49 @@ fn rewrite_ty<'tcx>(
             rw = Rewrite::TyCtor("core::cell::Cell".into(), vec![rw]);
         }
 
+        let lifetime_type = match rw_lty.label.lifetime {
+            [lifetime] => LifetimeName::Explicit(format!("{lifetime:?}")),
+            [] => LifetimeName::Elided,
+            _ => panic!("Pointer or reference type cannot have multiple lifetime parameters"),
+        };
+
         rw = match qty {
             Quantity::Single => rw,
             Quantity::Slice => Rewrite::TySlice(Box::new(rw)),
-            // TODO: This should generate `OffsetPtr<T>` rather than `&[T]`, but `OffsetPtr` is
-            // NYI
-            Quantity::OffsetPtr => Rewrite::TySlice(Box::new(rw)),
+            Quantity::OffsetPtr => {
+                // `OffsetPtr<'a, T>` takes the place of both the reference and the slice.
+                let name = match own {
+                    Ownership::Imm | Ownership::Cell => "c2rust_support::OffsetPtr",
+                    Ownership::Mut => "c2rust_support::OffsetPtrMut",
+                    _ => panic!("unsupported ownership {:?} for Quantity::OffsetPtr", own),
+                };
+                let mut args = Vec::with_capacity(2);
+                if let LifetimeName::Explicit(ref lifetime) = lifetime_type {
+                    args.push(Rewrite::Print(lifetime.clone()));
+                }
+                args.push(rw);
+                Rewrite::TyCtor(name.into(), args)
+            }
+            // `CStr` replaces the `c_char` pointee.
+            Quantity::CStr => Rewrite::Print("core::ffi::CStr".into()),
             Quantity::Array => panic!("can't rewrite to Quantity::Array"),
         };
 
-        let lifetime_type = match rw_lty.label.lifetime {
-            [lifetime] => LifetimeName::Explicit(format!("{lifetime:?}")),
-            [] => LifetimeName::Elided,
-            _ => panic!("Pointer or reference type cannot have multiple lifetime parameters"),
-        };
         rw = match own {
+            _ if qty == Quantity::OffsetPtr => rw,
             Ownership::Raw => Rewrite::TyPtr(Box::new(rw), Mutability::Not),
             Ownership::RawMut => Rewrite::TyPtr(Box::new(rw), Mutability::Mut),
             Ownership::Imm => Rewrite::TyRef(lifetime_type, Box::new(rw), Mutability::Not),
             Ownership::Cell => Rewrite::TyRef(lifetime_type, Box::new(rw), Mutability::Not),
             Ownership::Mut => Rewrite::TyRef(lifetime_type, Box::new(rw), Mutability::Mut),
             Ownership::Rc => todo!(),
-            Ownership::Box => todo!(),
+            Ownership::Box => Rewrite::TyCtor("std::boxed::Box".into(), vec![rw]),
         };
 
+        if rw_lty.label.option {
+            rw = Rewrite::TyCtor("core::option::Option".into(), vec![rw]);
+        }
+
         hir_rewrites.push((hir_ty.span, rw));
     }
 
@@ -528,6 +574,34 @@ impl<'tcx, 'a> intravisit::Visitor<'tcx> for HirTyVisitor<'a, 'tcx> {
             _ => (),
         }
     }
+
+    fn visit_expr(&mut self, ex: &'tcx hir::Expr<'tcx>) {
+        // A `fn` item cast to a `fn` ptr, as in `f as unsafe extern "C" fn(*mut T)`.  Dataflow
+        // unifies the `fn` ptr type with the signature of `f`, so it's rewritten the same way.
+        if let hir::ExprKind::Cast(inner, hir_ty) = ex.kind {
+            let tcx = self.acx.tcx();
+            let typeck_results = tcx.typeck(ex.hir_id.owner);
+            let fn_ptr_ty = typeck_results.expr_ty(ex);
+            if let (&TyKind::FnDef(def_id, _), TyKind::FnPtr(_)) =
+                (typeck_results.expr_ty(inner).kind(), fn_ptr_ty.kind())
+            {
+                if let Some(lsig) = self.acx.gacx.fn_sigs.get(&def_id) {
+                    let lcx = self.acx.lcx();
+                    let args = lcx.mk_slice(&lsig.inputs_and_output().collect::<Vec<_>>());
+                    let lty = lcx.mk(fn_ptr_ty, args, PointerId::NONE);
+                    let rw_lty = relabel_rewrites(
+                        &self.asn.perms(),
+                        &self.asn.flags(),
+                        self.rw_lcx,
+                        lty,
+                        self.acx.gacx,
+                    );
+                    self.handle_ty(rw_lty, hir_ty);
+                }
+            }
+        }
+        intravisit::walk_expr(self, ex);
+    }
 }
 
 pub fn gen_ty_rewrites<'tcx>(
@@ -615,7 +689,7 @@ pub fn gen_ty_rewrites<'tcx>(
     let body = acx.tcx().hir().body(hir_body_id);
     intravisit::Visitor::visit_body(&mut v, body);
 
-    // TODO: update cast RHS types
+    // TODO: update other cast RHS types
 
     v.hir_rewrites
 }
@@ -763,6 +837,18 @@ pub fn gen_adt_ty_rewrites(
     hir_rewrites
 }
 
+/// Compute the type that `lty` will be rewritten to under the given permissions and flags.
+pub fn rewritten_ty<'tcx>(
+    perms: &impl Index<PointerId, Output = PermissionSet>,
+    flags: &impl Index<PointerId, Output = FlagSet>,
+    lty: LTy<'tcx>,
+    gacx: &GlobalAnalysisCtxt<'tcx>,
+) -> ty::Ty<'tcx> {
+    let rw_lcx = LabeledTyCtxt::new(gacx.tcx);
+    let rw_lty = relabel_rewrites(perms, flags, rw_lcx, lty, gacx);
+    mk_rewritten_ty(rw_lcx, rw_lty)
+}
+
 /// Print the rewritten types for all locals in `mir`.  This is used for tests and debugging, as it
 /// reveals the inference results even for temporaries and other locals with no type annotation in
 /// the HIR.
@@ -772,17 +858,9 @@ pub fn dump_rewritten_local_tys<'tcx>(
     mir: &Body<'tcx>,
     mut describe_local: impl FnMut(TyCtxt<'tcx>, &LocalDecl) -> String,
 ) {
-    let rw_lcx = LabeledTyCtxt::new(acx.tcx());
     for (local, decl) in mir.local_decls.iter_enumerated() {
         // TODO: apply `Cell` if `addr_of_local` indicates it's needed
-        let rw_lty = relabel_rewrites(
-            &asn.perms(),
-            &asn.flags(),
-            rw_lcx,
-            acx.local_tys[local],
-            &acx.gacx,
-        );
-        let ty = mk_rewritten_ty(rw_lcx, rw_lty);
+        let ty = rewritten_ty(&asn.perms(), &asn.flags(), acx.local_tys[local], acx.gacx);
         eprintln!(
             "{:?} ({}): {:?}",
             local,
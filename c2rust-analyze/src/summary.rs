@@ -0,0 +1,440 @@
+//! Summaries of a crate's analysis results, for use when analyzing the crates that depend on it.
+//!
+//! When `C2RUST_ANALYZE_SUMMARIES` is set to a directory, `c2rust-analyze` writes a summary of the
+//! crate it analyzed to `<crate name>-<stable crate id>.summary.json` in that directory, and
+//! before analyzing, it loads the summaries of any of the crate's dependencies it finds there.  A
+//! summary records the final permissions and flags of every pointer in the signatures of the
+//! crate's functions and in the types of its fields, along with the hypothetical lifetime
+//! parameters that rewriting adds to its ADTs.
+//!
+//! Calls to summarized functions are analyzed like calls to local functions, and accesses to
+//! summarized fields like accesses to local fields, rather than being treated as calls into
+//! unknown code.  The summarized permissions are used as the initial permissions of those
+//! pointers.  As the other crate has already been rewritten according to them, they can't
+//! change; if this crate would need different permissions, we warn about it and keep the
+//! summarized ones.
+
+use crate::borrowck::{AdtMetadata, FieldMetadata, OriginArg, OriginParam};
+use crate::context::{
+    FlagSet, GlobalAnalysisCtxt, GlobalAssignment, LFnSig, LTy, PermissionSet, PointerId,
+    PointerInfo,
+};
+use crate::labeled_ty::LabeledTyCtxt;
+use crate::AssignPointerIds;
+use rustc_data_structures::fingerprint::Fingerprint;
+use rustc_hir::def::DefKind;
+use rustc_hir::def_id::{CrateNum, DefId, DefIndex, DefPathHash, LOCAL_CRATE};
+use rustc_metadata::creader::CStore;
+use rustc_middle::ty::{GenericArgKind, TyCtxt, TyKind};
+use rustc_type_ir::RegionKind::ReEarlyBound;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+#[derive(Serialize, Deserialize)]
+pub struct CrateSummary {
+    #[serde(rename = "crate")]
+    crate_name: String,
+    fns: Vec<FnSummary>,
+    adts: Vec<AdtSummary>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct FnSummary {
+    /// The path of the function.  This is only for debugging; the function is identified by its
+    /// `def_path_hash`.
+    name: String,
+    def_path_hash: (u64, u64),
+    /// Every pointer in the signature, inputs first, in the order of [`LabeledTyS::iter`].
+    ///
+    /// [`LabeledTyS::iter`]: crate::labeled_ty::LabeledTyS::iter
+    pointers: Vec<PointerSummary>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct AdtSummary {
+    name: String,
+    def_path_hash: (u64, u64),
+    /// The hypothetical lifetime parameters of the ADT, such as `'h0`, in order.  The ADT's
+    /// actual lifetime parameters always come first, so they aren't recorded.
+    hypothetical_params: Vec<i64>,
+    fields: Vec<FieldSummary>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct FieldSummary {
+    name: String,
+    def_path_hash: (u64, u64),
+    /// Every pointer in the field's type, in the order of [`LabeledTyS::iter`].
+    ///
+    /// [`LabeledTyS::iter`]: crate::labeled_ty::LabeledTyS::iter
+    pointers: Vec<PointerSummary>,
+    /// The hypothetical origin arguments of each node of the field's type, in the same order.
+    hypothetical_args: Vec<Vec<i64>>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct PointerSummary {
+    perms: Vec<String>,
+    flags: Vec<String>,
+}
+
+impl PointerSummary {
+    fn new(perms: PermissionSet, flags: FlagSet) -> PointerSummary {
+        PointerSummary {
+            perms: perms.names().map(String::from).collect(),
+            flags: flags.names().map(String::from).collect(),
+        }
+    }
+
+    fn parse(&self) -> Result<(PermissionSet, FlagSet), String> {
+        let perms = self.perms.join("|").parse()?;
+        let flags = self.flags.join("|").parse()?;
+        Ok((perms, flags))
+    }
+}
+
+/// The name of the summary file for crate `cnum`.  Including the stable crate ID keeps crates
+/// with the same name apart and ensures we never load a summary for the wrong crate.
+fn summary_file_name(tcx: TyCtxt, cnum: CrateNum) -> String {
+    format!(
+        "{}-{:016x}.summary.json",
+        tcx.crate_name(cnum),
+        tcx.stable_crate_id(cnum).to_u64()
+    )
+}
+
+/// Get the `PointerId`s of all pointers in `ltys`, in the order they appear.
+fn pointer_ids<'tcx>(ltys: impl IntoIterator<Item = LTy<'tcx>>) -> Vec<PointerId> {
+    ltys.into_iter()
+        .flat_map(|lty| lty.iter())
+        .map(|lty| lty.label)
+        .filter(|ptr| !ptr.is_none())
+        .collect()
+}
+
+fn pointer_summaries(ptrs: &[PointerId], gasn: &GlobalAssignment) -> Vec<PointerSummary> {
+    ptrs.iter()
+        .map(|&ptr| PointerSummary::new(gasn.perms[ptr], gasn.flags[ptr]))
+        .collect()
+}
+
+fn def_path_hash(tcx: TyCtxt, did: DefId) -> (u64, u64) {
+    tcx.def_path_hash(did).0.as_value()
+}
+
+impl CrateSummary {
+    /// Summarize the final results for the functions in `fn_dids` and for all ADTs in the crate.
+    pub fn new(
+        gacx: &GlobalAnalysisCtxt,
+        gasn: &GlobalAssignment,
+        fn_dids: impl IntoIterator<Item = DefId>,
+    ) -> CrateSummary {
+        let tcx = gacx.tcx;
+        let fns = fn_dids
+            .into_iter()
+            .filter(|&did| matches!(tcx.def_kind(did), DefKind::Fn | DefKind::AssocFn))
+            .map(|did| {
+                let lsig = &gacx.fn_sigs[&did];
+                FnSummary {
+                    name: tcx.def_path_str(did),
+                    def_path_hash: def_path_hash(tcx, did),
+                    pointers: pointer_summaries(&pointer_ids(lsig.inputs_and_output()), gasn),
+                }
+            })
+            .collect();
+
+        let adts = gacx
+            .adt_metadata
+            .struct_dids
+            .iter()
+            .map(|&did| {
+                let metadata = &gacx.adt_metadata.table[&did];
+                let hypothetical_params = metadata
+                    .lifetime_params
+                    .iter()
+                    .filter_map(|param| match *param {
+                        OriginParam::Hypothetical(h) => Some(h),
+                        OriginParam::Actual(_) => None,
+                    })
+                    .collect();
+                let fields = tcx
+                    .adt_def(did)
+                    .all_fields()
+                    .map(|field| {
+                        let lty = gacx.field_ltys[&field.did];
+                        let origin_args = metadata.field_info[&field.did].origin_args;
+                        let hypothetical_args = origin_args
+                            .iter()
+                            .map(|lty| {
+                                lty.label
+                                    .iter()
+                                    .filter_map(|arg| match *arg {
+                                        OriginArg::Hypothetical(h) => Some(h),
+                                        OriginArg::Actual(_) => None,
+                                    })
+                                    .collect()
+                            })
+                            .collect();
+                        FieldSummary {
+                            name: tcx.def_path_str(field.did),
+                            def_path_hash: def_path_hash(tcx, field.did),
+                            pointers: pointer_summaries(&pointer_ids([lty]), gasn),
+                            hypothetical_args,
+                        }
+                    })
+                    .collect();
+                AdtSummary {
+                    name: tcx.def_path_str(did),
+                    def_path_hash: def_path_hash(tcx, did),
+                    hypothetical_params,
+                    fields,
+                }
+            })
+            .collect();
+
+        CrateSummary {
+            crate_name: tcx.crate_name(LOCAL_CRATE).to_string(),
+            fns,
+            adts,
+        }
+    }
+
+    /// Write the summary into the directory `dir`, creating it if needed.
+    pub fn write(&self, tcx: TyCtxt, dir: &Path) -> io::Result<()> {
+        let json =
+            serde_json::to_string(self).map_err(|e| io::Error::new(io::ErrorKind::Other, e))?;
+        fs::create_dir_all(dir)?;
+        let path = dir.join(summary_file_name(tcx, LOCAL_CRATE));
+        eprintln!("writing crate summary to {:?}", path);
+        fs::write(path, json)
+    }
+}
+
+/// The permissions and flags of each pointer in a summarized type, in the order of
+/// [`pointer_ids`].
+type SummaryPerms = Vec<(PermissionSet, FlagSet)>;
+
+/// The summaries loaded for the dependencies of the current crate.
+#[derive(Default)]
+pub struct Summaries {
+    fns: HashMap<DefId, SummaryPerms>,
+    fields: HashMap<DefId, SummaryPerms>,
+}
+
+impl Summaries {
+    /// Load the summaries of all dependencies of the current crate that are present in `dir`.
+    /// This labels the signature of every summarized function and the type of every summarized
+    /// field, adding them to `gacx` along with the region metadata of the summarized ADTs.
+    pub fn load(gacx: &mut GlobalAnalysisCtxt, dir: &Path) -> Result<Summaries, String> {
+        let tcx = gacx.tcx;
+        let mut summaries = Summaries::default();
+        for &cnum in tcx.crates(()) {
+            let path = dir.join(summary_file_name(tcx, cnum));
+            if !path.exists() {
+                continue;
+            }
+            eprintln!("loading crate summary from {:?}", path);
+            let json = fs::read_to_string(&path).map_err(|e| format!("{:?}: {}", path, e))?;
+            let summary: CrateSummary =
+                serde_json::from_str(&json).map_err(|e| format!("{:?}: {}", path, e))?;
+            summaries
+                .add_crate(gacx, cnum, &summary)
+                .map_err(|e| format!("{:?}: {}", path, e))?;
+        }
+        Ok(summaries)
+    }
+
+    fn add_crate<'tcx>(
+        &mut self,
+        gacx: &mut GlobalAnalysisCtxt<'tcx>,
+        cnum: CrateNum,
+        summary: &CrateSummary,
+    ) -> Result<(), String> {
+        let tcx = gacx.tcx;
+        let def_ids = crate_def_ids_by_path_hash(tcx, cnum);
+        let resolve = |hash, name: &str| resolve_def_path_hash(tcx, cnum, &def_ids, hash, name);
+
+        for fn_summary in &summary.fns {
+            let did = resolve(fn_summary.def_path_hash, &fn_summary.name)?;
+            let sig = tcx.erase_late_bound_regions(tcx.fn_sig(did));
+            let inputs = sig
+                .inputs()
+                .iter()
+                .map(|&ty| gacx.assign_pointer_ids_with_info(ty, PointerInfo::ANNOTATED))
+                .collect::<Vec<_>>();
+            let inputs = gacx.lcx.mk_slice(&inputs);
+            let output = gacx.assign_pointer_ids_with_info(sig.output(), PointerInfo::ANNOTATED);
+            let lsig = LFnSig { inputs, output };
+
+            let perms = parse_pointers(
+                &fn_summary.name,
+                &fn_summary.pointers,
+                &pointer_ids(lsig.inputs_and_output()),
+            )?;
+            gacx.fn_sigs.insert(did, lsig);
+            self.fns.insert(did, perms);
+        }
+
+        let ltcx = LabeledTyCtxt::<&[OriginArg]>::new(tcx);
+        for adt_summary in &summary.adts {
+            let did = resolve(adt_summary.def_path_hash, &adt_summary.name)?;
+            let adt_def = tcx.adt_def(did);
+            let mut metadata = AdtMetadata::default();
+
+            // The actual lifetime parameters come first, as in `construct_adt_metadata`.
+            if let TyKind::Adt(_, substs) = tcx.type_of(did).kind() {
+                for sub in substs.iter() {
+                    if let GenericArgKind::Lifetime(r) = sub.unpack() {
+                        if let ReEarlyBound(eb) = r.kind() {
+                            metadata.lifetime_params.insert(OriginParam::Actual(eb));
+                        }
+                    }
+                }
+            }
+            metadata.lifetime_params.extend(
+                adt_summary
+                    .hypothetical_params
+                    .iter()
+                    .map(|&h| OriginParam::Hypothetical(h)),
+            );
+
+            for field_summary in &adt_summary.fields {
+                let field_did = resolve(field_summary.def_path_hash, &field_summary.name)?;
+                let field = adt_def
+                    .all_fields()
+                    .find(|field| field.did == field_did)
+                    .ok_or_else(|| {
+                        format!(
+                            "`{}` is not a field of `{}`",
+                            field_summary.name, adt_summary.name
+                        )
+                    })?;
+                gacx.assign_pointer_to_field(field);
+                let lty = gacx.field_ltys[&field_did];
+
+                let perms = parse_pointers(
+                    &field_summary.name,
+                    &field_summary.pointers,
+                    &pointer_ids([lty]),
+                )?;
+                self.fields.insert(field_did, perms);
+
+                // Rebuild the origin arguments of each node the same way `construct_adt_metadata`
+                // does: actual lifetimes, followed by hypothetical ones.
+                if field_summary.hypothetical_args.len() != lty.iter().count() {
+                    return Err(format!(
+                        "wrong number of origin arguments for `{}`",
+                        field_summary.name
+                    ));
+                }
+                let mut hypothetical_args = field_summary.hypothetical_args.iter();
+                let origin_args = ltcx.label(tcx.type_of(field_did), &mut |ty| {
+                    let mut args = Vec::new();
+                    match *ty.kind() {
+                        TyKind::Ref(r, _, _) => args.push(OriginArg::Actual(r)),
+                        TyKind::Adt(_, substs) => {
+                            args.extend(substs.regions().map(OriginArg::Actual))
+                        }
+                        _ => {}
+                    }
+                    let hypothetical = hypothetical_args.next().unwrap();
+                    args.extend(hypothetical.iter().map(|&h| OriginArg::Hypothetical(h)));
+                    if args.is_empty() {
+                        return &[];
+                    }
+                    ltcx.arena().alloc_slice(&args)
+                });
+                metadata
+                    .field_info
+                    .insert(field_did, FieldMetadata { origin_args });
+            }
+
+            gacx.extern_adt_metadata.insert(did, metadata);
+        }
+
+        Ok(())
+    }
+
+    /// Get the summarized permissions and flags of every pointer in the signatures of summarized
+    /// functions and the types of summarized fields.
+    pub fn ptr_perms(&self, gacx: &GlobalAnalysisCtxt) -> Vec<(PointerId, PermissionSet, FlagSet)> {
+        let fn_ptrs = self.fns.iter().flat_map(|(did, perms)| {
+            let ptrs = pointer_ids(gacx.fn_sigs[did].inputs_and_output());
+            ptrs.into_iter().zip(perms.iter().copied())
+        });
+        let field_ptrs = self.fields.iter().flat_map(|(did, perms)| {
+            let ptrs = pointer_ids([gacx.field_ltys[did]]);
+            ptrs.into_iter().zip(perms.iter().copied())
+        });
+        fn_ptrs
+            .chain(field_ptrs)
+            .map(|(ptr, (perms, flags))| (ptr, perms, flags))
+            .collect()
+    }
+}
+
+/// Map the `DefPathHash` of every item in the non-local crate `cnum` to its `DefId`.
+/// `TyCtxt::def_path_hash_to_def_id` panics on hashes that don't exist in the crate, which
+/// happens when a summary is stale, so we look hashes up in this map instead.
+fn crate_def_ids_by_path_hash(tcx: TyCtxt, cnum: CrateNum) -> HashMap<DefPathHash, DefId> {
+    let num_def_ids = CStore::from_tcx(tcx).num_def_ids_untracked(cnum);
+    (0..num_def_ids)
+        .map(|i| {
+            let did = DefId {
+                krate: cnum,
+                index: DefIndex::from_usize(i),
+            };
+            (tcx.def_path_hash(did), did)
+        })
+        .collect()
+}
+
+/// Find the item in crate `cnum` with the given `DefPathHash`, using the map built by
+/// [`crate_def_ids_by_path_hash`].  `name` is used for errors.
+fn resolve_def_path_hash(
+    tcx: TyCtxt,
+    cnum: CrateNum,
+    def_ids: &HashMap<DefPathHash, DefId>,
+    hash: (u64, u64),
+    name: &str,
+) -> Result<DefId, String> {
+    let hash = DefPathHash(Fingerprint::new(hash.0, hash.1));
+    if hash.stable_crate_id() != tcx.stable_crate_id(cnum) {
+        return Err(format!(
+            "`{}` is not in crate `{}`",
+            name,
+            tcx.crate_name(cnum)
+        ));
+    }
+    def_ids.get(&hash).copied().ok_or_else(|| {
+        format!(
+            "no item `{}` with {:?} in crate `{}`; the summary may be stale",
+            name,
+            hash,
+            tcx.crate_name(cnum)
+        )
+    })
+}
+
+/// Parse the summaries of the pointers `ptrs` in the type of `name`.
+fn parse_pointers(
+    name: &str,
+    summaries: &[PointerSummary],
+    ptrs: &[PointerId],
+) -> Result<SummaryPerms, String> {
+    if summaries.len() != ptrs.len() {
+        return Err(format!(
+            "expected {} pointers in the type of `{}`, but the summary has {}",
+            ptrs.len(),
+            name,
+            summaries.len()
+        ));
+    }
+    summaries
+        .iter()
+        .map(|ptr| ptr.parse().map_err(|e| format!("`{}`: {}", name, e)))
+        .collect()
+}
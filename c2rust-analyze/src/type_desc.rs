@@ -1,6 +1,7 @@
 use crate::context::{FlagSet, PermissionSet};
 use rustc_middle::mir::Mutability;
-use rustc_middle::ty::{AdtDef, Ty, TyCtxt, TyKind};
+use rustc_middle::ty::{AdtDef, IntTy, Ty, TyCtxt, TyKind, UintTy};
+use rustc_span::sym;
 
 #[allow(dead_code)]
 #[derive(Clone, Copy, PartialEq, Eq, Debug, Hash)]
@@ -27,8 +28,12 @@ pub enum Quantity {
     Single,
     /// E.g. `&[T]`
     Slice,
-    /// E.g. `OffsetPtr<T>`
+    /// E.g. `OffsetPtr<T>`.  This is a safe cursor type from the `c2rust-support` crate, which
+    /// combines the `Ownership` and `Quantity`, so it's only used with `Imm`, `Cell`, and `Mut`.
     OffsetPtr,
+    /// E.g. `&CStr`.  This replaces the pointee type, which must be `i8` or `u8`, and is only used
+    /// with `Imm`.
+    CStr,
 
     /// E.g. `&[T; 10]`.  This is used only for existing `FIXED` pointers; `perms_to_desc` on a raw
     /// pointer never produces `Array`.
@@ -39,11 +44,22 @@ pub enum Quantity {
 pub struct TypeDesc<'tcx> {
     pub own: Ownership,
     pub qty: Quantity,
+    /// If set, the pointer is nullable and is wrapped in `Option`, as in `Option<&T>`.  This is
+    /// never set for raw pointers, which can already be null.
+    pub option: bool,
     pub pointee_ty: Ty<'tcx>,
 }
 
-fn perms_to_own_and_qty(perms: PermissionSet, flags: FlagSet) -> (Ownership, Quantity) {
-    let own = if perms.contains(PermissionSet::UNIQUE | PermissionSet::WRITE) {
+fn perms_to_own_and_qty(
+    perms: PermissionSet,
+    flags: FlagSet,
+    pointee_ty: Ty,
+) -> (Ownership, Quantity) {
+    let own = if perms.contains(PermissionSet::FREE) && flags.contains(FlagSet::HEAP) {
+        // A pointer that gets passed to `free` owns the memory it points to, but it can only be a
+        // `Box` if it always comes from an allocation that's rewritten to `Box` as well.
+        Ownership::Box
+    } else if perms.contains(PermissionSet::UNIQUE | PermissionSet::WRITE) {
         Ownership::Mut
     } else if flags.contains(FlagSet::CELL) {
         Ownership::Cell
@@ -53,9 +69,19 @@ fn perms_to_own_and_qty(perms: PermissionSet, flags: FlagSet) -> (Ownership, Qua
         Ownership::Imm
     };
 
-    let qty = if perms.contains(PermissionSet::OFFSET_SUB) {
+    let qty = if perms.contains(PermissionSet::NUL_TERMINATED)
+        && !perms.intersects(PermissionSet::OFFSET_ADD | PermissionSet::OFFSET_SUB)
+        && own == Ownership::Imm
+        && is_c_char(pointee_ty)
+    {
+        // A C string that's only passed around and read as a whole.  Strings that are scanned
+        // with pointer arithmetic stay as slices, since `CStr` has no way to step through them.
+        Quantity::CStr
+    } else if perms.contains(PermissionSet::OFFSET_SUB) && own != Ownership::Box {
         Quantity::OffsetPtr
-    } else if perms.contains(PermissionSet::OFFSET_ADD) {
+    } else if perms.intersects(PermissionSet::OFFSET_ADD | PermissionSet::OFFSET_SUB) {
+        // There is no owning version of `OffsetPtr`, so a `Box` with `OFFSET_SUB` uses `Box<[T]>`
+        // instead.
         Quantity::Slice
     } else {
         Quantity::Single
@@ -64,6 +90,10 @@ fn perms_to_own_and_qty(perms: PermissionSet, flags: FlagSet) -> (Ownership, Qua
     (own, qty)
 }
 
+fn perms_to_option(perms: PermissionSet) -> bool {
+    !perms.contains(PermissionSet::NON_NULL)
+}
+
 /// Obtain the `TypeDesc` for a pointer.  `ptr_ty` should be the `Ty` of the pointer, and `perms`
 /// and `flags` should be taken from its outermost `PointerId`.
 pub fn perms_to_desc(ptr_ty: Ty, perms: PermissionSet, flags: FlagSet) -> TypeDesc {
@@ -73,8 +103,6 @@ pub fn perms_to_desc(ptr_ty: Ty, perms: PermissionSet, flags: FlagSet) -> TypeDe
         "building TypeDesc for FIXED pointer requires a related pointee type"
     );
 
-    let (own, qty) = perms_to_own_and_qty(perms, flags);
-
     let pointee_ty = match *ptr_ty.kind() {
         TyKind::Ref(_, ty, _) => ty,
         TyKind::RawPtr(mt) => mt.ty,
@@ -83,9 +111,12 @@ pub fn perms_to_desc(ptr_ty: Ty, perms: PermissionSet, flags: FlagSet) -> TypeDe
         _ => panic!("expected a pointer type, but got {:?}", ptr_ty),
     };
 
+    let (own, qty) = perms_to_own_and_qty(perms, flags, pointee_ty);
+
     TypeDesc {
         own,
         qty,
+        option: perms_to_option(perms),
         pointee_ty,
     }
 }
@@ -93,11 +124,12 @@ pub fn perms_to_desc(ptr_ty: Ty, perms: PermissionSet, flags: FlagSet) -> TypeDe
 /// Obtain the `TypeDesc` for a pointer to a local.  `local_ty` should be the `Ty` of the local
 /// itself, and `perms` and `flags` should be taken from its `addr_of_local` `PointerId`.
 pub fn local_perms_to_desc(local_ty: Ty, perms: PermissionSet, flags: FlagSet) -> TypeDesc {
-    let (own, qty) = perms_to_own_and_qty(perms, flags);
     let pointee_ty = local_ty;
+    let (own, qty) = perms_to_own_and_qty(perms, flags, pointee_ty);
     TypeDesc {
         own,
         qty,
+        option: perms_to_option(perms),
         pointee_ty,
     }
 }
@@ -109,14 +141,19 @@ pub fn perms_to_desc_with_pointee<'tcx>(
     perms: PermissionSet,
     flags: FlagSet,
 ) -> TypeDesc<'tcx> {
-    let (own, qty) = if flags.contains(FlagSet::FIXED) {
-        unpack_pointer_type(tcx, ptr_ty, pointee_ty)
+    // `FIXED` pointers keep their existing type, which is a reference or raw pointer, not an
+    // `Option`.
+    let (own, qty, option) = if flags.contains(FlagSet::FIXED) {
+        let (own, qty) = unpack_pointer_type(tcx, ptr_ty, pointee_ty);
+        (own, qty, false)
     } else {
-        perms_to_own_and_qty(perms, flags)
+        let (own, qty) = perms_to_own_and_qty(perms, flags, pointee_ty);
+        (own, qty, perms_to_option(perms))
     };
     TypeDesc {
         own,
         qty,
+        option,
         pointee_ty,
     }
 }
@@ -137,7 +174,8 @@ pub fn unpack_pointer_type<'tcx>(
         Box,
         Rc,
         Slice,
-        OffsetPtr,
+        OffsetPtr(Mutability),
+        CStr,
         Array,
     }
 
@@ -152,8 +190,13 @@ pub fn unpack_pointer_type<'tcx>(
             TyKind::Adt(adt_def, substs) if is_cell(tcx, adt_def) => {
                 (Step::Cell, substs.type_at(0))
             }
-            TyKind::Adt(adt_def, substs) if is_offset_ptr(tcx, adt_def) => {
-                (Step::OffsetPtr, substs.type_at(0))
+            TyKind::Adt(adt_def, substs) if offset_ptr_mutability(tcx, adt_def).is_some() => {
+                let mutbl = offset_ptr_mutability(tcx, adt_def).unwrap();
+                (Step::OffsetPtr(mutbl), substs.type_at(0))
+            }
+            // `CStr` has no type parameter, so it stands in for the `c_char` pointee directly.
+            TyKind::Adt(adt_def, _) if is_cstr(tcx, adt_def) && is_c_char(pointee_ty) => {
+                (Step::CStr, pointee_ty)
             }
             TyKind::Slice(inner_ty) => (Step::Slice, inner_ty),
             TyKind::Array(inner_ty, _) => (Step::Array, inner_ty),
@@ -181,7 +224,15 @@ pub fn unpack_pointer_type<'tcx>(
     };
 
     // This logic is roughly the inverse of that in `rewrite::ty::mk_rewritten_ty`.
-    let mut own = if eat(Step::Ref(Mutability::Not)) {
+    // `OffsetPtr` and `OffsetPtrMut` provide both the ownership and the quantity.
+    let mut is_offset_ptr = false;
+    let mut own = if eat(Step::OffsetPtr(Mutability::Not)) {
+        is_offset_ptr = true;
+        Ownership::Imm
+    } else if eat(Step::OffsetPtr(Mutability::Mut)) {
+        is_offset_ptr = true;
+        Ownership::Mut
+    } else if eat(Step::Ref(Mutability::Not)) {
         Ownership::Imm
     } else if eat(Step::Ref(Mutability::Mut)) {
         Ownership::Mut
@@ -203,10 +254,12 @@ pub fn unpack_pointer_type<'tcx>(
         );
     };
 
-    let qty = if eat(Step::Slice) {
-        Quantity::Slice
-    } else if eat(Step::OffsetPtr) {
+    let qty = if is_offset_ptr {
         Quantity::OffsetPtr
+    } else if eat(Step::CStr) {
+        Quantity::CStr
+    } else if eat(Step::Slice) {
+        Quantity::Slice
     } else if eat(Step::Array) {
         Quantity::Array
     } else {
@@ -241,8 +294,26 @@ fn is_rc<'tcx>(_tcx: TyCtxt<'tcx>, _adt_def: AdtDef<'tcx>) -> bool {
     false
 }
 
-/// Returns `true` if `adt_def` is the type `OffsetPtr` from the C2Rust support library.
-fn is_offset_ptr<'tcx>(_tcx: TyCtxt<'tcx>, _adt_def: AdtDef<'tcx>) -> bool {
-    // TODO
-    false
+/// If `adt_def` is the type `OffsetPtr` or `OffsetPtrMut` from the C2Rust support library,
+/// returns the mutability of the pointer.
+fn offset_ptr_mutability<'tcx>(tcx: TyCtxt<'tcx>, adt_def: AdtDef<'tcx>) -> Option<Mutability> {
+    let did = adt_def.did();
+    if tcx.crate_name(did.krate).as_str() != "c2rust_support" {
+        return None;
+    }
+    match tcx.item_name(did).as_str() {
+        "OffsetPtr" => Some(Mutability::Not),
+        "OffsetPtrMut" => Some(Mutability::Mut),
+        _ => None,
+    }
+}
+
+/// Returns `true` if `adt_def` is the type `core::ffi::CStr`.
+fn is_cstr<'tcx>(tcx: TyCtxt<'tcx>, adt_def: AdtDef<'tcx>) -> bool {
+    tcx.is_diagnostic_item(sym::CStr, adt_def.did())
+}
+
+/// Returns `true` if `ty` is a C character type that can be the pointee of a `CStr`.
+pub fn is_c_char(ty: Ty) -> bool {
+    matches!(ty.kind(), TyKind::Int(IntTy::I8) | TyKind::Uint(UintTy::U8))
 }
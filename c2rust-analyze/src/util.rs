@@ -1,18 +1,24 @@
+use crate::context::const_alloc_id;
 use crate::labeled_ty::LabeledTy;
 use crate::trivial::IsTrivial;
+use crate::type_desc::is_c_char;
 use rustc_ast::ast::AttrKind;
 use rustc_const_eval::interpret::Scalar;
 use rustc_hir::def::DefKind;
 use rustc_hir::def_id::{DefId, LocalDefId};
+use rustc_infer::infer::TyCtxtInferExt;
+use rustc_middle::mir::interpret::GlobalAlloc;
 use rustc_middle::mir::{
-    BasicBlock, BasicBlockData, Body, Constant, Field, Local, Location, Mutability, Operand, Place,
-    PlaceElem, PlaceRef, ProjectionElem, Rvalue, Statement, StatementKind,
+    BasicBlock, BasicBlockData, Body, CastKind, Constant, Field, Local, Location, Mutability,
+    Operand, Place, PlaceElem, PlaceRef, ProjectionElem, Rvalue, Statement, StatementKind,
+    TerminatorKind,
 };
 use rustc_middle::ty::{
     self, AdtDef, DefIdTree, EarlyBinder, FnSig, GenericArg, List, Subst, SubstsRef, Ty, TyCtxt,
     TyKind, UintTy,
 };
 use rustc_span::symbol::{sym, Symbol};
+use rustc_trait_selection::infer::InferCtxtExt;
 use rustc_type_ir::IntTy;
 use std::fmt::Debug;
 
@@ -159,6 +165,12 @@ pub enum Callee<'tcx> {
         mutbl: Mutability,
     },
 
+    /// `<*mut T>::offset_from` or `<*const T>::offset_from`.
+    PtrOffsetFrom {
+        pointee_ty: Ty<'tcx>,
+        mutbl: Mutability,
+    },
+
     /// `<[T]>::as_ptr` and `<[T]>::as_mut_ptr` methods.  Also covers the array and str versions.
     SliceAsPtr {
         /// The pointee type.  This is either `TyKind::Slice`, `TyKind::Array`, or `TyKind::Str`.
@@ -191,6 +203,20 @@ pub enum Callee<'tcx> {
 
     /// core::ptr::is_null
     IsNull,
+
+    /// `core::ptr::null` or `core::ptr::null_mut`.
+    Null {
+        /// Mutability of the output pointer.
+        mutbl: Mutability,
+    },
+
+    /// `<Option<F>>::unwrap` or `<Option<F>>::expect`, where `F` is a `fn` ptr type.  C2Rust
+    /// translates C function pointers to `Option<unsafe extern "C" fn(...)>`, and unwraps them
+    /// this way before each call.
+    UnwrapFnPtr {
+        /// The `fn` ptr type `F`.
+        fn_ptr_ty: Ty<'tcx>,
+    },
 }
 
 pub fn ty_callee<'tcx>(tcx: TyCtxt<'tcx>, ty: Ty<'tcx>) -> Callee<'tcx> {
@@ -236,8 +262,8 @@ fn builtin_callee<'tcx>(tcx: TyCtxt<'tcx>, did: DefId, substs: SubstsRef<'tcx>)
     let name = tcx.item_name(did);
 
     match name.as_str() {
-        "offset" => {
-            // The `offset` inherent method of `*const T` and `*mut T`.
+        name @ "offset" | name @ "offset_from" => {
+            // The `offset` and `offset_from` inherent methods of `*const T` and `*mut T`.
             let parent_did = tcx.parent(did);
             if tcx.def_kind(parent_did) != DefKind::Impl {
                 return None;
@@ -250,7 +276,11 @@ fn builtin_callee<'tcx>(tcx: TyCtxt<'tcx>, did: DefId, substs: SubstsRef<'tcx>)
                 TyKind::RawPtr(tm) => (tm.ty, tm.mutbl),
                 _ => return None,
             };
-            Some(Callee::PtrOffset { pointee_ty, mutbl })
+            match name {
+                "offset" => Some(Callee::PtrOffset { pointee_ty, mutbl }),
+                "offset_from" => Some(Callee::PtrOffsetFrom { pointee_ty, mutbl }),
+                _ => unreachable!(),
+            }
         }
 
         name @ "as_ptr" | name @ "as_mut_ptr" => {
@@ -340,6 +370,49 @@ fn builtin_callee<'tcx>(tcx: TyCtxt<'tcx>, did: DefId, substs: SubstsRef<'tcx>)
             Some(Callee::IsNull)
         }
 
+        name @ "null" | name @ "null_mut" => {
+            // `core::ptr::null` and `core::ptr::null_mut`.
+            let parent_did = tcx.parent(did);
+            if tcx.def_kind(parent_did) != DefKind::Mod {
+                return None;
+            }
+            if tcx.crate_name(did.krate).as_str() != "core"
+                || tcx.item_name(parent_did).as_str() != "ptr"
+            {
+                return None;
+            }
+            let mutbl = match name {
+                "null" => Mutability::Not,
+                "null_mut" => Mutability::Mut,
+                _ => unreachable!(),
+            };
+            Some(Callee::Null { mutbl })
+        }
+
+        "unwrap" | "expect" => {
+            // The `unwrap` and `expect` inherent methods of `Option<F>`, for `fn` ptr types `F`.
+            let parent_did = tcx.parent(did);
+            if tcx.def_kind(parent_did) != DefKind::Impl {
+                return None;
+            }
+            if tcx.impl_trait_ref(parent_did).is_some() {
+                return None;
+            }
+            let parent_impl_ty = EarlyBinder(tcx.type_of(parent_did)).subst(tcx, substs);
+            let fn_ptr_ty = match *parent_impl_ty.kind() {
+                TyKind::Adt(adt_def, substs)
+                    if tcx.is_diagnostic_item(sym::Option, adt_def.did()) =>
+                {
+                    substs.type_at(0)
+                }
+                _ => return None,
+            };
+            if !fn_ptr_ty.is_fn_ptr() {
+                return None;
+            }
+            Some(Callee::UnwrapFnPtr { fn_ptr_ty })
+        }
+
         _ => {
             eprintln!("name: {name:?}");
             None
@@ -405,6 +478,213 @@ pub fn is_null_const(constant: Constant) -> bool {
     }
 }
 
+/// Returns the rvalue assigned to `op`, if `op` is a local that's assigned exactly once.  This is
+/// usually the case for temporaries.
+fn single_assignment<'a, 'tcx>(
+    mir: &'a Body<'tcx>,
+    op: &Operand<'tcx>,
+) -> Option<&'a Rvalue<'tcx>> {
+    let local = op.place()?.as_local()?;
+    let mut assigns = mir
+        .basic_blocks()
+        .iter()
+        .flat_map(|bb_data| &bb_data.statements)
+        .filter_map(|stmt| match stmt.kind {
+            StatementKind::Assign(ref x) if x.0.as_local() == Some(local) => Some(&x.1),
+            _ => None,
+        });
+    match (assigns.next(), assigns.next()) {
+        (Some(rv), None) => Some(rv),
+        _ => None,
+    }
+}
+
+/// Returns `true` if `op` is an integer constant that is not negative.
+fn is_nonnegative_const(op: &Operand) -> bool {
+    match *op {
+        Operand::Constant(ref c) => match c.literal.try_to_scalar() {
+            Some(Scalar::Int(i)) => i.try_to_int(i.size()).map_or(false, |x| x >= 0),
+            _ => false,
+        },
+        Operand::Copy(_) | Operand::Move(_) => false,
+    }
+}
+
+/// Returns `true` if the offset operand `op` of a call to `offset` is known to be non-negative.
+/// This is the case for non-negative constants and for unsigned integers cast to `isize`, as in
+/// `p.offset(i as isize)` with `i: usize`.  Signed integers may be negative, so offsetting by
+/// them may step backward.
+pub fn is_nonnegative_offset<'tcx>(
+    tcx: TyCtxt<'tcx>,
+    mir: &Body<'tcx>,
+    op: &Operand<'tcx>,
+) -> bool {
+    if is_nonnegative_const(op) {
+        return true;
+    }
+    match single_assignment(mir, op) {
+        Some(&Rvalue::Use(ref src)) => is_nonnegative_const(src),
+        Some(&Rvalue::Cast(CastKind::Misc, ref src, _)) => {
+            matches!(src.ty(mir, tcx).kind(), TyKind::Uint(_)) || is_nonnegative_const(src)
+        }
+        _ => false,
+    }
+}
+
+/// Follow `op` back through copies and casts of temporaries, returning the operand that it was
+/// originally computed from.  For example, given `_3` in `_2 = (*_1); _3 = move _2 as i32`, this
+/// returns `(*_1)`.
+fn trace_operand<'a, 'tcx>(mir: &'a Body<'tcx>, mut op: &'a Operand<'tcx>) -> &'a Operand<'tcx> {
+    // Bound the number of steps, in case the assignments form a cycle.
+    for _ in 0..mir.local_decls.len() {
+        match single_assignment(mir, op) {
+            Some(Rvalue::Use(src)) | Some(Rvalue::Cast(CastKind::Misc, src, _)) => op = src,
+            _ => break,
+        }
+    }
+    op
+}
+
+/// Returns `true` if `op` is the constant zero, possibly cast to another integer type, as in `0`
+/// or `'\0' as i32`.
+pub fn is_zero_operand<'tcx>(mir: &Body<'tcx>, op: &Operand<'tcx>) -> bool {
+    match *trace_operand(mir, op) {
+        Operand::Constant(ref c) => match c.literal.try_to_scalar() {
+            Some(Scalar::Int(i)) => i.is_null(),
+            _ => false,
+        },
+        Operand::Copy(_) | Operand::Move(_) => false,
+    }
+}
+
+/// If `pl` is a temporary holding the result of `p.offset(i)`, returns the place of `p`.
+fn ptr_offset_base<'tcx>(
+    tcx: TyCtxt<'tcx>,
+    mir: &Body<'tcx>,
+    pl: PlaceRef<'tcx>,
+) -> Option<Place<'tcx>> {
+    let local = pl.as_local()?;
+    mir.basic_blocks()
+        .iter()
+        .find_map(|bb_data| match bb_data.terminator().kind {
+            TerminatorKind::Call {
+                ref func,
+                ref args,
+                destination,
+                ..
+            } if destination.as_local() == Some(local) => match ty_callee(tcx, func.ty(mir, tcx)) {
+                Callee::PtrOffset { .. } => args[0].place(),
+                _ => None,
+            },
+            _ => None,
+        })
+}
+
+/// If `op` is a C character read through a pointer, as in `*p` or `*p as i32`, returns the place
+/// of the pointer `p`.  For `*p.offset(i)`, this returns the place of `p`.
+pub fn c_char_load_pointer<'tcx>(
+    tcx: TyCtxt<'tcx>,
+    mir: &Body<'tcx>,
+    op: &Operand<'tcx>,
+) -> Option<PlaceRef<'tcx>> {
+    let pl = trace_operand(mir, op).place()?;
+    if !is_c_char(pl.ty(mir, tcx).ty) {
+        return None;
+    }
+    let ptr_pl = match pl.as_ref().last_projection() {
+        Some((ptr_pl, ProjectionElem::Deref)) => ptr_pl,
+        _ => return None,
+    };
+    // The result of `offset` is only a temporary, so the string belongs to the base pointer.
+    match ptr_offset_base(tcx, mir, ptr_pl) {
+        Some(base_pl) => Some(base_pl.as_ref()),
+        None => Some(ptr_pl),
+    }
+}
+
+/// If `op` is computed from a byte string literal, as in `b"%d\0" as *const u8 as *const c_char`,
+/// returns the contents of the literal.
+pub fn string_literal_bytes<'tcx>(
+    tcx: TyCtxt<'tcx>,
+    mir: &Body<'tcx>,
+    op: &Operand<'tcx>,
+) -> Option<Vec<u8>> {
+    let mut op = op.clone();
+    // Bound the number of steps, in case the assignments form a cycle.
+    for _ in 0..mir.local_decls.len() {
+        let rv = match op {
+            Operand::Constant(ref c) => {
+                return match tcx.try_get_global_alloc(const_alloc_id(c)?)? {
+                    GlobalAlloc::Memory(alloc) => {
+                        let alloc = alloc.inner();
+                        let bytes =
+                            alloc.inspect_with_uninit_and_ptr_outside_interpreter(0..alloc.len());
+                        Some(bytes.to_owned())
+                    }
+                    _ => None,
+                };
+            }
+            Operand::Copy(_) | Operand::Move(_) => single_assignment(mir, &op)?,
+        };
+        op = match *rv {
+            Rvalue::Use(ref src) | Rvalue::Cast(_, ref src, _) => src.clone(),
+            // `&raw const *s`, which is inserted for the cast from `&[u8; N]` to `*const u8`.
+            Rvalue::AddressOf(_, pl) | Rvalue::Ref(_, _, pl)
+                if pl.projection[..] == [ProjectionElem::Deref] =>
+            {
+                Operand::Copy(Place::from(pl.local))
+            }
+            _ => return None,
+        };
+    }
+    None
+}
+
+/// For a `printf`-style function, returns the index of its format string argument.
+pub fn printf_format_arg(name: &str) -> Option<usize> {
+    match name {
+        "printf" => Some(0),
+        "fprintf" | "sprintf" | "dprintf" => Some(1),
+        "snprintf" => Some(2),
+        _ => None,
+    }
+}
+
+/// Parse a `printf` format string, and return the indices of the arguments that are printed with
+/// `%s`.  Indices are relative to the first argument after the format string.
+pub fn printf_string_args(fmt: &[u8]) -> Vec<usize> {
+    let fmt = fmt.split(|&b| b == 0).next().unwrap_or(fmt);
+    let mut string_args = Vec::new();
+    let mut arg = 0;
+    let mut i = 0;
+    while i < fmt.len() {
+        if fmt[i] != b'%' {
+            i += 1;
+            continue;
+        }
+        i += 1;
+        // Skip flags, field width, precision, and length modifiers.  A `*` width or precision
+        // consumes an argument of its own.
+        while i < fmt.len() && b"-+ #0123456789.*hlLqjzt".contains(&fmt[i]) {
+            if fmt[i] == b'*' {
+                arg += 1;
+            }
+            i += 1;
+        }
+        match fmt.get(i) {
+            Some(b'%') => {}
+            Some(b's') => {
+                string_args.push(arg);
+                arg += 1;
+            }
+            Some(_) => arg += 1,
+            None => break,
+        }
+        i += 1;
+    }
+    string_args
+}
+
 pub trait PhantomLifetime<'a> {}
 impl<'a, T: ?Sized> PhantomLifetime<'a> for T {}
 
@@ -545,3 +825,17 @@ pub fn is_automatically_derived<'tcx>(tcx: TyCtxt<'tcx>, body: &Body<'tcx>) -> b
         .map(|parent_def_id| tcx.has_attr(parent_def_id, sym::automatically_derived))
         .unwrap_or(false)
 }
+
+/// Check if `ty` implements `Default` in the context of the item `did`.
+pub fn implements_default<'tcx>(tcx: TyCtxt<'tcx>, did: DefId, ty: Ty<'tcx>) -> bool {
+    let default_did = match tcx.get_diagnostic_item(sym::Default) {
+        Some(x) => x,
+        None => return false,
+    };
+    let param_env = tcx.param_env(did);
+    tcx.infer_ctxt().enter(|infcx| {
+        infcx
+            .type_implements_trait(default_did, ty, List::empty(), param_env)
+            .must_apply_modulo_regions()
+    })
+}
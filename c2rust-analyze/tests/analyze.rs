@@ -41,6 +41,27 @@ fn lighttpd_minimal() {
     Analyze::resolve().run("../analysis/tests/lighttpd-minimal/src/main.rs");
 }
 
+#[test]
+fn cargo_workspace() {
+    let output = Analyze::resolve().run_cargo("../analysis/tests/workspace/Cargo.toml", &["build"]);
+    // Both workspace members are analyzed, the library before the binary that depends on it.
+    let lib = output.find("final labeling for \"set\"");
+    let app = output.find("final labeling for \"incr\"");
+    assert!(
+        matches!((lib, app), (Some(lib), Some(app)) if lib < app),
+        "both crates should be analyzed in dependency order:\n{output}"
+    );
+    // With the library's summary, calls into it and uses of its struct can be analyzed too.
+    assert!(
+        output.contains("final labeling for \"main\""),
+        "analysis of `main` should succeed:\n{output}"
+    );
+    assert!(
+        output.contains("c: &'h0 (workspace_lib::Counter<'h1>)"),
+        "`Counter` should get the lifetime parameter added in the library:\n{output}"
+    );
+}
+
 #[test]
 fn with_pdg_file() {
     use std::path::PathBuf;
@@ -50,6 +50,10 @@ struct AnalyzeArgs {
     #[clap(long, value_parser)]
     env: Vec<EnvVar>,
 
+    /// Output modes for the rewritten code, passed to `c2rust-analyze` as `--rewrite-mode`.
+    #[arg(long)]
+    rewrite_mode: Option<String>,
+
     /// Enable catching panics during analysis and rewriting.  This behavior is enabled by default
     /// when running the tool manually, but for testing we disable it to detect errors more easily.
     /// Tests that are meant to exercise the panic-catching behavior can explicitly enable it with
@@ -165,6 +169,9 @@ impl Analyze {
         if !args.catch_panics {
             cmd.env("C2RUST_ANALYZE_TEST_DONT_CATCH_PANIC", "1");
         }
+        if let Some(ref modes) = args.rewrite_mode {
+            cmd.arg("--rewrite-mode").arg(modes);
+        }
         cmd.arg(&rs_path)
             .arg("-L")
             .arg(lib_dir)
@@ -209,6 +216,33 @@ impl Analyze {
     pub fn run(&self, rs_path: impl AsRef<Path>) -> PathBuf {
         self.run_with(rs_path, |_| {}, None)
     }
+
+    /// Run `c2rust-analyze -- <cargo_args> --manifest-path <manifest_path>` and return its
+    /// output.
+    pub fn run_cargo(&self, manifest_path: impl AsRef<Path>, cargo_args: &[&str]) -> String {
+        let dir = Path::new(env!("CARGO_MANIFEST_DIR"));
+        let manifest_path = dir.join(manifest_path);
+        // `cargo` skips crates that are up to date, so start from a clean build to make sure every
+        // crate is analyzed, and with fresh summaries of its dependencies.
+        let target_dir = manifest_path.parent().unwrap().join("analyze.target");
+        if target_dir.exists() {
+            fs::remove_dir_all(&target_dir).unwrap();
+        }
+        let mut cmd = Command::new(&self.path);
+        cmd.arg("--")
+            .args(cargo_args)
+            .arg("--manifest-path")
+            .arg(&manifest_path);
+        let output = cmd.output().unwrap();
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        assert!(
+            output.status.success(),
+            "c2rust-analyze failed with status {}:\n> {cmd:?}\n{stdout}\n{stderr}",
+            output.status,
+        );
+        format!("{stdout}{stderr}")
+    }
 }
 
 pub struct FileCheck {
@@ -39,6 +39,9 @@ define_tests! {
     alias2,
     alias3,
     alloc,
+    alloc_box,
+    annotations,
+    annotations_config,
     as_ptr,
     call1,
     call_cast,
@@ -46,19 +49,26 @@ define_tests! {
     catch_panic,
     cell,
     clone1,
+    cstr,
     extern_fn1,
     fields,
     field_temp,
     fixed,
+    fn_ptr,
     foreign,
     insertion_sort,
     insertion_sort_driver,
     insertion_sort_rewrites,
     known_fn,
+    non_null,
     offset1,
     offset2,
+    offset_ptr,
     ptrptr1,
     regions_fixed,
+    results_json,
+    rewrite_mode_diff,
+    rewrite_mode_flag,
     statics,
     test_attrs,
     trivial,
@@ -26,7 +26,7 @@ extern "C" {
 
 // CHECK-LABEL: final labeling for "calloc1"
 unsafe extern "C" fn calloc1() -> *mut i32 {
-    // CHECK-DAG: ([[@LINE+1]]: i): addr_of = UNIQUE
+    // CHECK-DAG: ([[@LINE+1]]: i): addr_of = UNIQUE | NON_NULL
     let i = calloc(
         1 as libc::c_int as libc::c_ulong,
         ::std::mem::size_of::<i32>() as libc::c_ulong,
@@ -38,7 +38,7 @@ unsafe extern "C" fn calloc1() -> *mut i32 {
 
 // CHECK-LABEL: final labeling for "malloc1"
 pub unsafe extern "C" fn malloc1(mut cnt: libc::c_int) -> *mut i32 {
-    // CHECK-DAG: ([[@LINE+1]]: i): addr_of = UNIQUE, type = READ
+    // CHECK-DAG: ([[@LINE+1]]: i): addr_of = UNIQUE | NON_NULL, type = READ#
     let i = malloc(::std::mem::size_of::<i32>() as libc::c_ulong) as *mut i32;
     let x = *i;
     return i;
@@ -46,7 +46,7 @@ pub unsafe extern "C" fn malloc1(mut cnt: libc::c_int) -> *mut i32 {
 
 // CHECK-LABEL: final labeling for "free1"
 unsafe extern "C" fn free1(mut i: *mut i32) {
-    // CHECK-DAG: ([[@LINE+1]]: i{{.*}}): {{.*}}type = UNIQUE | FREE#
+    // CHECK-DAG: ([[@LINE+1]]: i{{.*}}): {{.*}}type = UNIQUE | FREE | NON_NULL#
     free(i as *mut libc::c_void);
 }
 
@@ -54,12 +54,12 @@ unsafe extern "C" fn free1(mut i: *mut i32) {
 unsafe extern "C" fn realloc1(mut i: *mut i32, len: libc::c_ulong) {
     let mut capacity = 1;
     let mut x = 1;
-    // CHECK-DAG: ([[@LINE+1]]: mut elem): addr_of = UNIQUE, type = READ | WRITE | OFFSET_ADD | OFFSET_SUB
+    // CHECK-DAG: ([[@LINE+1]]: mut elem): addr_of = UNIQUE | NON_NULL, type = READ | WRITE | OFFSET_ADD#
     let mut elem = i;
     loop {
         if x == capacity {
             capacity *= 2;
-            // CHECK-DAG: ([[@LINE+2]]: i{{.*}}): addr_of = UNIQUE, type = FREE
+            // CHECK-DAG: ([[@LINE+2]]: i{{.*}}): addr_of = UNIQUE | NON_NULL, type = OFFSET_ADD | FREE#
             i = realloc(
                 i as *mut libc::c_void,
                 4 as libc::c_ulong,
@@ -76,7 +76,7 @@ unsafe extern "C" fn realloc1(mut i: *mut i32, len: libc::c_ulong) {
 
 // CHECK-LABEL: final labeling for "alloc_and_free1"
 pub unsafe extern "C" fn alloc_and_free1(mut cnt: libc::c_int) {
-    // CHECK-DAG: ([[@LINE+1]]: i): addr_of = UNIQUE, type = UNIQUE | FREE#
+    // CHECK-DAG: ([[@LINE+1]]: i): addr_of = UNIQUE | NON_NULL, type = UNIQUE | FREE#
     let i = malloc(::std::mem::size_of::<i32>() as libc::c_ulong) as *mut i32;
     // CHECK-DAG: ([[@LINE+1]]: i{{.*}}): {{.*}}type = UNIQUE | FREE#
     free(i as *mut libc::c_void);
@@ -85,13 +85,26 @@ pub unsafe extern "C" fn alloc_and_free1(mut cnt: libc::c_int) {
 
 // CHECK-LABEL: final labeling for "alloc_and_free2"
 pub unsafe extern "C" fn alloc_and_free2(mut cnt: libc::c_int) {
-    // CHECK-DAG: ([[@LINE+1]]: i): addr_of = UNIQUE, type = READ | WRITE | UNIQUE | FREE#
+    // CHECK-DAG: ([[@LINE+1]]: i): addr_of = UNIQUE | NON_NULL, type = READ | WRITE | UNIQUE | FREE#
     let i = malloc(::std::mem::size_of::<i32>() as libc::c_ulong) as *mut i32;
     if !i.is_null() {
-        // CHECK-DAG: ([[@LINE+1]]: mut b): addr_of = UNIQUE, type = READ | WRITE | UNIQUE#
+        // CHECK-DAG: ([[@LINE+1]]: mut b): addr_of = UNIQUE | NON_NULL, type = READ | WRITE | UNIQUE#
         let mut b = i;
         *b = 2;
         // CHECK-DAG: ([[@LINE+1]]: i): {{.*}}type = UNIQUE | FREE#
         free(i as *mut libc::c_void);
     }
 }
+
+// `free1` and `realloc1` have no callers, so `i` may not point to an allocation, and stays raw.
+// CHECK-LABEL: unsafe extern "C" fn free1{{.*}}(mut i: *mut i32) {
+// CHECK: free(i as *mut libc::c_void);
+
+// `elem` steps through the raw `i`, so it stays raw too.
+// CHECK-LABEL: unsafe extern "C" fn realloc1{{.*}}(mut i: *mut i32, len: libc::c_ulong) {
+// CHECK: let mut elem = i;
+// CHECK: elem = elem.offset(1isize);
+
+// CHECK-LABEL: pub unsafe extern "C" fn alloc_and_free1(mut cnt: libc::c_int) {
+// CHECK: let i = (Some(Box::new(Default::default())));
+// CHECK: drop((i));
@@ -0,0 +1,92 @@
+#![feature(rustc_private)]
+#![allow(dead_code)]
+#![allow(unused_mut)]
+#![allow(unused_variables)]
+
+extern crate libc;
+
+extern "C" {
+    fn malloc(_: libc::c_ulong) -> *mut libc::c_void;
+    fn free(_: *mut libc::c_void);
+    fn calloc(_: libc::c_ulong, _: libc::c_ulong) -> *mut libc::c_void;
+}
+
+#[derive(Default)]
+pub struct S {
+    i: i32,
+}
+
+/// Has no `Default` impl, so its allocations can't be rewritten to `Box`.
+pub struct T {
+    i: i32,
+}
+
+// CHECK-LABEL: final labeling for "malloc_free"
+pub unsafe fn malloc_free() {
+    // CHECK-DAG: ([[@LINE+1]]: p): addr_of = UNIQUE | NON_NULL, type = READ | WRITE | UNIQUE | FREE#
+    let p = malloc(::std::mem::size_of::<S>() as libc::c_ulong) as *mut S;
+    (*p).i = 1;
+    let x = (*p).i;
+    free(p as *mut libc::c_void);
+}
+
+// CHECK-LABEL: final labeling for "calloc_free"
+pub unsafe fn calloc_free(n: libc::c_ulong) {
+    // CHECK-DAG: ([[@LINE+1]]: p): addr_of = UNIQUE | NON_NULL, type = READ | WRITE | UNIQUE | OFFSET_ADD | FREE#
+    let p = calloc(n, ::std::mem::size_of::<i32>() as libc::c_ulong) as *mut i32;
+    *p.offset(1) = 1;
+    free(p as *mut libc::c_void);
+}
+
+pub unsafe fn malloc_free_no_default() {
+    let p = malloc(::std::mem::size_of::<T>() as libc::c_ulong) as *mut T;
+    (*p).i = 1;
+    free(p as *mut libc::c_void);
+}
+
+pub unsafe fn read_s(p: *const S) -> i32 {
+    (*p).i
+}
+
+// CHECK-LABEL: final labeling for "read_and_free"
+pub unsafe fn read_and_free(p: *mut S) -> i32 {
+    let x = read_s(p);
+    free(p as *mut libc::c_void);
+    x
+}
+
+pub unsafe fn free_s(p: *mut S) {
+    free(p as *mut libc::c_void);
+}
+
+pub unsafe fn malloc_and_free_s() {
+    let p = malloc(::std::mem::size_of::<S>() as libc::c_ulong) as *mut S;
+    free_s(p);
+}
+
+// CHECK-LABEL: pub unsafe fn malloc_free() {
+// CHECK: let p = (Some(Box::new(Default::default())));
+// CHECK: drop((p));
+
+// CHECK-LABEL: pub unsafe fn calloc_free(n: libc::c_ulong) {
+// CHECK: let p = (Some(std::iter::repeat_with(Default::default).take((n) as usize).collect::<Box<[_]>>()));
+// CHECK: drop((p));
+
+// The pointee has no `Default` value to fill the `Box` with, so the pointer stays raw.
+// CHECK-LABEL: pub unsafe fn malloc_free_no_default() {
+// CHECK: let p = malloc(::std::mem::size_of::<T>() as libc::c_ulong) as *mut T;
+// CHECK: free(p as *mut libc::c_void);
+
+// CHECK-LABEL: pub unsafe fn read_s<'h0>(p: &'h0 (S)) -> i32 {
+
+// `p` may not point to a heap allocation, as `read_and_free` has no callers, so it stays raw.
+// CHECK-LABEL: pub unsafe fn read_and_free{{.*}}(p: *mut S) -> i32 {
+// CHECK: free(p as *mut libc::c_void);
+
+// Every caller of `free_s` passes it an allocation, so it can take ownership.
+// CHECK-LABEL: pub unsafe fn free_s{{.*}}(p: core::option::Option<std::boxed::Box<(S)>>) {
+// CHECK: drop((p));
+
+// CHECK-LABEL: pub unsafe fn malloc_and_free_s() {
+// CHECK: let p = (Some(Box::new(Default::default())));
+// CHECK: free_s(p);
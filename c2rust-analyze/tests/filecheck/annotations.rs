@@ -0,0 +1,56 @@
+#![feature(register_tool)]
+#![register_tool(c2rust_analyze)]
+
+#[c2rust_analyze::fixed]
+pub unsafe fn fixed_fn(p: *mut i32) {
+    *p = 1;
+}
+
+// CHECK-LABEL: final labeling for "fixed_param"
+// CHECK: ([[@LINE+1]]: q): {{.*}}type flags = FIXED#*mut i32
+pub unsafe fn fixed_param(p: *mut i32, #[c2rust_analyze::fixed] q: *mut i32) {
+    *p = *q;
+}
+
+#[c2rust_analyze::perms(p = "READ | WRITE | UNIQUE | OFFSET_ADD | NON_NULL")]
+pub unsafe fn perms_fn(p: *mut i32) {
+    *p = 1;
+}
+
+pub unsafe fn perms_param(#[c2rust_analyze::perms("READ | UNIQUE")] p: *const i32) -> i32 {
+    *p
+}
+
+#[c2rust_analyze::perms(r#return = "UNIQUE")]
+pub unsafe fn perms_return(p: *const i32) -> *const i32 {
+    p
+}
+
+pub struct S {
+    #[c2rust_analyze::fixed]
+    a: *mut i32,
+    #[c2rust_analyze::perms("READ | WRITE | UNIQUE | NON_NULL")]
+    b: *mut i32,
+    c: *mut i32,
+}
+
+pub unsafe fn use_s(s: *mut S) -> i32 {
+    *(*s).a + *(*s).c
+}
+
+#[c2rust_analyze::fixed]
+pub static mut FIXED_STATIC: *mut i32 = 0 as *mut i32;
+
+// CHECK-LABEL: ===== BEGIN {{.*}}annotations.rs
+// CHECK: pub unsafe fn fixed_fn(p: *mut i32) {
+// CHECK: pub unsafe fn fixed_param{{.*}}(p: &{{.*}}mut (i32), #[c2rust_analyze::fixed] q: *mut i32) {
+// CHECK: pub unsafe fn perms_fn{{.*}}(p: &{{.*}}mut [(i32)]) {
+// CHECK: p: core::option::Option<&{{.*}}(i32)>) -> i32 {
+// CHECK: pub unsafe fn perms_return{{.*}}(p: &{{.*}}(i32)) -> core::option::Option<&{{.*}}(i32)> {
+// CHECK: pub struct S
+// CHECK-NEXT: #[c2rust_analyze::fixed]
+// CHECK-NEXT: a: *mut i32,
+// CHECK-NEXT: #[c2rust_analyze::perms
+// CHECK-NEXT: b: &{{.*}}mut (i32),
+// CHECK-NEXT: c: &{{.*}}(i32),
+// CHECK: pub static mut FIXED_STATIC: *mut i32 = 0 as *mut i32;
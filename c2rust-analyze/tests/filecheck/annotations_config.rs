@@ -0,0 +1,36 @@
+//! --env C2RUST_ANALYZE_CONFIG=tests/filecheck/annotations_config.toml
+
+// The same annotations as in `annotations.rs`, but given by path in a config file.
+
+pub unsafe fn fixed_fn(p: *mut i32) {
+    *p = 1;
+}
+
+pub mod m {
+    pub unsafe fn fixed_param(p: *mut i32, q: *mut i32) {
+        *p = *q;
+    }
+
+    pub unsafe fn perms_return(p: *const i32) -> *const i32 {
+        p
+    }
+}
+
+pub struct S {
+    a: *mut i32,
+    b: *mut i32,
+    c: *mut i32,
+}
+
+pub unsafe fn use_s(s: *mut S) -> i32 {
+    *(*s).a + *(*s).c
+}
+
+// CHECK-LABEL: ===== BEGIN {{.*}}annotations_config.rs
+// CHECK: pub unsafe fn fixed_fn(p: *mut i32) {
+// CHECK: pub unsafe fn fixed_param{{.*}}(p: &{{.*}}mut (i32), q: *mut i32) {
+// CHECK: pub unsafe fn perms_return{{.*}}(p: &{{.*}}(i32)) -> core::option::Option<&{{.*}}(i32)> {
+// CHECK: pub struct S
+// CHECK-NEXT: a: *mut i32,
+// CHECK-NEXT: b: &{{.*}}mut (i32),
+// CHECK-NEXT: c: &{{.*}}(i32),
@@ -10,9 +10,9 @@ struct S {
 
 // CHECK-LABEL: final labeling for "null_ptr"
 pub unsafe fn null_ptr() {
-    // CHECK-DAG: ([[@LINE+3]]: s): addr_of = UNIQUE, type = READ | WRITE | UNIQUE#
+    // CHECK-DAG: ([[@LINE+3]]: s): addr_of = UNIQUE | NON_NULL, type = READ | WRITE | UNIQUE#
     // CHECK-LABEL: type assignment for "null_ptr":
-    // CHECK-DAG: ([[@LINE+1]]: s): &mut S
+    // CHECK-DAG: ([[@LINE+1]]: s): std::option::Option<&mut S>
     let s = 0 as *mut S;
     (*s).i = 0 as *const i32;
 }
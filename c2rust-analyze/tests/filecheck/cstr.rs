@@ -0,0 +1,57 @@
+use std::ffi::{c_char, c_int, c_ulong};
+
+extern "C" {
+    fn strlen(s: *const c_char) -> c_ulong;
+    fn strcmp(s1: *const c_char, s2: *const c_char) -> c_int;
+    fn printf(fmt: *const c_char, ...) -> c_int;
+}
+
+// CHECK-LABEL: final labeling for "cstr_len"
+// CHECK-DAG: ([[@LINE+1]]: s): {{.*}}type = READ | UNIQUE | NON_NULL | NUL_TERMINATED#
+pub unsafe fn cstr_len(s: *const c_char) -> c_ulong {
+    strlen(s)
+}
+
+// CHECK-LABEL: final labeling for "cstr_eq"
+// CHECK-DAG: ([[@LINE+2]]: a): {{.*}}type = READ | UNIQUE | NON_NULL | NUL_TERMINATED#
+// CHECK-DAG: ([[@LINE+1]]: b): {{.*}}type = READ | UNIQUE | NON_NULL | NUL_TERMINATED#
+pub unsafe fn cstr_eq(a: *const c_char, b: *const c_char) -> bool {
+    strcmp(a, b) == 0
+}
+
+// CHECK-LABEL: final labeling for "cstr_print"
+// CHECK-DAG: ([[@LINE+1]]: s): {{.*}}type = READ | UNIQUE | NON_NULL | NUL_TERMINATED#
+pub unsafe fn cstr_print(s: *const c_char) {
+    printf(b"%s\n\0" as *const u8 as *const c_char, s);
+}
+
+// CHECK-LABEL: final labeling for "cstr_is_empty"
+// CHECK-DAG: ([[@LINE+1]]: s): {{.*}}type = READ | UNIQUE | NON_NULL | NUL_TERMINATED#
+pub unsafe fn cstr_is_empty(s: *const c_char) -> bool {
+    *s == 0
+}
+
+// CHECK-LABEL: final labeling for "cstr_scan"
+// CHECK-DAG: ([[@LINE+1]]: s): {{.*}}type = READ | UNIQUE | OFFSET_ADD | NON_NULL | NUL_TERMINATED#
+pub unsafe fn cstr_scan(s: *const c_char) -> usize {
+    let mut n = 0;
+    while *s.offset(n as isize) != 0 {
+        n += 1;
+    }
+    n
+}
+
+// CHECK-LABEL: pub unsafe fn cstr_len{{.*}}(s: &{{.*}}core::ffi::CStr) -> c_ulong {
+// CHECK: (s).to_bytes().len() as u64
+
+// CHECK-LABEL: pub unsafe fn cstr_eq{{.*}}(a: &{{.*}}core::ffi::CStr, b: &{{.*}}core::ffi::CStr) -> bool {
+// CHECK: (a).cmp((b)) as i32 == 0
+
+// CHECK-LABEL: pub unsafe fn cstr_print{{.*}}(s: &{{.*}}core::ffi::CStr) {
+// CHECK: (s).as_ptr() as *const i8
+
+// CHECK-LABEL: pub unsafe fn cstr_is_empty{{.*}}(s: &{{.*}}core::ffi::CStr) -> bool {
+// CHECK: *&c2rust_support::cstr_to_chars::<i8>((s))[0] == 0
+
+// CHECK-LABEL: pub unsafe fn cstr_scan{{.*}}(s: &{{.*}}[(c_char)]) -> usize {
+// CHECK: while *&(&(s)[((n as isize) as usize) ..])[0] != 0 {
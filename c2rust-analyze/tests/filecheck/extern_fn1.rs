@@ -19,7 +19,6 @@ unsafe fn test_malloc() -> u32 {
 
 // CHECK-LABEL: final labeling for "fn_ptr"
 fn fn_ptr() {
-    // TODO: function pointer types are not fully supported yet
-    //let f: unsafe extern "C" fn(i32) -> i32 = foo;
-    let f = foo;
+    let f: unsafe extern "C" fn(i32) -> i32 = foo;
+    let g = foo;
 }
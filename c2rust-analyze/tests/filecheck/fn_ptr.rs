@@ -0,0 +1,79 @@
+use std::ffi::c_int;
+
+pub struct Ops {
+    pub apply: Option<unsafe extern "C" fn(*mut i32, i32)>,
+    pub get: Option<unsafe extern "C" fn(*const i32) -> i32>,
+}
+
+// `set` only writes through `p`, but `add` also reads it.  Both are stored in
+// `Ops::apply`, so they must get the same signature.
+// CHECK-LABEL: final labeling for "set"
+// CHECK-DAG: ([[@LINE+1]]: p): {{.*}}type = READ | WRITE | UNIQUE | NON_NULL#
+unsafe extern "C" fn set(p: *mut i32, x: i32) {
+    *p = x;
+}
+
+// CHECK-LABEL: final labeling for "add"
+// CHECK-DAG: ([[@LINE+1]]: p): {{.*}}type = READ | WRITE | UNIQUE | NON_NULL#
+unsafe extern "C" fn add(p: *mut i32, x: i32) {
+    *p += x;
+}
+
+// CHECK-LABEL: final labeling for "load"
+// CHECK-DAG: ([[@LINE+1]]: p): {{.*}}type = READ | UNIQUE | NON_NULL#
+unsafe extern "C" fn load(p: *const i32) -> i32 {
+    *p
+}
+
+// CHECK-LABEL: final labeling for "cmp"
+// CHECK-DAG: ([[@LINE+1]]: a): {{.*}}type = READ | UNIQUE | NON_NULL#
+unsafe extern "C" fn cmp(a: *const i32, b: *const i32) -> c_int {
+    (*a - *b) as c_int
+}
+
+pub unsafe fn make_ops(which: bool) -> Ops {
+    Ops {
+        apply: if which {
+            Some(set as unsafe extern "C" fn(*mut i32, i32))
+        } else {
+            Some(add as unsafe extern "C" fn(*mut i32, i32))
+        },
+        get: Some(load as unsafe extern "C" fn(*const i32) -> i32),
+    }
+}
+
+// CHECK-LABEL: final labeling for "run_ops"
+// CHECK-DAG: ([[@LINE+1]]: p): {{.*}}type = READ | WRITE | UNIQUE | NON_NULL#
+pub unsafe fn run_ops(ops: *const Ops, p: *mut i32) -> i32 {
+    ((*ops).apply).expect("non-null function pointer")(p, 1);
+    ((*ops).get).expect("non-null function pointer")(p)
+}
+
+unsafe fn max_by(
+    a: *const i32,
+    b: *const i32,
+    f: unsafe extern "C" fn(*const i32, *const i32) -> c_int,
+) -> i32 {
+    if f(a, b) >= 0 {
+        *a
+    } else {
+        *b
+    }
+}
+
+pub unsafe fn max(a: *const i32, b: *const i32) -> i32 {
+    max_by(a, b, cmp)
+}
+
+// CHECK-LABEL: ===== BEGIN {{.*}}fn_ptr.rs
+// CHECK: pub struct Ops {
+// CHECK: pub apply: Option<unsafe extern "C" fn(&mut (i32), i32)>,
+// CHECK: pub get: Option<unsafe extern "C" fn(&(i32)) -> i32>,
+// CHECK: unsafe extern "C" fn set<'h0>(p: &'h0 mut (i32), x: i32) {
+// CHECK: unsafe extern "C" fn add<'h0>(p: &'h0 mut (i32), x: i32) {
+// CHECK: unsafe extern "C" fn cmp<'h0,'h1>(a: &'h0 (i32), b: &'h1 (i32)) -> c_int {
+// CHECK: Some(set as unsafe extern "C" fn(&mut (i32), i32))
+// CHECK: Some(add as unsafe extern "C" fn(&mut (i32), i32))
+// CHECK: Some(load as unsafe extern "C" fn(&(i32)) -> i32)
+// CHECK: pub unsafe fn run_ops<'h0,'h1>(ops: &'h0 (Ops), p: &'h1 mut (i32)) -> i32 {
+// CHECK: f: unsafe extern "C" fn(&(i32), &(i32)) -> c_int,
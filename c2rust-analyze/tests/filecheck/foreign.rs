@@ -4,10 +4,10 @@ extern "C" {
 
 type Alias = Bar;
 
-// CHECK-DAG: br: ({{.*}}) perms = UNIQUE, flags = FIXED
-// CHECK-DAG: bz: ({{.*}}) perms = UNIQUE, flags = FIXED
-// CHECK-DAG: x: ({{.*}}) perms = UNIQUE, flags = FIXED
-// CHECK-DAG: y: ({{.*}}) perms = UNIQUE, flags = FIXED
+// CHECK-DAG: br: ({{.*}}) perms = UNIQUE | NON_NULL, flags = FIXED
+// CHECK-DAG: bz: ({{.*}}) perms = UNIQUE | NON_NULL, flags = FIXED
+// CHECK-DAG: x: ({{.*}}) perms = UNIQUE | NON_NULL, flags = FIXED
+// CHECK-DAG: y: ({{.*}}) perms = UNIQUE | NON_NULL, flags = FIXED
 // CHECK-DAG: "s": addr_of flags = FIXED
 // CHECK-DAG: "STATIC_PTR": addr_of flags = FIXED, type flags = FIXED#{{.*}}
 
@@ -92,10 +92,11 @@ struct NeedsLifetime {
     p: *mut u8,
 }
 
-// CHECK-DAG: struct Vtable<'h1,'h2,'h3,'h4> {
+// References in `fn` ptr types stay elided, but `NeedsLifetime` still needs its lifetime.
+// CHECK-DAG: struct Vtable<'h1> {
 struct Vtable {
-    // CHECK-DAG: copy: Option<unsafe extern "C" fn(&'h2 (NeedsLifetime<'h1>)) -> &'h3 (NeedsLifetime<'h1>)>
+    // CHECK-DAG: copy: Option<unsafe extern "C" fn(&(NeedsLifetime<'h1>)) -> &(NeedsLifetime<'h1>)>
     copy: Option<unsafe extern "C" fn(*const NeedsLifetime) -> *mut NeedsLifetime>,
-    // CHECK-DAG: noop: Option<unsafe extern "C" fn(&'h4 (NeedsLifetime<'h1>))>
+    // CHECK-DAG: noop: Option<unsafe extern "C" fn(&(NeedsLifetime<'h1>))>
     noop: Option<unsafe extern "C" fn(*const NeedsLifetime)>,
 }
@@ -5,12 +5,12 @@
 extern crate libc;
 
 // CHECK-LABEL: pub unsafe extern "C" fn insertion_sort
-// CHECK-SAME: p: &'h0 mut [(libc::c_int)]
+// CHECK-SAME: p: c2rust_support::OffsetPtrMut<'h0,(libc::c_int)>
 #[no_mangle]
 pub unsafe extern "C" fn insertion_sort(n: libc::c_int, p: *mut libc::c_int) {
     let mut i: libc::c_int = 1 as libc::c_int;
     while i < n {
-        // CHECK: let tmp: {{.*}} = *&(&(&*(p))[((i as isize) as usize) ..])[0];
+        // CHECK: let tmp: {{.*}} = *((p).as_imm().offset(i as isize)).get();
         let tmp: libc::c_int = *p.offset(i as isize);
         let mut j: libc::c_int = i;
         // CHECK-NOT: p.offset
@@ -20,23 +20,23 @@ pub unsafe extern "C" fn insertion_sort(n: libc::c_int, p: *mut libc::c_int) {
                 *p.offset((j - 1 as libc::c_int) as isize);
             j -= 1
         }
-        // CHECK: *&mut (&mut (p)[((j as isize) as usize) ..])[0] = tmp;
+        // CHECK: *((p).reborrow().offset(j as isize)).get_mut() = tmp;
         *p.offset(j as isize) = tmp;
         i += 1
     };
 }
 
 // CHECK-LABEL: pub unsafe extern "C" fn check_eq
-// CHECK-SAME: p: &'h0 [(libc::c_int)]
-// CHECK-NEXT: q: &'h1 [(libc::c_int)]
+// CHECK-SAME: p: c2rust_support::OffsetPtr<'h0,(libc::c_int)>
+// CHECK-NEXT: q: c2rust_support::OffsetPtr<'h1,(libc::c_int)>
 #[no_mangle]
 pub unsafe extern "C" fn check_eq(n: libc::c_int, p: *mut libc::c_int,
                                   q: *mut libc::c_int) {
     let mut i: libc::c_int = 0 as libc::c_int;
     while i < n {
         //assert!(*p.offset(i as isize) == *q.offset(i as isize));
-        // CHECK: *&(&(p)[((i as isize) as usize) ..])[0]
-        // CHECK: *&(&(q)[((i as isize) as usize) ..])[0]
+        // CHECK: *(p.offset(i as isize)).get()
+        // CHECK-SAME: *(q.offset(i as isize)).get()
         if *p.offset(i as isize) != *q.offset(i as isize) {
             std::process::abort();
         }
@@ -47,11 +47,11 @@ pub unsafe extern "C" fn check_eq(n: libc::c_int, p: *mut libc::c_int,
 // CHECK-LABEL: unsafe fn main_0
 unsafe fn main_0() -> libc::c_int {
     let mut arr1: [libc::c_int; 3] = [1, 3, 2];
-    // CHECK: &mut (arr1) as &mut [i32]
+    // CHECK: c2rust_support::OffsetPtrMut::new((&mut (arr1) as &mut [i32]))
     insertion_sort(3 as libc::c_int, arr1.as_mut_ptr());
     let mut expect1: [libc::c_int; 3] = [1, 2, 3];
-    // CHECK: &mut *((&mut arr1)) as &mut [i32]
-    // CHECK-SAME: &mut (expect1) as &mut [i32]
+    // CHECK: c2rust_support::OffsetPtr::new(&*(&mut *((&mut arr1)) as &mut [i32]))
+    // CHECK-SAME: c2rust_support::OffsetPtr::new(&*(&mut (expect1) as &mut [i32]))
     check_eq(3 as libc::c_int, (&mut arr1).as_mut_ptr(), expect1.as_mut_ptr());
 
     let mut arr2: [libc::c_int; 7] = [15, 31, 50, 99, 18, 98, 85];
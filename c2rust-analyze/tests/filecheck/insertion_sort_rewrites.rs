@@ -5,24 +5,27 @@
 extern crate libc;
 
 #[no_mangle]
-// CHECK-LABEL: generated 8 expr rewrites + {{[0-9]*}} ty rewrites for "insertion_sort"
+// CHECK-LABEL: generated 10 expr rewrites + {{[0-9]*}} ty rewrites for "insertion_sort"
+// CHECK-DAG: [[@LINE+1]]: *mut libc::c_int: c2rust_support::OffsetPtrMut<'h0,$0>
 pub unsafe extern "C" fn insertion_sort(n: libc::c_int, p: *mut libc::c_int) {
     let mut i: libc::c_int = 1 as libc::c_int;
     while i < n {
-        // CHECK-DAG: [[@LINE+2]]: p: &*$e
-        // CHECK-DAG: [[@LINE+1]]: p.offset({{.*}}): &(&$0[($1 as usize) ..])[0]
+        // CHECK-DAG: [[@LINE+2]]: p: $e.as_imm()
+        // CHECK-DAG: [[@LINE+1]]: p.offset({{.*}}): $e.get()
         let tmp: libc::c_int = *p.offset(i as isize);
         let mut j: libc::c_int = i;
-        // CHECK-DAG: [[@LINE+2]]: p: &*$e
-        // CHECK-DAG: [[@LINE+1]]: p.offset({{.*}}): &(&$0[($1 as usize) ..])[0]
+        // CHECK-DAG: [[@LINE+2]]: p: $e.as_imm()
+        // CHECK-DAG: [[@LINE+1]]: p.offset({{.*}}): $e.get()
         while j > 0 as libc::c_int && *p.offset((j - 1 as libc::c_int) as isize) > tmp {
-            // CHECK-DAG: [[@LINE+3]]: p: &*$e
-            // CHECK-DAG: [[@LINE+2]]: p.offset({{.*}}): &(&$0[($1 as usize) ..])[0]
-            // CHECK-DAG: [[@LINE+1]]: p.offset({{.*}}): &mut (&mut $0[($1 as usize) ..])[0]
+            // CHECK-DAG: [[@LINE+4]]: p: $e.as_imm()
+            // CHECK-DAG: [[@LINE+3]]: p.offset({{.*}}): $e.get()
+            // CHECK-DAG: [[@LINE+2]]: p: $e.reborrow()
+            // CHECK-DAG: [[@LINE+1]]: p.offset({{.*}}): $e.get_mut()
             *p.offset(j as isize) = *p.offset((j - 1 as libc::c_int) as isize);
             j -= 1
         }
-        // CHECK-DAG: [[@LINE+1]]: p.offset({{.*}}): &mut (&mut $0[($1 as usize) ..])[0]
+        // CHECK-DAG: [[@LINE+2]]: p: $e.reborrow()
+        // CHECK-DAG: [[@LINE+1]]: p.offset({{.*}}): $e.get_mut()
         *p.offset(j as isize) = tmp;
         i += 1
     }
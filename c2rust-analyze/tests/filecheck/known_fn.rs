@@ -9,7 +9,7 @@ extern "C" {
 
 // CHECK-LABEL: final labeling for "known_fn"
 pub fn known_fn() {
-    // CHECK-DAG: ([[@LINE+3]]: path): addr_of = UNIQUE, type = READ | UNIQUE | OFFSET_ADD#
+    // CHECK-DAG: ([[@LINE+3]]: path): addr_of = UNIQUE | NON_NULL, type = READ | UNIQUE | OFFSET_ADD | NON_NULL#
     // CHECK-LABEL: type assignment for "known_fn":
     // CHECK-DAG: ([[@LINE+1]]: path): &[i8]
     let path = b".\0" as *const u8 as *const c_char;
@@ -0,0 +1,61 @@
+use std::ptr;
+
+// CHECK-LABEL: final labeling for "null_mut_to_none"
+// CHECK-DAG: ([[@LINE+2]]: p): {{.*}}type = READ | WRITE | UNIQUE | NON_NULL#
+// CHECK-LABEL: pub unsafe fn null_mut_to_none<'h0>(cond: bool, p: &'h0 mut (i32)) {
+pub unsafe fn null_mut_to_none(cond: bool, p: *mut i32) {
+    // CHECK: let mut q = Some((p));
+    let mut q = p;
+    if cond {
+        // CHECK: q = None;
+        q = ptr::null_mut();
+    }
+    // CHECK: if !((q).as_deref()).is_none() {
+    if !q.is_null() {
+        // CHECK: *(q).as_deref_mut().unwrap() = 1;
+        *q = 1;
+    }
+}
+
+// CHECK-LABEL: pub unsafe fn zero_to_none() -> i32 {
+pub unsafe fn zero_to_none() -> i32 {
+    // CHECK: let p = None;
+    let p = 0 as *const i32;
+    // CHECK: if (p).is_none() {
+    if p.is_null() {
+        return 0;
+    }
+    // CHECK: *(p).unwrap()
+    *p
+}
+
+// CHECK-LABEL: pub unsafe fn is_null_false<'h0>(p: &'h0 (i32)) -> i32 {
+pub unsafe fn is_null_false(p: *const i32) -> i32 {
+    // CHECK: if false {
+    if p.is_null() {
+        return 0;
+    }
+    *p
+}
+
+pub struct S {
+    i: i32,
+}
+
+// CHECK-LABEL: pub unsafe fn nullable_arg<'h0>(s: core::option::Option<&'h0 mut (S)>) {
+pub unsafe fn nullable_arg(s: *mut S) {
+    // CHECK: if ((s).as_deref()).is_none() {
+    if s.is_null() {
+        return;
+    }
+    // CHECK: (*(s).as_deref_mut().unwrap()).i = 1;
+    (*s).i = 1;
+}
+
+// CHECK-LABEL: pub unsafe fn call_nullable_arg<'h0>(s: &'h0 mut (S)) {
+pub unsafe fn call_nullable_arg(s: *mut S) {
+    // CHECK: nullable_arg(Some((s)));
+    nullable_arg(s);
+    // CHECK: nullable_arg(None);
+    nullable_arg(ptr::null_mut());
+}
@@ -0,0 +1,76 @@
+#![allow(unused_mut)]
+
+// CHECK-LABEL: final labeling for "offset_ptr_neg"
+// CHECK-DAG: ([[@LINE+1]]: p): {{.*}}type = READ | UNIQUE | OFFSET_ADD | OFFSET_SUB | NON_NULL#
+pub unsafe fn offset_ptr_neg(p: *const i32) -> i32 {
+    *p.offset(-1)
+}
+
+// CHECK-LABEL: final labeling for "offset_ptr_neg_var"
+// CHECK-DAG: ([[@LINE+1]]: p): {{.*}}type = READ | UNIQUE | OFFSET_ADD | OFFSET_SUB | NON_NULL#
+pub unsafe fn offset_ptr_neg_var(p: *const i32, n: i32) -> i32 {
+    *p.offset((-n) as isize)
+}
+
+// CHECK-LABEL: final labeling for "offset_ptr_pos"
+// CHECK-DAG: ([[@LINE+1]]: p): {{.*}}type = READ | UNIQUE | OFFSET_ADD | NON_NULL#
+pub unsafe fn offset_ptr_pos(p: *const i32, i: usize) -> i32 {
+    *p.offset(i as isize)
+}
+
+// CHECK-LABEL: final labeling for "offset_ptr_write"
+// CHECK-DAG: ([[@LINE+1]]: p): {{.*}}type = READ | WRITE | UNIQUE | OFFSET_ADD | OFFSET_SUB | NON_NULL#
+pub unsafe fn offset_ptr_write(p: *mut i32) {
+    *p.offset(-1) = 1;
+    *p = 2;
+}
+
+// CHECK-LABEL: final labeling for "offset_ptr_clear_back"
+// CHECK-DAG: ([[@LINE+1]]: mut p): {{.*}}type = READ | WRITE | OFFSET_ADD | OFFSET_SUB | NON_NULL#
+pub unsafe fn offset_ptr_clear_back(mut p: *mut i32) {
+    p = p.offset(-1);
+    *p = 0;
+    p = p.offset(-1);
+    *p = 0;
+}
+
+// CHECK-LABEL: final labeling for "offset_ptr_compare"
+// CHECK-DAG: ([[@LINE+1]]: p): {{.*}}type = READ | OFFSET_ADD | OFFSET_SUB | NON_NULL#
+pub unsafe fn offset_ptr_compare(p: *const i32, n: usize) -> i32 {
+    let mut sum = 0;
+    let mut q = p.offset(n as isize);
+    while q > p {
+        q = q.offset(-1);
+        sum += *q;
+    }
+    sum
+}
+
+// CHECK-LABEL: final labeling for "offset_ptr_distance"
+// CHECK-DAG: ([[@LINE+1]]: start): {{.*}}type = UNIQUE | OFFSET_ADD | OFFSET_SUB | NON_NULL#
+pub unsafe fn offset_ptr_distance(start: *const i32, end: *const i32) -> isize {
+    end.offset_from(start)
+}
+
+// CHECK-LABEL: pub unsafe fn offset_ptr_neg{{.*}}(p: c2rust_support::OffsetPtr<{{.*}}>) -> i32 {
+// CHECK: *(p.offset(-1)).get()
+
+// CHECK-LABEL: pub unsafe fn offset_ptr_neg_var{{.*}}(p: c2rust_support::OffsetPtr<{{.*}}>, n: i32) -> i32 {
+// CHECK: *(p.offset((-n) as isize)).get()
+
+// CHECK-LABEL: pub unsafe fn offset_ptr_pos{{.*}}(p: &{{.*}}[(i32)], i: usize) -> i32 {
+// CHECK: *&(&(p)[((i as isize) as usize) ..])[0]
+
+// CHECK-LABEL: pub unsafe fn offset_ptr_write{{.*}}(p: c2rust_support::OffsetPtrMut<{{.*}}>) {
+// CHECK: *((p).reborrow().offset(-1)).get_mut() = 1;
+// CHECK: *(p).get_mut() = 2;
+
+// CHECK-LABEL: pub unsafe fn offset_ptr_clear_back{{.*}}(mut p: c2rust_support::OffsetPtr<{{.*}}core::cell::Cell<(i32)>>) {
+// CHECK: p = p.offset(-1);
+// CHECK: ((p).get()).set((0));
+
+// CHECK-LABEL: pub unsafe fn offset_ptr_compare{{.*}}(p: c2rust_support::OffsetPtr<{{.*}}>, n: usize) -> i32 {
+// CHECK: while q > p {
+
+// CHECK-LABEL: pub unsafe fn offset_ptr_distance{{.*}}(start: c2rust_support::OffsetPtr<{{.*}}>, end: c2rust_support::OffsetPtr<{{.*}}>) -> isize {
+// CHECK: end.offset_from(start)
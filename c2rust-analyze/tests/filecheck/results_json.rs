@@ -0,0 +1,62 @@
+//! --catch-panics
+//! --env C2RUST_ANALYZE_RESULTS_JSON=-
+
+// The final labeling is also written as JSON.  The JSON goes to stdout while the debug output goes
+// to stderr, so the checks below only look at the JSON.
+
+use std::ptr::NonNull;
+
+// CHECK: "crate": "results_json",
+
+// CHECK-LABEL: "name": "set",
+// CHECK-NEXT: "span": {
+// CHECK-NEXT: "file": "{{.*}}results_json.rs",
+// CHECK-NEXT: "lo_line": [[@LINE+3]],
+// CHECK: "error": null,
+// CHECK: "name": "_1",
+pub unsafe fn set(s: *mut S, x: i32) {
+    *(*s).p = x;
+}
+// CHECK: "pointers": [
+// CHECK-NEXT: {
+// CHECK-NEXT: "equiv_class": "g{{[0-9]+}}",
+// CHECK-NEXT: "perms": [
+// CHECK-NEXT: "READ",
+// CHECK-NEXT: "WRITE",
+// CHECK-NEXT: "UNIQUE",
+// CHECK-NEXT: "NON_NULL"
+// CHECK-NEXT: ],
+// CHECK-NEXT: "flags": [],
+// CHECK-NEXT: "ty": "*mut S",
+// CHECK-NEXT: "rewritten_ty": "&mut S"
+
+// Analysis of `bad` fails, so only its error is reported.
+// CHECK-LABEL: "name": "bad",
+// CHECK: "error": {
+// CHECK-NEXT: "summary": "{{.*}}TODO: visit Callee::UnknownDef{{.*}}",
+// CHECK-NEXT: "span": {
+// CHECK-NEXT: "file": "{{.*}}results_json.rs",
+// CHECK-NEXT: "lo_line": [[@LINE+4]],
+// CHECK: },
+// CHECK-NEXT: "locals": []
+pub unsafe fn bad(p: NonNull<u8>) {
+    *p.as_ptr() = 1;
+}
+
+// CHECK-LABEL: "statics": [
+// CHECK: "name": "COUNT",
+// CHECK: "addr_of": {
+// CHECK: "ty": "*const u8",
+static mut COUNT: *const u8 = 0 as *const u8;
+
+// CHECK-LABEL: "fields": [
+// CHECK: "name": "S::p",
+// CHECK: "addr_of": null,
+// CHECK: "ty": "*mut i32",
+// CHECK-NEXT: "rewritten_ty": "&mut i32"
+// CHECK: "name": "S::n",
+// CHECK: "pointers": []
+pub struct S {
+    p: *mut i32,
+    n: i32,
+}
@@ -0,0 +1,15 @@
+//! --env C2RUST_ANALYZE_REWRITE_MODE=diff
+pub unsafe fn set(p: *mut i32) {
+    *p = 1;
+}
+
+// The rewrites are printed as a unified diff instead of the rewritten file.  The directives are
+// kept out of the context lines of the diff, so they can't match themselves.
+//
+//
+// CHECK-NOT: ===== BEGIN
+// CHECK: --- a/{{.*}}rewrite_mode_diff.rs
+// CHECK-NEXT: +++ b/{{.*}}rewrite_mode_diff.rs
+// CHECK-NEXT: @@ -1,{{[0-9]+}} +1,{{[0-9]+}} @@
+// CHECK: -pub unsafe fn set(p: *mut i32) {
+// CHECK-NEXT: +pub unsafe fn set<'h0>(p: &'h0 mut (i32)) {
@@ -0,0 +1,15 @@
+//! --rewrite-mode diff
+//! --env C2RUST_ANALYZE_REWRITE_MODE=print
+pub unsafe fn set(p: *mut i32) {
+    *p = 1;
+}
+
+// `--rewrite-mode` takes precedence over `C2RUST_ANALYZE_REWRITE_MODE`, so the rewrites are
+// printed as a diff rather than as the rewritten file.
+//
+//
+// CHECK-NOT: ===== BEGIN
+// CHECK: --- a/{{.*}}rewrite_mode_flag.rs
+// CHECK-NEXT: +++ b/{{.*}}rewrite_mode_flag.rs
+// CHECK: -pub unsafe fn set(p: *mut i32) {
+// CHECK-NEXT: +pub unsafe fn set<'h0>(p: &'h0 mut (i32)) {
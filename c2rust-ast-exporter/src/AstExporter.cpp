@@ -490,6 +490,16 @@ class TypeEncoder final : public TypeVisitor<TypeEncoder> {
         VisitQualType(pointee);
     }
 
+    void VisitAtomicType(const AtomicType *T) {
+        auto t = T->getValueType();
+        auto qt = encodeQualType(t);
+
+        encodeType(T, TagAtomicType,
+                   [qt](CborEncoder *local) { cbor_encode_uint(local, qt); });
+
+        VisitQualType(t);
+    }
+
     // Although C does not have references, Clang's built-in functions for
     // `va_start`, `va_end`, etc. may use C++ references in 32-bit mode.
     void VisitReferenceType(const clang::ReferenceType *T) {
@@ -1095,6 +1105,9 @@ class TranslateASTVisitor final
     //   List of input constraints
     //   List of output constraints
     //   List of clobbers
+    //   List of input operand names (empty if the operand is unnamed)
+    //   List of output operand names (empty if the operand is unnamed)
+    //   Boolean true if this is an `asm goto` statement
     //
     // The number of input and output expressions in the child id list will
     // match the length of the corresponding constraint arrays.
@@ -1110,6 +1123,7 @@ class TranslateASTVisitor final
             cbor_encode_string(local, E->generateAsmString(*Context));
 
             std::vector<std::string> outputs, inputs, clobbers;
+            std::vector<std::string> output_names, input_names;
             std::vector<TargetInfo::ConstraintInfo> output_infos;
             for (unsigned i = 0, num = E->getNumOutputs(); i < num; ++i) {
                 auto constraint = E->getOutputConstraint(i);
@@ -1130,6 +1144,7 @@ class TranslateASTVisitor final
                 }
                 convertedConstraint += SimplifyConstraint(constraint.str());
                 outputs.push_back(convertedConstraint);
+                output_names.push_back(E->getOutputName(i).str());
                 output_infos.push_back(std::move(info));
             }
             for (unsigned i = 0, num = E->getNumInputs(); i < num; ++i) {
@@ -1144,6 +1159,7 @@ class TranslateASTVisitor final
                 }
                 convertedConstraint += SimplifyConstraint(constraint.str());
                 inputs.emplace_back(convertedConstraint);
+                input_names.push_back(E->getInputName(i).str());
             }
             for (unsigned i = 0, num = E->getNumClobbers(); i < num; ++i) {
                 auto clobber = E->getClobber(i);
@@ -1154,6 +1170,13 @@ class TranslateASTVisitor final
             cbor_encode_string_array(local, ArrayRef<std::string>(inputs));
             cbor_encode_string_array(local, ArrayRef<std::string>(outputs));
             cbor_encode_string_array(local, ArrayRef<std::string>(clobbers));
+            cbor_encode_string_array(local, ArrayRef<std::string>(input_names));
+            cbor_encode_string_array(local, ArrayRef<std::string>(output_names));
+#if CLANG_VERSION_MAJOR < 9
+            cbor_encode_boolean(local, false);
+#else
+            cbor_encode_boolean(local, E->isAsmGoto());
+#endif // CLANG_VERSION_MAJOR
         });
         return true;
     }
@@ -1939,10 +1962,6 @@ class TranslateASTVisitor final
         // Use the type from the definition in case the extern was an incomplete
         // type
         auto T = def->getType();
-        if (isa<AtomicType>(T)) {
-            printC11AtomicError(def);
-            abort();
-        }
 
         auto loc = is_defn ? def->getLocation() : VD->getLocation();
 
@@ -2026,12 +2045,6 @@ class TranslateASTVisitor final
         auto recordAlignment = 0;
         auto byteSize = 0;
 
-        auto t = D->getTypeForDecl();
-        if (isa<AtomicType>(t)) {
-            printC11AtomicError(D);
-            abort();
-        }
-
         auto loc = D->getLocation();
         std::vector<void *> childIds;
         if (def) {
@@ -2104,12 +2117,6 @@ class TranslateASTVisitor final
         // They are used in actual code and accepted by compilers, so we cannot
         // exit early via code like `if (!D->isCompleteDefinition()) return true;`.
 
-        auto t = D->getTypeForDecl();
-        if (isa<AtomicType>(t)) {
-            printC11AtomicError(D);
-            abort();
-        }
-
         std::vector<void *> childIds;
         for (auto x : D->enumerators()) {
             childIds.push_back(x->getCanonicalDecl());
@@ -2168,10 +2175,6 @@ class TranslateASTVisitor final
 
         std::vector<void *> childIds;
         auto t = D->getType();
-        if (isa<AtomicType>(t)) {
-            printC11AtomicError(D);
-            abort();
-        }
 
         auto record = D->getParent();
         const ASTRecordLayout &layout =
@@ -2386,11 +2389,6 @@ class TranslateASTVisitor final
             CharSourceRange::getCharRange(E->getSourceRange()));
     }
 
-    void printC11AtomicError(Decl *D) {
-        std::string msg = "C11 Atomics are not supported. Aborting.";
-        printError(msg, D);
-    }
-
     void printError(std::string Message, Decl *D) {
         auto DiagBuilder =
                 getDiagBuilder(D->getLocation(), DiagnosticsEngine::Error);
@@ -2476,15 +2474,6 @@ void TypeEncoder::VisitVariableArrayType(const VariableArrayType *T) {
 
     VisitQualType(t);
 }
-//
-//void TypeEncoder::VisitAtomicType(const AtomicType *AT) {
-//    std::string msg =
-//            "C11 Atomic types are not supported. Aborting.";
-////    auto horse = AT->get
-////    astEncoder->printError(msg, AT);
-//    AT->getValueType()->dump();
-//    abort();
-//}
 
 class TranslateConsumer : public clang::ASTConsumer {
     Outputs *outputs;
@@ -2510,7 +2499,7 @@ class TranslateConsumer : public clang::ASTConsumer {
             cbor_encoder_init(&encoder, buffer, len, 0);
 
             CborEncoder outer;
-            cbor_encoder_create_array(&encoder, &outer, 6);
+            cbor_encoder_create_array(&encoder, &outer, 7);
 
             CborEncoder array;
 
@@ -2616,6 +2605,13 @@ class TranslateConsumer : public clang::ASTConsumer {
             auto target = Context.getTargetInfo().getTriple().str();
             cbor_encode_string(&outer, target);
 
+            // 7. Target-dependent properties of integer types: the size of
+            // `long` in bytes and whether plain `char` is signed
+            cbor_encoder_create_array(&outer, &array, 2);
+            cbor_encode_uint(&array, Context.getTypeSizeInChars(Context.LongTy).getQuantity());
+            cbor_encode_boolean(&array, Context.CharTy->isSignedIntegerType());
+            cbor_encoder_close_container(&outer, &array);
+
             cbor_encoder_close_container(&encoder, &outer);
         };
 
@@ -9,6 +9,11 @@
 #ifndef ast_tags_h
 #define ast_tags_h
 
+// Version of the CBOR format produced by the exporter. Bump this whenever the
+// exporter's output or the tags below change, so that saved AST dumps from an
+// incompatible version are rejected rather than misread.
+#define AST_FORMAT_VERSION 2
+
 enum ASTEntryTag {
     TagFunctionDecl = 0,
     TagParmVarDecl,
@@ -142,6 +147,7 @@ enum TypeTag {
     TagComplexType,
     TagHalf,
     TagBFloat16,
+    TagAtomicType,
 };
 
 enum StringTypeTag {
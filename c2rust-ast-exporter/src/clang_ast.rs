@@ -111,6 +111,15 @@ impl TypeNode {
     pub const VOLATILE_MASK: u64 = 0b100;
 }
 
+/// Properties of the integer types that differ between targets.
+#[derive(Debug, Clone, Copy)]
+pub struct TargetTypes {
+    /// Size of `long` in bytes
+    pub long_size: u64,
+    /// Whether plain `char` is signed
+    pub char_is_signed: bool,
+}
+
 #[derive(Debug, Clone)]
 pub struct AstContext {
     pub ast_nodes: HashMap<u64, AstNode>,
@@ -120,6 +129,7 @@ pub struct AstContext {
     pub files: Vec<SrcFile>,
     pub va_list_kind: BuiltinVaListKind,
     pub target: String,
+    pub target_types: TargetTypes,
 }
 
 pub fn expect_opt_str(val: &Value) -> Option<Option<&str>> {
@@ -161,16 +171,25 @@ pub fn process(items: Value) -> error::Result<AstContext> {
     type RawComment = (u64, u64, u64, ByteBuf);
     type VaListKind = u64;
     type Target = String;
-    let (all_nodes, top_nodes, files, raw_comments, va_list_kind, target): (
+    type TargetTypeProps = (u64, bool);
+    type Ast = (
         Vec<AllNode>,
         Vec<TopNode>,
         Vec<File>,
         Vec<RawComment>,
         VaListKind,
         Target,
-    ) = from_value(items)?;
+        TargetTypeProps,
+    );
+    let (all_nodes, top_nodes, files, raw_comments, va_list_kind, target, target_types): Ast =
+        from_value(items)?;
 
     let va_list_kind = import_va_list_kind(va_list_kind);
+    let (long_size, char_is_signed) = target_types;
+    let target_types = TargetTypes {
+        long_size,
+        char_is_signed,
+    };
 
     for (fileid, line, column, bytes) in raw_comments {
         comments.push(CommentNode {
@@ -272,5 +291,6 @@ pub fn process(items: Value) -> error::Result<AstContext> {
         files,
         va_list_kind,
         target,
+        target_types,
     })
 }
@@ -1,6 +1,8 @@
-use serde_cbor::{from_slice, Value};
+use serde_bytes::ByteBuf;
+use serde_cbor::{from_slice, to_vec, Value};
 use std::collections::HashMap;
 use std::ffi::{CStr, CString};
+use std::fs;
 use std::io::{Error, ErrorKind};
 use std::path::Path;
 use std::slice;
@@ -24,18 +26,28 @@ pub fn get_untyped_ast(
     extra_args: &[&str],
     debug: bool,
 ) -> Result<clang_ast::AstContext, Error> {
+    let buffer = get_ast_cbor(file_path, cc_db, extra_args, debug)?;
+    process_ast_cbor(&buffer)
+}
+
+/// Run the clang plugin on `file_path` and return the CBOR encoding of its AST.
+pub fn get_ast_cbor(
+    file_path: &Path,
+    cc_db: &Path,
+    extra_args: &[&str],
+    debug: bool,
+) -> Result<Vec<u8>, Error> {
     let cbors = get_ast_cbors(file_path, cc_db, extra_args, debug);
-    let buffer = cbors
-        .values()
+    cbors
+        .into_values()
         .next()
-        .ok_or_else(|| Error::new(ErrorKind::InvalidData, "Could not parse input file"))?;
-
-    // let cbor_path = file_path.with_extension("cbor");
-    // let mut cbor_file = File::create(&cbor_path)?;
-    // cbor_file.write_all(&buffer[..])?;
-    // eprintln!("Dumped CBOR to {}", cbor_path.to_string_lossy());
+        .ok_or_else(|| Error::new(ErrorKind::InvalidData, "Could not parse input file"))
+}
 
-    let items: Value = from_slice(&buffer[..]).unwrap();
+/// Decode an AST exported by [`get_ast_cbor`].
+pub fn process_ast_cbor(buffer: &[u8]) -> Result<clang_ast::AstContext, Error> {
+    let items: Value =
+        from_slice(buffer).map_err(|e| Error::new(ErrorKind::InvalidData, format!("{:}", e)))?;
 
     match clang_ast::process(items) {
         Ok(cxt) => Ok(cxt),
@@ -43,6 +55,55 @@ pub fn get_untyped_ast(
     }
 }
 
+/// Identifies files written by [`write_ast_dump`].
+const AST_DUMP_MAGIC: &str = "c2rust-ast-dump";
+
+/// Save an AST exported by [`get_ast_cbor`] to `path`, so that it can be
+/// translated later without clang. The dump starts with a header recording
+/// [`clang_ast::AST_FORMAT_VERSION`], which is defined in `ast_tags.hpp`.
+pub fn write_ast_dump(buffer: &[u8], path: &Path) -> Result<(), Error> {
+    let dump = (
+        AST_DUMP_MAGIC,
+        clang_ast::AST_FORMAT_VERSION,
+        ByteBuf::from(buffer),
+    );
+    let bytes = to_vec(&dump).map_err(|e| Error::new(ErrorKind::Other, format!("{:}", e)))?;
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    fs::write(path, bytes)
+}
+
+/// Load an AST saved by [`write_ast_dump`], checking that its format is the
+/// one this version of the exporter can process.
+pub fn read_ast_dump(path: &Path) -> Result<Vec<u8>, Error> {
+    let bytes = fs::read(path)?;
+    let not_a_dump = || {
+        Error::new(
+            ErrorKind::InvalidData,
+            format!("{} is not a c2rust AST dump", path.display()),
+        )
+    };
+    let (magic, version, buffer): (String, u64, ByteBuf) =
+        from_slice(&bytes).map_err(|_| not_a_dump())?;
+    if magic != AST_DUMP_MAGIC {
+        return Err(not_a_dump());
+    }
+    if version != u64::from(clang_ast::AST_FORMAT_VERSION) {
+        return Err(Error::new(
+            ErrorKind::InvalidData,
+            format!(
+                "{} has AST format version {}, but this version of c2rust reads version {}; \
+                 dump it again with a matching c2rust",
+                path.display(),
+                version,
+                clang_ast::AST_FORMAT_VERSION,
+            ),
+        ));
+    }
+    Ok(buffer.into_vec())
+}
+
 fn get_ast_cbors(
     file_path: &Path,
     cc_db: &Path,
@@ -120,3 +181,49 @@ unsafe fn marshal_result(result: *const ffi::ExportResult) -> HashMap<String, Ve
     }
     output
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+    use std::path::PathBuf;
+    use std::process;
+
+    /// A dump path in a fresh directory, so that tests do not clobber each other.
+    fn temp_dump_path(test: &str) -> PathBuf {
+        env::temp_dir()
+            .join(format!("c2rust-ast-exporter-{}-{}", test, process::id()))
+            .join("src/foo.c.cbor")
+    }
+
+    #[test]
+    fn ast_dump_round_trip() {
+        let path = temp_dump_path("round-trip");
+        let buffer = b"not really an AST".to_vec();
+
+        write_ast_dump(&buffer, &path).unwrap();
+        let read = read_ast_dump(&path);
+        fs::remove_dir_all(path.parent().unwrap().parent().unwrap()).unwrap();
+
+        assert_eq!(read.unwrap(), buffer);
+    }
+
+    #[test]
+    fn ast_dump_version_mismatch() {
+        let path = temp_dump_path("version-mismatch");
+        let dump = (
+            AST_DUMP_MAGIC,
+            u64::from(clang_ast::AST_FORMAT_VERSION) + 1,
+            ByteBuf::from(b"from the future".to_vec()),
+        );
+
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(&path, to_vec(&dump).unwrap()).unwrap();
+        let read = read_ast_dump(&path);
+        fs::remove_dir_all(path.parent().unwrap().parent().unwrap()).unwrap();
+
+        let err = read.unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::InvalidData);
+        assert!(err.to_string().contains("AST format version"), "{}", err);
+    }
+}
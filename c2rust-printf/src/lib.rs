@@ -0,0 +1,772 @@
+//! Runtime support for C2Rust's translation of `printf`-family calls.
+//!
+//! A call like `printf("%5.2f|%-10s\n", x, s)` is translated into
+//! `print_c!(Formatted::new("%5.2f", x), b"|", Formatted::new("%-10s", s), b"\n")`,
+//! where each [`Formatted`] value reproduces the output of the corresponding
+//! C conversion specification. Output is written as raw bytes, so strings
+//! and characters that are not valid UTF-8 are written unchanged.
+#![no_std]
+use core::fmt::{self, Write};
+use core::slice;
+
+mod spec;
+
+pub use crate::spec::{
+    Amount, Conversion, Flags, FloatStyle, Length, ParseError, Piece, Pieces, Spec,
+};
+
+/// A value passed as a variadic argument to a `printf`-family function.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Value {
+    Signed(i64),
+    Unsigned(u64),
+    Float(f64),
+    Pointer(usize),
+}
+
+impl Value {
+    fn to_bits(self) -> u64 {
+        match self {
+            Value::Signed(x) => x as u64,
+            Value::Unsigned(x) => x,
+            Value::Float(x) => x as i64 as u64,
+            Value::Pointer(x) => x as u64,
+        }
+    }
+
+    /// Reinterprets the value as an unsigned integer of `bits` bits,
+    /// the way `va_arg` would for the corresponding length modifier.
+    fn to_unsigned(self, bits: u32) -> u64 {
+        let x = self.to_bits();
+        if bits >= 64 {
+            x
+        } else {
+            x & ((1 << bits) - 1)
+        }
+    }
+
+    /// Reinterprets the value as a signed integer of `bits` bits.
+    fn to_signed(self, bits: u32) -> i64 {
+        let shift = 64 - bits.min(64);
+        ((self.to_unsigned(bits) << shift) as i64) >> shift
+    }
+
+    fn to_float(self) -> f64 {
+        match self {
+            Value::Signed(x) => x as f64,
+            Value::Unsigned(x) => x as f64,
+            Value::Float(x) => x,
+            Value::Pointer(x) => x as f64,
+        }
+    }
+}
+
+/// Types that can be formatted by a C conversion specification.
+pub trait Argument {
+    fn value(&self) -> Value;
+}
+
+macro_rules! impl_argument {
+    ($variant:ident($repr:ty): $($ty:ty),*) => {
+        $(
+            impl Argument for $ty {
+                fn value(&self) -> Value {
+                    Value::$variant(*self as $repr)
+                }
+            }
+        )*
+    };
+}
+
+impl_argument!(Signed(i64): i8, i16, i32, i64, isize);
+impl_argument!(Unsigned(u64): u8, u16, u32, u64, usize);
+impl_argument!(Float(f64): f32, f64);
+
+impl<T> Argument for *const T {
+    fn value(&self) -> Value {
+        Value::Pointer(*self as usize)
+    }
+}
+
+impl<T> Argument for *mut T {
+    fn value(&self) -> Value {
+        Value::Pointer(*self as usize)
+    }
+}
+
+#[cfg(windows)]
+const LONG_BITS: u32 = 32;
+#[cfg(not(windows))]
+const LONG_BITS: u32 = usize::BITS;
+
+impl Length {
+    /// The size in bits of the integer argument read for this length modifier.
+    fn bits(self) -> u32 {
+        match self {
+            Length::Char => 8,
+            Length::Short => 16,
+            Length::Default => 32,
+            Length::Long => LONG_BITS,
+            Length::LongLong | Length::IntMax | Length::LongDouble => 64,
+            Length::Size | Length::PtrDiff => usize::BITS,
+        }
+    }
+}
+
+/// A destination for the bytes written by a translated `printf`-family call.
+pub trait Output {
+    fn write_bytes(&mut self, bytes: &[u8]);
+}
+
+impl<F: FnMut(&[u8])> Output for F {
+    fn write_bytes(&mut self, bytes: &[u8]) {
+        self(bytes)
+    }
+}
+
+/// The arguments of [`write_c!`]: byte strings, and [`Formatted`] values.
+pub trait Print {
+    fn print(&self, out: &mut dyn Output);
+}
+
+impl Print for [u8] {
+    fn print(&self, out: &mut dyn Output) {
+        out.write_bytes(self)
+    }
+}
+
+impl<const N: usize> Print for [u8; N] {
+    fn print(&self, out: &mut dyn Output) {
+        out.write_bytes(self)
+    }
+}
+
+impl<T: Print + ?Sized> Print for &T {
+    fn print(&self, out: &mut dyn Output) {
+        (**self).print(out)
+    }
+}
+
+/// Writes each argument, a byte string or a [`Formatted`] value, to an
+/// [`Output`], e.g. `write_c!(CBuffer::new(buf, size), b"x = ", Formatted::new("%d", x))`.
+#[macro_export]
+macro_rules! write_c {
+    ($out:expr $(, $arg:expr)* $(,)?) => {{
+        let out: &mut dyn $crate::Output = &mut $out;
+        $($crate::Print::print(&$arg, out);)*
+    }};
+}
+
+/// Like [`write_c!`], but writes to standard output. The calling crate must
+/// link `std`.
+///
+/// ```
+/// use c2rust_printf::{print_c, Formatted};
+///
+/// print_c!(b"x = ", unsafe { Formatted::new("%d", 42) }, b"\n");
+/// ```
+#[macro_export]
+macro_rules! print_c {
+    ($($arg:expr),* $(,)?) => {{
+        let mut stdout = ::std::io::stdout().lock();
+        $crate::write_c!(
+            |bytes: &[u8]| {
+                let _ = ::std::io::Write::write_all(&mut stdout, bytes);
+            }
+            $(, $arg)*
+        )
+    }};
+}
+
+/// Like [`write_c!`], but writes to standard error. The calling crate must
+/// link `std`.
+#[macro_export]
+macro_rules! eprint_c {
+    ($($arg:expr),* $(,)?) => {{
+        let mut stderr = ::std::io::stderr().lock();
+        $crate::write_c!(
+            |bytes: &[u8]| {
+                let _ = ::std::io::Write::write_all(&mut stderr, bytes);
+            }
+            $(, $arg)*
+        )
+    }};
+}
+
+/// A value formatted according to a C conversion specification.
+///
+/// The [`Print`] implementation writes the same bytes as the C library would
+/// for the same specification and argument.
+pub struct Formatted<T> {
+    spec: Spec,
+    width: Option<usize>,
+    left: bool,
+    precision: Option<usize>,
+    value: T,
+}
+
+impl<T: Argument> Formatted<T> {
+    /// Creates a formatted value from a single conversion specification,
+    /// e.g. `"%-10s"`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `spec` is not a single supported conversion specification.
+    ///
+    /// # Safety
+    ///
+    /// For `%s`, `value` must either be null or point to a string that is
+    /// NUL-terminated or at least as long as the precision.
+    pub unsafe fn new(spec: &str, value: T) -> Self {
+        let spec = match Spec::parse(spec) {
+            Some(spec) => spec,
+            None => panic!("unsupported printf conversion specification: {:?}", spec),
+        };
+        let width = match spec.width {
+            Some(Amount::Fixed(width)) => Some(width),
+            _ => None,
+        };
+        let precision = match spec.precision {
+            Some(Amount::Fixed(precision)) => Some(precision),
+            _ => None,
+        };
+        Formatted {
+            spec,
+            width,
+            left: spec.flags.left,
+            precision,
+            value,
+        }
+    }
+
+    /// Sets the field width for a `*` width. A negative width is taken as
+    /// a `-` flag followed by a positive width.
+    pub fn width(mut self, width: i32) -> Self {
+        self.left |= width < 0;
+        self.width = Some(width.unsigned_abs() as usize);
+        self
+    }
+
+    /// Sets the precision for a `*` precision. A negative precision is
+    /// taken as if the precision were omitted.
+    pub fn precision(mut self, precision: i32) -> Self {
+        self.precision = usize::try_from(precision).ok();
+        self
+    }
+
+    fn fmt_int(&self, out: &mut dyn Output, spec: Spec, value: Value) {
+        let Spec {
+            flags,
+            length,
+            conversion,
+            ..
+        } = spec;
+        let (negative, magnitude) = match conversion {
+            Conversion::Signed => {
+                let x = value.to_signed(length.bits());
+                (x < 0, x.unsigned_abs())
+            }
+            _ => (false, value.to_unsigned(length.bits())),
+        };
+        let (base, upper) = match conversion {
+            Conversion::Octal => (8, false),
+            Conversion::Hex { upper } => (16, upper),
+            _ => (10, false),
+        };
+
+        let mut buf = [0u8; 64];
+        let mut start = buf.len();
+        let mut x = magnitude;
+        while x != 0 || (start == buf.len() && self.precision != Some(0)) {
+            let digit = (x % base) as u8;
+            start -= 1;
+            buf[start] = match digit {
+                0..=9 => b'0' + digit,
+                _ if upper => b'A' + digit - 10,
+                _ => b'a' + digit - 10,
+            };
+            x /= base;
+        }
+        let digits = &buf[start..];
+
+        let mut zeros = self.precision.unwrap_or(0).saturating_sub(digits.len());
+        if flags.alternate && base == 8 && zeros == 0 && digits.first() != Some(&b'0') {
+            zeros = 1;
+        }
+
+        let sign: &[u8] = if negative {
+            b"-"
+        } else if conversion == Conversion::Signed && flags.plus {
+            b"+"
+        } else if conversion == Conversion::Signed && flags.space {
+            b" "
+        } else {
+            b""
+        };
+        let radix: &[u8] = match (flags.alternate && magnitude != 0, conversion) {
+            (true, Conversion::Hex { upper: false }) => b"0x",
+            (true, Conversion::Hex { upper: true }) => b"0X",
+            _ => b"",
+        };
+
+        let zero_pad = flags.zero && self.precision.is_none();
+        self.pad(out, &[sign, radix], zero_pad, &|w| {
+            repeat(w, b'0', zeros);
+            w.write_bytes(digits);
+        })
+    }
+
+    fn fmt_float(&self, out: &mut dyn Output, style: FloatStyle, upper: bool) {
+        let flags = self.spec.flags;
+        let x = self.value.value().to_float();
+        let sign: &[u8] = if x.is_sign_negative() {
+            b"-"
+        } else if flags.plus {
+            b"+"
+        } else if flags.space {
+            b" "
+        } else {
+            b""
+        };
+
+        if !x.is_finite() {
+            let s: &[u8] = match (x.is_nan(), upper) {
+                (true, false) => b"nan",
+                (true, true) => b"NAN",
+                (false, false) => b"inf",
+                (false, true) => b"INF",
+            };
+            return self.pad(out, &[sign], false, &|w| w.write_bytes(s));
+        }
+
+        // `f64::abs` is not available in `core`
+        let x = if x.is_sign_negative() { -x } else { x };
+        let alt = flags.alternate;
+        let precision = self.precision.unwrap_or(6);
+        self.pad(out, &[sign], flags.zero, &|w| match style {
+            FloatStyle::Fixed => write_fixed(w, x, precision, alt, false),
+            FloatStyle::Exponent => write_exp(w, x, precision, alt, upper, false),
+            FloatStyle::General => {
+                let precision = precision.max(1);
+                let mut split = ExpSplit::new(Counter(0));
+                write_ascii(&mut split, format_args!("{:.*e}", precision - 1, x));
+                let exp = split.exp();
+                if (-4..precision as i32).contains(&exp) {
+                    let precision = (precision as i32 - 1 - exp) as usize;
+                    write_fixed(w, x, precision, alt, !alt)
+                } else {
+                    write_exp(w, x, precision - 1, alt, upper, !alt)
+                }
+            }
+        })
+    }
+
+    /// Writes the output of `body`, preceded by `prefix`, padded to the field width.
+    fn pad(
+        &self,
+        out: &mut dyn Output,
+        prefix: &[&[u8]],
+        zero_pad: bool,
+        body: &dyn Fn(&mut dyn Output),
+    ) {
+        let mut counter = Counter(prefix.iter().map(|s| s.len()).sum());
+        body(&mut counter);
+        let fill = self.width.unwrap_or(0).saturating_sub(counter.0);
+        let zero_pad = zero_pad && !self.left;
+
+        if !self.left && !zero_pad {
+            repeat(out, b' ', fill);
+        }
+        for s in prefix {
+            out.write_bytes(s);
+        }
+        if zero_pad {
+            repeat(out, b'0', fill);
+        }
+        body(out);
+        if self.left {
+            repeat(out, b' ', fill);
+        }
+    }
+}
+
+impl<T: Argument> Print for Formatted<T> {
+    fn print(&self, out: &mut dyn Output) {
+        let value = self.value.value();
+        match self.spec.conversion {
+            Conversion::Signed
+            | Conversion::Unsigned
+            | Conversion::Octal
+            | Conversion::Hex { .. } => self.fmt_int(out, self.spec, value),
+            Conversion::Char => {
+                let c = value.to_bits() as u8;
+                self.pad(out, &[], false, &|w| w.write_bytes(&[c]))
+            }
+            Conversion::Str => {
+                let ptr = value.to_bits() as usize as *const u8;
+                if ptr.is_null() {
+                    return self.pad(out, &[], false, &|w| w.write_bytes(b"(null)"));
+                }
+                let mut len = 0;
+                // Safety: guaranteed by the caller of `Formatted::new`.
+                unsafe {
+                    while self.precision.map_or(true, |p| len < p) && *ptr.add(len) != 0 {
+                        len += 1;
+                    }
+                }
+                let bytes = unsafe { slice::from_raw_parts(ptr, len) };
+                self.pad(out, &[], false, &|w| w.write_bytes(bytes))
+            }
+            Conversion::Pointer => {
+                if value.to_bits() == 0 {
+                    return self.pad(out, &[], false, &|w| w.write_bytes(b"(nil)"));
+                }
+                let spec = Spec {
+                    flags: Flags {
+                        alternate: true,
+                        ..self.spec.flags
+                    },
+                    length: Length::Size,
+                    conversion: Conversion::Hex { upper: false },
+                    ..self.spec
+                };
+                self.fmt_int(out, spec, value)
+            }
+            Conversion::Float { style, upper } => self.fmt_float(out, style, upper),
+        }
+    }
+}
+
+/// The destination of a translated `sprintf` or `snprintf` call.
+///
+/// Output beyond the buffer size is discarded, and the buffer is
+/// NUL-terminated when the `CBuffer` is dropped.
+pub struct CBuffer {
+    ptr: *mut u8,
+    size: usize,
+    len: usize,
+}
+
+impl CBuffer {
+    /// # Safety
+    ///
+    /// `ptr` must be valid for writes of `size` bytes, or of the formatted
+    /// output plus its NUL terminator, whichever is smaller.
+    pub unsafe fn new<T>(ptr: *mut T, size: usize) -> Self {
+        CBuffer {
+            ptr: ptr as *mut u8,
+            size,
+            len: 0,
+        }
+    }
+}
+
+impl Output for CBuffer {
+    fn write_bytes(&mut self, bytes: &[u8]) {
+        let avail = self.size.saturating_sub(self.len + 1);
+        let n = bytes.len().min(avail);
+        // Safety: guaranteed by the caller of `CBuffer::new`.
+        unsafe {
+            self.ptr
+                .add(self.len)
+                .copy_from_nonoverlapping(bytes.as_ptr(), n)
+        };
+        self.len += n;
+    }
+}
+
+impl Drop for CBuffer {
+    fn drop(&mut self) {
+        if self.size > 0 {
+            // Safety: `len` is less than `size`.
+            unsafe { *self.ptr.add(self.len) = 0 };
+        }
+    }
+}
+
+/// Counts the bytes written, for computing padding.
+struct Counter(usize);
+
+impl Output for Counter {
+    fn write_bytes(&mut self, bytes: &[u8]) {
+        self.0 += bytes.len();
+    }
+}
+
+fn repeat(w: &mut dyn Output, b: u8, n: usize) {
+    (0..n).for_each(|_| w.write_bytes(&[b]))
+}
+
+/// Writes Rust's formatting of a number, which is always ASCII, as bytes.
+fn write_ascii(w: &mut dyn Output, args: fmt::Arguments) {
+    struct Ascii<'a>(&'a mut dyn Output);
+
+    impl Write for Ascii<'_> {
+        fn write_str(&mut self, s: &str) -> fmt::Result {
+            self.0.write_bytes(s.as_bytes());
+            Ok(())
+        }
+    }
+
+    // `Ascii` never fails, and neither does formatting a number.
+    let _ = Ascii(w).write_fmt(args);
+}
+
+/// `%f`: `x` is non-negative and finite.
+fn write_fixed(w: &mut dyn Output, x: f64, precision: usize, alt: bool, trim: bool) {
+    let mut w = TrimZeros::new(w, trim);
+    write_ascii(&mut w, format_args!("{:.*}", precision, x));
+    if alt && precision == 0 {
+        w.write_bytes(b".");
+    }
+}
+
+/// `%e`: `x` is non-negative and finite. Rust prints `1.5e2` where C
+/// prints `1.5e+02`.
+fn write_exp(w: &mut dyn Output, x: f64, precision: usize, alt: bool, upper: bool, trim: bool) {
+    let mut split = ExpSplit::new(TrimZeros::new(w, trim));
+    write_ascii(&mut split, format_args!("{:.*e}", precision, x));
+    let exp = split.exp();
+    let mut mantissa = split.inner;
+    if alt && precision == 0 {
+        mantissa.write_bytes(b".");
+    }
+    let w = mantissa.inner;
+    let e = if upper { 'E' } else { 'e' };
+    let sign = if exp < 0 { '-' } else { '+' };
+    write_ascii(w, format_args!("{}{}{:02}", e, sign, exp.unsigned_abs()));
+}
+
+/// Passes the mantissa of a number in Rust's `{:e}` notation through to
+/// `inner`, and parses the exponent.
+struct ExpSplit<W> {
+    inner: W,
+    in_exp: bool,
+    exp_negative: bool,
+    exp: i32,
+}
+
+impl<W: Output> ExpSplit<W> {
+    fn new(inner: W) -> Self {
+        ExpSplit {
+            inner,
+            in_exp: false,
+            exp_negative: false,
+            exp: 0,
+        }
+    }
+
+    fn exp(&self) -> i32 {
+        if self.exp_negative {
+            -self.exp
+        } else {
+            self.exp
+        }
+    }
+}
+
+impl<W: Output> Output for ExpSplit<W> {
+    fn write_bytes(&mut self, mut bytes: &[u8]) {
+        if !self.in_exp {
+            match bytes.iter().position(|&b| b == b'e') {
+                Some(i) => {
+                    self.inner.write_bytes(&bytes[..i]);
+                    self.in_exp = true;
+                    bytes = &bytes[i + 1..];
+                }
+                None => return self.inner.write_bytes(bytes),
+            }
+        }
+        for &b in bytes {
+            match b {
+                b'-' => self.exp_negative = true,
+                b'0'..=b'9' => self.exp = self.exp * 10 + (b - b'0') as i32,
+                _ => (),
+            }
+        }
+    }
+}
+
+/// Drops trailing zeros after the decimal point, and the point itself if
+/// no digits remain, as `%g` does without the `#` flag.
+struct TrimZeros<'a> {
+    inner: &'a mut dyn Output,
+    enabled: bool,
+    seen_point: bool,
+    pending_point: bool,
+    pending_zeros: usize,
+}
+
+impl<'a> TrimZeros<'a> {
+    fn new(inner: &'a mut dyn Output, enabled: bool) -> Self {
+        TrimZeros {
+            inner,
+            enabled,
+            seen_point: false,
+            pending_point: false,
+            pending_zeros: 0,
+        }
+    }
+}
+
+impl Output for TrimZeros<'_> {
+    fn write_bytes(&mut self, bytes: &[u8]) {
+        if !self.enabled {
+            return self.inner.write_bytes(bytes);
+        }
+        for &b in bytes {
+            match b {
+                b'.' if !self.seen_point => {
+                    self.seen_point = true;
+                    self.pending_point = true;
+                }
+                b'0' if self.seen_point => self.pending_zeros += 1,
+                _ => {
+                    if self.pending_point {
+                        self.inner.write_bytes(b".");
+                        self.pending_point = false;
+                    }
+                    repeat(self.inner, b'0', self.pending_zeros);
+                    self.pending_zeros = 0;
+                    self.inner.write_bytes(&[b]);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use super::*;
+    use std::string::String;
+    use std::vec::Vec;
+
+    fn print(value: &dyn Print) -> Vec<u8> {
+        let mut out = Vec::new();
+        value.print(&mut |bytes: &[u8]| out.extend_from_slice(bytes));
+        out
+    }
+
+    fn bytes(spec: &str, value: impl Argument) -> Vec<u8> {
+        print(&unsafe { Formatted::new(spec, value) })
+    }
+
+    fn c(spec: &str, value: impl Argument) -> String {
+        String::from_utf8(bytes(spec, value)).unwrap()
+    }
+
+    #[test]
+    fn integers() {
+        assert_eq!(c("%d", -42), "-42");
+        assert_eq!(c("%5d", 42), "   42");
+        assert_eq!(c("%-5d", 42), "42   ");
+        assert_eq!(c("%05d", -42), "-0042");
+        assert_eq!(c("%+d", 42), "+42");
+        assert_eq!(c("% d", 42), " 42");
+        assert_eq!(c("%.3d", 7), "007");
+        assert_eq!(c("%.0d", 0), "");
+        assert_eq!(c("%x", -1), "ffffffff");
+        assert_eq!(c("%lx", -1i64), "ffffffffffffffff");
+        assert_eq!(c("%hhx", -1), "ff");
+        assert_eq!(c("%#X", 255), "0XFF");
+        assert_eq!(c("%#o", 8), "010");
+        assert_eq!(c("%#o", 0), "0");
+        assert_eq!(c("%u", -1), "4294967295");
+        assert_eq!(c("%hd", 65535), "-1");
+        assert_eq!(c("%c", b'A' as i32), "A");
+    }
+
+    #[test]
+    fn floats() {
+        assert_eq!(c("%f", 1.5), "1.500000");
+        assert_eq!(c("%5.2f", 1.23456), " 1.23");
+        assert_eq!(c("%-8.1f", -2.25), "-2.2    ");
+        assert_eq!(c("%08.3f", -1.5), "-001.500");
+        assert_eq!(c("%.0f", 2.5), "2");
+        assert_eq!(c("%#.0f", 2.0), "2.");
+        assert_eq!(c("%e", 12345.678), "1.234568e+04");
+        assert_eq!(c("%.2E", 0.000123), "1.23E-04");
+        assert_eq!(c("%g", 100000.0), "100000");
+        assert_eq!(c("%g", 1000000.0), "1e+06");
+        assert_eq!(c("%g", 0.0001), "0.0001");
+        assert_eq!(c("%g", 0.00001), "1e-05");
+        assert_eq!(c("%g", 3.5), "3.5");
+        assert_eq!(c("%#g", 3.5), "3.50000");
+        assert_eq!(c("%G", 1e-10), "1E-10");
+        assert_eq!(c("%f", f64::INFINITY), "inf");
+        assert_eq!(c("%5F", f64::NEG_INFINITY), " -INF");
+    }
+
+    #[test]
+    fn strings_and_pointers() {
+        let s = b"hello\0";
+        assert_eq!(c("%s", s.as_ptr()), "hello");
+        assert_eq!(c("%-10s", s.as_ptr()), "hello     ");
+        assert_eq!(c("%.3s", s.as_ptr()), "hel");
+        assert_eq!(c("%s", core::ptr::null::<u8>()), "(null)");
+        assert_eq!(c("%p", core::ptr::null::<u8>()), "(nil)");
+        assert_eq!(c("%p", 0x1234 as *const u8), "0x1234");
+    }
+
+    #[test]
+    fn star_amounts() {
+        let f = |w, p| print(&unsafe { Formatted::new("%*.*f", 1.0).width(w).precision(p) });
+        assert_eq!(f(6, 2), b"  1.00");
+        assert_eq!(f(-6, 2), b"1.00  ");
+        assert_eq!(f(0, -1), b"1.000000");
+    }
+
+    #[test]
+    fn buffer() {
+        let mut buf = [0xffu8; 8];
+        crate::write_c!(
+            unsafe { CBuffer::new(buf.as_mut_ptr(), buf.len()) },
+            unsafe { Formatted::new("%d", 123) },
+            b"-abcdefgh",
+        );
+        assert_eq!(&buf, b"123-abc\0");
+        crate::write_c!(unsafe { CBuffer::new(buf.as_mut_ptr(), 3) }, b"9");
+        assert_eq!(buf[..3], [b'9', 0, b'3']);
+    }
+
+    #[test]
+    fn non_utf8() {
+        assert_eq!(bytes("%c", 0xff), b"\xff");
+        assert_eq!(bytes("%3c", 0xe9), b"  \xe9");
+        let s = b"caf\xe9\0";
+        assert_eq!(bytes("%s", s.as_ptr()), b"caf\xe9");
+        assert_eq!(bytes("%-6s", s.as_ptr()), b"caf\xe9  ");
+    }
+
+    #[test]
+    fn pieces() {
+        let pieces = Pieces::new("a%%b%-5.*lds").collect::<Result<std::vec::Vec<_>, _>>();
+        let spec = Spec {
+            flags: Flags {
+                left: true,
+                ..Flags::default()
+            },
+            width: Some(Amount::Fixed(5)),
+            precision: Some(Amount::Arg),
+            length: Length::Long,
+            conversion: Conversion::Signed,
+        };
+        assert_eq!(
+            pieces,
+            Ok(std::vec![
+                Piece::Text("a"),
+                Piece::Percent,
+                Piece::Text("b"),
+                Piece::Spec(spec, "%-5.*ld"),
+                Piece::Text("s"),
+            ])
+        );
+        assert_eq!(spec.num_args(), 2);
+        assert!(Pieces::new("%n").any(|p| p.is_err()));
+        assert!(Pieces::new("%ls").any(|p| p.is_err()));
+        assert!(Pieces::new("%1$d").any(|p| p.is_err()));
+        assert!(Pieces::new("abc%").any(|p| p.is_err()));
+    }
+}
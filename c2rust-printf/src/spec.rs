@@ -0,0 +1,311 @@
+//! Parsing of C `printf` format strings and conversion specifications.
+
+/// Flag characters that may follow the `%` of a conversion specification.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Flags {
+    /// `-`: left-justify within the field width.
+    pub left: bool,
+    /// `+`: always print a sign for signed conversions.
+    pub plus: bool,
+    /// ` `: print a space in place of a `+` sign.
+    pub space: bool,
+    /// `#`: use the alternate form of the conversion.
+    pub alternate: bool,
+    /// `0`: pad with zeros instead of spaces.
+    pub zero: bool,
+}
+
+/// A field width or precision.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Amount {
+    /// A value written directly in the format string.
+    Fixed(usize),
+    /// `*`: the value is taken from the next argument, which is an `int`.
+    Arg,
+}
+
+/// The length modifier of a conversion specification.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Length {
+    Default,
+    /// `hh`
+    Char,
+    /// `h`
+    Short,
+    /// `l`
+    Long,
+    /// `ll` or `q`
+    LongLong,
+    /// `j`
+    IntMax,
+    /// `z`
+    Size,
+    /// `t`
+    PtrDiff,
+    /// `L`
+    LongDouble,
+}
+
+/// The notation used by a floating-point conversion.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FloatStyle {
+    /// `%f`
+    Fixed,
+    /// `%e`
+    Exponent,
+    /// `%g`
+    General,
+}
+
+/// The conversion performed by a conversion specification.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Conversion {
+    /// `%d` and `%i`
+    Signed,
+    /// `%u`
+    Unsigned,
+    /// `%o`
+    Octal,
+    /// `%x` and `%X`
+    Hex { upper: bool },
+    /// `%c`
+    Char,
+    /// `%s`
+    Str,
+    /// `%p`
+    Pointer,
+    /// `%f`, `%e`, `%g` and their uppercase variants
+    Float { style: FloatStyle, upper: bool },
+}
+
+impl Conversion {
+    /// Whether this conversion formats an integer argument.
+    pub fn is_integer(self) -> bool {
+        matches!(
+            self,
+            Conversion::Signed
+                | Conversion::Unsigned
+                | Conversion::Octal
+                | Conversion::Hex { .. }
+                | Conversion::Char
+        )
+    }
+}
+
+/// A single conversion specification, such as `%-10s` or `%5.2f`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Spec {
+    pub flags: Flags,
+    pub width: Option<Amount>,
+    pub precision: Option<Amount>,
+    pub length: Length,
+    pub conversion: Conversion,
+}
+
+impl Spec {
+    /// Parses a complete conversion specification, including the leading `%`.
+    pub fn parse(s: &str) -> Option<Spec> {
+        let mut pieces = Pieces::new(s);
+        match (pieces.next(), pieces.next()) {
+            (Some(Ok(Piece::Spec(spec, _))), None) => Some(spec),
+            _ => None,
+        }
+    }
+
+    /// The number of arguments consumed by this specification, counting
+    /// `*` widths and precisions.
+    pub fn num_args(&self) -> usize {
+        1 + (self.width == Some(Amount::Arg)) as usize
+            + (self.precision == Some(Amount::Arg)) as usize
+    }
+}
+
+/// A piece of a format string.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Piece<'a> {
+    /// Literal text, copied to the output as-is.
+    Text(&'a str),
+    /// `%%`
+    Percent,
+    /// A conversion specification, along with its source text.
+    Spec(Spec, &'a str),
+}
+
+/// An unsupported or malformed conversion specification.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ParseError {
+    /// Byte offset of the offending `%` in the format string.
+    pub offset: usize,
+}
+
+/// Iterator over the pieces of a C format string.
+///
+/// Conversions that have no Rust counterpart (`%n`, `%a`, wide characters
+/// and strings, positional arguments) are reported as errors.
+pub struct Pieces<'a> {
+    s: &'a str,
+    pos: usize,
+    in_spec: bool,
+}
+
+impl<'a> Pieces<'a> {
+    pub fn new(s: &'a str) -> Self {
+        Pieces {
+            s,
+            pos: 0,
+            in_spec: false,
+        }
+    }
+
+    fn parse_number(&mut self) -> Option<usize> {
+        let bytes = self.s.as_bytes();
+        let start = self.pos;
+        while self.pos < bytes.len() && bytes[self.pos].is_ascii_digit() {
+            self.pos += 1;
+        }
+        if start == self.pos {
+            return None;
+        }
+        self.s[start..self.pos].parse().ok()
+    }
+
+    fn parse_amount(&mut self) -> Option<Amount> {
+        if self.s.as_bytes().get(self.pos) == Some(&b'*') {
+            self.pos += 1;
+            return Some(Amount::Arg);
+        }
+        self.parse_number().map(Amount::Fixed)
+    }
+
+    /// Parses the specification following a `%`, which has already been consumed.
+    fn parse_spec(&mut self) -> Option<Spec> {
+        let bytes = self.s.as_bytes();
+        let peek = |pos: usize| bytes.get(pos).copied();
+
+        let mut flags = Flags::default();
+        loop {
+            match peek(self.pos)? {
+                b'-' => flags.left = true,
+                b'+' => flags.plus = true,
+                b' ' => flags.space = true,
+                b'#' => flags.alternate = true,
+                b'0' => flags.zero = true,
+                _ => break,
+            }
+            self.pos += 1;
+        }
+
+        let width = self.parse_amount();
+        // Positional arguments (`%1$d`) are not supported.
+        if peek(self.pos) == Some(b'$') {
+            return None;
+        }
+
+        let precision = if peek(self.pos) == Some(b'.') {
+            self.pos += 1;
+            Some(self.parse_amount().unwrap_or(Amount::Fixed(0)))
+        } else {
+            None
+        };
+
+        let (length, len) = match (peek(self.pos)?, peek(self.pos + 1)) {
+            (b'h', Some(b'h')) => (Length::Char, 2),
+            (b'h', _) => (Length::Short, 1),
+            (b'l', Some(b'l')) => (Length::LongLong, 2),
+            (b'l', _) => (Length::Long, 1),
+            (b'q', _) => (Length::LongLong, 1),
+            (b'j', _) => (Length::IntMax, 1),
+            (b'z', _) => (Length::Size, 1),
+            (b't', _) => (Length::PtrDiff, 1),
+            (b'L', _) => (Length::LongDouble, 1),
+            _ => (Length::Default, 0),
+        };
+        self.pos += len;
+
+        let conversion = match peek(self.pos)? {
+            b'd' | b'i' => Conversion::Signed,
+            b'u' => Conversion::Unsigned,
+            b'o' => Conversion::Octal,
+            b'x' => Conversion::Hex { upper: false },
+            b'X' => Conversion::Hex { upper: true },
+            b'c' => Conversion::Char,
+            b's' => Conversion::Str,
+            b'p' => Conversion::Pointer,
+            c @ (b'f' | b'F' | b'e' | b'E' | b'g' | b'G') => Conversion::Float {
+                style: match c.to_ascii_lowercase() {
+                    b'f' => FloatStyle::Fixed,
+                    b'e' => FloatStyle::Exponent,
+                    _ => FloatStyle::General,
+                },
+                upper: c.is_ascii_uppercase(),
+            },
+            _ => return None,
+        };
+        self.pos += 1;
+
+        let valid_length = match conversion {
+            Conversion::Signed
+            | Conversion::Unsigned
+            | Conversion::Octal
+            | Conversion::Hex { .. } => length != Length::LongDouble,
+            // `%lc` and `%ls` take wide characters.
+            Conversion::Char | Conversion::Str | Conversion::Pointer => length == Length::Default,
+            // `%lf` is the same as `%f`.
+            Conversion::Float { .. } => matches!(length, Length::Default | Length::Long),
+        };
+        if !valid_length {
+            return None;
+        }
+
+        Some(Spec {
+            flags,
+            width,
+            precision,
+            length,
+            conversion,
+        })
+    }
+}
+
+impl<'a> Iterator for Pieces<'a> {
+    type Item = Result<Piece<'a>, ParseError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.pos >= self.s.len() {
+            return None;
+        }
+
+        if self.in_spec {
+            self.in_spec = false;
+            let start = self.pos - 1;
+            return Some(match self.parse_spec() {
+                Some(spec) => Ok(Piece::Spec(spec, &self.s[start..self.pos])),
+                None => {
+                    // Stop after the first error.
+                    self.pos = self.s.len();
+                    Err(ParseError { offset: start })
+                }
+            });
+        }
+
+        let rest = &self.s[self.pos..];
+        if let Some(rest) = rest.strip_prefix("%%") {
+            self.pos = self.s.len() - rest.len();
+            return Some(Ok(Piece::Percent));
+        }
+        if rest.starts_with('%') {
+            self.pos += 1;
+            self.in_spec = true;
+            if self.pos == self.s.len() {
+                return Some(Err(ParseError {
+                    offset: self.pos - 1,
+                }));
+            }
+            return self.next();
+        }
+
+        let len = rest.find('%').unwrap_or(rest.len());
+        self.pos += len;
+        Some(Ok(Piece::Text(&rest[..len])))
+    }
+}
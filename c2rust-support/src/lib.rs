@@ -0,0 +1,296 @@
+//! Runtime support types for code rewritten by `c2rust-analyze`.
+//!
+//! Most raw pointers in translated C code can be rewritten to plain Rust references or slices.
+//! This crate provides types and helpers for the remaining cases where no standard library type has
+//! the right semantics.
+#![no_std]
+
+use core::cmp::Ordering;
+use core::ffi::CStr;
+use core::{mem, slice};
+
+/// A safe replacement for a `*const T` that moves both forward and backward through an array.
+///
+/// An `OffsetPtr` is a slice plus an index into that slice.  Like a C pointer, it may point one
+/// element past the end of the slice, but it can't be dereferenced there.  Unlike a C pointer,
+/// every `offset` is bounds-checked, and moving outside the slice panics instead of producing
+/// undefined behavior.
+#[derive(Debug)]
+pub struct OffsetPtr<'a, T> {
+    slice: &'a [T],
+    index: usize,
+}
+
+impl<'a, T> OffsetPtr<'a, T> {
+    /// Create an `OffsetPtr` pointing to the first element of `slice`.
+    pub fn new(slice: &'a [T]) -> OffsetPtr<'a, T> {
+        OffsetPtr { slice, index: 0 }
+    }
+
+    /// Move the pointer by `n` elements, which may be negative.  Panics if the result would be
+    /// outside the underlying slice.
+    pub fn offset(self, n: isize) -> OffsetPtr<'a, T> {
+        let index = offset_index(self.index, n, self.slice.len());
+        OffsetPtr { index, ..self }
+    }
+
+    /// Compute the distance in elements from `origin` to `self`, like `<*const T>::offset_from`.
+    pub fn offset_from(self, origin: OffsetPtr<T>) -> isize {
+        element_distance::<T>(self.addr(), origin.addr())
+    }
+
+    /// Get a reference to the element the pointer points to.  Panics if the pointer is one past
+    /// the end of the slice.
+    pub fn get(self) -> &'a T {
+        &self.slice[self.index]
+    }
+
+    /// Get the remainder of the underlying slice, starting at the current position.
+    pub fn as_slice(self) -> &'a [T] {
+        &self.slice[self.index..]
+    }
+
+    /// Get the current position within the underlying slice.
+    pub fn index(self) -> usize {
+        self.index
+    }
+
+    fn addr(self) -> usize {
+        self.slice.as_ptr().wrapping_add(self.index) as usize
+    }
+}
+
+impl<'a, T> Clone for OffsetPtr<'a, T> {
+    fn clone(&self) -> OffsetPtr<'a, T> {
+        *self
+    }
+}
+
+impl<'a, T> Copy for OffsetPtr<'a, T> {}
+
+impl<'a, T> From<&'a [T]> for OffsetPtr<'a, T> {
+    fn from(slice: &'a [T]) -> OffsetPtr<'a, T> {
+        OffsetPtr::new(slice)
+    }
+}
+
+/// Pointers are compared by address, like raw pointers.
+impl<'a, 'b, T> PartialEq<OffsetPtr<'b, T>> for OffsetPtr<'a, T> {
+    fn eq(&self, other: &OffsetPtr<'b, T>) -> bool {
+        self.addr() == other.addr()
+    }
+}
+
+impl<'a, T> Eq for OffsetPtr<'a, T> {}
+
+impl<'a, 'b, T> PartialOrd<OffsetPtr<'b, T>> for OffsetPtr<'a, T> {
+    fn partial_cmp(&self, other: &OffsetPtr<'b, T>) -> Option<Ordering> {
+        self.addr().partial_cmp(&other.addr())
+    }
+}
+
+/// A safe replacement for a `*mut T` that moves both forward and backward through an array.
+///
+/// This is the mutable counterpart of [`OffsetPtr`].  Since it holds a `&mut [T]`, it can't be
+/// copied; use [`reborrow`](OffsetPtrMut::reborrow) to obtain a temporary copy instead.
+#[derive(Debug)]
+pub struct OffsetPtrMut<'a, T> {
+    slice: &'a mut [T],
+    index: usize,
+}
+
+impl<'a, T> OffsetPtrMut<'a, T> {
+    /// Create an `OffsetPtrMut` pointing to the first element of `slice`.
+    pub fn new(slice: &'a mut [T]) -> OffsetPtrMut<'a, T> {
+        OffsetPtrMut { slice, index: 0 }
+    }
+
+    /// Move the pointer by `n` elements, which may be negative.  Panics if the result would be
+    /// outside the underlying slice.
+    pub fn offset(self, n: isize) -> OffsetPtrMut<'a, T> {
+        let index = offset_index(self.index, n, self.slice.len());
+        OffsetPtrMut { index, ..self }
+    }
+
+    /// Borrow `self` to produce a new `OffsetPtrMut` with a shorter lifetime, pointing to the
+    /// same position.
+    pub fn reborrow(&mut self) -> OffsetPtrMut<T> {
+        OffsetPtrMut {
+            slice: self.slice,
+            index: self.index,
+        }
+    }
+
+    /// Borrow `self` as an immutable [`OffsetPtr`] pointing to the same position.
+    pub fn as_imm(&self) -> OffsetPtr<T> {
+        OffsetPtr {
+            slice: self.slice,
+            index: self.index,
+        }
+    }
+
+    /// Get a reference to the element the pointer points to.  Panics if the pointer is one past
+    /// the end of the slice.
+    pub fn get(&self) -> &T {
+        &self.slice[self.index]
+    }
+
+    /// Get a mutable reference to the element the pointer points to.  Panics if the pointer is
+    /// one past the end of the slice.
+    pub fn get_mut(&mut self) -> &mut T {
+        &mut self.slice[self.index]
+    }
+
+    /// Get the remainder of the underlying slice, starting at the current position.
+    pub fn as_slice(&self) -> &[T] {
+        &self.slice[self.index..]
+    }
+
+    /// Get the remainder of the underlying slice, starting at the current position.
+    pub fn as_mut_slice(&mut self) -> &mut [T] {
+        &mut self.slice[self.index..]
+    }
+
+    /// Convert the pointer into the remainder of the underlying slice, starting at the current
+    /// position.
+    pub fn into_slice(self) -> &'a mut [T] {
+        &mut self.slice[self.index..]
+    }
+
+    /// Get the current position within the underlying slice.
+    pub fn index(&self) -> usize {
+        self.index
+    }
+}
+
+impl<'a, T> From<&'a mut [T]> for OffsetPtrMut<'a, T> {
+    fn from(slice: &'a mut [T]) -> OffsetPtrMut<'a, T> {
+        OffsetPtrMut::new(slice)
+    }
+}
+
+mod sealed {
+    pub trait Sealed {}
+    impl Sealed for i8 {}
+    impl Sealed for u8 {}
+}
+
+/// A C character type: `i8` or `u8`, depending on how the translated code spells `c_char`.
+pub trait CChar: sealed::Sealed + Copy {}
+
+impl CChar for i8 {}
+impl CChar for u8 {}
+
+/// View the contents of `s` as a slice of C characters, including the NUL terminator.
+pub fn cstr_to_chars<T: CChar>(s: &CStr) -> &[T] {
+    let bytes = s.to_bytes_with_nul();
+    // Safety: `T` is `i8` or `u8`, which have the same size and alignment as `u8`, and every
+    // byte is a valid value of either type.
+    unsafe { slice::from_raw_parts(bytes.as_ptr().cast::<T>(), bytes.len()) }
+}
+
+/// Interpret the C characters in `chars` as a NUL-terminated string.  The string ends at the
+/// first NUL; any characters after it are ignored.  Panics if `chars` contains no NUL.
+pub fn cstr_from_chars<T: CChar>(chars: &[T]) -> &CStr {
+    // Safety: as in `cstr_to_chars`.
+    let bytes = unsafe { slice::from_raw_parts(chars.as_ptr().cast::<u8>(), chars.len()) };
+    let len = match bytes.iter().position(|&b| b == 0) {
+        Some(i) => i + 1,
+        None => panic!("string of length {} is not NUL-terminated", bytes.len()),
+    };
+    CStr::from_bytes_with_nul(&bytes[..len]).unwrap()
+}
+
+/// Compute `index + n`, panicking if the result is outside `0 ..= len`.
+fn offset_index(index: usize, n: isize, len: usize) -> usize {
+    let new_index = if n >= 0 {
+        index.checked_add(n as usize)
+    } else {
+        index.checked_sub(n.unsigned_abs())
+    };
+    match new_index {
+        Some(i) if i <= len => i,
+        _ => panic!(
+            "offset {} from index {} is out of bounds for slice of length {}",
+            n, index, len
+        ),
+    }
+}
+
+/// Compute the distance in elements of type `T` from address `origin` to address `addr`.
+fn element_distance<T>(addr: usize, origin: usize) -> isize {
+    let bytes = addr.wrapping_sub(origin) as isize;
+    match mem::size_of::<T>() {
+        // All elements of a slice of zero-sized types have the same address.
+        0 => 0,
+        size => bytes / size as isize,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{cstr_from_chars, cstr_to_chars, OffsetPtr, OffsetPtrMut};
+
+    #[test]
+    fn test_offset() {
+        let arr = [1, 2, 3, 4];
+        let p = OffsetPtr::new(&arr);
+        let q = p.offset(3);
+        assert_eq!(*q.get(), 4);
+        assert_eq!(*q.offset(-2).get(), 2);
+        assert_eq!(q.offset(1).index(), 4);
+        assert_eq!(q.offset(-3), p);
+        assert!(p < q);
+        assert_eq!(q.offset_from(p), 3);
+        assert_eq!(p.offset_from(q), -3);
+        assert_eq!(q.as_slice(), &[4]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_offset_before_start() {
+        let arr = [1, 2, 3, 4];
+        OffsetPtr::new(&arr).offset(2).offset(-3);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_offset_past_end() {
+        let arr = [1, 2, 3, 4];
+        OffsetPtr::new(&arr).offset(5);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_get_past_end() {
+        let arr = [1, 2, 3, 4];
+        OffsetPtr::new(&arr).offset(4).get();
+    }
+
+    #[test]
+    fn test_offset_mut() {
+        let mut arr = [1, 2, 3, 4];
+        let mut p = OffsetPtrMut::new(&mut arr).offset(2);
+        *p.reborrow().offset(-1).get_mut() = 20;
+        *p.get_mut() = 30;
+        assert_eq!(p.as_imm().offset_from(p.as_imm().offset(-2)), 2);
+        let p = p.offset(1);
+        assert_eq!(p.into_slice(), &[4]);
+        assert_eq!(arr, [1, 20, 30, 4]);
+    }
+
+    #[test]
+    fn test_cstr_chars() {
+        let chars: [i8; 6] = [104, 105, 0, 120, 0, 0];
+        let s = cstr_from_chars(&chars[..]);
+        assert_eq!(s.to_bytes(), b"hi");
+        assert_eq!(cstr_to_chars::<i8>(s), &[104, 105, 0]);
+        assert_eq!(cstr_to_chars::<u8>(s), b"hi\0");
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_cstr_from_chars_without_nul() {
+        cstr_from_chars(&b"hi"[..]);
+    }
+}
@@ -12,6 +12,7 @@ use serde_json::json;
 use super::compile_cmds::LinkCmd;
 use super::TranspilerConfig;
 use crate::get_module_name;
+use crate::translator::SharedHeaders;
 use crate::CrateSet;
 use crate::ExternCrateDetails;
 use crate::PragmaSet;
@@ -81,6 +82,10 @@ pub fn emit_build_files<'lcmd>(
         .unwrap();
     reg.register_template_string("build.rs", include_str!("build.rs.hbs"))
         .unwrap();
+    reg.register_template_string("libc.rs", include_str!("libc.rs.hbs"))
+        .unwrap();
+    reg.register_template_string("libc_hooks.rs", include_str!("libc_hooks.rs.hbs"))
+        .unwrap();
 
     if !build_dir.exists() {
         fs::create_dir_all(&build_dir)
@@ -93,6 +98,9 @@ pub fn emit_build_files<'lcmd>(
     }
     crate_cfg.and_then(|ccfg| {
         emit_build_rs(tcfg, &reg, build_dir, ccfg.link_cmd);
+        if tcfg.emit_no_libc {
+            emit_libc_rs(tcfg, &reg, build_dir);
+        }
         emit_lib_rs(
             tcfg,
             &reg,
@@ -157,6 +165,19 @@ enum ModuleSubset {
     //Both,
 }
 
+/// The path from the crate root of the module translated to `module`, which
+/// is nested according to its path inside `build_dir`, or declared at the
+/// crate root otherwise.
+pub fn get_module_path(build_dir: &Path, module: &Path) -> Vec<String> {
+    match module.strip_prefix(build_dir) {
+        Ok(relpath) => relpath
+            .iter()
+            .map(|sm| get_module_name(Path::new(sm), true, false, false).unwrap())
+            .collect(),
+        Err(_) => vec![get_module_name(module, true, false, false).unwrap()],
+    }
+}
+
 fn convert_module_list(
     tcfg: &TranspilerConfig,
     build_dir: &Path,
@@ -175,12 +196,10 @@ fn convert_module_list(
     let mut module_tree = ModuleTree(BTreeMap::new());
     for m in &modules {
         match m.strip_prefix(build_dir) {
-            Ok(relpath) if !tcfg.is_binary(m) => {
+            Ok(_) if !tcfg.is_binary(m) => {
                 // The module is inside the build directory, use nested modules
                 let mut cur = &mut module_tree;
-                for sm in relpath.iter() {
-                    let path = Path::new(sm);
-                    let name = get_module_name(path, true, false, false).unwrap();
+                for name in get_module_path(build_dir, m) {
                     cur = cur.0.entry(name).or_default();
                 }
             }
@@ -245,6 +264,7 @@ fn emit_lib_rs(
         "lib_rs_file": file_name,
         "reorganize_definitions": tcfg.reorganize_definitions,
         "translate_valist": tcfg.translate_valist,
+        "emit_no_libc": tcfg.emit_no_libc,
         "modules": modules,
         "pragmas": pragmas,
         "crates": crates,
@@ -256,6 +276,53 @@ fn emit_lib_rs(
     maybe_write_to_file(&output_path, output, tcfg.overwrite_existing)
 }
 
+/// The C library functions defined by the `libc` module emitted for
+/// `--emit-no-libc`, i.e. the `pub unsafe extern "C" fn`s in `libc.rs.hbs`.
+const LIBC_SHIM_FUNCTIONS: &[&str] = &[
+    "memcpy", "memmove", "memset", "memcmp", "memchr", "strlen", "strnlen", "strcmp", "strncmp",
+    "strcpy", "strncpy", "strcat", "strncat", "strchr", "strrchr", "strspn", "strcspn", "strpbrk",
+    "strstr", "strdup", "strndup", "abs", "labs", "llabs", "isalnum", "isalpha", "isdigit",
+    "isxdigit", "islower", "isupper", "isspace", "isprint", "ispunct", "tolower", "toupper",
+    "malloc", "calloc", "realloc", "free", "abort", "exit", "getenv", "rand", "srand", "putchar",
+    "puts",
+];
+
+/// Whether the `libc` module emitted for `--emit-no-libc` defines the C
+/// library function `name`.
+pub fn is_libc_shim_function(name: &str) -> bool {
+    LIBC_SHIM_FUNCTIONS.contains(&name)
+}
+
+/// Emit the `libc` module that replaces the C library for `--emit-no-libc`,
+/// along with the `libc_hooks` module for the functions it cannot implement
+/// portably. The latter is meant to be edited, so it is never overwritten.
+fn emit_libc_rs(tcfg: &TranspilerConfig, reg: &Handlebars, build_dir: &Path) {
+    let output = reg.render("libc.rs", &json!({})).unwrap();
+    maybe_write_to_file(&build_dir.join("libc.rs"), output, tcfg.overwrite_existing);
+
+    let output = reg.render("libc_hooks.rs", &json!({})).unwrap();
+    maybe_write_to_file(&build_dir.join("libc_hooks.rs"), output, false);
+}
+
+/// Emit the `headers` module shared by the translated files for
+/// `--dedup-headers`. Returns the path to the module, if it was written.
+pub fn emit_headers_rs(
+    tcfg: &TranspilerConfig,
+    build_dir: &Path,
+    shared_headers: SharedHeaders,
+) -> Option<PathBuf> {
+    if !build_dir.exists() {
+        fs::create_dir_all(&build_dir)
+            .unwrap_or_else(|_| panic!("couldn't create build directory: {}", build_dir.display()));
+    }
+    let output = shared_headers.into_string(tcfg);
+    maybe_write_to_file(
+        &build_dir.join("headers.rs"),
+        output,
+        tcfg.overwrite_existing,
+    )
+}
+
 /// If we translate variadic functions, the output will only compile
 /// on a nightly toolchain until the `c_variadics` feature is stable.
 fn emit_rust_toolchain(tcfg: &TranspilerConfig, build_dir: &Path) {
@@ -327,3 +394,54 @@ fn maybe_write_to_file(output_path: &Path, output: String, overwrite: bool) -> O
 
     Some(PathBuf::from(output_path))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::compile_cmds::LinkType;
+    use crate::ExternCrate;
+
+    #[test]
+    fn printf_dependency_is_published_version() {
+        let mut reg = Handlebars::new();
+        reg.register_template_string("Cargo.toml", include_str!("Cargo.toml.hbs"))
+            .unwrap();
+        let crates = [ExternCrate::C2RustPrintf].into_iter().collect();
+        let json = json!({
+            "is_crate": true,
+            "crate_name": "printf-test",
+            "crate_rust_name": "printf_test",
+            "crate_types": LinkType::Static.as_cargo_types(),
+            "is_library": true,
+            "lib_rs_file": "lib.rs",
+            "binaries": [],
+            "dependencies": convert_dependencies_list(crates),
+        });
+        let cargo_toml = reg.render("Cargo.toml", &json).unwrap();
+
+        // `c2rust-printf` is versioned with the rest of the workspace
+        let printf_cargo_toml = include_str!("../../../c2rust-printf/Cargo.toml");
+        assert!(printf_cargo_toml.contains("\nversion.workspace = true\n"));
+        let dependency = cargo_toml
+            .lines()
+            .find_map(|line| line.strip_prefix("c2rust-printf"))
+            .unwrap_or_else(|| panic!("no c2rust-printf dependency in:\n{}", cargo_toml));
+        let version = dependency.trim_start().strip_prefix('=').map(str::trim);
+        let expected = format!("\"{}\"", env!("CARGO_PKG_VERSION"));
+        assert_eq!(version, Some(expected.as_str()), "{}", cargo_toml);
+    }
+
+    #[test]
+    fn libc_shim_functions_match_template() {
+        let mut reg = Handlebars::new();
+        reg.register_template_string("libc.rs", include_str!("libc.rs.hbs"))
+            .unwrap();
+        let libc_rs = reg.render("libc.rs", &json!({})).unwrap();
+        let defined = libc_rs
+            .lines()
+            .filter_map(|line| line.strip_prefix("pub unsafe extern \"C\" fn "))
+            .map(|decl| &decl[..decl.find('(').unwrap()])
+            .collect::<Vec<_>>();
+        assert_eq!(defined, LIBC_SHIM_FUNCTIONS);
+    }
+}
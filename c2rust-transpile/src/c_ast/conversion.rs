@@ -162,6 +162,8 @@ fn parse_cast_kind(kind: &str) -> CastKind {
         "BuiltinFnToFnPtr" => CastKind::BuiltinFnToFnPtr,
         "ConstCast" => CastKind::ConstCast,
         "VectorSplat" => CastKind::VectorSplat,
+        "AtomicToNonAtomic" => CastKind::AtomicToNonAtomic,
+        "NonAtomicToAtomic" => CastKind::NonAtomicToAtomic,
         k => panic!("Unsupported implicit cast: {}", k),
     }
 }
@@ -477,6 +479,7 @@ impl ConversionContext {
 
         self.typed_context.va_list_kind = untyped_context.va_list_kind;
         self.typed_context.target = untyped_context.target.clone();
+        self.typed_context.target_types = untyped_context.target_types;
     }
 
     /// Visit one node.
@@ -627,6 +630,16 @@ impl ConversionContext {
                     self.processed_nodes.insert(new_id, OTHER_TYPE);
                 }
 
+                TypeTag::TagAtomicType if expected_ty & OTHER_TYPE != 0 => {
+                    let value =
+                        from_value(ty_node.extras[0].clone()).expect("Atomic child not found");
+                    let value_new = self.visit_qualified_type(value);
+
+                    let atomic_ty = CTypeKind::Atomic(value_new);
+                    self.add_type(new_id, not_located(atomic_ty));
+                    self.processed_nodes.insert(new_id, OTHER_TYPE);
+                }
+
                 TypeTag::TagComplexType if expected_ty & OTHER_TYPE != 0 => {
                     let subelt =
                         from_value(ty_node.extras[0].clone()).expect("Complex child not found");
@@ -1084,6 +1097,11 @@ impl ConversionContext {
                         .expect("output constraints array");
                     let raw_clobbers =
                         from_value::<Vec<Value>>(node.extras[4].clone()).expect("clobber array");
+                    let input_names = from_value::<Vec<String>>(node.extras[5].clone())
+                        .expect("input names array");
+                    let output_names = from_value::<Vec<String>>(node.extras[6].clone())
+                        .expect("output names array");
+                    let is_goto = from_value(node.extras[7].clone()).expect("asm goto flag");
 
                     let (input_children, output_children) =
                         node.children.split_at(raw_inputs.len());
@@ -1091,12 +1109,14 @@ impl ConversionContext {
                     let inputs: Vec<AsmOperand> = raw_inputs
                         .into_iter()
                         .zip(input_children)
-                        .map(|(c, e)| {
+                        .zip(input_names)
+                        .map(|((c, e), name)| {
                             let constraints = from_value(c).expect("constraint string");
                             let expression = self.visit_expr(e.expect("expression"));
                             AsmOperand {
                                 constraints,
                                 expression,
+                                name: Some(name).filter(|name| !name.is_empty()),
                             }
                         })
                         .collect();
@@ -1104,12 +1124,14 @@ impl ConversionContext {
                     let outputs: Vec<AsmOperand> = raw_outputs
                         .into_iter()
                         .zip(output_children)
-                        .map(|(c, e)| {
+                        .zip(output_names)
+                        .map(|((c, e), name)| {
                             let constraints = from_value(c).expect("constraint string");
                             let expression = self.visit_expr(e.expect("expression"));
                             AsmOperand {
                                 constraints,
                                 expression,
+                                name: Some(name).filter(|name| !name.is_empty()),
                             }
                         })
                         .collect();
@@ -1121,6 +1143,7 @@ impl ConversionContext {
 
                     let stmt = CStmtKind::Asm {
                         is_volatile,
+                        is_goto,
                         asm,
                         inputs,
                         outputs,
@@ -2008,6 +2031,10 @@ impl ConversionContext {
                         .expect("Expected has_def flag on struct");
                     let attrs = from_value::<Vec<Value>>(node.extras[2].clone())
                         .expect("Expected attribute array on record");
+                    let platform_byte_size =
+                        from_value(node.extras[5].clone()).expect("Expected union size");
+                    let platform_alignment =
+                        from_value(node.extras[6].clone()).expect("Expected union alignment");
                     let fields: Option<Vec<CDeclId>> = if has_def {
                         Some(
                             node.children
@@ -2030,6 +2057,8 @@ impl ConversionContext {
                         name,
                         fields,
                         is_packed,
+                        platform_byte_size,
+                        platform_alignment,
                     };
 
                     self.add_decl(new_id, located(node, record));
@@ -9,7 +9,9 @@ use std::ops::Index;
 use std::path::{Path, PathBuf};
 use std::rc::Rc;
 
-pub use c2rust_ast_exporter::clang_ast::{BuiltinVaListKind, SrcFile, SrcLoc, SrcSpan};
+pub use c2rust_ast_exporter::clang_ast::{
+    BuiltinVaListKind, SrcFile, SrcLoc, SrcSpan, TargetTypes,
+};
 
 #[derive(Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Copy, Clone)]
 pub struct CTypeId(pub u64);
@@ -88,6 +90,7 @@ pub struct TypedAstContext {
 
     pub va_list_kind: BuiltinVaListKind,
     pub target: String,
+    pub target_types: TargetTypes,
 }
 
 /// Comments associated with a typed AST context
@@ -187,6 +190,10 @@ impl TypedAstContext {
             prenamed_decls: IndexMap::new(),
             va_list_kind: BuiltinVaListKind::CharPtrBuiltinVaList,
             target: String::new(),
+            target_types: TargetTypes {
+                long_size: 8,
+                char_is_signed: true,
+            },
         }
     }
 
@@ -265,6 +272,10 @@ impl TypedAstContext {
         self.c_decls.iter()
     }
 
+    pub fn iter_exprs(&self) -> impl Iterator<Item = (&CExprId, &CExpr)> {
+        self.c_exprs.iter()
+    }
+
     pub fn iter_mut_decls(&mut self) -> indexmap::map::IterMut<CDeclId, CDecl> {
         self.c_decls.iter_mut()
     }
@@ -395,6 +406,86 @@ impl TypedAstContext {
         }
     }
 
+    /// The type of the value stored in an `_Atomic` object of type `typ`, if
+    /// `typ` is an atomic type.
+    pub fn atomic_value_type(&self, typ: CTypeId) -> Option<CQualTypeId> {
+        match self.resolve_type(typ).kind {
+            CTypeKind::Atomic(value_ty) => Some(value_ty),
+            _ => None,
+        }
+    }
+
+    /// Whether `typ` is an atomic type, or an array, struct or union that
+    /// contains one.
+    pub fn contains_atomic(&self, typ: CTypeId) -> bool {
+        match self.resolve_type(typ).kind {
+            CTypeKind::Atomic(..) => true,
+            CTypeKind::ConstantArray(elt, _)
+            | CTypeKind::IncompleteArray(elt)
+            | CTypeKind::VariableArray(elt, _) => self.contains_atomic(elt),
+            CTypeKind::Struct(decl_id) | CTypeKind::Union(decl_id) => {
+                self.record_contains_atomic(decl_id)
+            }
+            _ => false,
+        }
+    }
+
+    /// Whether any field of the struct or union `decl_id` contains an atomic
+    /// type.
+    pub fn record_contains_atomic(&self, decl_id: CRecordId) -> bool {
+        let fields = match self.index(decl_id).kind {
+            CDeclKind::Struct {
+                fields: Some(ref fields),
+                ..
+            }
+            | CDeclKind::Union {
+                fields: Some(ref fields),
+                ..
+            } => fields,
+            _ => return false,
+        };
+        fields
+            .iter()
+            .any(|&field_id| match self.index(field_id).kind {
+                CDeclKind::Field { typ, .. } => self.contains_atomic(typ.ctype),
+                _ => false,
+            })
+    }
+
+    /// Whether `typ` is a raw pointer other than a function pointer, or an
+    /// array, struct or union that contains one.
+    pub fn contains_raw_pointer(&self, typ: CTypeId) -> bool {
+        match self.resolve_type(typ).kind {
+            CTypeKind::Pointer(pointee) => !matches!(
+                self.resolve_type(pointee.ctype).kind,
+                CTypeKind::Function(..)
+            ),
+            CTypeKind::Atomic(inner) => self.contains_raw_pointer(inner.ctype),
+            CTypeKind::ConstantArray(elt, _)
+            | CTypeKind::IncompleteArray(elt)
+            | CTypeKind::VariableArray(elt, _) => self.contains_raw_pointer(elt),
+            CTypeKind::Struct(decl_id) | CTypeKind::Union(decl_id) => {
+                match self.index(decl_id).kind {
+                    CDeclKind::Struct {
+                        fields: Some(ref fields),
+                        ..
+                    }
+                    | CDeclKind::Union {
+                        fields: Some(ref fields),
+                        ..
+                    } => fields
+                        .iter()
+                        .any(|&field_id| match self.index(field_id).kind {
+                            CDeclKind::Field { typ, .. } => self.contains_raw_pointer(typ.ctype),
+                            _ => false,
+                        }),
+                    _ => false,
+                }
+            }
+            _ => false,
+        }
+    }
+
     /// Can the given field decl be a flexible array member?
     pub fn maybe_flexible_array(&self, typ: CTypeId) -> bool {
         let field_ty = self.resolve_type(typ);
@@ -952,6 +1043,8 @@ pub enum CDeclKind {
         name: Option<String>,
         fields: Option<Vec<CFieldId>>,
         is_packed: bool,
+        platform_byte_size: u64,
+        platform_alignment: u64,
     },
 
     // Field
@@ -1214,6 +1307,8 @@ pub enum CastKind {
     BuiltinFnToFnPtr,
     ConstCast,
     VectorSplat,
+    AtomicToNonAtomic,
+    NonAtomicToAtomic,
 }
 
 /// Represents a unary operator in C (6.5.3 Unary operators) and GNU C extensions
@@ -1503,6 +1598,7 @@ pub enum CStmtKind {
         outputs: Vec<AsmOperand>,
         clobbers: Vec<String>,
         is_volatile: bool,
+        is_goto: bool,
     },
 
     // Statements annotated with attributes. The substatement can be a NULL
@@ -1518,6 +1614,8 @@ pub enum CStmtKind {
 pub struct AsmOperand {
     pub constraints: String,
     pub expression: CExprId,
+    /// The symbolic name of the operand, as in `[name] "r" (x)`
+    pub name: Option<String>,
 }
 
 /// Type qualifiers (6.7.3)
@@ -1672,6 +1770,10 @@ pub enum CTypeKind {
 
     Half,
     BFloat16,
+
+    // Atomic type (6.2.5.20), from either the `_Atomic` qualifier or the
+    // `_Atomic(T)` type specifier
+    Atomic(CQualTypeId),
 }
 
 impl CTypeKind {
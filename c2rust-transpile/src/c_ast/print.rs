@@ -726,6 +726,13 @@ impl<W: Write> Printer<W> {
             &Paren(ctype) => {
                 self.parenthesize(true, |slf| slf.print_type(ctype, ident, context))?;
             }
+            &Atomic(qual_ty) => {
+                self.writer.write_all(b"_Atomic")?;
+                self.parenthesize(true, |slf| slf.print_qtype(qual_ty, None, context))?;
+                if let Some(i) = ident {
+                    self.writer.write_fmt(format_args!(" {}", i))?;
+                }
+            }
 
             Enum(enum_id) => {
                 let decl = context
@@ -17,7 +17,7 @@
 
 use crate::c_ast::iterators::{DFExpr, SomeId};
 use crate::c_ast::CLabelId;
-use crate::diagnostics::TranslationResult;
+use crate::diagnostics::{diag, Diagnostic, TranslationResult};
 use crate::rust_ast::SpanExt;
 use c2rust_ast_printer::pprust;
 use proc_macro2::Span;
@@ -51,6 +51,7 @@ pub mod loops;
 pub mod multiples;
 pub mod relooper;
 pub mod structures;
+mod switch_match;
 
 use crate::cfg::inc_cleanup::IncCleanup;
 use crate::cfg::loops::*;
@@ -546,6 +547,39 @@ pub enum ImplicitReturnType {
     ///
     /// TODO: document
     StmtExpr(ExprContext, CExprId, Label),
+
+    /// The arms of a `match` translated from a `switch` continue after the `match`
+    MatchArm,
+}
+
+/// Convert the value of a `case` label into an expression that can be used as a pattern: the
+/// literal or enum constant it was written as if possible, or else its value.
+fn convert_case_value(
+    translator: &Translation,
+    ctx: ExprContext,
+    case_expr: CExprId,
+    cie: ConstIntExpr,
+) -> TranslationResult<Box<Expr>> {
+    let resolved = translator.ast_context.resolve_expr(case_expr);
+    let branch = match resolved.1 {
+        CExprKind::Literal(..) | CExprKind::ConstantExpr(_, _, Some(_)) => {
+            match translator
+                .convert_expr(ctx.used(), resolved.0)?
+                .to_pure_expr()
+            {
+                Some(expr) => match *expr {
+                    Expr::Lit(..) | Expr::Path(..) => Some(expr),
+                    _ => None,
+                },
+                _ => None,
+            }
+        }
+        _ => None,
+    };
+    match branch {
+        Some(expr) => Ok(expr),
+        None => translator.convert_constant(cie),
+    }
 }
 
 /// A complete control-flow graph
@@ -619,6 +653,7 @@ impl Cfg<Label, StmtOrDecl> {
                             mk().break_expr_value(Some(brk_label.pretty_print()), Some(val)),
                         )));
                     }
+                    ImplicitReturnType::MatchArm => {}
                 };
 
                 cfg_builder.add_wip_block(wip, End);
@@ -1827,26 +1862,7 @@ impl CfgBuilder {
                 self.add_wip_block(wip, Jump(this_label.clone()));
 
                 // Case
-                let resolved = translator.ast_context.resolve_expr(case_expr);
-                let branch = match resolved.1 {
-                    CExprKind::Literal(..) | CExprKind::ConstantExpr(_, _, Some(_)) => {
-                        match translator
-                            .convert_expr(ctx.used(), resolved.0)?
-                            .to_pure_expr()
-                        {
-                            Some(expr) => match *expr {
-                                Expr::Lit(..) | Expr::Path(..) => Some(expr),
-                                _ => None,
-                            },
-                            _ => None,
-                        }
-                    }
-                    _ => None,
-                };
-                let branch = match branch {
-                    Some(expr) => expr,
-                    None => translator.convert_constant(cie)?,
-                };
+                let branch = convert_case_value(translator, ctx, case_expr, cie)?;
                 self.switch_expr_cases
                     .last_mut()
                     .ok_or_else(|| {
@@ -1886,71 +1902,104 @@ impl CfgBuilder {
                 scrutinee,
                 body: switch_body,
             } => {
-                let next_label = self.fresh_label();
-                let body_label = self.fresh_label();
-
                 // Convert the condition
                 let (stmts, val) = translator
                     .convert_expr(ctx.used(), scrutinee)?
                     .discard_unsafe();
                 wip.extend(stmts);
 
-                let wip_label = wip.label.clone();
-                self.add_wip_block(wip, End); // NOTE: the `End` here is temporary and gets updated
-
-                // Body
-                let saw_unmatched_break = self.last_per_stmt_mut().saw_unmatched_break;
-                let saw_unmatched_case = self.last_per_stmt_mut().saw_unmatched_case;
-                let saw_unmatched_default = self.last_per_stmt_mut().saw_unmatched_default;
-                self.break_labels.push(next_label.clone());
-                self.switch_expr_cases.push(SwitchCases::default());
+                let sections = if translator.tcfg.switch_to_match {
+                    match switch_match::switch_sections(&translator.ast_context, switch_body) {
+                        Ok(sections) => Some(sections),
+                        Err(reason) => {
+                            let loc = translator
+                                .ast_context
+                                .display_loc(&translator.ast_context[stmt_id].loc);
+                            diag!(
+                                Diagnostic::SwitchCfg,
+                                "{}: translating switch through the CFG because {}",
+                                loc.map_or("unknown location".to_string(), |l| l.to_string()),
+                                reason,
+                            );
+                            None
+                        }
+                    }
+                } else {
+                    None
+                };
 
-                let body_stuff = self.convert_stmt_help(
-                    translator,
-                    ctx,
-                    switch_body,
-                    in_tail.clone(),
-                    body_label,
-                )?;
-                if let Some(body_end) = body_stuff {
-                    let body_wip = self.new_wip_block(body_end);
-                    self.add_wip_block(body_wip, Jump(next_label.clone()));
-                }
+                if let Some(sections) = sections {
+                    let match_expr =
+                        switch_match::convert_switch_match(translator, ctx, val, &sections)?;
+                    wip.push_stmt(mk().expr_stmt(match_expr));
+                    Ok(Some(wip))
+                } else {
+                    let next_label = self.fresh_label();
+                    let body_label = self.fresh_label();
 
-                self.last_per_stmt_mut().saw_unmatched_break = saw_unmatched_break;
-                self.last_per_stmt_mut().saw_unmatched_case = saw_unmatched_case;
-                self.last_per_stmt_mut().saw_unmatched_default = saw_unmatched_default;
-                self.break_labels.pop();
-                let switch_case = self
-                    .switch_expr_cases
-                    .pop()
-                    .expect("No 'SwitchCases' to pop");
+                    let wip_label = wip.label.clone();
+                    self.add_wip_block(wip, End); // NOTE: the `End` here is temporary and gets updated
 
-                let mut cases: Vec<_> = switch_case.cases.clone();
-                cases.push((
-                    mk().wild_pat(),
-                    switch_case.default.unwrap_or_else(|| next_label.clone()),
-                ));
+                    // Body
+                    let saw_unmatched_break = self.last_per_stmt_mut().saw_unmatched_break;
+                    let saw_unmatched_case = self.last_per_stmt_mut().saw_unmatched_case;
+                    let saw_unmatched_default = self.last_per_stmt_mut().saw_unmatched_default;
+                    self.break_labels.push(next_label.clone());
+                    self.switch_expr_cases.push(SwitchCases::default());
 
-                // Add the condition basic block terminator (we need the information built up during
-                // the conversion of the body to make the right terminator)
-                self.update_terminator(wip_label, Switch { expr: val, cases });
+                    let body_stuff = self.convert_stmt_help(
+                        translator,
+                        ctx,
+                        switch_body,
+                        in_tail.clone(),
+                        body_label,
+                    )?;
+                    if let Some(body_end) = body_stuff {
+                        let body_wip = self.new_wip_block(body_end);
+                        self.add_wip_block(body_wip, Jump(next_label.clone()));
+                    }
 
-                // Return
-                Ok(Some(self.new_wip_block(next_label)))
+                    self.last_per_stmt_mut().saw_unmatched_break = saw_unmatched_break;
+                    self.last_per_stmt_mut().saw_unmatched_case = saw_unmatched_case;
+                    self.last_per_stmt_mut().saw_unmatched_default = saw_unmatched_default;
+                    self.break_labels.pop();
+                    let switch_case = self
+                        .switch_expr_cases
+                        .pop()
+                        .expect("No 'SwitchCases' to pop");
+
+                    let mut cases: Vec<_> = switch_case.cases.clone();
+                    cases.push((
+                        mk().wild_pat(),
+                        switch_case.default.unwrap_or_else(|| next_label.clone()),
+                    ));
+
+                    // Add the condition basic block terminator (we need the information built up during
+                    // the conversion of the body to make the right terminator)
+                    self.update_terminator(wip_label, Switch { expr: val, cases });
+
+                    // Return
+                    Ok(Some(self.new_wip_block(next_label)))
+                }
             }
 
             CStmtKind::Asm {
                 is_volatile,
+                is_goto,
                 ref asm,
                 ref inputs,
                 ref outputs,
                 ref clobbers,
             } => {
+                let loc = translator
+                    .ast_context
+                    .display_loc(&translator.ast_context[stmt_id].loc);
                 wip.extend(translator.convert_asm(
                     ctx,
                     Span::call_site(),
+                    loc,
                     is_volatile,
+                    is_goto,
                     asm,
                     inputs,
                     outputs,
@@ -0,0 +1,308 @@
+//! Direct translation of `switch` statements into `match` expressions
+//! (`--switch-to-match`).
+//!
+//! Going through the CFG and relooper, a `switch` whose cases fall through
+//! into one another is often translated into nested loops and assignments to
+//! `current_block`. When the cases of a `switch` do not fall through, or only
+//! fall through into the next case, which ends the `switch`, we instead
+//! translate each case directly into an arm of a `match` on the scrutinee,
+//! copying the body of the next case into the arms that fall through into it.
+//! Only the bodies of the arms are relooped, each on its own.
+
+use super::*;
+use crate::c_ast::iterators::immediate_children_all_types;
+
+/// The statements of a `switch` body from one or more `case` or `default`
+/// labels up to the next label.
+pub struct Section {
+    /// The `case` labels of the section, with `None` for `default`
+    labels: Vec<Option<CStmtId>>,
+    /// The statements of the section, without the trailing `break`
+    stmts: Vec<CStmtId>,
+    /// Whether the section ends with a `break` or `return` rather than falling
+    /// through into the next section
+    terminates: bool,
+}
+
+/// Split the body of a `switch` into sections that can be translated into the
+/// arms of a `match`, or give the reason they cannot.
+pub fn switch_sections(
+    ast_context: &TypedAstContext,
+    body: CStmtId,
+) -> Result<Vec<Section>, String> {
+    let body_stmts = match ast_context[body].kind {
+        CStmtKind::Compound(ref stmts) => stmts,
+        _ => return Err("its body is not a compound statement".to_owned()),
+    };
+
+    let mut sections: Vec<Section> = vec![];
+    for &stmt_id in body_stmts {
+        let mut labels = vec![];
+        let mut stmt_id = stmt_id;
+        loop {
+            stmt_id = match ast_context[stmt_id].kind {
+                CStmtKind::Case(_, sub_stmt, _) => {
+                    labels.push(Some(stmt_id));
+                    sub_stmt
+                }
+                CStmtKind::Default(sub_stmt) => {
+                    labels.push(None);
+                    sub_stmt
+                }
+                _ => break,
+            };
+        }
+        if !labels.is_empty() {
+            sections.push(Section {
+                labels,
+                stmts: vec![],
+                terminates: false,
+            });
+        }
+        let section = sections
+            .last_mut()
+            .ok_or_else(|| "it has statements before its first case".to_owned())?;
+        section.stmts.push(stmt_id);
+    }
+
+    for section in &mut sections {
+        // The block of a case often ends with its `break`
+        while let Some(&last) = section.stmts.last() {
+            match ast_context[last].kind {
+                CStmtKind::Compound(ref stmts) => {
+                    section.stmts.pop();
+                    section.stmts.extend(stmts);
+                }
+                CStmtKind::Empty => {
+                    section.stmts.pop();
+                }
+                // `__attribute__((fallthrough))`
+                CStmtKind::Attributed { substatement, .. }
+                    if matches!(ast_context[substatement].kind, CStmtKind::Empty) =>
+                {
+                    section.stmts.pop();
+                }
+                _ => break,
+            }
+        }
+        match section.stmts.last().map(|&last| &ast_context[last].kind) {
+            Some(CStmtKind::Break) => {
+                section.stmts.pop();
+                section.terminates = true;
+            }
+            Some(CStmtKind::Return(_)) => section.terminates = true,
+            _ => {}
+        }
+        for &stmt_id in &section.stmts {
+            check_jumps(ast_context, stmt_id.into(), Nesting::default())?;
+        }
+    }
+
+    for (i, section) in sections.iter().enumerate() {
+        let falls_into = sections.get(i + 1).filter(|_| !section.terminates);
+        if let Some(next) = falls_into {
+            if !next.terminates && i + 2 < sections.len() {
+                return Err("a case falls through more than one case".to_owned());
+            }
+            // The body of the next section is translated again in this arm
+            let next_decls = next
+                .stmts
+                .iter()
+                .flat_map(|&stmt_id| DFExpr::new(ast_context, stmt_id.into()))
+                .any(|id| matches!(id, SomeId::Decl(_)));
+            if next_decls {
+                return Err("a case that is fallen into declares variables".to_owned());
+            }
+        }
+
+        // Variables are declared in the arm of the case declaring them
+        let decls = section
+            .stmts
+            .iter()
+            .filter_map(|&stmt_id| match ast_context[stmt_id].kind {
+                CStmtKind::Decls(ref decls) => Some(decls),
+                _ => None,
+            })
+            .flatten()
+            .collect::<IndexSet<_>>();
+        let used_elsewhere = sections
+            .iter()
+            .enumerate()
+            .filter(|&(j, _)| j != i)
+            .flat_map(|(_, other)| &other.stmts)
+            .flat_map(|&stmt_id| DFExpr::new(ast_context, stmt_id.into()))
+            .filter_map(SomeId::expr)
+            .any(|expr_id| match ast_context[expr_id].kind {
+                CExprKind::DeclRef(_, decl_id, _) => decls.contains(&decl_id),
+                _ => false,
+            });
+        if used_elsewhere {
+            return Err("a variable declared in one case is used in another".to_owned());
+        }
+    }
+
+    Ok(sections)
+}
+
+/// The statements a statement in a case is nested in, which are the targets
+/// of the jumps in it.
+#[derive(Copy, Clone, Default)]
+struct Nesting {
+    in_loop: bool,
+    in_switch: bool,
+}
+
+/// Check that control only leaves `id` through `return`s or by reaching its
+/// end, given the loops and `switch`es it is nested in within its case.
+fn check_jumps(ast_context: &TypedAstContext, id: SomeId, nesting: Nesting) -> Result<(), String> {
+    let stmt_id = match id {
+        SomeId::Stmt(stmt_id) => stmt_id,
+        SomeId::Expr(_) => {
+            // Statement expressions are checked conservatively
+            let jumps = DFExpr::new(ast_context, id)
+                .filter_map(SomeId::stmt)
+                .any(|stmt_id| {
+                    matches!(
+                        ast_context[stmt_id].kind,
+                        CStmtKind::Break
+                            | CStmtKind::Continue
+                            | CStmtKind::Goto(_)
+                            | CStmtKind::Label(_)
+                            | CStmtKind::Case(..)
+                            | CStmtKind::Default(_)
+                    )
+                });
+            if jumps {
+                return Err("a statement expression in a case jumps".to_owned());
+            }
+            return Ok(());
+        }
+        _ => return Ok(()),
+    };
+
+    let mut nesting = nesting;
+    match ast_context[stmt_id].kind {
+        CStmtKind::Goto(_) | CStmtKind::Label(_) => {
+            return Err("a case contains a goto or label".to_owned())
+        }
+        // Labels of the `switch` nested in other statements, as in Duff's
+        // device
+        CStmtKind::Case(..) | CStmtKind::Default(_) if !nesting.in_switch => {
+            return Err("a case label is nested in another statement".to_owned())
+        }
+        CStmtKind::Break if !nesting.in_loop && !nesting.in_switch => {
+            return Err("a case breaks out of the switch before its end".to_owned())
+        }
+        CStmtKind::Continue if !nesting.in_loop => {
+            return Err("a case continues a loop around the switch".to_owned())
+        }
+        CStmtKind::While { .. } | CStmtKind::DoWhile { .. } | CStmtKind::ForLoop { .. } => {
+            nesting.in_loop = true
+        }
+        CStmtKind::Switch { .. } => nesting.in_switch = true,
+        _ => {}
+    }
+    for child in immediate_children_all_types(ast_context, id) {
+        check_jumps(ast_context, child, nesting)?;
+    }
+    Ok(())
+}
+
+/// Translate the `sections` of a `switch` on the already translated `scrutinee`
+/// into a `match`.
+pub fn convert_switch_match(
+    translator: &Translation,
+    ctx: ExprContext,
+    scrutinee: Box<Expr>,
+    sections: &[Section],
+) -> TranslationResult<Box<Expr>> {
+    let mut arms = vec![];
+    let mut default_arm = None;
+    for (i, section) in sections.iter().enumerate() {
+        let mut stmts = section.stmts.clone();
+        if !section.terminates {
+            if let Some(next) = sections.get(i + 1) {
+                stmts.extend(&next.stmts);
+            }
+        }
+
+        let name = format!("<switch-arm_{}>", i);
+        let (graph, store) =
+            Cfg::from_stmts(translator, ctx, &stmts, ImplicitReturnType::MatchArm)?;
+        let arm_stmts = translator.convert_cfg(&name, graph, store, IndexSet::new(), false)?;
+        let arm_body = mk().block_expr(mk().block(arm_stmts));
+
+        // `default` matches everything that is not matched by the other arms
+        if section.labels.contains(&None) {
+            default_arm = Some(mk().arm(mk().wild_pat(), None, arm_body));
+            continue;
+        }
+
+        let mut values = vec![];
+        for &case_id in section.labels.iter().flatten() {
+            if let CStmtKind::Case(case_expr, _, cie) = translator.ast_context[case_id].kind {
+                values.push((convert_case_value(translator, ctx, case_expr, cie)?, cie));
+            }
+        }
+        let mut pats = case_patterns(translator, values)?;
+        let pat = if pats.len() == 1 {
+            pats.pop().unwrap()
+        } else {
+            mk().or_pat(pats)
+        };
+        arms.push(mk().arm(pat, None, arm_body));
+    }
+    arms.push(
+        default_arm.unwrap_or_else(|| {
+            mk().arm(mk().wild_pat(), None, mk().block_expr(mk().block(vec![])))
+        }),
+    );
+
+    Ok(mk().match_expr(scrutinee, arms))
+}
+
+/// Build the patterns matching the values of the `case` labels of a section,
+/// combining runs of at least three consecutive numbers into a range.
+fn case_patterns(
+    translator: &Translation,
+    values: Vec<(Box<Expr>, ConstIntExpr)>,
+) -> TranslationResult<Vec<Pat>> {
+    fn as_i128(cie: ConstIntExpr) -> i128 {
+        match cie {
+            ConstIntExpr::U(n) => n as i128,
+            ConstIntExpr::I(n) => n as i128,
+        }
+    }
+
+    let mut pats = vec![];
+    let mut values = values.into_iter().peekable();
+    while let Some((expr, cie)) = values.next() {
+        // Enum constants are kept by name
+        if matches!(*expr, Expr::Path(..)) {
+            pats.push(mk().lit_pat(expr));
+            continue;
+        }
+        let mut last = cie;
+        let mut len = 1;
+        while let Some(&(ref next_expr, next)) = values.peek() {
+            if matches!(**next_expr, Expr::Path(..)) || as_i128(next) != as_i128(last) + 1 {
+                break;
+            }
+            last = next;
+            len += 1;
+            values.next();
+        }
+        match len {
+            1 => pats.push(mk().lit_pat(expr)),
+            2 => {
+                pats.push(mk().lit_pat(expr));
+                pats.push(mk().lit_pat(translator.convert_constant(last)?));
+            }
+            _ => pats.push(mk().range_pat(
+                translator.convert_constant(cie)?,
+                translator.convert_constant(last)?,
+            )),
+        }
+    }
+    Ok(pats)
+}
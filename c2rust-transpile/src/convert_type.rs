@@ -397,10 +397,72 @@ impl TypeConverter {
 
             CTypeKind::TypeOf(ty) => self.convert(ctxt, ty),
 
+            CTypeKind::Atomic(value_ty) => {
+                let name = Self::atomic_type_name(ctxt, value_ty).ok_or_else(|| {
+                    format_err!(
+                        "Unsupported atomic type {:?}",
+                        ctxt.resolve_type(value_ty.ctype)
+                    )
+                })?;
+                let path = vec!["core", "sync", "atomic"];
+                if name == "AtomicPtr" {
+                    let pointee = match ctxt.resolve_type(value_ty.ctype).kind {
+                        CTypeKind::Pointer(pointee) => pointee,
+                        _ => unreachable!(),
+                    };
+                    let pointee_ty = match ctxt.resolve_type(pointee.ctype).kind {
+                        CTypeKind::Void => mk().path_ty(vec!["libc", "c_void"]),
+                        _ => self.convert(ctxt, pointee.ctype)?,
+                    };
+                    let mut segments: Vec<PathSegment> =
+                        path.into_iter().map(|s| mk().path_segment(s)).collect();
+                    segments.push(
+                        mk().path_segment_with_args(
+                            name,
+                            mk().angle_bracketed_args(vec![pointee_ty]),
+                        ),
+                    );
+                    Ok(mk().path_ty(mk().abs_path(segments)))
+                } else {
+                    let mut path = path;
+                    path.push(name);
+                    Ok(mk().path_ty(mk().abs_path(path)))
+                }
+            }
+
             ref t => Err(format_err!("Unsupported type {:?}", t).into()),
         }
     }
 
+    /// The name of the `core::sync::atomic` type that represents an `_Atomic`
+    /// object holding a `value_ty`, or `None` if Rust has no counterpart.
+    /// Pointers to const or function types have none, since `AtomicPtr` only
+    /// holds `*mut` pointers. The signedness of `char` and the size of `long`
+    /// are those clang reports for the target.
+    pub fn atomic_type_name(ctxt: &TypedAstContext, value_ty: CQualTypeId) -> Option<&'static str> {
+        let target_types = ctxt.target_types;
+        match ctxt.resolve_type(value_ty.ctype).kind {
+            CTypeKind::Bool => Some("AtomicBool"),
+            CTypeKind::Char => atomic_int_name(1, target_types.char_is_signed),
+            CTypeKind::SChar => atomic_int_name(1, true),
+            CTypeKind::UChar => atomic_int_name(1, false),
+            CTypeKind::Short => atomic_int_name(2, true),
+            CTypeKind::UShort => atomic_int_name(2, false),
+            CTypeKind::Int => atomic_int_name(4, true),
+            CTypeKind::UInt => atomic_int_name(4, false),
+            CTypeKind::Long => atomic_int_name(target_types.long_size, true),
+            CTypeKind::ULong => atomic_int_name(target_types.long_size, false),
+            CTypeKind::LongLong => atomic_int_name(8, true),
+            CTypeKind::ULongLong => atomic_int_name(8, false),
+            CTypeKind::Pointer(pointee) => match ctxt.resolve_type(pointee.ctype).kind {
+                _ if pointee.qualifiers.is_const => None,
+                CTypeKind::Function(..) => None,
+                _ => Some("AtomicPtr"),
+            },
+            _ => None,
+        }
+    }
+
     /// Add the given parameters to a K&R function pointer type,
     /// returning a full signature or `None` if the function isn't K&R.
     pub fn knr_function_type_with_parameters(
@@ -454,3 +516,20 @@ impl TypeConverter {
         }
     }
 }
+
+/// The `core::sync::atomic` integer type with the given size in bytes and
+/// signedness.
+fn atomic_int_name(size: u64, signed: bool) -> Option<&'static str> {
+    let name = match (size, signed) {
+        (1, true) => "AtomicI8",
+        (1, false) => "AtomicU8",
+        (2, true) => "AtomicI16",
+        (2, false) => "AtomicU16",
+        (4, true) => "AtomicI32",
+        (4, false) => "AtomicU32",
+        (8, true) => "AtomicI64",
+        (8, false) => "AtomicU64",
+        _ => return None,
+    };
+    Some(name)
+}
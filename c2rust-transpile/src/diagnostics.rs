@@ -12,7 +12,11 @@ use strum_macros::{Display, EnumString};
 use crate::c_ast::{ClangAstParseErrorKind, DisplaySrcSpan};
 use c2rust_ast_exporter::get_clang_major_version;
 
-const DEFAULT_WARNINGS: &[Diagnostic] = &[Diagnostic::ClangAst];
+const DEFAULT_WARNINGS: &[Diagnostic] = &[
+    Diagnostic::ClangAst,
+    Diagnostic::InlineAsm,
+    Diagnostic::HeaderConflict,
+];
 
 #[derive(PartialEq, Eq, Hash, Debug, Display, EnumString, Clone)]
 #[strum(serialize_all = "kebab-case")]
@@ -20,6 +24,9 @@ pub enum Diagnostic {
     All,
     Comments,
     ClangAst,
+    InlineAsm,
+    HeaderConflict,
+    SwitchCfg,
 }
 
 macro_rules! diag {
@@ -7,16 +7,18 @@ pub mod c_ast;
 pub mod cfg;
 mod compile_cmds;
 pub mod convert_type;
+mod naming;
 pub mod renamer;
 pub mod rust_ast;
 pub mod translator;
+mod visibility;
 pub mod with_stmts;
 
 use std::collections::HashSet;
 use std::fs::{self, File};
 use std::io;
 use std::io::prelude::*;
-use std::path::{Path, PathBuf};
+use std::path::{Component, Path, PathBuf};
 use std::process;
 
 use failure::Error;
@@ -30,10 +32,15 @@ use crate::c_ast::*;
 pub use crate::diagnostics::Diagnostic;
 use c2rust_ast_exporter as ast_exporter;
 
-use crate::build_files::{emit_build_files, get_build_dir, CrateConfig};
-use crate::compile_cmds::get_compile_commands;
+use crate::build_files::{
+    emit_build_files, emit_headers_rs, get_build_dir, get_module_path, CrateConfig,
+};
+use crate::compile_cmds::{get_compile_commands, LinkCmd, LinkType};
 use crate::convert_type::RESERVED_NAMES;
-pub use crate::translator::ReplaceMode;
+use crate::naming::NameCase;
+use crate::translator::SharedHeaders;
+pub use crate::translator::{ReplaceMode, SignedOverflow};
+use crate::visibility::{imported_fns, InternalFns, LinkSummary};
 use std::prelude::v1::Vec;
 
 type PragmaVec = Vec<(&'static str, Vec<&'static str>)>;
@@ -54,6 +61,11 @@ pub struct TranspilerConfig {
     pub dump_structures: bool,
     pub verbose: bool,
     pub debug_ast_exporter: bool,
+    /// Save the CBOR AST of each input file to this directory
+    pub dump_cbor: Option<PathBuf>,
+    /// Read the CBOR AST of each input file from this directory instead of
+    /// running clang
+    pub from_cbor: Option<PathBuf>,
 
     // Options that control translation
     pub incremental_relooper: bool,
@@ -73,11 +85,34 @@ pub struct TranspilerConfig {
     pub overwrite_existing: bool,
     pub reduce_type_annotations: bool,
     pub reorganize_definitions: bool,
+    pub dedup_headers: bool,
     pub enabled_warnings: HashSet<Diagnostic>,
     pub emit_no_std: bool,
+    pub emit_no_libc: bool,
     pub output_dir: Option<PathBuf>,
     pub translate_const_macros: bool,
     pub translate_fn_macros: bool,
+    pub translate_printf: bool,
+    pub safe_main: bool,
+    /// Emit `const` assertions checking the layout of translated records
+    /// against clang's
+    pub emit_layout_asserts: bool,
+    /// Only declare the C definitions, for calling them from Rust
+    pub bindings_only: bool,
+    /// Translate functions only used within an executable with the Rust ABI
+    pub infer_visibility: bool,
+    /// Rename items to the Rust naming conventions, keeping their C symbol names
+    pub rust_naming_conventions: bool,
+    /// Translate switches that need no CFG directly into `match`
+    pub switch_to_match: bool,
+    /// Index fixed-size arrays with `get_unchecked` instead of bounds-checked indexing
+    pub unchecked_array_indexing: bool,
+    /// Only make functions using raw pointer parameters `unsafe`, and wrap the
+    /// statements of the others that need it in `unsafe` blocks
+    pub minimize_unsafe: bool,
+    /// How to translate signed integer arithmetic, whose overflow is undefined
+    /// behavior in C
+    pub signed_overflow: SignedOverflow,
     pub disable_refactoring: bool,
     pub preserve_unused_functions: bool,
     pub log_level: log::LevelFilter,
@@ -96,6 +131,11 @@ impl TranspilerConfig {
         get_module_name(file, false, false, false).unwrap()
     }
 
+    /// Whether declarations from headers are translated into a submodule per header.
+    fn headers_in_submodules(&self) -> bool {
+        self.reorganize_definitions || self.dedup_headers
+    }
+
     fn is_binary(&self, file: &Path) -> bool {
         let module_name = Self::binary_name_from_path(file);
         self.binaries.contains(&module_name)
@@ -135,6 +175,7 @@ impl TranspilerConfig {
 pub enum ExternCrate {
     C2RustBitfields,
     C2RustAsmCasts,
+    C2RustPrintf,
     F128,
     NumTraits,
     Memoffset,
@@ -165,6 +206,10 @@ impl From<ExternCrate> for ExternCrateDetails {
         match extern_crate {
             ExternCrate::C2RustBitfields => Self::new("c2rust-bitfields", "0.3", true),
             ExternCrate::C2RustAsmCasts => Self::new("c2rust-asm-casts", "0.2", true),
+            // Published along with the transpiler by `scripts/package.py`
+            ExternCrate::C2RustPrintf => {
+                Self::new("c2rust-printf", env!("CARGO_PKG_VERSION"), false)
+            }
             ExternCrate::F128 => Self::new("f128", "0.2", false),
             ExternCrate::NumTraits => Self::new("num-traits", "0.2", true),
             ExternCrate::Memoffset => Self::new("memoffset", "0.5", true),
@@ -242,12 +287,18 @@ pub fn transpile(tcfg: TranspilerConfig, cc_db: &Path, extra_clang_args: &[&str]
     let mut clang_args: Vec<&str> = clang_args.iter().map(AsRef::as_ref).collect();
     clang_args.extend_from_slice(extra_clang_args);
 
+    let link_summaries = if tcfg.infer_visibility {
+        summarize_links(&tcfg, &lcmds, cc_db, &clang_args)
+    } else {
+        None
+    };
+
     let mut top_level_ccfg = None;
     let mut workspace_members = vec![];
     let mut num_transpiled_files = 0;
     let mut transpiled_modules = Vec::new();
     let build_dir = get_build_dir(&tcfg, cc_db);
-    for lcmd in &lcmds {
+    for (lcmd_idx, lcmd) in lcmds.iter().enumerate() {
         let cmds = &lcmd.cmd_inputs;
         let lcmd_name = lcmd
             .output
@@ -289,6 +340,35 @@ pub fn transpile(tcfg: TranspilerConfig, cc_db: &Path, extra_clang_args: &[&str]
             }
         }
 
+        // Executables are only called from outside through `main`
+        let internal_fns = match link_summaries {
+            Some(ref summaries) if lcmd.r#type == LinkType::Exe && !lcmd.top_level => {
+                let external = summaries
+                    .iter()
+                    .enumerate()
+                    .filter(|&(idx, _)| idx != lcmd_idx)
+                    .flat_map(|(_, units)| imported_fns(units))
+                    .collect();
+                let units = summaries[lcmd_idx].iter().zip(cmds).map(|(summary, cmd)| {
+                    let output_path =
+                        get_output_path(&tcfg, cmd.abs_file(), &ancestor_path, &build_dir);
+                    (summary, get_module_path(&build_dir, &output_path))
+                });
+                let fn_ident = |name: &str| {
+                    let prefix = tcfg.prefix_function_names.as_deref().unwrap_or_default();
+                    let name = format!("{}{}", prefix, name);
+                    if tcfg.rust_naming_conventions {
+                        NameCase::Snake.apply(&name)
+                    } else {
+                        name
+                    }
+                };
+                Some(InternalFns::infer(units, &external, fn_ident))
+            }
+            _ => None,
+        };
+
+        let mut shared_headers = SharedHeaders::new();
         let results = cmds
             .iter()
             .map(|cmd| {
@@ -299,6 +379,12 @@ pub fn transpile(tcfg: TranspilerConfig, cc_db: &Path, extra_clang_args: &[&str]
                     &build_dir,
                     cc_db,
                     &clang_args,
+                    if tcfg.dedup_headers {
+                        Some(&mut shared_headers)
+                    } else {
+                        None
+                    },
+                    internal_fns.as_ref(),
                 )
             })
             .collect::<Vec<TranspileResult>>();
@@ -327,6 +413,12 @@ pub fn transpile(tcfg: TranspilerConfig, cc_db: &Path, extra_clang_args: &[&str]
         pragmas.sort();
         crates.sort();
 
+        if !shared_headers.is_empty() {
+            if let Some(headers_file) = emit_headers_rs(&tcfg, &build_dir, shared_headers) {
+                modules.push(headers_file);
+            }
+        }
+
         transpiled_modules.extend(modules.iter().cloned());
 
         if tcfg.emit_build_files {
@@ -427,6 +519,73 @@ fn reorganize_definitions(
     Ok(())
 }
 
+/// Summarize the functions defined and used by the translation units of each
+/// link target, or return `None` if some of them cannot be parsed.
+fn summarize_links(
+    tcfg: &TranspilerConfig,
+    lcmds: &[LinkCmd],
+    cc_db: &Path,
+    extra_clang_args: &[&str],
+) -> Option<Vec<Vec<LinkSummary>>> {
+    let summaries = lcmds
+        .iter()
+        .map(|lcmd| {
+            lcmd.cmd_inputs
+                .iter()
+                .map(|cmd| {
+                    let input_path = cmd.abs_file();
+                    let cbor = load_ast_cbor(tcfg, &input_path, cc_db, extra_clang_args)?;
+                    let untyped_context = ast_exporter::process_ast_cbor(&cbor).map_err(|e| {
+                        warn!("Error: {}. Skipping {}", e, input_path.display());
+                    })?;
+                    let typed_context = ConversionContext::new(&untyped_context).typed_context;
+                    Ok(LinkSummary::new(&typed_context))
+                })
+                .collect::<Result<Vec<_>, ()>>()
+        })
+        .collect::<Result<Vec<_>, ()>>();
+    if summaries.is_err() {
+        warn!("Not inferring function visibility, as some input files could not be parsed");
+    }
+    summaries.ok()
+}
+
+/// Read the CBOR AST of `input_path`, from a dump if replaying dumps or else
+/// by running clang.
+fn load_ast_cbor(
+    tcfg: &TranspilerConfig,
+    input_path: &Path,
+    cc_db: &Path,
+    extra_clang_args: &[&str],
+) -> Result<Vec<u8>, ()> {
+    // Replayed ASTs do not need the sources
+    if tcfg.from_cbor.is_none() && !input_path.exists() {
+        warn!(
+            "Input C file {} does not exist, skipping!",
+            input_path.display()
+        );
+        return Err(());
+    }
+
+    match &tcfg.from_cbor {
+        Some(dir) => get_cbor_dump_path(dir, input_path, cc_db)
+            .and_then(|dump_path| ast_exporter::read_ast_dump(&dump_path))
+            .map_err(|e| {
+                warn!("Error: {}. Skipping {}", e, input_path.display());
+            }),
+        None => {
+            ast_exporter::get_ast_cbor(input_path, cc_db, extra_clang_args, tcfg.debug_ast_exporter)
+                .map_err(|e| {
+                    warn!(
+                        "Error: {}. Skipping {}; is it well-formed C?",
+                        e,
+                        input_path.display()
+                    );
+                })
+        }
+    }
+}
+
 fn transpile_single(
     tcfg: &TranspilerConfig,
     input_path: PathBuf,
@@ -434,6 +593,8 @@ fn transpile_single(
     build_dir: &Path,
     cc_db: &Path,
     extra_clang_args: &[&str],
+    shared_headers: Option<&mut SharedHeaders>,
+    internal_fns: Option<&InternalFns>,
 ) -> TranspileResult {
     let output_path = get_output_path(tcfg, input_path.clone(), ancestor_path, build_dir);
     if output_path.exists() && !tcfg.overwrite_existing {
@@ -442,31 +603,28 @@ fn transpile_single(
     }
 
     let file = input_path.file_name().unwrap().to_str().unwrap();
-    if !input_path.exists() {
-        warn!(
-            "Input C file {} does not exist, skipping!",
-            input_path.display()
-        );
-        return Err(());
-    }
 
     if tcfg.verbose {
         println!("Additional Clang arguments: {}", extra_clang_args.join(" "));
     }
 
     // Extract the untyped AST from the CBOR file
-    let untyped_context = match ast_exporter::get_untyped_ast(
-        input_path.as_path(),
-        cc_db,
-        extra_clang_args,
-        tcfg.debug_ast_exporter,
-    ) {
+    let cbor = load_ast_cbor(tcfg, &input_path, cc_db, extra_clang_args)?;
+
+    if let Some(dir) = &tcfg.dump_cbor {
+        match get_cbor_dump_path(dir, &input_path, cc_db) {
+            Ok(dump_path) => {
+                if let Err(e) = ast_exporter::write_ast_dump(&cbor, &dump_path) {
+                    warn!("Unable to dump CBOR to {}: {}", dump_path.display(), e);
+                }
+            }
+            Err(e) => warn!("Unable to dump CBOR of {}: {}", input_path.display(), e),
+        }
+    }
+
+    let untyped_context = match ast_exporter::process_ast_cbor(&cbor) {
         Err(e) => {
-            warn!(
-                "Error: {}. Skipping {}; is it well-formed C?",
-                e,
-                input_path.display()
-            );
+            warn!("Error: {}. Skipping {}", e, input_path.display());
             return Err(());
         }
         Ok(cxt) => cxt,
@@ -499,8 +657,13 @@ fn transpile_single(
     }
 
     // Perform the translation
-    let (translated_string, pragmas, crates) =
-        translator::translate(typed_context, tcfg, input_path);
+    let (translated_string, pragmas, crates) = translator::translate(
+        typed_context,
+        tcfg,
+        input_path,
+        shared_headers,
+        internal_fns,
+    );
 
     let mut file = match File::create(&output_path) {
         Ok(file) => file,
@@ -523,6 +686,34 @@ fn transpile_single(
     Ok((output_path, pragmas, crates))
 }
 
+/// Path of the CBOR dump of `input_path` in `dump_dir`. Dumps mirror the
+/// layout of the sources relative to `compile_commands.json`, so that they
+/// can be found again when translating on another machine.
+fn get_cbor_dump_path(dump_dir: &Path, input_path: &Path, cc_db: &Path) -> io::Result<PathBuf> {
+    let cc_db_dir = cc_db.parent().ok_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("{} is not in a directory", cc_db.display()),
+        )
+    })?;
+    // Keep sources outside the project apart, e.g. `dump_dir/usr/src/foo.c.cbor`
+    let rel_path = input_path
+        .strip_prefix(cc_db_dir)
+        .unwrap_or(input_path)
+        .components()
+        .filter(|c| !matches!(c, Component::Prefix(_) | Component::RootDir))
+        .collect::<PathBuf>();
+    if rel_path.file_name().is_none() {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("{} does not name a file", input_path.display()),
+        ));
+    }
+    let mut file_name = rel_path.into_os_string();
+    file_name.push(".cbor");
+    Ok(dump_dir.join(file_name))
+}
+
 fn get_output_path(
     tcfg: &TranspilerConfig,
     mut input_path: PathBuf,
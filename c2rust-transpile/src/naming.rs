@@ -0,0 +1,143 @@
+//! Conversion of C identifiers to the Rust naming conventions
+//! (`--rust-naming-conventions`).
+//!
+//! Items keep their C symbol names through `#[export_name]` and
+//! `#[link_name]`, so renaming them does not change the ABI. Collisions
+//! between the converted names are resolved by the `Renamer` like any other.
+
+use crate::c_ast::CDeclKind;
+
+/// The Rust naming convention for a kind of item.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum NameCase {
+    /// `UpperCamelCase`, for types
+    UpperCamel,
+    /// `snake_case`, for functions, local variables, and fields
+    Snake,
+    /// `SCREAMING_SNAKE_CASE`, for constants and statics
+    ScreamingSnake,
+}
+
+impl NameCase {
+    /// The naming convention for the translation of a declaration, if it is
+    /// named in Rust.
+    pub fn for_decl(kind: &CDeclKind) -> Option<NameCase> {
+        use CDeclKind::*;
+        match *kind {
+            Struct { .. } | Union { .. } | Enum { .. } | Typedef { .. } => {
+                Some(NameCase::UpperCamel)
+            }
+            Function { .. } | Field { .. } => Some(NameCase::Snake),
+            Variable {
+                has_static_duration,
+                has_thread_duration,
+                ..
+            } => Some(if has_static_duration || has_thread_duration {
+                NameCase::ScreamingSnake
+            } else {
+                NameCase::Snake
+            }),
+            EnumConstant { .. } | MacroObject { .. } => Some(NameCase::ScreamingSnake),
+            _ => None,
+        }
+    }
+
+    /// Convert `name` to this case. Names with a leading underscore are
+    /// reserved for the implementation, e.g. `__m128i` or `__va_list_tag`,
+    /// and the translator recognizes some of them, so they are kept as is.
+    pub fn apply(self, name: &str) -> String {
+        let words = split_words(name);
+        if name.starts_with('_') || words.is_empty() {
+            return name.to_owned();
+        }
+
+        match self {
+            NameCase::UpperCamel => {
+                let mut camel = String::with_capacity(name.len());
+                for word in words {
+                    // Keep digits of adjacent words apart, e.g. `V1_2`
+                    let ends_in_digit = camel.ends_with(|c: char| c.is_ascii_digit());
+                    if ends_in_digit && word.starts_with(|c: char| c.is_ascii_digit()) {
+                        camel.push('_');
+                    }
+                    let mut chars = word.chars();
+                    camel.extend(chars.next().map(|c| c.to_ascii_uppercase()));
+                    camel.extend(chars.map(|c| c.to_ascii_lowercase()));
+                }
+                camel
+            }
+            NameCase::Snake => words
+                .iter()
+                .map(|word| word.to_ascii_lowercase())
+                .collect::<Vec<_>>()
+                .join("_"),
+            NameCase::ScreamingSnake => words
+                .iter()
+                .map(|word| word.to_ascii_uppercase())
+                .collect::<Vec<_>>()
+                .join("_"),
+        }
+    }
+}
+
+/// Split an identifier into words at underscores and case changes, keeping
+/// acronyms and trailing digits together: `HTTPRequest_t` is split into
+/// `HTTP`, `Request`, and `t`, and `uint32Value` into `uint32` and `Value`.
+fn split_words(name: &str) -> Vec<&str> {
+    let mut words = vec![];
+    for part in name.split('_').filter(|part| !part.is_empty()) {
+        let chars = part.char_indices().collect::<Vec<_>>();
+        let mut start = 0;
+        for i in 1..chars.len() {
+            let (idx, cur) = chars[i];
+            let prev = chars[i - 1].1;
+            let next = chars.get(i + 1).map(|&(_, c)| c);
+            let is_boundary = cur.is_ascii_uppercase()
+                && (prev.is_ascii_lowercase()
+                    || prev.is_ascii_digit()
+                    || (prev.is_ascii_uppercase()
+                        && next.map_or(false, |c| c.is_ascii_lowercase())));
+            if is_boundary {
+                words.push(&part[start..idx]);
+                start = idx;
+            }
+        }
+        words.push(&part[start..]);
+    }
+    words
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn upper_camel() {
+        let case = NameCase::UpperCamel;
+        assert_eq!(case.apply("MyStruct_t"), "MyStructT");
+        assert_eq!(case.apply("HTTP_Request"), "HttpRequest");
+        assert_eq!(case.apply("HTTPRequest"), "HttpRequest");
+        assert_eq!(case.apply("list_node"), "ListNode");
+        assert_eq!(case.apply("vec3_2"), "Vec3_2");
+        assert_eq!(case.apply("__va_list_tag"), "__va_list_tag");
+    }
+
+    #[test]
+    fn snake() {
+        let case = NameCase::Snake;
+        assert_eq!(case.apply("parseHTTPRequest"), "parse_http_request");
+        assert_eq!(case.apply("GetValue"), "get_value");
+        assert_eq!(case.apply("uint32Value"), "uint32_value");
+        assert_eq!(case.apply("already_snake"), "already_snake");
+        assert_eq!(case.apply("MAX__LEN"), "max_len");
+        assert_eq!(case.apply("_mm_add_ps"), "_mm_add_ps");
+    }
+
+    #[test]
+    fn screaming_snake() {
+        let case = NameCase::ScreamingSnake;
+        assert_eq!(case.apply("maxCount"), "MAX_COUNT");
+        assert_eq!(case.apply("ColorRed"), "COLOR_RED");
+        assert_eq!(case.apply("BUFSIZ"), "BUFSIZ");
+    }
+}
@@ -47,6 +47,11 @@ impl PathedMultiImports {
         self.0.entry(path).or_insert(MultiImport::new())
     }
 
+    /// Iterate over the imported paths and the names imported from each.
+    pub fn iter(&self) -> impl Iterator<Item = (&Vec<String>, &IndexSet<String>)> {
+        self.0.iter().map(|(path, import)| (path, &import.leaves))
+    }
+
     pub fn into_items(self) -> Vec<Box<Item>> {
         fn build_items((mut path, imports): (Vec<String>, MultiImport)) -> Box<Item> {
             let mut leaves = imports.leaves;
@@ -79,6 +84,18 @@ impl ItemStore {
         Self::default()
     }
 
+    pub fn items(&self) -> &[Box<Item>] {
+        &self.items
+    }
+
+    pub fn foreign_items(&self) -> &[ForeignItem] {
+        &self.foreign_items
+    }
+
+    pub fn uses(&self) -> &PathedMultiImports {
+        &self.uses
+    }
+
     pub fn add_item(&mut self, item: Box<Item>) {
         self.items.push(item);
     }
@@ -1,20 +1,24 @@
 #![deny(missing_docs)]
 //! This module provides basic support for converting inline assembly statements.
 
-use crate::diagnostics::TranslationResult;
+use crate::convert_type::RESERVED_NAMES;
+use crate::diagnostics::{diag, Diagnostic, TranslationResult};
 
 use super::*;
-use log::warn;
+use colored::Colorize;
+use log::{info, warn};
 use proc_macro2::{TokenStream, TokenTree};
 use syn::__private::ToTokens;
 
 /// An argument direction specifier for a Rust asm! expression
+#[derive(Copy, Clone, PartialEq)]
 enum ArgDirSpec {
     In,
     Out,
     InOut,
     LateOut,
     InLateOut,
+    Const,
 }
 
 impl ToString for ArgDirSpec {
@@ -26,6 +30,7 @@ impl ToString for ArgDirSpec {
             InOut => "inout",
             LateOut => "lateout",
             InLateOut => "inlateout",
+            Const => "const",
         }
         .to_owned()
     }
@@ -40,6 +45,7 @@ impl ArgDirSpec {
             InOut => InOut,
             LateOut => InLateOut,
             InLateOut => InLateOut,
+            Const => Const,
         }
     }
 }
@@ -145,10 +151,10 @@ fn parse_constraints(
         "r" => {
             constraints = "reg".into();
         }
-        "i" => {
-            // Rust inline assembly has no constraint for that, but uses the argument as an
-            // immediate value anyway
-            constraints = "reg".into();
+        "i" | "n" => {
+            // Immediates become `const` operands. The caller falls back to a
+            // register if the operand turns out not to be an integer constant.
+            return Ok((Const, false, String::new()));
         }
         _ => {
             let is_explicit_reg = constraints.starts_with('"');
@@ -156,19 +162,13 @@ fn parse_constraints(
 
             if !(is_explicit_reg || is_tied) {
                 // Attempt to parse machine-specific constraints
+                // Unrecognized constraints are passed through unchanged and
+                // reported by `convert_asm`
                 if let Some((machine_constraints, is_mem)) =
                     translate_machine_constraint(&constraints, arch)
                 {
                     constraints = machine_constraints.into();
                     mem_only = is_mem;
-                } else {
-                    warn!(
-                        "Did not recognize inline asm constraint: {}\n\
-                    It is likely that this will cause compilation errors or \
-                    incorrect semantics in the translated program; please \
-                    manually correct.",
-                        constraints
-                    );
                 }
             }
         }
@@ -192,6 +192,31 @@ fn is_regname_or_int(parsed_constraint: &str) -> bool {
     parsed_constraint.contains('"') || parsed_constraint.starts_with(|c: char| c.is_ascii_digit())
 }
 
+/// Return whether a constraint produced by `parse_constraints` is something
+/// rustc will accept: a register class, an explicit register, or a tied operand.
+fn is_known_constraint(parsed_constraint: &str) -> bool {
+    const REG_CLASSES: &[&str] = &[
+        "reg",
+        "reg_abcd",
+        "reg_byte",
+        "x87_reg",
+        "xmm_reg",
+        "mmx_reg",
+        "zmm_reg",
+        "kreg",
+        "vreg",
+        "vreg_low16",
+        "preg",
+        "sreg",
+        "sreg_low16",
+        "dreg",
+        "freg",
+    ];
+    parsed_constraint.is_empty()
+        || is_regname_or_int(parsed_constraint)
+        || REG_CLASSES.contains(&parsed_constraint)
+}
+
 /// Translate an architecture-specific assembly constraint from llvm/gcc
 /// to those accepted by the Rust asm! macro. "Simple" (arch-independent)
 /// constraints are handled in `parse_constraints`, not here.
@@ -280,25 +305,30 @@ fn translate_machine_constraint(constraint: &str, arch: Arch) -> Option<(&str, b
 
 /// Translate a template modifier from llvm/gcc asm template argument modifiers
 /// to those accepted by the Rust asm! macro. This is arch-dependent, so we need
-/// to know which architecture the asm targets.
+/// to know which architecture the asm targets. `Ok(None)` means the modifier
+/// is implied by the way we emit the operand, and `Err(())` means it has no
+/// Rust equivalent.
 /// See <https://doc.rust-lang.org/nightly/reference/inline-assembly.html#template-modifiers>
-fn translate_modifier(modifier: char, arch: Arch) -> Option<char> {
-    Some(match arch {
+fn translate_modifier(modifier: char, arch: Arch, is_const: bool) -> Result<Option<char>, ()> {
+    Ok(Some(match arch {
         Arch::X86OrX86_64 => match modifier {
             'k' => 'e',
             'q' => 'r',
             'b' => 'l',
             'h' => 'h',
             'w' => 'x',
-            _ => return None,
+            // Print a constant without the `$` prefix; `const` operands are
+            // always emitted that way.
+            'c' | 'P' if is_const => return Ok(None),
+            _ => return Err(()),
         },
         Arch::Aarch64 => modifier,
         Arch::Arm => match modifier {
-            'p' | 'q' => return None,
+            'p' | 'q' => return Err(()),
             _ => modifier,
         },
         Arch::Riscv => modifier,
-    })
+    }))
 }
 
 /// Rust-native asm! operands, which may be inputs, outputs, or both.
@@ -315,10 +345,46 @@ struct BidirAsmOperand {
 impl BidirAsmOperand {
     /// Return whether an operand is positional (as opposed to named or using an explicit register)
     fn is_positional(&self) -> bool {
-        !self.constraints.contains('"') && self.name.is_none()
+        !self.is_explicit_reg() && self.name.is_none()
+    }
+
+    /// Return whether an operand is constrained to an explicit register
+    fn is_explicit_reg(&self) -> bool {
+        self.constraints.contains('"')
+    }
+
+    /// Rank operands in the order `asm!` requires them: positional operands,
+    /// then named operands, then explicit registers.
+    fn order_key(&self) -> u8 {
+        if self.is_explicit_reg() {
+            2
+        } else if self.name.is_some() {
+            1
+        } else {
+            0
+        }
     }
 }
 
+/// Return whether a GCC symbolic operand name can be reused as an `asm!`
+/// operand name.
+fn is_valid_operand_name(name: &str) -> bool {
+    name.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_')
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+        && !RESERVED_NAMES.contains(&name)
+        && ![
+            "clobber_abi",
+            "inlateout",
+            "inout",
+            "lateout",
+            "options",
+            "out",
+            "sym",
+        ]
+        .contains(&name)
+        && !name.starts_with("restmp")
+}
+
 /// Return the register and corresponding template modifiers if the constraint
 /// uses a reserved register.
 fn reg_is_reserved(constraint: &str, arch: Arch) -> Option<(&str, &str)> {
@@ -348,7 +414,8 @@ fn reg_is_reserved(constraint: &str, arch: Arch) -> Option<(&str, &str)> {
 /// between the reserved register and the (suitably modified, e.g. `{0:x}`)
 /// operand.
 /// This also requires reordering the operands because we convert them to
-/// named operands, which must precede explicit register operands.
+/// named operands, which must precede explicit register operands. The
+/// operands are left in the order `asm!` expects.
 ///
 /// Modifies operands and returns a pair of prefix and suffix strings that
 /// should be appended to the assembly template.
@@ -360,15 +427,10 @@ fn rewrite_reserved_reg_operands(
     let (mut prolog, mut epilog) = (String::new(), String::new());
 
     let mut rewrite_idxs = vec![];
-    let mut total_positional = 0;
 
-    // Determine which operands must be rewritten and how many
-    // positional operands there are. Positional operands must precede named
-    // operands, so this tells us where to reinsert operands we rewrite.
+    // Determine which operands must be rewritten
     for (i, operand) in operands.iter().enumerate() {
-        if operand.is_positional() {
-            total_positional += 1;
-        } else if let Some((reg, mods)) = reg_is_reserved(&operand.constraints, arch) {
+        if let Some((reg, mods)) = reg_is_reserved(&operand.constraints, arch) {
             rewrite_idxs.push((i, reg.to_owned(), mods.to_owned()));
         }
     }
@@ -380,7 +442,7 @@ fn rewrite_reserved_reg_operands(
             let move_input = if att_syntax {
                 format!("mov %{}, {{{}:{}}}\n", reg, name, mods)
             } else {
-                format!("mov {{{}:{}}}\n, {}", name, mods, reg)
+                format!("mov {{{}:{}}}, {}\n", name, mods, reg)
             };
             prolog.push_str(&move_input);
         }
@@ -394,16 +456,12 @@ fn rewrite_reserved_reg_operands(
         }
         operand.constraints = "reg".into();
         operand.name = Some(name);
-
-        // Move operand to after all positional arguments. This does not
-        // interfere with moving subsequent operands that use reserved registers
-        // because explicit register operands must all come after positional
-        // and named operands.
-        //let (positional, named, explicit) = split_operands(operands);
-        let nth_non_positional = total_positional + n_moved;
-        operands.swap(idx, nth_non_positional);
     }
 
+    // Named operands must follow positional ones and precede explicit
+    // registers; the sort is stable, so relative order is otherwise kept.
+    operands.sort_by_key(BidirAsmOperand::order_key);
+
     (prolog, epilog)
 }
 
@@ -480,151 +538,540 @@ fn asm_is_att_syntax(asm: &str) -> bool {
     }
 }
 
-/// If applicable, maps the index of an input operand to the
-/// output operand it is tied to.
-///
-/// Utilizes the fact that GNU inline assembly specifies all
-/// operands in a particular order of [output1, ... outputN]
-/// followed by [input1, ..., inputN] where the indices for all
-/// input operands are indexed relative to the size of the
-/// output operand sequence.
-fn map_input_op_idx(
-    idx: usize,
-    num_output_operands: usize,
-    tied_operands: &HashMap<(usize, bool), usize>,
-) -> usize {
-    if let Some(adj_idx) = idx.checked_sub(num_output_operands) {
-        // will only be Some(idx) if it was an input operand
-        match tied_operands.get(&(adj_idx, false)) {
-            Some(&out_idx) => {
-                return out_idx;
+/// A piece of an LLVM inline assembly template, as produced by clang from
+/// the GCC template (where `%0` has become `$0`, `%k0` has become `${0:k}`,
+/// and `%[name]` has been resolved to the operand's index).
+#[derive(Debug, PartialEq)]
+enum TemplatePiece {
+    /// Literal assembly text
+    Text(String),
+    /// A reference to an operand by its GCC index, with an optional modifier
+    Operand { idx: usize, modifier: Option<char> },
+    /// A reference to one of the labels of an `asm goto` statement (`%l0`)
+    Label(usize),
+    /// A number unique to each instance of the statement (`%=`)
+    Uid,
+}
+
+/// Split an LLVM inline assembly template into text and operand references.
+fn parse_template(asm: &str) -> TranslationResult<Vec<TemplatePiece>> {
+    let parse_error = |reference: &str| {
+        Err(TranslationError::new(
+            None,
+            failure::err_msg(
+                "Inline assembly operand reference could not be parsed: $".to_owned() + reference,
+            )
+            .context(TranslationErrorKind::Generic),
+        ))
+    };
+
+    let mut pieces = vec![];
+    let mut text = String::new();
+    let mut rest = asm;
+    while let Some(dollar) = rest.find('$') {
+        text.push_str(&rest[..dollar]);
+        rest = &rest[dollar + 1..];
+
+        let reference = if rest.starts_with('$') {
+            // `$$` is a literal `$`
+            text.push('$');
+            rest = &rest[1..];
+            continue;
+        } else if rest.starts_with(|c: char| c.is_ascii_digit()) {
+            let end = rest
+                .find(|c: char| !c.is_ascii_digit())
+                .unwrap_or(rest.len());
+            let (idx, tail) = rest.split_at(end);
+            rest = tail;
+            TemplatePiece::Operand {
+                idx: idx.parse().unwrap(),
+                modifier: None,
             }
-            None => {
-                // get number of tied inputs before this index
-                // TODO: can calculate this once before the call, not once for each input
-                let num_tied_before = tied_operands
-                    .keys()
-                    .filter(|&&(iidx, is_out)| !is_out && iidx < adj_idx)
-                    .count();
-                // shift the original index by the number of tied input operands prior to the current one
-                return idx - num_tied_before;
+        } else if let Some(braced) = rest.strip_prefix('{') {
+            let end = match braced.find('}') {
+                Some(end) => end,
+                None => return parse_error(rest),
+            };
+            let (body, tail) = (&braced[..end], &braced[end + 1..]);
+            rest = tail;
+            let (idx, modifiers) = body.split_once(':').unwrap_or((body, ""));
+            match (idx, modifiers) {
+                ("", "uid") => TemplatePiece::Uid,
+                (idx, modifiers) => {
+                    let idx = match idx.parse() {
+                        Ok(idx) => idx,
+                        Err(_) => return parse_error(body),
+                    };
+                    let mut modifiers = modifiers.chars();
+                    match (modifiers.next(), modifiers.next()) {
+                        (Some('l'), None) => TemplatePiece::Label(idx),
+                        (modifier, None) => TemplatePiece::Operand { idx, modifier },
+                        _ => return parse_error(body),
+                    }
+                }
             }
+        } else {
+            // Not an operand reference; pass it through unchanged
+            text.push('$');
+            continue;
         };
+
+        if !text.is_empty() {
+            pieces.push(TemplatePiece::Text(std::mem::take(&mut text)));
+        }
+        pieces.push(reference);
+    }
+    text.push_str(rest);
+    if !text.is_empty() {
+        pieces.push(TemplatePiece::Text(text));
     }
+    Ok(pieces)
+}
 
-    idx
+/// How a GCC operand is referred to from the `asm!` template.
+struct OperandRef {
+    /// The positional index or name of the `asm!` operand, or `None` for
+    /// explicit registers, which cannot appear in the template.
+    reference: Option<String>,
+    mem_only: bool,
+    is_const: bool,
 }
 
-/// Rewrite a LLVM inline assembly template string into an asm!-compatible one
-/// by translating its references to operands (of the form $0 or $x0) to {0} or
-/// {0:y} (and wrapping mem-only references in square brackets).
-fn rewrite_asm<F: Fn(&str) -> bool, M: Fn(usize) -> usize>(
-    asm: &str,
-    input_op_mapper: M,
-    is_mem_only: F,
+/// Render parsed template pieces as an `asm!` template string, looking up
+/// each GCC operand in `operands`. Constructs that cannot be translated
+/// faithfully are described in `issues`.
+fn render_template(
+    pieces: &[TemplatePiece],
+    operands: &HashMap<usize, OperandRef>,
     arch: Arch,
-) -> TranslationResult<String> {
-    let mut out = String::with_capacity(asm.len());
+    att_syntax: bool,
+    issues: &mut Vec<String>,
+) -> String {
+    let mut out = String::new();
+    for piece in pieces {
+        let (idx, modifier) = match *piece {
+            TemplatePiece::Text(ref text) => {
+                // Braces are format placeholders in `asm!` templates
+                out.push_str(&text.replace('{', "{{").replace('}', "}}"));
+                continue;
+            }
+            TemplatePiece::Label(idx) => {
+                issues.push(format!("reference to goto label {}", idx));
+                continue;
+            }
+            TemplatePiece::Uid => {
+                issues.push("`%=` has no equivalent in Rust inline assembly".into());
+                out.push('0');
+                continue;
+            }
+            TemplatePiece::Operand { idx, modifier } => (idx, modifier),
+        };
 
-    let mut first = true;
-    let mut last_empty = false;
+        let operand = match operands.get(&idx) {
+            Some(operand) => operand,
+            None => {
+                issues.push(format!("reference to nonexistent operand {}", idx));
+                out.push_str(&format!("{{{}}}", idx));
+                continue;
+            }
+        };
+        let reference = match operand.reference {
+            Some(ref reference) => reference,
+            None => {
+                issues.push(format!(
+                    "operand {} uses an explicit register, which cannot be \
+                     referenced from a Rust asm! template",
+                    idx
+                ));
+                out.push_str(&format!("{{{}}}", idx));
+                continue;
+            }
+        };
 
-    // Iterate over $-prefixed chunks
-    for chunk in asm.split('$') {
-        // No modification needed for first chunk
-        if first {
-            first = false;
-            out.push_str(chunk);
-            continue;
+        let mut new_modifier = None;
+        let mut print_bare_const = false;
+        if let Some(modifier) = modifier {
+            match translate_modifier(modifier, arch, operand.is_const) {
+                Ok(Some(new)) => new_modifier = Some(new),
+                Ok(None) => print_bare_const = true,
+                Err(()) => issues.push(format!(
+                    "operand modifier `{}` on operand {} has no Rust equivalent",
+                    modifier, idx
+                )),
+            }
         }
 
-        // Pass-through $$ as one $
-        if last_empty {
-            last_empty = false;
+        // GCC prints immediates with a `$` prefix in AT&T syntax, but Rust
+        // substitutes only the value of a `const` operand.
+        if operand.is_const && att_syntax && !print_bare_const {
             out.push('$');
-            out.push_str(chunk);
-            continue;
         }
+        // Memory operands are passed by address in a register
+        if operand.mem_only {
+            out.push(if att_syntax { '(' } else { '[' });
+        }
+        out.push('{');
+        out.push_str(reference);
+        if let Some(new_modifier) = new_modifier {
+            out.push(':');
+            out.push(new_modifier);
+        }
+        out.push('}');
+        if operand.mem_only {
+            out.push(if att_syntax { ')' } else { ']' });
+        }
+    }
+    out
+}
 
-        // Note empty chunks
-        if chunk.is_empty() {
-            last_empty = true;
-            continue;
+/// Instructions that take an AT&T operand-size suffix (`b`, `w`, `l` or `q`)
+/// which is dropped in Intel syntax.
+const SIZE_SUFFIXED_MNEMONICS: &[&str] = &[
+    "adc", "add", "and", "bsf", "bsr", "bswap", "bt", "btc", "btr", "bts", "call", "cmp",
+    "cmpxchg", "dec", "div", "idiv", "imul", "in", "inc", "jmp", "lea", "lzcnt", "mov", "mul",
+    "neg", "nop", "not", "or", "out", "pop", "popcnt", "push", "rcl", "rcr", "ret", "rol", "ror",
+    "sal", "sar", "sbb", "shl", "shld", "shr", "shrd", "sub", "test", "tzcnt", "xadd", "xchg",
+    "xor",
+];
+
+/// String instructions, whose `l` suffix becomes `d` in Intel syntax.
+const STRING_MNEMONICS: &[&str] = &["cmps", "ins", "lods", "movs", "outs", "scas", "stos"];
+
+/// Condition codes, for recognizing `cmovcc` instructions.
+const CONDITION_CODES: &[&str] = &[
+    "a", "ae", "b", "be", "c", "e", "g", "ge", "l", "le", "na", "nae", "nb", "nbe", "nc", "ne",
+    "ng", "nge", "nl", "nle", "no", "np", "ns", "nz", "o", "p", "pe", "po", "s", "z",
+];
+
+/// Instruction prefixes that may precede the mnemonic on the same line.
+const INSN_PREFIXES: &[&str] = &["lock", "rep", "repe", "repne", "repnz", "repz"];
+
+/// Return the Intel `ptr` size keyword for an AT&T size suffix.
+fn suffix_ptr_size(suffix: char) -> Option<&'static str> {
+    Some(match suffix {
+        'b' => "byte",
+        'w' => "word",
+        'l' => "dword",
+        'q' => "qword",
+        _ => return None,
+    })
+}
+
+/// An x86 instruction operand converted to Intel syntax.
+#[derive(Debug, PartialEq)]
+enum IntelOperand {
+    Reg(String),
+    Imm(String),
+    Mem(String),
+    Label(String),
+}
+
+/// Convert an AT&T `disp(base, index, scale)` memory reference, optionally
+/// prefixed by a segment override, into an Intel address expression.
+fn att_mem_to_intel(operand: &str) -> Result<String, String> {
+    let strip_reg = |reg: &str| reg.trim().trim_start_matches('%').to_owned();
+
+    let (segment, operand) = match operand.split_once(':') {
+        Some((seg, rest)) if seg.starts_with('%') => (Some(strip_reg(seg)), rest),
+        _ => (None, operand),
+    };
+
+    let mut terms = vec![];
+    let disp = if let Some(inner) = operand.strip_suffix(')') {
+        let open = inner
+            .rfind('(')
+            .ok_or_else(|| format!("unbalanced memory operand `{}`", operand))?;
+        let (disp, regs) = (&inner[..open], &inner[open + 1..]);
+        let mut regs = regs.split(',');
+        let base = regs.next().map(strip_reg).unwrap_or_default();
+        let index = regs.next().map(strip_reg).unwrap_or_default();
+        let scale = regs.next().map(str::trim).unwrap_or("1");
+        if regs.next().is_some() {
+            return Err(format!("malformed memory operand `{}`", operand));
+        }
+        if !base.is_empty() {
+            terms.push(base);
+        }
+        if !index.is_empty() {
+            terms.push(if scale == "1" {
+                index
+            } else {
+                format!("{}*{}", index, scale)
+            });
         }
+        disp.trim()
+    } else {
+        operand.trim()
+    };
 
-        // Do not re-wrap ${...}, but do translate modifiers
-        if chunk.starts_with('{') {
-            // Translate operand modifiers ("template modifiers" per Rust)
-            if let Some(end_idx) = chunk.find('}') {
-                let ref_str = &chunk[..end_idx];
-                if let Some(colon_idx) = ref_str.find(':') {
-                    let (before_mods, _modifiers) = ref_str.split_at(colon_idx + 1);
-                    out.push('{');
-                    let idx: usize = before_mods
-                        .trim_matches(|c: char| !c.is_ascii_digit())
-                        .parse()
-                        .map_err(|_| TranslationError::generic("could not parse operand idx"))?;
-                    out.push_str(input_op_mapper(idx).to_string().as_str());
-                    out.push(':');
-                    let modifiers = ref_str[colon_idx + 1..].chars();
-                    for modifier in modifiers {
-                        if let Some(new) = translate_modifier(modifier, arch) {
-                            out.push(new);
-                        }
-                    }
-                    out.push_str(&chunk[end_idx..]);
+    let mut address = terms.join(" + ");
+    if !disp.is_empty() {
+        if address.is_empty() {
+            address.push_str(disp);
+        } else if let Some(negated) = disp.strip_prefix('-') {
+            address.push_str(" - ");
+            address.push_str(negated);
+        } else {
+            address.push_str(" + ");
+            address.push_str(disp);
+        }
+    }
+    let address = format!("[{}]", address);
+    Ok(match segment {
+        Some(segment) => format!("{}:{}", segment, address),
+        None => address,
+    })
+}
+
+/// Convert a single AT&T operand into Intel syntax.
+fn att_operand_to_intel(operand: &str, is_branch: bool) -> Result<IntelOperand, String> {
+    let operand = operand.trim();
+    // Indirect branch targets are marked with `*` in AT&T syntax only
+    let (operand, indirect) = match operand.strip_prefix('*') {
+        Some(operand) => (operand.trim(), true),
+        None => (operand, false),
+    };
+
+    let is_placeholder = |s: &str| s.starts_with('{') && s.ends_with('}') && !s.starts_with("{{");
+
+    if let Some(imm) = operand.strip_prefix('$') {
+        // Numbers and `const` operands are used as-is; symbols need `offset`
+        let numeric = imm.starts_with(|c: char| c.is_ascii_digit() || "-+~(".contains(c))
+            || is_placeholder(imm);
+        return Ok(IntelOperand::Imm(if numeric {
+            imm.to_owned()
+        } else {
+            format!("offset {}", imm)
+        }));
+    }
+    if let Some(reg) = operand.strip_prefix('%') {
+        if !reg.contains(':') {
+            return Ok(IntelOperand::Reg(reg.to_owned()));
+        }
+    }
+    if is_placeholder(operand) {
+        return Ok(IntelOperand::Reg(operand.to_owned()));
+    }
+    if is_branch && !indirect {
+        return Ok(IntelOperand::Label(operand.to_owned()));
+    }
+    if operand.is_empty() {
+        return Err("empty operand".into());
+    }
+    att_mem_to_intel(operand).map(IntelOperand::Mem)
+}
+
+/// Split an operand list on commas that are not nested within parentheses.
+fn split_att_operands(operands: &str) -> Vec<&str> {
+    let mut result = vec![];
+    let mut depth = 0;
+    let mut start = 0;
+    for (i, c) in operands.char_indices() {
+        match c {
+            '(' => depth += 1,
+            ')' => depth -= 1,
+            ',' if depth == 0 => {
+                result.push(&operands[start..i]);
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    let last = operands[start..].trim();
+    if !last.is_empty() || !result.is_empty() {
+        result.push(&operands[start..]);
+    }
+    result
+}
+
+/// Convert an AT&T mnemonic to Intel syntax, returning the new mnemonic
+/// and the operand size implied by its suffix, if any.
+fn att_mnemonic_to_intel(mnemonic: &str, num_operands: usize) -> (String, Option<char>) {
+    if !mnemonic.is_ascii() {
+        return (mnemonic.to_owned(), None);
+    }
+    let renamed = match mnemonic {
+        "cbtw" => Some("cbw"),
+        "cwtl" => Some("cwde"),
+        "cltq" => Some("cdqe"),
+        "cwtd" => Some("cwd"),
+        "cltd" => Some("cdq"),
+        "cqto" => Some("cqo"),
+        "movslq" => Some("movsxd"),
+        _ => None,
+    };
+    if let Some(renamed) = renamed {
+        let size = if mnemonic == "movslq" {
+            Some('l')
+        } else {
+            None
+        };
+        return (renamed.to_owned(), size);
+    }
+
+    // Zero- and sign-extending moves carry both the source and destination
+    // size, e.g. `movzbl`; the source size is the one Intel syntax needs.
+    if num_operands == 2 && mnemonic.len() == 6 {
+        let (base, sizes) = mnemonic.split_at(4);
+        let mut sizes = sizes.chars();
+        let (src, dst) = (sizes.next().unwrap(), sizes.next().unwrap());
+        if (base == "movz" || base == "movs") && "bw".contains(src) && "wlq".contains(dst) {
+            return (format!("{}x", base), Some(src));
+        }
+    }
+
+    let (base, suffix) = mnemonic.split_at(mnemonic.len().saturating_sub(1));
+    let suffix = suffix.chars().next();
+    match suffix {
+        Some(suffix @ ('b' | 'w' | 'l' | 'q')) => {
+            if SIZE_SUFFIXED_MNEMONICS.contains(&base) {
+                return (base.to_owned(), Some(suffix));
+            }
+            if STRING_MNEMONICS.contains(&base) {
+                let suffix = if suffix == 'l' { 'd' } else { suffix };
+                return (format!("{}{}", base, suffix), None);
+            }
+            if let Some(cc) = mnemonic.strip_prefix("cmov") {
+                let cc_without_suffix = &cc[..cc.len() - 1];
+                if !CONDITION_CODES.contains(&cc) && CONDITION_CODES.contains(&cc_without_suffix) {
+                    return (base.to_owned(), Some(suffix));
                 }
-            } else {
-                out.push_str(chunk);
             }
-            continue;
+            (mnemonic.to_owned(), None)
         }
+        _ => (mnemonic.to_owned(), None),
+    }
+}
 
-        // Translate references of the form %k0 or %3, which look like $k0
-        // or $3 in LLVM asm.
-        if chunk.starts_with(|c: char| c.is_ascii_alphanumeric()) {
-            // Find the end of the reference itself (after 'k0' or '3').
-            let end_idx = chunk
-                .find(|c: char| c == ',' || !c.is_ascii_alphanumeric())
-                .unwrap_or(chunk.len());
-            let ref_str = &chunk[..end_idx];
+/// Convert one AT&T instruction (without labels) to Intel syntax.
+fn att_insn_to_intel(insn: &str) -> Result<String, String> {
+    let mut words = insn.trim();
+    let mut prefixes = vec![];
+    loop {
+        let (word, rest) = words.split_once(char::is_whitespace).unwrap_or((words, ""));
+        if INSN_PREFIXES.contains(&word) && !rest.trim().is_empty() {
+            prefixes.push(word);
+            words = rest.trim_start();
+        } else {
+            break;
+        }
+    }
 
-            let index_str;
-            let mut new_modifiers = String::new();
-            // If the ref string starts with a letter, it's a modifier to translate.
-            if let Some(true) = ref_str.chars().next().map(|c| c.is_ascii_alphabetic()) {
-                let (modifiers, index) = ref_str.split_at(1);
+    let (mnemonic, operands) = words.split_once(char::is_whitespace).unwrap_or((words, ""));
+    let operands = split_att_operands(operands);
+    let (new_mnemonic, size) = att_mnemonic_to_intel(mnemonic, operands.len());
 
-                index_str = index;
+    // x87 instructions have operand-order quirks between the two syntaxes
+    if mnemonic.starts_with('f') && !operands.is_empty() {
+        return Err(format!("x87 instruction `{}`", insn.trim()));
+    }
 
-                for modifier in modifiers.chars() {
-                    if let Some(new) = translate_modifier(modifier, arch) {
-                        new_modifiers.push(new);
+    let is_branch = mnemonic.starts_with('j')
+        || mnemonic.starts_with("call")
+        || mnemonic.starts_with("loop")
+        || mnemonic == "xbegin";
+    let mut intel_operands = operands
+        .iter()
+        .map(|operand| att_operand_to_intel(operand, is_branch))
+        .collect::<Result<Vec<_>, _>>()?;
+    intel_operands.reverse();
+
+    let has_reg = intel_operands
+        .iter()
+        .any(|operand| matches!(operand, IntelOperand::Reg(_)));
+    let is_extend = new_mnemonic == "movzx" || new_mnemonic == "movsx" || new_mnemonic == "movsxd";
+    let mut rendered = vec![];
+    for operand in intel_operands {
+        rendered.push(match operand {
+            IntelOperand::Mem(mem) if new_mnemonic != "lea" => {
+                match size.and_then(suffix_ptr_size) {
+                    Some(ptr_size) => format!("{} ptr {}", ptr_size, mem),
+                    None if has_reg && !is_extend => mem,
+                    None => {
+                        return Err(format!(
+                            "operand size of `{}` is ambiguous without a suffix",
+                            insn.trim()
+                        ))
                     }
                 }
-            } else {
-                // Just digits
-                index_str = ref_str;
-            }
-            let mem_only = is_mem_only(index_str);
-            // Push the reference wrapped in {}, or in [{}] if mem-only
-            out.push_str(if mem_only { "[{" } else { "{" });
-            let idx: usize = index_str
-                .parse()
-                .map_err(|_| TranslationError::generic("could not parse operand idx"))?;
-            out.push_str(input_op_mapper(idx).to_string().as_str());
-            if !new_modifiers.is_empty() {
-                out.push(':');
-                out.push_str(&new_modifiers);
-            }
-            out.push_str(if mem_only { "}]" } else { "}" });
-            // Push the rest of the chunk
-            out.push_str(&chunk[end_idx..]);
-            continue;
+            }
+            IntelOperand::Reg(s)
+            | IntelOperand::Imm(s)
+            | IntelOperand::Mem(s)
+            | IntelOperand::Label(s) => s,
+        });
+    }
+
+    let mut out = prefixes.join(" ");
+    if !out.is_empty() {
+        out.push(' ');
+    }
+    out.push_str(&new_mnemonic);
+    if !rendered.is_empty() {
+        out.push(' ');
+        out.push_str(&rendered.join(", "));
+    }
+    Ok(out)
+}
+
+/// Convert an x86 assembly template in AT&T syntax to Intel syntax, the
+/// default for Rust inline assembly. Operand placeholders such as `{0}` or
+/// `{0:e}` are treated as registers, `({0})` as memory, and `${0}` as an
+/// immediate. Returns a description of the first construct that could not be
+/// converted, in which case the caller should keep AT&T syntax.
+fn att_to_intel(asm: &str) -> Result<String, String> {
+    let mut out = String::with_capacity(asm.len());
+    for (line_idx, line) in asm.split('\n').enumerate() {
+        if line_idx > 0 {
+            out.push('\n');
         }
+        // Keep end-of-line comments as they are
+        let (code, comment) = match line.find('#') {
+            Some(idx) => line.split_at(idx),
+            None => (line, ""),
+        };
+        if code.contains("/*") {
+            return Err("C-style comment".into());
+        }
+
+        for (stmt_idx, stmt) in code.split(';').enumerate() {
+            if stmt_idx > 0 {
+                out.push(';');
+            }
+            let indent_len = stmt.len() - stmt.trim_start().len();
+            out.push_str(&stmt[..indent_len]);
+            let mut stmt = &stmt[indent_len..];
+
+            // Labels are the same in both syntaxes
+            while let Some(colon) = stmt.find(':') {
+                let label = &stmt[..colon];
+                if label.is_empty()
+                    || !label
+                        .chars()
+                        .all(|c| c.is_ascii_alphanumeric() || "_.$".contains(c))
+                {
+                    break;
+                }
+                out.push_str(&stmt[..=colon]);
+                let rest = &stmt[colon + 1..];
+                let trimmed = rest.trim_start();
+                out.push_str(&rest[..rest.len() - trimmed.len()]);
+                stmt = trimmed;
+            }
 
-        // We failed to parse this operand reference
-        out.push_str(chunk);
+            let trailing = &stmt[stmt.trim_end().len()..];
+            let stmt = stmt.trim_end();
+            if stmt.starts_with(".att_syntax") || stmt.starts_with(".intel_syntax") {
+                return Err("assembler syntax directive".into());
+            } else if stmt.is_empty() || stmt.starts_with('.') {
+                // Other directives do not depend on the syntax
+                out.push_str(stmt);
+            } else {
+                out.push_str(&att_insn_to_intel(stmt)?);
+            }
+            out.push_str(trailing);
+        }
+        out.push_str(comment);
     }
     Ok(out)
 }
@@ -632,16 +1079,18 @@ fn rewrite_asm<F: Fn(&str) -> bool, M: Fn(usize) -> usize>(
 impl<'c> Translation<'c> {
     /// Convert an inline-assembly statement into one or more Rust statements.
     /// If inline assembly translation is not enabled this will result in an
-    /// error message instead of a conversion. Because the inline assembly syntax
-    /// used in C is different than the one used in Rust (Rust uses the LLVM syntax
-    /// directly) the resulting translated assembly statements will be unlikely to work
-    /// without further manual translation. The translator will properly translate
-    /// the arguments to the assembly statement, however.
+    /// error message instead of a conversion. Operand references, modifiers,
+    /// constraints and clobbers are translated to their `asm!` equivalents, and
+    /// x86 templates are converted from AT&T to Intel syntax where possible.
+    /// Any construct that cannot be translated faithfully is reported through
+    /// the `inline-asm` diagnostic, together with the location of `loc`.
     pub fn convert_asm(
         &self,
         ctx: ExprContext,
         span: Span,
+        loc: Option<DisplaySrcSpan>,
         is_volatile: bool,
+        is_goto: bool,
         asm: &str,
         inputs: &[AsmOperand],
         outputs: &[AsmOperand],
@@ -662,12 +1111,22 @@ impl<'c> Translation<'c> {
             }
         };
 
+        if is_goto {
+            return Err(TranslationError::generic(
+                "Cannot translate `asm goto`: Rust inline assembly cannot jump to labels",
+            )
+            .add_loc(loc));
+        }
+
         self.use_feature("asm");
 
         fn push_expr(tokens: &mut Vec<TokenTree>, expr: Box<Expr>) {
             tokens.extend(expr.to_token_stream());
         }
 
+        // Reasons why the translated assembly may not behave like the original
+        let mut issues: Vec<String> = vec![];
+
         let mut stmts: Vec<Stmt> = vec![];
         let mut post_stmts: Vec<Stmt> = vec![];
         let mut tokens: Vec<TokenTree> = vec![];
@@ -689,35 +1148,7 @@ impl<'c> Translation<'c> {
             }
         }
 
-        let operand_is_mem_only = |operand: &AsmOperand| -> bool {
-            if let Ok((_dir_spec, mem_only, _parsed)) =
-                parse_constraints(&operand.constraints, arch)
-            {
-                mem_only
-            } else {
-                println!("could not parse asm constraints: {}", operand.constraints);
-                false
-            }
-        };
-
-        // Rewrite arg references in assembly template
-        let rewritten_asm = rewrite_asm(
-            asm,
-            |idx: usize| map_input_op_idx(idx, outputs.len(), &tied_operands),
-            |ref_str: &str| {
-                if let Ok(idx) = ref_str.parse::<usize>() {
-                    outputs
-                        .iter()
-                        .chain(inputs.iter())
-                        .nth(idx)
-                        .map(operand_is_mem_only)
-                        .unwrap_or(false)
-                } else {
-                    false
-                }
-            },
-            arch,
-        )?;
+        let pieces = parse_template(asm).map_err(|e| e.add_loc(loc.clone()))?;
 
         // Detect and pair inputs/outputs that constrain themselves to the same register
         let mut inputs_by_register = HashMap::new();
@@ -732,6 +1163,13 @@ impl<'c> Translation<'c> {
             }
         }
 
+        let operand_name = |operand: &AsmOperand| {
+            operand
+                .name
+                .clone()
+                .filter(|name| is_valid_operand_name(name))
+        };
+
         // Convert gcc asm arguments (input and output lists) into a single list
         // of operands with explicit arg dir specs (asm!-style)
 
@@ -742,6 +1180,12 @@ impl<'c> Translation<'c> {
         for (i, output) in outputs.iter().enumerate() {
             match parse_constraints(&output.constraints, arch) {
                 Ok((mut dir_spec, mem_only, parsed)) => {
+                    if !is_known_constraint(&parsed) {
+                        issues.push(format!(
+                            "unrecognized constraint `{}` on output operand {}",
+                            output.constraints, i
+                        ));
+                    }
                     // Add to args list; if a matching in_expr is found, this is
                     // an inout and we remove the output from the outputs list
                     let mut in_expr = inputs_by_register.remove(&parsed);
@@ -749,6 +1193,8 @@ impl<'c> Translation<'c> {
                         // Also check for by-index references to this output
                         in_expr = inputs_by_register.remove(&i.to_string());
                     }
+                    let name = operand_name(output)
+                        .or_else(|| in_expr.as_ref().and_then(|(_, input)| operand_name(input)));
                     // Extract expression
                     let in_expr = in_expr.map(|(i, operand)| (i, operand.expression));
 
@@ -759,14 +1205,16 @@ impl<'c> Translation<'c> {
                     args.push(BidirAsmOperand {
                         dir_spec,
                         mem_only,
-                        name: None,
+                        name,
                         constraints: parsed,
                         in_expr,
                         out_expr: Some((i, output.expression)),
                     });
                 }
                 // Constraint could not be parsed, drop it
-                Err(e) => eprintln!("{}", e),
+                Err(e) => {
+                    issues.push(format!("dropped output operand {}: {}", i, e));
+                }
             }
         }
         // Add unmatched inputs
@@ -774,36 +1222,123 @@ impl<'c> Translation<'c> {
             .into_iter()
             .chain(other_inputs.into_iter())
         {
-            let (dir_spec, mem_only, parsed) = match parse_constraints(&input.constraints, arch) {
-                Ok(x) => x,
-                Err(e) => {
-                    eprintln!("{}", e);
-                    continue;
-                }
-            };
+            let (mut dir_spec, mem_only, mut parsed) =
+                match parse_constraints(&input.constraints, arch) {
+                    Ok(x) => x,
+                    Err(e) => {
+                        issues.push(format!("dropped input operand {}: {}", i, e));
+                        continue;
+                    }
+                };
+            if !is_known_constraint(&parsed) {
+                issues.push(format!(
+                    "unrecognized constraint `{}` on input operand {}",
+                    input.constraints, i
+                ));
+            }
+            if dir_spec == ArgDirSpec::Const && !self.is_integer_constant(input.expression) {
+                // Immediates that are not integer constants, such as
+                // addresses of symbols, are passed in registers instead.
+                issues.push(format!(
+                    "immediate input operand {} is not an integer constant and is \
+                     passed in a register instead",
+                    i
+                ));
+                dir_spec = ArgDirSpec::In;
+                parsed = "reg".into();
+            }
             args.push(BidirAsmOperand {
                 dir_spec,
                 mem_only,
-                name: None,
+                name: operand_name(&input),
                 constraints: parsed,
                 in_expr: Some((i, input.expression)),
                 out_expr: None,
             });
         }
 
-        // Determine whether the assembly is in AT&T syntax
+        // Determine whether the assembly is in AT&T syntax. Render the
+        // template once with placeholder references for this purpose.
         let att_syntax = match arch {
-            Arch::X86OrX86_64 => asm_is_att_syntax(&rewritten_asm),
+            Arch::X86OrX86_64 => {
+                let mut template = String::new();
+                for piece in &pieces {
+                    match piece {
+                        TemplatePiece::Text(text) => template.push_str(text),
+                        _ => template.push_str("{}"),
+                    }
+                }
+                asm_is_att_syntax(&template)
+            }
             _ => false,
         };
 
         // Add workaround for reserved registers (e.g. rbx on x86_64)
         let (prolog, epilog) = rewrite_reserved_reg_operands(att_syntax, arch, &mut args);
+
+        // Determine how each gcc operand is referenced from the template now
+        // that the operands are in their final order
+        let mut operand_refs = HashMap::new();
+        let mut num_positional = 0;
+        for operand in &args {
+            let reference = if operand.is_positional() {
+                num_positional += 1;
+                Some((num_positional - 1).to_string())
+            } else {
+                operand.name.clone()
+            };
+            let gcc_indices = operand
+                .out_expr
+                .map(|(i, _)| i)
+                .into_iter()
+                .chain(operand.in_expr.map(|(i, _)| outputs.len() + i));
+            for gcc_idx in gcc_indices {
+                operand_refs.insert(
+                    gcc_idx,
+                    OperandRef {
+                        reference: reference.clone(),
+                        mem_only: operand.mem_only,
+                        is_const: operand.dir_spec == ArgDirSpec::Const,
+                    },
+                );
+            }
+        }
+
+        let rewritten_asm = render_template(&pieces, &operand_refs, arch, att_syntax, &mut issues);
         let rewritten_asm = prolog + &rewritten_asm + &epilog;
 
+        // Prefer Intel syntax, the Rust default, keeping AT&T syntax only for
+        // templates we cannot convert
+        let (rewritten_asm, att_syntax) = if att_syntax {
+            match att_to_intel(&rewritten_asm) {
+                Ok(intel_asm) => (intel_asm, false),
+                Err(reason) => {
+                    info!(
+                        "Keeping AT&T syntax for inline assembly ({}){}",
+                        reason,
+                        loc.as_ref()
+                            .map(|loc| format!(" at {}", loc))
+                            .unwrap_or_default()
+                    );
+                    (rewritten_asm, true)
+                }
+            }
+        } else {
+            (rewritten_asm, false)
+        };
+
         // Emit assembly template
         push_expr(&mut tokens, mk().lit_expr(rewritten_asm));
 
+        // Memory accessed through operands determines the memory options
+        let reads_mem_operands = args.iter().any(|arg| arg.mem_only && arg.in_expr.is_some());
+        let writes_mem_operands = args
+            .iter()
+            .any(|arg| arg.mem_only && arg.out_expr.is_some());
+        let has_outputs = args
+            .iter()
+            .any(|arg| !arg.mem_only && arg.out_expr.is_some());
+
         // Outputs and Inputs
         let mut operand_renames = HashMap::new();
         for operand in args {
@@ -904,6 +1439,14 @@ impl<'c> Translation<'c> {
 
             // Emit dir_spec(constraint), quoting constraint if needed
             push_expr(&mut tokens, mk().ident_expr(operand.dir_spec.to_string()));
+            if operand.dir_spec == ArgDirSpec::Const {
+                // `const` operands take an expression but no constraint
+                self.use_feature("asm_const");
+                if let Some(in_expr) = in_expr {
+                    push_expr(&mut tokens, in_expr);
+                }
+                continue;
+            }
             let constraints_ident = if is_regname_or_int(&operand.constraints) {
                 mk().lit_expr(operand.constraints.trim_matches('"'))
             } else {
@@ -932,20 +1475,27 @@ impl<'c> Translation<'c> {
             }
         }
 
-        let mut preserves_flags = true;
-        let mut read_only = true;
+        // GCC assumes that x86 inline assembly always clobbers the flags
+        let mut preserves_flags = arch != Arch::X86OrX86_64;
+        let mut clobbers_memory = false;
 
         // Clobbers
         for clobber in clobbers {
             // Process and drop non-register clobbers
-            if clobber == "cc" {
-                preserves_flags = false;
-                continue;
-            };
-            if clobber == "memory" {
-                read_only = false;
-                continue;
-            };
+            match clobber.as_str() {
+                "cc" | "flags" => {
+                    preserves_flags = false;
+                    continue;
+                }
+                "memory" => {
+                    clobbers_memory = true;
+                    continue;
+                }
+                // Rust requires the direction flag to be clear around inline
+                // assembly, and x87 status is not a register operand
+                "dirflag" | "fpsr" if arch == Arch::X86OrX86_64 => continue,
+                _ => {}
+            }
 
             // We must drop clobbers of reserved registers, even though this
             // really means we're misinforming the compiler of what's been
@@ -959,9 +1509,17 @@ impl<'c> Translation<'c> {
                 if at all possible.",
                     clobber
                 );
+                issues.push(format!("dropped clobber of reserved register {}", clobber));
                 continue;
             }
 
+            // Clang calls the top of the x87 stack `st`
+            let clobber = if arch == Arch::X86OrX86_64 && clobber == "st" {
+                "st(0)"
+            } else {
+                clobber.as_str()
+            };
+
             tokens.push(TokenTree::Punct(Punct::new(',', Alone)));
             let result = mk().call_expr(mk().ident_expr("out"), vec![mk().lit_expr(clobber)]);
             push_expr(&mut tokens, result);
@@ -975,13 +1533,28 @@ impl<'c> Translation<'c> {
             if preserves_flags {
                 options.push(mk().ident_expr("preserves_flags"));
             }
-            if !is_volatile {
-                // Pure cannot be applied if we have no outputs
-                if read_only && (outputs.len() + clobbers.len()) > 0 {
-                    options.push(mk().ident_expr("pure"));
-                    options.push(mk().ident_expr("readonly"));
-                }
-                // We never emit [pure, nomem] right now, but it would be nice
+
+            // Without a "memory" clobber, gcc assumes the assembly only
+            // accesses memory through its memory operands
+            let (nomem, readonly) = if clobbers_memory || writes_mem_operands {
+                (false, false)
+            } else if reads_mem_operands {
+                (false, true)
+            } else {
+                (true, false)
+            };
+            if nomem {
+                options.push(mk().ident_expr("nomem"));
+            }
+            if readonly {
+                options.push(mk().ident_expr("readonly"));
+            }
+            // Non-volatile assembly with outputs may be removed or combined
+            // by gcc, which corresponds to `pure`. Rust only allows `pure`
+            // along with `nomem` or `readonly`, so assembly that writes
+            // memory is never `pure`.
+            if !is_volatile && has_outputs && (nomem || readonly) {
+                options.push(mk().ident_expr("pure"));
             }
 
             if att_syntax {
@@ -995,6 +1568,19 @@ impl<'c> Translation<'c> {
             }
         }
 
+        if !issues.is_empty() {
+            diag!(
+                Diagnostic::InlineAsm,
+                "inline assembly may not be translated faithfully:\n{}{}",
+                issues
+                    .iter()
+                    .map(|issue| format!("  - {}\n", issue))
+                    .collect::<String>(),
+                loc.map(|loc| format!("{} {}", "-->".blue(), loc))
+                    .unwrap_or_default(),
+            );
+        }
+
         self.with_cur_file_item_store(|item_store| {
             item_store.add_use(vec!["core".into(), "arch".into()], "asm");
         });
@@ -1013,4 +1599,88 @@ impl<'c> Translation<'c> {
 
         Ok(stmts)
     }
+
+    /// Return whether an expression is an integer constant that can be used
+    /// as a `const` operand to `asm!`.
+    fn is_integer_constant(&self, expr_id: CExprId) -> bool {
+        match *self.ast_context.resolve_expr(expr_id).1 {
+            CExprKind::Literal(_, CLiteral::Integer(..))
+            | CExprKind::Literal(_, CLiteral::Character(..))
+            | CExprKind::ConstantExpr(_, _, Some(_)) => true,
+            CExprKind::DeclRef(_, decl_id, _) => matches!(
+                self.ast_context[decl_id].kind,
+                CDeclKind::EnumConstant { .. }
+            ),
+            _ => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn template_references() {
+        let pieces = parse_template("movl $$5, ${0:k}; add $1, $0 ${:uid}").unwrap();
+        assert_eq!(
+            pieces,
+            vec![
+                TemplatePiece::Text("movl $5, ".into()),
+                TemplatePiece::Operand {
+                    idx: 0,
+                    modifier: Some('k')
+                },
+                TemplatePiece::Text("; add ".into()),
+                TemplatePiece::Operand {
+                    idx: 1,
+                    modifier: None
+                },
+                TemplatePiece::Text(", ".into()),
+                TemplatePiece::Operand {
+                    idx: 0,
+                    modifier: None
+                },
+                TemplatePiece::Text(" ".into()),
+                TemplatePiece::Uid,
+            ]
+        );
+    }
+
+    #[test]
+    fn att_to_intel_operands() {
+        assert_eq!(
+            att_to_intel("leal ({1:e},{1:e},4), {0:e}").unwrap(),
+            "lea {0:e}, [{1:e} + {1:e}*4]"
+        );
+        assert_eq!(
+            att_to_intel("movl $5, ({0})").unwrap(),
+            "mov dword ptr [{0}], 5"
+        );
+        assert_eq!(
+            att_to_intel("movzbl ({1}), {0}").unwrap(),
+            "movzx {0}, byte ptr [{1}]"
+        );
+        assert_eq!(
+            att_to_intel("1: movl -4(%rbp,%rcx,8), %eax # load\n\tjne 1b").unwrap(),
+            "1: mov eax, dword ptr [rbp + rcx*8 - 4] # load\n\tjne 1b"
+        );
+        assert_eq!(
+            att_to_intel("lock; cmpxchgl {2}, ({1})").unwrap(),
+            "lock; cmpxchg dword ptr [{1}], {2}"
+        );
+        assert_eq!(
+            att_to_intel("call *%rax; rep movsl; cltq").unwrap(),
+            "call rax; rep movsd; cdqe"
+        );
+    }
+
+    #[test]
+    fn att_to_intel_failures() {
+        // Memory operands need an explicit size
+        assert!(att_to_intel("inc ({0})").is_err());
+        // x87 operand order differs between syntaxes
+        assert!(att_to_intel("fsubp %st, %st(1)").is_err());
+        assert!(att_to_intel(".att_syntax\nmovl %eax, %ebx").is_err());
+    }
 }
@@ -1,8 +1,66 @@
 use crate::format_translation_err;
 
 use super::*;
+use c2rust_ast_builder::CaptureBy;
 use std::sync::atomic::Ordering;
 
+/// `::core::sync::atomic::Ordering::<order>`
+fn ordering_expr(order: Ordering) -> Box<Expr> {
+    use Ordering::*;
+    let name = match order {
+        SeqCst => "SeqCst",
+        AcqRel => "AcqRel",
+        Acquire => "Acquire",
+        Release => "Release",
+        Relaxed => "Relaxed",
+        _ => unreachable!(
+            "new variants added to `{}`",
+            std::any::type_name::<Ordering>()
+        ),
+    };
+    mk().abs_path_expr(vec!["core", "sync", "atomic", "Ordering", name])
+}
+
+/// The ordering of the load that a read-modify-write with ordering `order`
+/// performs when it fails, which may not include a release.
+fn failure_ordering(order: Ordering) -> Ordering {
+    match order {
+        Ordering::Release => Ordering::Relaxed,
+        Ordering::AcqRel => Ordering::Acquire,
+        order => order,
+    }
+}
+
+/// `|param| body`
+fn closure(param: &str, body: Box<Expr>) -> Box<Expr> {
+    let decl = mk().fn_decl(
+        "closure",
+        vec![mk().arg(mk().infer_ty(), mk().ident_pat(param))],
+        None,
+        ReturnType::Default,
+    );
+    mk().closure_expr(CaptureBy::Ref, Movability::Movable, *decl, body)
+}
+
+/// `obj.fetch_update(order, failure_ordering(order), |old| Some(update(old))).unwrap()`,
+/// which returns the previous value
+fn fetch_update(obj: Box<Expr>, order: Ordering, old: &str, update: Box<Expr>) -> Box<Expr> {
+    let update = closure(old, mk().call_expr(mk().ident_expr("Some"), vec![update]));
+    let args = vec![
+        ordering_expr(order),
+        ordering_expr(failure_ordering(order)),
+        update,
+    ];
+    let fetch = mk().method_call_expr(obj, "fetch_update", args);
+    // The update function never fails
+    mk().method_call_expr(fetch, "unwrap", vec![])
+}
+
+/// A sequentially consistent load of the `_Atomic` object `obj`
+fn seq_cst_load(obj: Box<Expr>) -> Box<Expr> {
+    mk().method_call_expr(obj, "load", vec![ordering_expr(Ordering::SeqCst)])
+}
+
 impl<'c> Translation<'c> {
     fn convert_constant_bool(&self, expr: CExprId) -> Option<bool> {
         let val = self.ast_context.resolve_expr(expr).1;
@@ -13,11 +71,25 @@ impl<'c> Translation<'c> {
     }
 
     fn convert_memordering(&self, expr: CExprId) -> Option<Ordering> {
-        let memorder = &self.ast_context[expr];
-        let i = match memorder.kind {
-            CExprKind::Literal(_, CLiteral::Integer(i, _)) => Some(i),
-            _ => None,
-        }?;
+        // `stdatomic.h` passes the `memory_order` enum constants, whose
+        // values are the `__ATOMIC_*` ones
+        let i = match *self.ast_context.resolve_expr(expr).1 {
+            CExprKind::Literal(_, CLiteral::Integer(i, _)) => i,
+            CExprKind::ConstantExpr(_, _, Some(ConstIntExpr::U(i))) => i,
+            CExprKind::ConstantExpr(_, _, Some(ConstIntExpr::I(i))) => i as u64,
+            CExprKind::DeclRef(_, decl_id, _) => match self.ast_context[decl_id].kind {
+                CDeclKind::EnumConstant {
+                    value: ConstIntExpr::U(i),
+                    ..
+                } => i,
+                CDeclKind::EnumConstant {
+                    value: ConstIntExpr::I(i),
+                    ..
+                } => i as u64,
+                _ => return None,
+            },
+            _ => return None,
+        };
         use Ordering::*;
         let ordering = match i {
             0 => Relaxed,
@@ -42,6 +114,18 @@ impl<'c> Translation<'c> {
         val2_id: Option<CExprId>,
         weak_id: Option<CExprId>,
     ) -> TranslationResult<WithStmts<Box<Expr>>> {
+        if name.starts_with("__c11_atomic") {
+            return self.convert_c11_atomic(
+                ctx,
+                name,
+                ptr_id,
+                order_id,
+                val1_id,
+                order_fail_id,
+                val2_id,
+            );
+        }
+
         let ptr = self.convert_expr(ctx.used(), ptr_id)?;
         let order = self.convert_memordering(order_id);
         let val1 = val1_id
@@ -289,6 +373,517 @@ impl<'c> Translation<'c> {
         }
     }
 
+    /// Translate the `__c11_atomic_*` builtins that `stdatomic.h` expands to.
+    /// These operate on `_Atomic` objects, so they become methods of the
+    /// `core::sync::atomic` types. Memory orders that are not constants are
+    /// strengthened to `SeqCst`.
+    fn convert_c11_atomic(
+        &self,
+        ctx: ExprContext,
+        name: &str,
+        ptr_id: CExprId,
+        order_id: CExprId,
+        val1_id: Option<CExprId>,
+        order_fail_id: Option<CExprId>,
+        val2_id: Option<CExprId>,
+    ) -> TranslationResult<WithStmts<Box<Expr>>> {
+        let value_ty = self.ast_context[ptr_id]
+            .kind
+            .get_qual_type()
+            .and_then(|ty| self.ast_context.get_pointee_qual_type(ty.ctype))
+            .and_then(|ty| self.ast_context.atomic_value_type(ty.ctype))
+            .ok_or_else(|| format_err!("{} must have a pointer to an atomic argument", name))?;
+        let invalid_order = |id: CExprId| {
+            format_translation_err!(
+                self.ast_context.display_loc(&self.ast_context[id].loc),
+                "Invalid memory ordering for {}",
+                name,
+            )
+        };
+
+        let mut stmts = vec![];
+        let mut is_unsafe = false;
+        let mut take = |val: WithStmts<Box<Expr>>| {
+            is_unsafe |= val.is_unsafe();
+            let (val_stmts, val) = val.discard_unsafe();
+            stmts.extend(val_stmts);
+            val
+        };
+        let obj = take(self.convert_atomic_object(ctx, ptr_id)?);
+
+        // `__c11_atomic_init` has no memory order; its value is in the order slot
+        if name == "__c11_atomic_init" {
+            let val = take(self.convert_expr(ctx.used(), order_id)?);
+            let store =
+                mk().method_call_expr(obj, "store", vec![val, ordering_expr(Ordering::Relaxed)]);
+            let mut res = WithStmts::new(stmts, store);
+            res.merge_unsafe(is_unsafe);
+            return self.convert_side_effects_expr(ctx, res, "Builtin is not supposed to be used");
+        }
+
+        let order = self.convert_c11_memordering(order_id);
+        let val1 = val1_id
+            .map(|x| self.convert_expr(ctx.used(), x))
+            .transpose()?
+            .map(&mut take);
+        let val2 = val2_id
+            .map(|x| self.convert_expr(ctx.used(), x))
+            .transpose()?
+            .map(&mut take);
+
+        use Ordering::*;
+        let val = match name {
+            "__c11_atomic_load" => {
+                if let Release | AcqRel = order {
+                    return Err(invalid_order(order_id));
+                }
+                mk().method_call_expr(obj, "load", vec![ordering_expr(order)])
+            }
+
+            "__c11_atomic_store" => {
+                if let Acquire | AcqRel = order {
+                    return Err(invalid_order(order_id));
+                }
+                let val = val1.expect("__c11_atomic_store must have a val argument");
+                mk().method_call_expr(obj, "store", vec![val, ordering_expr(order)])
+            }
+
+            "__c11_atomic_exchange" => {
+                let val = val1.expect("__c11_atomic_exchange must have a val argument");
+                mk().method_call_expr(obj, "swap", vec![val, ordering_expr(order)])
+            }
+
+            "__c11_atomic_compare_exchange_strong" | "__c11_atomic_compare_exchange_weak" => {
+                let expected =
+                    val1.expect("__c11_atomic_compare_exchange must have an expected argument");
+                let desired =
+                    val2.expect("__c11_atomic_compare_exchange must have a desired argument");
+                let order_fail_id =
+                    order_fail_id.expect("__c11_atomic_compare_exchange must have a failure order");
+                let order_fail = self.convert_c11_memordering(order_fail_id);
+                if let Release | AcqRel = order_fail {
+                    return Err(invalid_order(order_fail_id));
+                }
+
+                // `expected` is read, and written back on failure
+                let expected = match *expected {
+                    Expr::Path(..) => expected,
+                    _ => {
+                        let (expected_let, expected) = self.bind_temp(expected);
+                        stmts.push(expected_let);
+                        expected
+                    }
+                };
+                let deref_expected = mk().unary_expr(UnOp::Deref(Default::default()), expected);
+                let method = if name.ends_with("_weak") {
+                    "compare_exchange_weak"
+                } else {
+                    "compare_exchange"
+                };
+                let args = vec![
+                    deref_expected.clone(),
+                    desired,
+                    ordering_expr(order),
+                    ordering_expr(order_fail),
+                ];
+                let res = mk().method_call_expr(obj, method, args);
+
+                // `.map_err(|actual| *expected = actual).is_ok()`
+                let actual = self.renamer.borrow_mut().fresh();
+                let write_back = mk().assign_expr(deref_expected, mk().ident_expr(&actual));
+                let res = mk().method_call_expr(res, "map_err", vec![closure(&actual, write_back)]);
+                mk().method_call_expr(res, "is_ok", vec![])
+            }
+
+            "__c11_atomic_fetch_add" | "__c11_atomic_fetch_sub"
+                if self
+                    .ast_context
+                    .resolve_type(value_ty.ctype)
+                    .kind
+                    .is_pointer() =>
+            {
+                // `AtomicPtr` has no stable arithmetic, so update it with a
+                // pointer offset
+                let val = val1.expect("__c11_atomic_fetch_add must have a val argument");
+                let val = match *val {
+                    Expr::Lit(..) | Expr::Path(..) => val,
+                    _ => {
+                        let (val_let, val) = self.bind_temp(val);
+                        stmts.push(val_let);
+                        val
+                    }
+                };
+                let pointee = self
+                    .ast_context
+                    .get_pointee_qual_type(value_ty.ctype)
+                    .ok_or_else(|| format_err!("bad atomic pointer type"))?;
+                let mul = self.compute_size_of_expr(pointee.ctype);
+                let old = self.renamer.borrow_mut().fresh();
+                let new = pointer_offset(
+                    mk().ident_expr(&old),
+                    val,
+                    mul,
+                    name.ends_with("_sub"),
+                    false,
+                    self.signed_overflow(ctx),
+                );
+                fetch_update(obj, order, &old, new)
+            }
+
+            "__c11_atomic_fetch_add"
+            | "__c11_atomic_fetch_sub"
+            | "__c11_atomic_fetch_and"
+            | "__c11_atomic_fetch_or"
+            | "__c11_atomic_fetch_xor"
+            | "__c11_atomic_fetch_nand"
+            | "__c11_atomic_fetch_max"
+            | "__c11_atomic_fetch_min" => {
+                let method = name.trim_start_matches("__c11_atomic_");
+                let val =
+                    val1.expect("__c11_atomic arithmetic operations must have a val argument");
+                mk().method_call_expr(obj, method, vec![val, ordering_expr(order)])
+            }
+
+            _ => {
+                return Err(format_translation_err!(
+                    self.ast_context.display_loc(&self.ast_context[ptr_id].loc),
+                    "Unsupported atomic builtin {}",
+                    name,
+                ))
+            }
+        };
+
+        let mut res = WithStmts::new(stmts, val);
+        res.merge_unsafe(is_unsafe);
+        self.convert_side_effects_expr(ctx, res, "Builtin is not supposed to be used")
+    }
+
+    /// Translate the memory order of a C11 atomic operation. Orders that are
+    /// only known at runtime are strengthened to `SeqCst`.
+    fn convert_c11_memordering(&self, expr: CExprId) -> Ordering {
+        self.convert_memordering(expr).unwrap_or_else(|| {
+            let loc = self.ast_context.display_loc(&self.ast_context[expr].loc);
+            warn!(
+                "{}memory order is not a constant, using SeqCst",
+                loc.map(|loc| format!("{}: ", loc)).unwrap_or_default(),
+            );
+            Ordering::SeqCst
+        })
+    }
+
+    /// Translate `__c11_atomic_thread_fence` and `__c11_atomic_signal_fence`.
+    pub(crate) fn convert_c11_fence(
+        &self,
+        ctx: ExprContext,
+        name: &str,
+        order_id: CExprId,
+    ) -> TranslationResult<WithStmts<Box<Expr>>> {
+        let order = self.convert_c11_memordering(order_id);
+        // A relaxed fence has no effect, and Rust does not accept one
+        if order == Ordering::Relaxed {
+            return Ok(WithStmts::new_val(mk().tuple_expr(vec![])));
+        }
+        let fence = if name == "__c11_atomic_signal_fence" {
+            "compiler_fence"
+        } else {
+            "fence"
+        };
+        let fence = mk().abs_path_expr(vec!["core", "sync", "atomic", fence]);
+        let call = mk().call_expr(fence, vec![ordering_expr(order)]);
+        self.convert_side_effects_expr(
+            ctx,
+            WithStmts::new_val(call),
+            "Builtin is not supposed to be used",
+        )
+    }
+
+    /// Translate a pointer to an `_Atomic` object into the object itself.
+    fn convert_atomic_object(
+        &self,
+        ctx: ExprContext,
+        ptr: CExprId,
+    ) -> TranslationResult<WithStmts<Box<Expr>>> {
+        let mut expr = ptr;
+        while let CExprKind::Paren(_, inner) = self.ast_context[expr].kind {
+            expr = inner;
+        }
+        // `&x` designates `x` itself
+        if let CExprKind::Unary(_, c_ast::UnOp::AddressOf, arg, _) = self.ast_context[expr].kind {
+            return self.convert_expr(ctx.used(), arg);
+        }
+        Ok(self
+            .convert_expr(ctx.used(), ptr)?
+            .map(|ptr| mk().unary_expr(UnOp::Deref(Default::default()), ptr)))
+    }
+
+    /// `let fresh = val;`, along with a reference to `fresh`
+    fn bind_temp(&self, val: Box<Expr>) -> (Stmt, Box<Expr>) {
+        let name = self.renamer.borrow_mut().fresh();
+        let local = mk().local(mk().ident_pat(&name), None, Some(val));
+        (mk().local_stmt(Box::new(local)), mk().ident_expr(name))
+    }
+
+    /// Whether `expr` designates an `_Atomic` object.
+    pub(crate) fn is_atomic_lvalue(&self, expr: CExprId) -> bool {
+        self.ast_context[expr]
+            .kind
+            .get_qual_type()
+            .map_or(false, |ty| {
+                self.ast_context.atomic_value_type(ty.ctype).is_some()
+            })
+    }
+
+    /// `AtomicX::new(val)`, an object of the `_Atomic` type `atomic_ty`
+    pub(crate) fn atomic_new(
+        &self,
+        atomic_ty: CTypeId,
+        val: Box<Expr>,
+    ) -> TranslationResult<Box<Expr>> {
+        let name = self
+            .ast_context
+            .atomic_value_type(atomic_ty)
+            .and_then(|value_ty| TypeConverter::atomic_type_name(&self.ast_context, value_ty))
+            .ok_or_else(|| {
+                format_err!(
+                    "Unsupported atomic type {:?}",
+                    self.ast_context.resolve_type(atomic_ty)
+                )
+            })?;
+        let new = mk().abs_path_expr(vec!["core", "sync", "atomic", name, "new"]);
+        Ok(mk().call_expr(new, vec![val]))
+    }
+
+    /// Translate the conversion of an `_Atomic` lvalue to an rvalue, which C
+    /// makes a sequentially consistent load. Returns `None` for other casts.
+    pub(crate) fn convert_atomic_read(
+        &self,
+        ctx: ExprContext,
+        kind: CastKind,
+        expr: CExprId,
+    ) -> TranslationResult<Option<WithStmts<Box<Expr>>>> {
+        match kind {
+            // Reading the value out of an atomic object
+            CastKind::AtomicToNonAtomic => match self.ast_context[expr].kind {
+                CExprKind::ImplicitCast(_, lvalue, CastKind::LValueToRValue, _, _) => {
+                    Ok(Some(self.convert_expr(ctx, lvalue)?.map(seq_cst_load)))
+                }
+                _ => Ok(None),
+            },
+
+            // Copying an atomic object into a new one
+            CastKind::LValueToRValue if self.is_atomic_lvalue(expr) => {
+                let atomic_ty = self.ast_context[expr]
+                    .kind
+                    .get_type()
+                    .ok_or_else(|| format_err!("bad atomic type"))?;
+                self.convert_expr(ctx, expr)?
+                    .result_map(|obj| self.atomic_new(atomic_ty, seq_cst_load(obj)))
+                    .map(Some)
+            }
+
+            _ => Ok(None),
+        }
+    }
+
+    /// Translate an assignment or compound assignment to the `_Atomic`
+    /// lvalue `lhs`. C makes these sequentially consistent: assignments
+    /// become `store`s, and compound assignments a single read-modify-write,
+    /// using the matching `fetch_*` method where there is one and
+    /// `fetch_update` otherwise. If `returns_old` is set, the value of the
+    /// expression is the one before the update, as for postfix `++`.
+    pub(crate) fn convert_atomic_assignment(
+        &self,
+        ctx: ExprContext,
+        op: c_ast::BinOp,
+        lhs: CExprId,
+        rhs: WithStmts<Box<Expr>>,
+        rhs_ty: CQualTypeId,
+        compute_ty: Option<CQualTypeId>,
+        compute_res_ty: Option<CQualTypeId>,
+        returns_old: bool,
+    ) -> TranslationResult<WithStmts<Box<Expr>>> {
+        let value_ty = self.ast_context[lhs]
+            .kind
+            .get_qual_type()
+            .and_then(|ty| self.ast_context.atomic_value_type(ty.ctype))
+            .ok_or_else(|| format_err!("bad atomic lhs type"))?;
+        let value_kind = &self.ast_context.resolve_type(value_ty.ctype).kind;
+        let same_type = |a: CQualTypeId, b: CQualTypeId| {
+            self.ast_context.resolve_type_id(a.ctype) == self.ast_context.resolve_type_id(b.ctype)
+        };
+        let msg = "Atomic assignment is not supposed to be used";
+
+        let lhs = self.convert_expr(ctx.used(), lhs)?;
+        let mut rhs = rhs;
+        // Assigning one atomic to another stores the value of the copy
+        if self.ast_context.atomic_value_type(rhs_ty.ctype).is_some() {
+            rhs = rhs.map(|rhs| mk().method_call_expr(rhs, "into_inner", vec![]));
+        }
+
+        lhs.and_then(|lhs| {
+            rhs.and_then(|rhs| {
+                let op = match op.underlying_assignment() {
+                    Some(op) => op,
+                    None if ctx.is_unused() => {
+                        let args = vec![rhs, ordering_expr(Ordering::SeqCst)];
+                        let store = mk().method_call_expr(lhs, "store", args);
+                        return self.convert_side_effects_expr(ctx, WithStmts::new_val(store), msg);
+                    }
+                    None => {
+                        let (rhs_let, rhs) = self.bind_temp(rhs);
+                        let args = vec![rhs.clone(), ordering_expr(Ordering::SeqCst)];
+                        let store = mk().method_call_expr(lhs, "store", args);
+                        return Ok(WithStmts::new(vec![rhs_let, mk().semi_stmt(store)], rhs));
+                    }
+                };
+
+                // The right-hand side may be needed twice
+                let (rhs_let, rhs) = match *rhs {
+                    Expr::Lit(..) => (None, rhs),
+                    _ => {
+                        let (rhs_let, rhs) = self.bind_temp(rhs);
+                        (Some(rhs_let), rhs)
+                    }
+                };
+                let stmts = rhs_let.into_iter().collect::<Vec<_>>();
+                let returns_new = ctx.is_used() && !returns_old;
+
+                let compute_ty = compute_ty.unwrap_or(value_ty);
+                let compute_res_ty = compute_res_ty.unwrap_or(compute_ty);
+                let compute_kind = &self.ast_context.resolve_type(compute_ty.ctype).kind;
+                let is_integral = value_kind.is_integral_type()
+                    && !value_kind.is_bool()
+                    && compute_kind.is_integral_type();
+
+                use c_ast::BinOp::*;
+                let fetch_method = match op {
+                    Add => Some("fetch_add"),
+                    Subtract => Some("fetch_sub"),
+                    BitAnd => Some("fetch_and"),
+                    BitOr => Some("fetch_or"),
+                    BitXor => Some("fetch_xor"),
+                    _ => None,
+                };
+
+                let val = match fetch_method {
+                    // These operations commute with truncation to the value type
+                    Some(method) if is_integral => {
+                        let rhs = if same_type(rhs_ty, value_ty) {
+                            rhs
+                        } else {
+                            mk().cast_expr(rhs, self.convert_type(value_ty.ctype)?)
+                        };
+                        let args = vec![rhs.clone(), ordering_expr(Ordering::SeqCst)];
+                        let old = mk().method_call_expr(lhs, method, args);
+                        if !returns_new {
+                            old
+                        } else {
+                            match op {
+                                Add => mk().method_call_expr(old, "wrapping_add", vec![rhs]),
+                                Subtract => mk().method_call_expr(old, "wrapping_sub", vec![rhs]),
+                                BitAnd => {
+                                    mk().binary_expr(BinOp::BitAnd(Default::default()), old, rhs)
+                                }
+                                BitOr => {
+                                    mk().binary_expr(BinOp::BitOr(Default::default()), old, rhs)
+                                }
+                                _ => mk().binary_expr(BinOp::BitXor(Default::default()), old, rhs),
+                            }
+                        }
+                    }
+
+                    _ => {
+                        let update = |old: Box<Expr>| -> TranslationResult<Box<Expr>> {
+                            if let CTypeKind::Pointer(pointee) = *value_kind {
+                                let mul = self.compute_size_of_expr(pointee.ctype);
+                                return Ok(pointer_offset(
+                                    old,
+                                    rhs.clone(),
+                                    mul,
+                                    op == Subtract,
+                                    false,
+                                    self.signed_overflow(ctx),
+                                ));
+                            }
+
+                            let old = if same_type(compute_ty, value_ty) {
+                                old
+                            } else {
+                                mk().cast_expr(old, self.convert_type(compute_ty.ctype)?)
+                            };
+                            let res_ty = self.convert_type(compute_res_ty.ctype)?;
+                            let new = self.convert_binary_operator(
+                                ctx,
+                                op,
+                                res_ty.clone(),
+                                compute_res_ty.ctype,
+                                compute_ty,
+                                rhs_ty,
+                                old,
+                                rhs.clone(),
+                                None,
+                            )?;
+                            Ok(if same_type(compute_res_ty, value_ty) {
+                                new
+                            } else if value_kind.is_bool() {
+                                let zero = mk()
+                                    .cast_expr(mk().lit_expr(mk().int_unsuffixed_lit(0)), res_ty);
+                                mk().binary_expr(BinOp::Ne(Default::default()), new, zero)
+                            } else {
+                                mk().cast_expr(new, self.convert_type(value_ty.ctype)?)
+                            })
+                        };
+
+                        let old_name = self.renamer.borrow_mut().fresh();
+                        let new = update(mk().ident_expr(&old_name))?;
+                        let old = fetch_update(lhs, Ordering::SeqCst, &old_name, new);
+                        if !returns_new {
+                            old
+                        } else {
+                            update(old)?
+                        }
+                    }
+                };
+
+                self.convert_side_effects_expr(ctx, WithStmts::new(stmts, val), msg)
+            })
+        })
+    }
+
+    /// Translate `++`, `--` or their postfix forms applied to the `_Atomic`
+    /// lvalue `arg`.
+    pub(crate) fn convert_atomic_increment(
+        &self,
+        ctx: ExprContext,
+        op: c_ast::UnOp,
+        arg: CExprId,
+    ) -> TranslationResult<WithStmts<Box<Expr>>> {
+        let value_ty = self.ast_context[arg]
+            .kind
+            .get_qual_type()
+            .and_then(|ty| self.ast_context.atomic_value_type(ty.ctype))
+            .ok_or_else(|| format_err!("bad atomic increment type"))?;
+        let (op, returns_old) = match op {
+            c_ast::UnOp::PreIncrement => (c_ast::BinOp::AssignAdd, false),
+            c_ast::UnOp::PreDecrement => (c_ast::BinOp::AssignSubtract, false),
+            c_ast::UnOp::PostIncrement => (c_ast::BinOp::AssignAdd, true),
+            c_ast::UnOp::PostDecrement => (c_ast::BinOp::AssignSubtract, true),
+            _ => panic!("Unexpected atomic increment operator: {:?}", op),
+        };
+        let one = mk().lit_expr(mk().int_unsuffixed_lit(1));
+        // If the value is unused, the caller emits the update itself as a statement
+        self.convert_atomic_assignment(
+            ctx.used(),
+            op,
+            arg,
+            WithStmts::new_val(one),
+            value_ty,
+            None,
+            None,
+            returns_old || ctx.is_unused(),
+        )
+    }
+
     pub(crate) fn convert_atomic_cxchg(
         &self,
         ctx: ExprContext,
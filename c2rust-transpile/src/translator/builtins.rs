@@ -568,6 +568,10 @@ impl<'c> Translation<'c> {
                 )
             }
 
+            "__c11_atomic_thread_fence" | "__c11_atomic_signal_fence" => {
+                self.convert_c11_fence(ctx, builtin_name, args[0])
+            }
+
             "__sync_lock_test_and_set_1"
             | "__sync_lock_test_and_set_2"
             | "__sync_lock_test_and_set_4"
@@ -0,0 +1,159 @@
+//! Compile-time layout assertions for translated records (`--emit-layout-asserts`).
+//!
+//! For every struct and union with a definition, we emit constant assertions
+//! comparing the size and alignment of the Rust type, and the offset of each
+//! of its non-bitfield fields, against the layout clang computed for the C
+//! type, so that any mismatch fails the build of the translated crate.
+
+use super::*;
+use syn::__private::ToTokens;
+
+impl<'c> Translation<'c> {
+    /// Build the layout assertions for the record `decl_id`, which must have
+    /// already been translated.
+    // Items are boxed to be added to `ConvertedDecl::Items`.
+    #[allow(clippy::vec_box)]
+    pub(super) fn convert_layout_asserts(
+        &self,
+        decl_id: CRecordId,
+    ) -> TranslationResult<Vec<Box<Item>>> {
+        let (fields, size, alignment) = match self.ast_context.index(decl_id).kind {
+            CDeclKind::Struct {
+                fields: Some(ref fields),
+                platform_byte_size,
+                platform_alignment,
+                ..
+            }
+            | CDeclKind::Union {
+                fields: Some(ref fields),
+                platform_byte_size,
+                platform_alignment,
+                ..
+            } => (fields, platform_byte_size, platform_alignment),
+            _ => {
+                return Err(TranslationError::generic(
+                    "Layout assertions require a record definition",
+                ))
+            }
+        };
+
+        let name = self
+            .type_converter
+            .borrow()
+            .resolve_decl_name(decl_id)
+            .unwrap();
+        let ty = mk().path_ty(vec![name]);
+
+        // Structs with a manual alignment keep their fields in an inner struct
+        // at offset 0 of the outer one
+        let fields_ty = mk().path_ty(vec![self.resolve_decl_inner_name(decl_id)]);
+
+        let size_check = mk().binary_expr(
+            BinOp::Eq(Default::default()),
+            self.compute_size_of_ty(ty.clone())?.to_expr(),
+            mk().lit_expr(mk().int_unsuffixed_lit(size as u128)),
+        );
+        let align_path = vec![
+            mk().path_segment("core"),
+            mk().path_segment("mem"),
+            mk().path_segment_with_args("align_of", mk().angle_bracketed_args(vec![ty])),
+        ];
+        let align_check = mk().binary_expr(
+            BinOp::Eq(Default::default()),
+            mk().call_expr(mk().abs_path_expr(align_path), vec![]),
+            mk().lit_expr(mk().int_unsuffixed_lit(alignment as u128)),
+        );
+        let mut asserts =
+            vec![mk().binary_expr(BinOp::And(Default::default()), size_check, align_check)];
+
+        for &field_id in fields {
+            let offset = match self.ast_context.index(field_id).kind {
+                CDeclKind::Field {
+                    bitfield_width: None,
+                    platform_bit_offset,
+                    ..
+                } => platform_bit_offset / 8,
+                // Bitfields are grouped into byte arrays and have no Rust field
+                _ => continue,
+            };
+            let field_name = self
+                .type_converter
+                .borrow()
+                .resolve_field_name(Some(decl_id), field_id)
+                .ok_or_else(|| format_err!("Missing name for field {:?}", field_id))?;
+            asserts.push(mk().binary_expr(
+                BinOp::Eq(Default::default()),
+                self.field_offset_expr(fields_ty.clone(), &field_name),
+                mk().lit_expr(mk().int_unsuffixed_lit(offset as u128)),
+            ));
+        }
+        if asserts.len() > 1 {
+            self.use_feature("const_ptr_offset_from");
+        }
+
+        Ok(asserts
+            .into_iter()
+            .map(|check| {
+                let assert = mk().mac_expr(mk().mac(
+                    mk().path(vec!["assert"]),
+                    check.to_token_stream(),
+                    MacroDelimiter::Paren(Default::default()),
+                ));
+                mk().const_item("_", mk().tuple_ty(vec![]), assert)
+            })
+            .collect())
+    }
+
+    /// Build a constant expression computing the byte offset of `field` in
+    /// `ty`, as `offset_of!` would:
+    ///
+    /// ```ignore
+    /// unsafe {
+    ///     let uninit = ::core::mem::MaybeUninit::<ty>::uninit();
+    ///     let base = uninit.as_ptr();
+    ///     (::core::ptr::addr_of!((*base).field) as *const u8).offset_from(base as *const u8)
+    /// }
+    /// ```
+    fn field_offset_expr(&self, ty: Box<Type>, field: &str) -> Box<Expr> {
+        let uninit_path = vec![
+            mk().path_segment("core"),
+            mk().path_segment("mem"),
+            mk().path_segment_with_args("MaybeUninit", mk().angle_bracketed_args(vec![ty])),
+            mk().path_segment("uninit"),
+        ];
+        let uninit = mk().call_expr(mk().abs_path_expr(uninit_path), vec![]);
+        let base = mk().method_call_expr(mk().ident_expr("uninit"), "as_ptr", vec![]);
+
+        let field_place = mk().field_expr(
+            mk().paren_expr(
+                mk().unary_expr(UnOp::Deref(Default::default()), mk().ident_expr("base")),
+            ),
+            field,
+        );
+        let field_ptr = mk().mac_expr(mk().mac(
+            mk().abs_path(vec!["core", "ptr", "addr_of"]),
+            field_place.to_token_stream(),
+            MacroDelimiter::Paren(Default::default()),
+        ));
+        let byte_ptr_ty = || mk().ptr_ty(mk().path_ty(vec!["u8"]));
+        let offset = mk().method_call_expr(
+            mk().cast_expr(field_ptr, byte_ptr_ty()),
+            "offset_from",
+            vec![mk().cast_expr(mk().ident_expr("base"), byte_ptr_ty())],
+        );
+
+        mk().unsafe_block_expr(mk().unsafe_block(vec![
+            mk().local_stmt(Box::new(mk().local(
+                mk().ident_pat("uninit"),
+                None,
+                Some(uninit),
+            ))),
+            mk().local_stmt(Box::new(mk().local(
+                mk().ident_pat("base"),
+                None,
+                Some(base),
+            ))),
+            mk().expr_stmt(offset),
+        ]))
+    }
+}
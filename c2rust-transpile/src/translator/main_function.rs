@@ -8,7 +8,7 @@ use failure::format_err;
 use proc_macro2::{TokenStream, TokenTree};
 
 impl<'c> Translation<'c> {
-    pub fn convert_main(&self, main_id: CDeclId) -> TranslationResult<Box<Item>> {
+    pub fn convert_main(&self, main_id: CDeclId) -> TranslationResult<Vec<Box<Item>>> {
         if let CDeclKind::Function {
             ref parameters,
             typ,
@@ -35,6 +35,11 @@ impl<'c> Translation<'c> {
                 .get(&main_id)
                 .expect("Could not find main function in renamer");
 
+            if self.tcfg.safe_main {
+                let (c_main, main) = self.convert_safe_main(&main_fn_name, parameters, &ret)?;
+                return Ok(vec![c_main, main]);
+            }
+
             let decl = mk().fn_decl("main", vec![], None, ReturnType::Default);
 
             let main_fn = mk().path_expr(vec![main_fn_name]);
@@ -93,18 +98,8 @@ impl<'c> Translation<'c> {
                     ],
                 )));
 
-                let argc_ty: Box<Type> = match self.ast_context.index(parameters[0]).kind {
-                    CDeclKind::Variable { ref typ, .. } => self.convert_type(typ.ctype),
-                    _ => Err(TranslationError::generic(
-                        "Cannot find type of 'argc' argument in main function",
-                    )),
-                }?;
-                let argv_ty: Box<Type> = match self.ast_context.index(parameters[1]).kind {
-                    CDeclKind::Variable { ref typ, .. } => self.convert_type(typ.ctype),
-                    _ => Err(TranslationError::generic(
-                        "Cannot find type of 'argv' argument in main function",
-                    )),
-                }?;
+                let argc_ty = self.convert_main_param_type(parameters[0], "argc")?;
+                let argv_ty = self.convert_main_param_type(parameters[1], "argv")?;
 
                 let args = mk().ident_expr("args");
                 let argc = mk().binary_expr(
@@ -203,12 +198,7 @@ impl<'c> Translation<'c> {
                     ],
                 )));
 
-                let envp_ty: Box<Type> = match self.ast_context.index(parameters[2]).kind {
-                    CDeclKind::Variable { ref typ, .. } => self.convert_type(typ.ctype),
-                    _ => Err(TranslationError::generic(
-                        "Cannot find type of 'envp' argument in main function",
-                    )),
-                }?;
+                let envp_ty = self.convert_main_param_type(parameters[2], "envp")?;
 
                 let envp = mk().method_call_expr(mk().ident_expr("vars"), "as_mut_ptr", no_args);
 
@@ -247,11 +237,270 @@ impl<'c> Translation<'c> {
             };
 
             let block = mk().block(stmts);
-            Ok(mk().pub_().fn_item(decl, block))
+            Ok(vec![mk().pub_().fn_item(decl, block)])
         } else {
             Err(TranslationError::generic(
                 "Cannot translate non-function main entry point",
             ))
         }
     }
+
+    fn convert_main_param_type(&self, param: CDeclId, name: &str) -> TranslationResult<Box<Type>> {
+        match self.ast_context.index(param).kind {
+            CDeclKind::Variable { ref typ, .. } => self.convert_type(typ.ctype),
+            _ => {
+                Err(format_err!("Cannot find type of '{}' argument in main function", name).into())
+            }
+        }
+    }
+
+    /// Generate a `c_main(args: &[&CStr]) -> i32` function that calls the C
+    /// main function with copies of `args`, so that it can also be called as a
+    /// library function, and a `main` that calls it with the arguments of the
+    /// process. Unlike the default translation, arguments do not have to be
+    /// UTF-8, nothing is leaked, and the exit code is returned from `main`
+    /// rather than passed to `std::process::exit`, so destructors run.
+    ///
+    /// The process arguments and environment are converted to C strings with
+    /// `std::os::unix::ffi::OsStringExt`, so the generated `main` (and `c_main`
+    /// when the C main takes `envp`) only builds on Unix.
+    fn convert_safe_main(
+        &self,
+        main_fn_name: &str,
+        parameters: &[CDeclId],
+        ret: &CTypeKind,
+    ) -> TranslationResult<(Box<Item>, Box<Item>)> {
+        let n = parameters.len();
+        if n != 0 && n != 2 && n != 3 {
+            return Err(format_err!(
+                "Main function should have 0, 2, or 3 parameters, not {}.",
+                n
+            )
+            .into());
+        };
+
+        let c_main_name = self.renamer.borrow_mut().pick_name_root("c_main");
+        let c_char_ptr_ty = || mk().mutbl().ptr_ty(mk().path_ty(vec!["libc", "c_char"]));
+        let vec_ty = |ty: Box<Type>| {
+            mk().path_ty(vec![
+                mk().path_segment_with_args("Vec", mk().angle_bracketed_args(vec![ty]))
+            ])
+        };
+        let new_vec = || mk().call_expr(mk().path_expr(vec!["Vec", "new"]), vec![]);
+        let into_vec =
+            || mk().abs_path_expr(vec!["std", "os", "unix", "ffi", "OsStringExt", "into_vec"]);
+
+        // `let mut ptrs: Vec<*mut c_char> = Vec::new();`
+        // `for buf in &mut bufs { ptrs.push(buf.as_mut_ptr() as *mut c_char); }`
+        // `ptrs.push(core::ptr::null_mut());`
+        let c_string_ptrs = |bufs: &str, ptrs: &str| {
+            vec![
+                mk().local_stmt(Box::new(mk().local(
+                    mk().mutbl().ident_pat(ptrs),
+                    Some(vec_ty(c_char_ptr_ty())),
+                    Some(new_vec()),
+                ))),
+                mk().semi_stmt(mk().for_expr(
+                    mk().ident_pat("buf"),
+                    mk().mutbl().addr_of_expr(mk().ident_expr(bufs)),
+                    mk().block(vec![mk().semi_stmt(mk().method_call_expr(
+                        mk().ident_expr(ptrs),
+                        "push",
+                        vec![mk().cast_expr(
+                            mk().method_call_expr(mk().ident_expr("buf"), "as_mut_ptr", vec![]),
+                            c_char_ptr_ty(),
+                        )],
+                    ))]),
+                    None::<Ident>,
+                )),
+                mk().semi_stmt(mk().method_call_expr(
+                    mk().ident_expr(ptrs),
+                    "push",
+                    vec![
+                        mk().call_expr(mk().abs_path_expr(vec!["core", "ptr", "null_mut"]), vec![]),
+                    ],
+                )),
+            ]
+        };
+
+        let mut stmts = vec![];
+        let mut main_args = vec![];
+
+        if n >= 2 {
+            // The C main function may modify its arguments, so give it copies
+            stmts.push(mk().local_stmt(Box::new(mk().local(
+                mk().mutbl().ident_pat("arg_bufs"),
+                Some(vec_ty(vec_ty(mk().path_ty(vec!["u8"])))),
+                Some(new_vec()),
+            ))));
+            stmts.push(mk().semi_stmt(mk().for_expr(
+                mk().ident_pat("arg"),
+                mk().ident_expr("args"),
+                mk().block(vec![mk().semi_stmt(mk().method_call_expr(
+                    mk().ident_expr("arg_bufs"),
+                    "push",
+                    vec![mk().method_call_expr(
+                        mk().method_call_expr(mk().ident_expr("arg"), "to_bytes_with_nul", vec![]),
+                        "to_vec",
+                        vec![],
+                    )],
+                ))]),
+                None::<Ident>,
+            )));
+            stmts.extend(c_string_ptrs("arg_bufs", "argv"));
+
+            let argc_ty = self.convert_main_param_type(parameters[0], "argc")?;
+            let argv_ty = self.convert_main_param_type(parameters[1], "argv")?;
+            let argc = mk().method_call_expr(mk().ident_expr("args"), "len", vec![]);
+            let argv = mk().method_call_expr(mk().ident_expr("argv"), "as_mut_ptr", vec![]);
+            main_args.push(mk().cast_expr(argc, argc_ty));
+            main_args.push(mk().cast_expr(argv, argv_ty));
+        }
+
+        if n >= 3 {
+            // non-standard `envp`, built from the environment of the process
+            stmts.push(mk().local_stmt(Box::new(mk().local(
+                mk().mutbl().ident_pat("var_bufs"),
+                Some(vec_ty(vec_ty(mk().path_ty(vec!["u8"])))),
+                Some(new_vec()),
+            ))));
+            stmts.push(mk().semi_stmt(mk().for_expr(
+                mk().tuple_pat(vec![
+                    mk().ident_pat("var_name"),
+                    mk().ident_pat("var_value"),
+                ]),
+                mk().call_expr(mk().abs_path_expr(vec!["std", "env", "vars_os"]), vec![]),
+                mk().block(vec![
+                    mk().local_stmt(Box::new(mk().local(
+                        mk().mutbl().ident_pat("var"),
+                        Some(mk().path_ty(vec!["std", "ffi", "OsString"])),
+                        Some(mk().ident_expr("var_name")),
+                    ))),
+                    mk().semi_stmt(mk().method_call_expr(
+                        mk().ident_expr("var"),
+                        "push",
+                        vec![mk().lit_expr("=")],
+                    )),
+                    mk().semi_stmt(mk().method_call_expr(
+                        mk().ident_expr("var"),
+                        "push",
+                        vec![mk().ident_expr("var_value")],
+                    )),
+                    mk().semi_stmt(mk().method_call_expr(
+                        mk().ident_expr("var_bufs"),
+                        "push",
+                        vec![mk().method_call_expr(
+                            mk().method_call_expr(
+                                mk().call_expr(
+                                    mk().abs_path_expr(vec!["std", "ffi", "CString", "new"]),
+                                    vec![mk().call_expr(into_vec(), vec![mk().ident_expr("var")])],
+                                ),
+                                "expect",
+                                vec![mk().lit_expr(
+                                    "Failed to convert environment variable into CString.",
+                                )],
+                            ),
+                            "into_bytes_with_nul",
+                            vec![],
+                        )],
+                    )),
+                ]),
+                None::<Ident>,
+            )));
+            stmts.extend(c_string_ptrs("var_bufs", "envp"));
+
+            let envp_ty = self.convert_main_param_type(parameters[2], "envp")?;
+            let envp = mk().method_call_expr(mk().ident_expr("envp"), "as_mut_ptr", vec![]);
+            main_args.push(mk().cast_expr(envp, envp_ty));
+        }
+
+        let call_main = mk().call_expr(mk().path_expr(vec![main_fn_name]), main_args);
+        if let CTypeKind::Void = ret {
+            let unsafe_block = mk().unsafe_block(vec![mk().expr_stmt(call_main)]);
+            stmts.push(mk().semi_stmt(mk().unsafe_block_expr(unsafe_block)));
+            stmts.push(mk().expr_stmt(mk().lit_expr(mk().int_lit(0, ""))));
+        } else {
+            let call_main = mk().cast_expr(call_main, mk().path_ty(vec!["i32"]));
+            let unsafe_block = mk().unsafe_block(vec![mk().expr_stmt(call_main)]);
+            stmts.push(mk().expr_stmt(mk().unsafe_block_expr(unsafe_block)));
+        }
+
+        // `pub fn c_main(args: &[&std::ffi::CStr]) -> i32`
+        let args_name = if n == 0 { "_args" } else { "args" };
+        let args_ty =
+            mk().ref_ty(mk().slice_ty(mk().ref_ty(mk().path_ty(vec!["std", "ffi", "CStr"]))));
+        let c_main_decl = mk().fn_decl(
+            &c_main_name,
+            vec![mk().arg(args_ty, mk().ident_pat(args_name))],
+            None,
+            ReturnType::Type(Default::default(), mk().path_ty(vec!["i32"])),
+        );
+        let c_main = mk().pub_().fn_item(c_main_decl, mk().block(stmts));
+
+        // `pub fn main() -> std::process::ExitCode`
+        let mut stmts = vec![];
+        stmts.push(mk().local_stmt(Box::new(mk().local(
+            mk().mutbl().ident_pat("args"),
+            Some(vec_ty(mk().path_ty(vec!["std", "ffi", "CString"]))),
+            Some(new_vec()),
+        ))));
+        stmts.push(mk().semi_stmt(mk().for_expr(
+            mk().ident_pat("arg"),
+            mk().call_expr(mk().abs_path_expr(vec!["std", "env", "args_os"]), vec![]),
+            mk().block(vec![mk().semi_stmt(mk().method_call_expr(
+                mk().ident_expr("args"),
+                "push",
+                vec![mk().method_call_expr(
+                    mk().call_expr(
+                        mk().abs_path_expr(vec!["std", "ffi", "CString", "new"]),
+                        vec![mk().call_expr(into_vec(), vec![mk().ident_expr("arg")])],
+                    ),
+                    "expect",
+                    vec![mk().lit_expr("Failed to convert argument into CString.")],
+                )],
+            ))]),
+            None::<Ident>,
+        )));
+        // `let args: Vec<&std::ffi::CStr> = args.iter().map(std::ffi::CString::as_c_str).collect();`
+        stmts.push(mk().local_stmt(Box::new(mk().local(
+            mk().ident_pat("args"),
+            Some(vec_ty(
+                mk().ref_ty(mk().path_ty(vec!["std", "ffi", "CStr"])),
+            )),
+            Some(mk().method_call_expr(
+                mk().method_call_expr(
+                    mk().method_call_expr(mk().ident_expr("args"), "iter", vec![]),
+                    "map",
+                    vec![mk().path_expr(vec!["std", "ffi", "CString", "as_c_str"])],
+                ),
+                "collect",
+                vec![],
+            )),
+        ))));
+        // C only passes the low 8 bits of the exit status to the parent process
+        let exit_code = mk().cast_expr(
+            mk().call_expr(
+                mk().path_expr(vec![&*c_main_name]),
+                vec![mk().addr_of_expr(mk().ident_expr("args"))],
+            ),
+            mk().path_ty(vec!["u8"]),
+        );
+        stmts.push(mk().expr_stmt(mk().call_expr(
+            mk().abs_path_expr(vec!["std", "process", "ExitCode", "from"]),
+            vec![exit_code],
+        )));
+
+        let main_decl = mk().fn_decl(
+            "main",
+            vec![],
+            None,
+            ReturnType::Type(
+                Default::default(),
+                mk().path_ty(vec!["std", "process", "ExitCode"]),
+            ),
+        );
+        let main = mk().pub_().fn_item(main_decl, mk().block(stmts));
+
+        Ok((c_main, main))
+    }
 }
@@ -1,6 +1,8 @@
-use std::cell::RefCell;
+use std::borrow::Cow;
+use std::cell::{Cell, RefCell};
 use std::char;
 use std::collections::HashMap;
+use std::iter;
 use std::mem;
 use std::ops::Index;
 use std::path::{self, PathBuf};
@@ -30,9 +32,11 @@ use crate::c_ast::iterators::{DFExpr, SomeId};
 use crate::c_ast::*;
 use crate::cfg;
 use crate::convert_type::TypeConverter;
+use crate::naming::NameCase;
 use crate::renamer::Renamer;
+use crate::visibility::{InternalFn, InternalFns};
 use crate::with_stmts::WithStmts;
-use crate::{c_ast, format_translation_err};
+use crate::{build_files, c_ast, format_translation_err};
 use crate::{ExternCrate, ExternCrateDetails, TranspilerConfig};
 use c2rust_ast_exporter::clang_ast::LRValue;
 
@@ -40,14 +44,21 @@ mod assembly;
 mod atomics;
 mod builtins;
 mod comments;
+mod layout_asserts;
 mod literals;
 mod main_function;
 mod named_references;
 mod operators;
+mod printf;
+mod shared_headers;
 mod simd;
 mod structs;
+mod unsafety;
 mod variadic;
 
+pub use shared_headers::SharedHeaders;
+use unsafety::{UnsafeCounts, UnsafeDecls};
+
 pub use crate::diagnostics::{TranslationError, TranslationErrorKind};
 use crate::CrateSet;
 use crate::PragmaVec;
@@ -100,6 +111,60 @@ pub enum ReplaceMode {
     Extern,
 }
 
+/// How signed integer arithmetic, whose overflow is undefined behavior in C,
+/// is translated (`--signed-overflow`).
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum SignedOverflow {
+    /// Operators, which panic on overflow in debug builds and wrap in release
+    /// builds
+    Plain,
+    /// `wrapping_*` methods, which wrap around in all builds
+    Wrapping,
+    /// `checked_*` methods, which panic on overflow in all builds
+    Checked,
+}
+
+impl SignedOverflow {
+    /// Translate the signed integer arithmetic `lhs op rhs`.
+    fn binary_expr(self, op: BinOp, lhs: Box<Expr>, rhs: Box<Expr>) -> Box<Expr> {
+        let (method, panic_msg) = match op {
+            BinOp::Add(_) => ("add", "attempt to add with overflow"),
+            BinOp::Sub(_) => ("sub", "attempt to subtract with overflow"),
+            BinOp::Mul(_) => ("mul", "attempt to multiply with overflow"),
+            BinOp::Div(_) => ("div", "attempt to divide by zero or with overflow"),
+            BinOp::Rem(_) => (
+                "rem",
+                "attempt to calculate the remainder with a divisor of zero or with overflow",
+            ),
+            _ => return mk().binary_expr(op, lhs, rhs),
+        };
+        match self {
+            SignedOverflow::Plain => mk().binary_expr(op, lhs, rhs),
+            SignedOverflow::Wrapping => {
+                mk().method_call_expr(lhs, format!("wrapping_{}", method), vec![rhs])
+            }
+            SignedOverflow::Checked => {
+                let checked = mk().method_call_expr(lhs, format!("checked_{}", method), vec![rhs]);
+                let msg = mk().lit_expr(panic_msg);
+                mk().method_call_expr(checked, "expect", vec![msg])
+            }
+        }
+    }
+
+    /// Translate the signed integer negation `-arg`.
+    fn neg_expr(self, arg: Box<Expr>) -> Box<Expr> {
+        match self {
+            SignedOverflow::Plain => mk().unary_expr(UnOp::Neg(Default::default()), arg),
+            SignedOverflow::Wrapping => mk().method_call_expr(arg, "wrapping_neg", vec![]),
+            SignedOverflow::Checked => {
+                let checked = mk().method_call_expr(arg, "checked_neg", vec![]);
+                let msg = mk().lit_expr("attempt to negate with overflow");
+                mk().method_call_expr(checked, "expect", vec![msg])
+            }
+        }
+    }
+}
+
 #[derive(Copy, Clone, Debug)]
 pub struct ExprContext {
     used: bool,
@@ -108,6 +173,10 @@ pub struct ExprContext {
     decay_ref: DecayRef,
     is_bitfield_write: bool,
 
+    /// The expression names a place that is written to, like the LHS of an assignment, rather
+    /// than one that is only read.
+    is_lvalue: bool,
+
     // We will be referring to the expression by address. In this context we
     // can't index arrays because they may legally go out of bounds. We also
     // need to explicitly cast function references to fn() so we get their
@@ -171,6 +240,12 @@ impl ExprContext {
             ..self
         }
     }
+    pub fn is_lvalue(&self) -> bool {
+        self.is_lvalue
+    }
+    pub fn set_lvalue(self, is_lvalue: bool) -> Self {
+        ExprContext { is_lvalue, ..self }
+    }
     pub fn needs_address(&self) -> bool {
         self.needs_address
     }
@@ -276,6 +351,14 @@ pub struct Translation<'c> {
     // expanded from. This is needed in order to note imports in items when
     // encountering DeclRefs.
     cur_file: RefCell<Option<FileId>>,
+
+    // Functions only used within the crate, with `--infer-visibility`
+    internal_fns: IndexMap<CDeclId, InternalFn>,
+
+    // Declarations deciding where `unsafe` is needed, and the `unsafe` code
+    // translated, with `--minimize-unsafe`
+    unsafe_decls: UnsafeDecls,
+    unsafe_counts: Cell<UnsafeCounts>,
 }
 
 fn simple_metaitem(name: &str) -> NestedMeta {
@@ -318,17 +401,18 @@ fn pointer_offset(
     multiply_by: Option<Box<Expr>>,
     neg: bool,
     mut deref: bool,
+    overflow: SignedOverflow,
 ) -> Box<Expr> {
     let mut offset = cast_int(offset, "isize", false);
 
     if let Some(mul) = multiply_by {
         let mul = cast_int(mul, "isize", false);
-        offset = mk().binary_expr(BinOp::Mul(Default::default()), offset, mul);
+        offset = overflow.binary_expr(BinOp::Mul(Default::default()), offset, mul);
         deref = false;
     }
 
     if neg {
-        offset = mk().unary_expr(UnOp::Neg(Default::default()), offset);
+        offset = overflow.neg_expr(offset);
     }
 
     let res = mk().method_call_expr(ptr, "offset", vec![offset]);
@@ -425,7 +509,12 @@ fn prefix_names(translation: &mut Translation, prefix: &str) {
 
                 name.insert_str(0, prefix);
 
-                translation.renamer.borrow_mut().insert(decl_id, name);
+                let new_name = if translation.tcfg.rust_naming_conventions {
+                    Cow::Owned(NameCase::Snake.apply(name))
+                } else {
+                    Cow::Borrowed(name.as_str())
+                };
+                translation.renamer.borrow_mut().insert(decl_id, &new_name);
             }
             CDeclKind::Variable {
                 ref mut ident,
@@ -491,14 +580,37 @@ pub fn translate(
     ast_context: TypedAstContext,
     tcfg: &TranspilerConfig,
     main_file: PathBuf,
+    shared_headers: Option<&mut SharedHeaders>,
+    internal_fns: Option<&InternalFns>,
 ) -> (String, PragmaVec, CrateSet) {
     let mut t = Translation::new(ast_context, tcfg, main_file.as_path());
+    // Look up the functions by their C names, before they are prefixed
+    if let Some(internal_fns) = internal_fns {
+        t.internal_fns = t
+            .ast_context
+            .iter_decls()
+            .filter_map(|(&decl_id, decl)| match decl.kind {
+                CDeclKind::Function {
+                    ref name,
+                    is_global: true,
+                    ..
+                } => Some((decl_id, internal_fns.get(name)?.clone())),
+                _ => None,
+            })
+            .collect();
+    }
+    // Binaries are separate crates, so they cannot use the shared headers
+    let shared_headers = shared_headers.filter(|_| !tcfg.is_binary(main_file.as_path()));
+    if let Some(shared_headers) = &shared_headers {
+        t.mod_names = RefCell::new(shared_headers.mod_names.clone());
+    }
     let ctx = ExprContext {
         used: true,
         is_static: false,
         is_const: false,
         decay_ref: DecayRef::Default,
         is_bitfield_write: false,
+        is_lvalue: false,
         needs_address: false,
         expecting_valistimpl: false,
         ternary_needs_parens: false,
@@ -506,7 +618,10 @@ pub fn translate(
     };
 
     {
-        t.use_crate(ExternCrate::Libc);
+        // With `--emit-no-libc`, `libc` refers to a module of the generated crate
+        if !tcfg.emit_no_libc {
+            t.use_crate(ExternCrate::Libc);
+        }
 
         // Sort the top-level declarations by file and source location so that we
         // preserve the ordering of all declarations in each file.
@@ -533,9 +648,12 @@ pub fn translate(
             }
         }
 
-        // Used for testing; so that we don't overlap with C function names
+        // Used for testing; so that we don't overlap with C function names.
+        // Bindings refer to the C definitions, so they keep their names.
         if let Some(ref prefix) = t.tcfg.prefix_function_names {
-            prefix_names(&mut t, prefix);
+            if !t.tcfg.bindings_only {
+                prefix_names(&mut t, prefix);
+            }
         }
 
         // Identify typedefs that name unnamed types and collapse the two declarations
@@ -583,7 +701,7 @@ pub fn translate(
 
                         t.type_converter
                             .borrow_mut()
-                            .declare_decl_name(decl_id, name);
+                            .declare_decl_name(decl_id, &t.rust_decl_name(decl_id, name));
                         t.type_converter
                             .borrow_mut()
                             .alias_decl_name(subdecl_id, decl_id);
@@ -621,25 +739,41 @@ pub fn translate(
             match decl_name {
                 Name::None => (),
                 Name::Anonymous => {
+                    // Shared headers must be translated the same way in every translation unit,
+                    // so their unnamed types are numbered within the header rather than the
+                    // translation unit
+                    let name = match t.ast_context.file_id(decl) {
+                        Some(file_id) if shared_headers.is_some() && file_id != t.main_file => {
+                            let path = t.ast_context.get_file_path(file_id);
+                            format!("C2RustUnnamed_{}", clean_path(&t.mod_names, path))
+                        }
+                        _ => "C2RustUnnamed".to_string(),
+                    };
                     t.type_converter
                         .borrow_mut()
-                        .declare_decl_name(decl_id, "C2RustUnnamed");
+                        .declare_decl_name(decl_id, &name);
                 }
                 Name::Type(name) => {
                     t.type_converter
                         .borrow_mut()
-                        .declare_decl_name(decl_id, name);
+                        .declare_decl_name(decl_id, &t.rust_decl_name(decl_id, name));
                 }
                 Name::Var(name) => {
-                    t.renamer.borrow_mut().insert(decl_id, name);
+                    t.renamer
+                        .borrow_mut()
+                        .insert(decl_id, &t.rust_decl_name(decl_id, name));
                 }
             }
         }
 
+        if t.tcfg.minimize_unsafe {
+            t.unsafe_decls = t.collect_unsafe_decls();
+        }
+
         {
             let convert_type = |decl_id: CDeclId, decl: &CDecl| {
                 let decl_file_id = t.ast_context.file_id(decl);
-                if t.tcfg.reorganize_definitions {
+                if t.tcfg.headers_in_submodules() {
                     *t.cur_file.borrow_mut() = decl_file_id;
                 }
                 match t.convert_decl(ctx, decl_id) {
@@ -668,7 +802,7 @@ pub fn translate(
                 }
                 t.cur_file.borrow_mut().take();
 
-                if t.tcfg.reorganize_definitions
+                if t.tcfg.headers_in_submodules()
                     && decl_file_id.map_or(false, |id| id != t.main_file)
                 {
                     t.generate_submodule_imports(decl_id, decl_file_id);
@@ -711,7 +845,7 @@ pub fn translate(
                 let decl = decl_opt.as_ref().unwrap();
                 let decl_file_id = t.ast_context.file_id(decl);
 
-                if t.tcfg.reorganize_definitions
+                if t.tcfg.headers_in_submodules()
                     && decl_file_id.map_or(false, |id| id != t.main_file)
                 {
                     *t.cur_file.borrow_mut() = decl_file_id;
@@ -755,7 +889,7 @@ pub fn translate(
                 }
                 t.cur_file.borrow_mut().take();
 
-                if t.tcfg.reorganize_definitions
+                if t.tcfg.headers_in_submodules()
                     && decl_file_id.map_or(false, |id| id != t.main_file)
                 {
                     t.generate_submodule_imports(*top_id, decl_file_id);
@@ -764,9 +898,13 @@ pub fn translate(
         }
 
         // Add the main entry point
-        if let Some(main_id) = t.ast_context.c_main {
+        if let Some(main_id) = t.ast_context.c_main.filter(|_| !tcfg.bindings_only) {
             match t.convert_main(main_id) {
-                Ok(item) => t.items.borrow_mut()[&t.main_file].add_item(item),
+                Ok(items) => {
+                    for item in items {
+                        t.items.borrow_mut()[&t.main_file].add_item(item);
+                    }
+                }
                 Err(e) => {
                     let msg = format!("Failed to translate main: {}", e);
                     translate_failure(t.tcfg, &msg)
@@ -786,11 +924,33 @@ pub fn translate(
         let pragmas = t.get_pragmas();
         let crates = t.extern_crates.borrow().clone();
 
+        if tcfg.minimize_unsafe {
+            let counts = t.unsafe_counts.get();
+            info!(
+                "{} unsafe operations in {}: {} of {} functions unsafe, {} unsafe blocks",
+                counts.ops,
+                main_file.display(),
+                counts.unsafe_fns,
+                counts.fns,
+                counts.blocks,
+            );
+        }
+
         let mut mod_items: Vec<Box<Item>> = Vec::new();
 
         // Keep track of new uses we need while building header submodules
         let mut new_uses = ItemStore::new();
 
+        // Header Deduplication: Shared Submodules
+        if let Some(shared_headers) = shared_headers {
+            for mod_name in t.share_headers(shared_headers, &main_file, &mut new_uses) {
+                mod_items.push(mk().use_simple_item(
+                    mk().path(vec!["crate", "headers", &mod_name]),
+                    None::<Ident>,
+                ));
+            }
+        }
+
         // Header Reorganization: Submodule Item Stores
         for (file_id, ref mut mod_item_store) in t.items.borrow_mut().iter_mut() {
             if *file_id != t.main_file {
@@ -892,6 +1052,8 @@ pub fn translate(
 fn item_ident(i: &Item) -> Option<&Ident> {
     use Item::*;
     Some(match i {
+        // `const _` items are unnamed
+        Const(ic) if ic.ident == "_" => return None,
         Const(ic) => &ic.ident,
         Enum(ie) => &ie.ident,
         ExternCrate(iec) => &iec.ident,
@@ -970,27 +1132,19 @@ fn foreign_item_ident_vis(fi: &ForeignItem) -> Option<(&Ident, Visibility)> {
     })
 }
 
-fn make_submodule(
-    ast_context: &TypedAstContext,
-    item_store: &mut ItemStore,
-    file_id: FileId,
+/// Import the items of submodule `mod_name` into the enclosing module.
+fn add_submodule_uses(
+    mod_name: &str,
+    items: &[Box<Item>],
+    foreign_items: &[ForeignItem],
     use_item_store: &mut ItemStore,
-    mod_names: &RefCell<IndexMap<String, PathBuf>>,
-    reorganize_definitions: bool,
-) -> Box<Item> {
-    let (mut items, foreign_items, uses) = item_store.drain();
-    let file_path = ast_context.get_file_path(file_id);
-    let include_line_number = ast_context
-        .get_file_include_line_number(file_id)
-        .unwrap_or(0);
-    let mod_name = clean_path(mod_names, file_path);
-
+) {
     for item in items.iter() {
         let ident_name = match item_ident(item) {
             Some(i) => i.to_string(),
             None => continue,
         };
-        let use_path = vec!["self".into(), mod_name.clone()];
+        let use_path = vec!["self".into(), mod_name.into()];
 
         let vis = match item_vis(item) {
             Some(Visibility::Public(_)) => mk().pub_(),
@@ -1006,10 +1160,28 @@ fn make_submodule(
             Some((ident, _vis)) => ident.to_string(),
             None => continue,
         };
-        let use_path = vec!["self".into(), mod_name.clone()];
+        let use_path = vec!["self".into(), mod_name.into()];
 
         use_item_store.add_use(use_path, &ident_name);
     }
+}
+
+fn make_submodule(
+    ast_context: &TypedAstContext,
+    item_store: &mut ItemStore,
+    file_id: FileId,
+    use_item_store: &mut ItemStore,
+    mod_names: &RefCell<IndexMap<String, PathBuf>>,
+    reorganize_definitions: bool,
+) -> Box<Item> {
+    let (mut items, foreign_items, uses) = item_store.drain();
+    let file_path = ast_context.get_file_path(file_id);
+    let include_line_number = ast_context
+        .get_file_include_line_number(file_id)
+        .unwrap_or(0);
+    let mod_name = clean_path(mod_names, file_path);
+
+    add_submodule_uses(&mod_name, &items, &foreign_items, use_item_store);
 
     for item in uses.into_items() {
         items.push(item);
@@ -1048,6 +1220,9 @@ fn arrange_header(t: &Translation, is_binary: bool) -> (Vec<syn::Attribute>, Vec
                 None::<Ident>,
             ))
         }
+        if t.tcfg.emit_no_libc {
+            out_items.push(mk().use_simple_item(mk().path(vec!["crate", "libc"]), None::<Ident>));
+        }
     } else {
         let pragmas = t.get_pragmas();
         for (key, mut values) in pragmas {
@@ -1241,6 +1416,19 @@ impl<'c> Translation<'c> {
             main_file,
             extern_crates: RefCell::new(IndexSet::new()),
             cur_file: RefCell::new(None),
+            internal_fns: IndexMap::new(),
+            unsafe_decls: UnsafeDecls::default(),
+            unsafe_counts: Cell::new(UnsafeCounts::default()),
+        }
+    }
+
+    /// The name to give to the translation of the declaration `decl_id` named
+    /// `name`, which follows the Rust naming conventions with
+    /// `--rust-naming-conventions`.
+    fn rust_decl_name<'a>(&self, decl_id: CDeclId, name: &'a str) -> Cow<'a, str> {
+        match NameCase::for_decl(&self.ast_context[decl_id].kind) {
+            Some(case) if self.tcfg.rust_naming_conventions => Cow::Owned(case.apply(name)),
+            _ => Cow::Borrowed(name),
         }
     }
 
@@ -1288,6 +1476,10 @@ impl<'c> Translation<'c> {
                 "unused_assignments",
             ],
         )];
+        // Calls to functions that became safe may remain in `unsafe` blocks
+        if self.tcfg.minimize_unsafe {
+            pragmas[0].1.push("unused_unsafe");
+        }
 
         if self.features.borrow().contains("register_tool") {
             pragmas.push(("register_tool", vec!["c2rust"]));
@@ -1621,9 +1813,11 @@ impl<'c> Translation<'c> {
                 // Pre-declare all the field names, checking for duplicates
                 for &x in fields {
                     if let CDeclKind::Field { ref name, .. } = self.ast_context.index(x).kind {
-                        self.type_converter
-                            .borrow_mut()
-                            .declare_field_name(decl_id, x, name);
+                        self.type_converter.borrow_mut().declare_field_name(
+                            decl_id,
+                            x,
+                            &self.rust_decl_name(x, name),
+                        );
                     }
                 }
 
@@ -1631,8 +1825,11 @@ impl<'c> Translation<'c> {
                 let (field_entries, contains_va_list) =
                     self.convert_struct_fields(decl_id, fields, platform_byte_size)?;
 
+                // Atomics are neither `Copy` nor `Clone`
+                let contains_atomic = self.ast_context.record_contains_atomic(decl_id);
+
                 let mut derives = vec![];
-                if !contains_va_list {
+                if !contains_va_list && !contains_atomic {
                     derives.push("Copy");
                     derives.push("Clone");
                 };
@@ -1662,7 +1859,7 @@ impl<'c> Translation<'c> {
                     _ => {}
                 }
 
-                if let Some(alignment) = manual_alignment {
+                let mut items = if let Some(alignment) = manual_alignment {
                     // This is the most complicated case: we have `align(N)` which
                     // might be mixed with or included into a `packed` structure,
                     // which Rust doesn't currently support; instead, we split
@@ -1697,10 +1894,15 @@ impl<'c> Translation<'c> {
                     ];
                     let repr_attr = mk().meta_list("repr", outer_reprs);
                     let outer_field = mk().pub_().enum_field(mk().ident_ty(inner_name));
+                    let outer_derives = if contains_atomic {
+                        vec![]
+                    } else {
+                        vec!["Copy", "Clone"]
+                    };
                     let outer_struct = mk()
                         .span(span)
                         .pub_()
-                        .call_attr("derive", vec!["Copy", "Clone"])
+                        .call_attr("derive", outer_derives)
                         .meta_item_attr(AttrStyle::Outer, repr_attr)
                         .struct_item(name, vec![outer_field], true);
 
@@ -1720,8 +1922,7 @@ impl<'c> Translation<'c> {
                         .call_attr("allow", vec!["dead_code", "non_upper_case_globals"])
                         .const_item(padding_name, padding_ty, padding_value);
 
-                    let structs = vec![outer_struct, inner_struct, padding_const];
-                    Ok(ConvertedDecl::Items(structs))
+                    vec![outer_struct, inner_struct, padding_const]
                 } else {
                     assert!(!self.ast_context.has_inner_struct_decl(decl_id));
                     let repr_attr = mk().meta_list("repr", reprs);
@@ -1736,12 +1937,14 @@ impl<'c> Translation<'c> {
                         mk_ = mk_.generic_over(mk().lt_param(mk().ident("a")))
                     }
 
-                    Ok(ConvertedDecl::Item(mk_.struct_item(
-                        name,
-                        field_entries,
-                        false,
-                    )))
+                    vec![mk_.struct_item(name, field_entries, false)]
+                };
+
+                // Rust's layout of `VaList` differs from that of C's `va_list`
+                if self.tcfg.emit_layout_asserts && !contains_va_list {
+                    items.extend(self.convert_layout_asserts(decl_id)?);
                 }
+                Ok(ConvertedDecl::Items(items))
             }
 
             Union {
@@ -1755,15 +1958,23 @@ impl<'c> Translation<'c> {
                     .resolve_decl_name(decl_id)
                     .unwrap();
 
+                // Union fields must be `Copy`, which atomics are not
+                if self.ast_context.record_contains_atomic(decl_id) {
+                    return Err(TranslationError::generic(
+                        "Unions with atomic fields are not supported",
+                    ));
+                }
+
                 let mut field_syns = vec![];
                 for &x in fields {
                     let field_decl = self.ast_context.index(x);
                     match field_decl.kind {
                         CDeclKind::Field { ref name, typ, .. } => {
-                            let name = self
-                                .type_converter
-                                .borrow_mut()
-                                .declare_field_name(decl_id, x, name);
+                            let name = self.type_converter.borrow_mut().declare_field_name(
+                                decl_id,
+                                x,
+                                &self.rust_decl_name(x, name),
+                            );
                             let typ = self.convert_type(typ.ctype)?;
                             field_syns.push(mk().pub_().struct_field(name, typ))
                         }
@@ -1780,24 +1991,26 @@ impl<'c> Translation<'c> {
                     repr.push("packed");
                 }
 
-                Ok(if field_syns.is_empty() {
+                let union = if field_syns.is_empty() {
                     // Empty unions are a GNU extension, but Rust doesn't allow empty unions.
-                    ConvertedDecl::Item(
-                        mk().span(span)
-                            .pub_()
-                            .call_attr("derive", vec!["Copy", "Clone"])
-                            .call_attr("repr", repr)
-                            .struct_item(name, vec![], false),
-                    )
+                    mk().span(span)
+                        .pub_()
+                        .call_attr("derive", vec!["Copy", "Clone"])
+                        .call_attr("repr", repr)
+                        .struct_item(name, vec![], false)
                 } else {
-                    ConvertedDecl::Item(
-                        mk().span(span)
-                            .pub_()
-                            .call_attr("derive", vec!["Copy", "Clone"])
-                            .call_attr("repr", repr)
-                            .union_item(name, field_syns),
-                    )
-                })
+                    mk().span(span)
+                        .pub_()
+                        .call_attr("derive", vec!["Copy", "Clone"])
+                        .call_attr("repr", repr)
+                        .union_item(name, field_syns)
+                };
+
+                let mut items = vec![union];
+                if self.tcfg.emit_layout_asserts {
+                    items.extend(self.convert_layout_asserts(decl_id)?);
+                }
+                Ok(ConvertedDecl::Items(items))
             }
 
             Field { .. } => Err(TranslationError::generic(
@@ -1897,8 +2110,36 @@ impl<'c> Translation<'c> {
                     }
                 }
 
+                // Bindings declare the externally visible functions, which
+                // stay defined in C, and omit the rest
+                let body = if self.tcfg.bindings_only {
+                    let is_exported = is_global
+                        && (!is_inline
+                            || (is_extern && !attrs.contains(&c_ast::Attribute::GnuInline)));
+                    if !is_exported {
+                        return Ok(ConvertedDecl::NoItem);
+                    }
+                    None
+                } else {
+                    body
+                };
+
                 let is_main = self.ast_context.c_main == Some(decl_id);
 
+                // Internal functions defined in another module are imported
+                // from it rather than declared
+                let internal_fn = self.internal_fns.get(&decl_id);
+                if let (None, Some(internal_fn)) = (body, internal_fn) {
+                    let path = iter::once("crate")
+                        .chain(internal_fn.module_path.iter().map(String::as_str))
+                        .chain(iter::once(internal_fn.ident.as_str()))
+                        .collect::<Vec<_>>();
+                    let rename = Some(new_name).filter(|&new_name| new_name != &internal_fn.ident);
+                    return Ok(ConvertedDecl::Item(
+                        mk().span(span).use_simple_item(path, rename),
+                    ));
+                }
+
                 let converted_function = self.convert_function(
                     ctx,
                     span,
@@ -1907,6 +2148,8 @@ impl<'c> Translation<'c> {
                     is_main,
                     is_variadic,
                     is_extern,
+                    internal_fn.is_some(),
+                    self.unsafe_decls.safe_fns.contains(&decl_id),
                     new_name,
                     name,
                     &args,
@@ -1924,6 +2167,8 @@ impl<'c> Translation<'c> {
                         is_main,
                         is_variadic,
                         is_extern,
+                        false,
+                        false,
                         new_name,
                         name,
                         &args,
@@ -1962,24 +2207,25 @@ impl<'c> Translation<'c> {
                 ))
             }
 
-            // Externally-visible variable without initializer (definition elsewhere)
+            // Externally-visible variable without initializer (definition elsewhere),
+            // or defined in C when emitting bindings
             Variable {
                 is_externally_visible: true,
                 has_static_duration,
                 has_thread_duration,
-                is_defn: false,
+                is_defn,
                 ref ident,
                 initializer,
                 typ,
                 ref attrs,
                 ..
-            } => {
+            } if !is_defn || self.tcfg.bindings_only => {
                 assert!(
                     has_static_duration || has_thread_duration,
                     "An extern variable must be static or thread-local"
                 );
                 assert!(
-                    initializer.is_none(),
+                    is_defn || initializer.is_none(),
                     "An extern variable that isn't a definition can't have an initializer"
                 );
 
@@ -1994,8 +2240,9 @@ impl<'c> Translation<'c> {
                     .expect("Variables should already be renamed");
                 let ConvertedVariable { ty, mutbl, init: _ } =
                     self.convert_variable(ctx.static_(), None, typ)?;
-                // When putting extern statics into submodules, they need to be public to be accessible
-                let visibility = if self.tcfg.reorganize_definitions {
+                // When putting extern statics into submodules or emitting bindings, they need to
+                // be public to be accessible
+                let visibility = if self.tcfg.headers_in_submodules() || self.tcfg.bindings_only {
                     "pub"
                 } else {
                     ""
@@ -2022,6 +2269,15 @@ impl<'c> Translation<'c> {
                 ))
             }
 
+            // Variables with internal linkage are not part of the bindings
+            Variable {
+                has_static_duration,
+                has_thread_duration,
+                ..
+            } if self.tcfg.bindings_only && (has_static_duration || has_thread_duration) => {
+                Ok(ConvertedDecl::NoItem)
+            }
+
             // Static-storage or thread-local variable with initializer (definition here)
             Variable {
                 has_static_duration,
@@ -2238,6 +2494,8 @@ impl<'c> Translation<'c> {
         is_main: bool,
         is_variadic: bool,
         is_extern: bool,
+        is_internal: bool,
+        is_safe: bool,
         new_name: &str,
         name: &str,
         arguments: &[(CDeclId, String, CQualTypeId)],
@@ -2247,15 +2505,24 @@ impl<'c> Translation<'c> {
     ) -> TranslationResult<ConvertedDecl> {
         self.function_context.borrow_mut().enter_new(name);
 
+        // With `--emit-no-libc`, C library functions provided by the generated
+        // `libc` module are declared as wrappers around it instead of being
+        // imported from the system C library.
+        let is_libc_wrapper = body.is_none()
+            && !is_variadic
+            && self.tcfg.emit_no_libc
+            && build_files::is_libc_shim_function(name);
+
         self.with_scope(|| {
             let mut args: Vec<FnArg> = vec![];
+            let mut arg_names = vec![];
 
             // handle regular (non-variadic) arguments
             for &(decl_id, ref var, typ) in arguments {
                 let ConvertedVariable { ty, mutbl, init: _ } =
                     self.convert_variable(ctx, None, typ)?;
 
-                let pat = if var.is_empty() {
+                let pat = if var.is_empty() && !is_libc_wrapper {
                     mk().wild_pat()
                 } else {
                     // extern function declarations don't support/require mut patterns
@@ -2265,16 +2532,20 @@ impl<'c> Translation<'c> {
                         mutbl
                     };
 
-                    let new_var = self
-                        .renamer
-                        .borrow_mut()
-                        .insert(decl_id, var.as_str())
-                        .unwrap_or_else(|| {
-                            panic!(
-                                "Failed to insert argument '{}' while converting '{}'",
-                                var, name
-                            )
-                        });
+                    let new_var = if var.is_empty() {
+                        self.renamer.borrow_mut().pick_name("arg")
+                    } else {
+                        self.renamer
+                            .borrow_mut()
+                            .insert(decl_id, &self.rust_decl_name(decl_id, var))
+                            .unwrap_or_else(|| {
+                                panic!(
+                                    "Failed to insert argument '{}' while converting '{}'",
+                                    var, name
+                                )
+                            })
+                    };
+                    arg_names.push(new_var.clone());
 
                     mk().set_mutbl(mutbl).ident_pat(new_var)
                 };
@@ -2351,6 +2622,9 @@ impl<'c> Translation<'c> {
                 };
                 body_stmts.append(&mut self.convert_function_body(ctx, name, body_ids, ret)?);
                 let mut block = stmts_block(body_stmts);
+                if self.tcfg.minimize_unsafe {
+                    block = self.minimize_unsafe(block, !is_safe);
+                }
                 if let Some(span) = self.get_span(SomeId::Stmt(body)) {
                     block.set_span(span);
                 }
@@ -2361,8 +2635,11 @@ impl<'c> Translation<'c> {
                     is_inline && is_extern && !attrs.contains(&c_ast::Attribute::GnuInline);
 
                 // Only add linkage attributes if the function is `extern`
+                // Functions only called from within the crate keep the Rust ABI
                 let mut mk_ = if is_main {
                     mk()
+                } else if is_internal {
+                    mk().vis("pub(crate)")
                 } else if (is_global && !is_inline) || is_extern_inline {
                     mk_linkage(false, new_name, name).extern_("C").pub_()
                 } else if self.cur_file.borrow().is_some() {
@@ -2406,19 +2683,45 @@ impl<'c> Translation<'c> {
                     // specifies internal linkage in all other cases due to name mangling by rustc.
                 }
 
-                Ok(ConvertedDecl::Item(
-                    mk_.span(span).unsafe_().fn_item(decl, block),
-                ))
+                if !is_safe {
+                    mk_ = mk_.unsafe_();
+                }
+
+                Ok(ConvertedDecl::Item(mk_.span(span).fn_item(decl, block)))
             } else {
                 // Translating an extern function declaration
 
-                // When putting extern fns into submodules, they need to be public to be accessible
-                let visibility = if self.tcfg.reorganize_definitions {
+                // When putting extern fns into submodules or emitting bindings, they need to
+                // be public to be accessible
+                let visibility = if self.tcfg.headers_in_submodules() || self.tcfg.bindings_only {
                     "pub"
                 } else {
                     ""
                 };
 
+                if is_libc_wrapper {
+                    // `libc::name(arg as _, ...) as _`, as the argument and return types of
+                    // the declaration may be spelled differently from those in `libc`
+                    let args = arg_names
+                        .iter()
+                        .map(|arg| mk().cast_expr(mk().ident_expr(arg), mk().infer_ty()))
+                        .collect();
+                    let mut call = mk().call_expr(mk().path_expr(vec!["libc", name]), args);
+                    if return_type.is_some() && !is_void_ret {
+                        call = mk().cast_expr(call, mk().infer_ty());
+                    }
+                    let block = mk().block(vec![mk().expr_stmt(call)]);
+
+                    return Ok(ConvertedDecl::Item(
+                        mk().span(span)
+                            .vis(visibility)
+                            .single_attr("inline")
+                            .unsafe_()
+                            .extern_("C")
+                            .fn_item(decl, block),
+                    ));
+                }
+
                 let mut mk_ = mk_linkage(true, new_name, name).span(span).vis(visibility);
 
                 for attr in attrs {
@@ -2642,7 +2945,7 @@ impl<'c> Translation<'c> {
                 let ident2 = self
                     .renamer
                     .borrow_mut()
-                    .insert_root(decl_id, ident)
+                    .insert_root(decl_id, &self.rust_decl_name(decl_id, ident))
                     .ok_or_else(|| {
                         TranslationError::generic(
                             "Unable to rename function scoped static initializer",
@@ -2692,7 +2995,7 @@ impl<'c> Translation<'c> {
                 let rust_name = self
                     .renamer
                     .borrow_mut()
-                    .insert(decl_id, ident)
+                    .insert(decl_id, &self.rust_decl_name(decl_id, ident))
                     .unwrap_or_else(|| panic!("Failed to insert variable '{}'", ident));
 
                 if self.ast_context.is_va_list(typ.ctype) {
@@ -2780,7 +3083,10 @@ impl<'c> Translation<'c> {
 
             ref decl => {
                 let inserted = if let Some(ident) = decl.get_name() {
-                    self.renamer.borrow_mut().insert(decl_id, ident).is_some()
+                    self.renamer
+                        .borrow_mut()
+                        .insert(decl_id, &self.rust_decl_name(decl_id, ident))
+                        .is_some()
                 } else {
                     false
                 };
@@ -3219,6 +3525,15 @@ impl<'c> Translation<'c> {
             self.ast_context[expr_id]
         );
 
+        // Of the subexpressions of an lvalue, only the array being indexed and the struct whose
+        // field is accessed name (part of) the same place; all others, like the index, are read.
+        if !matches!(
+            expr_kind,
+            CExprKind::ArraySubscript(..) | CExprKind::Member(..) | CExprKind::Paren(..)
+        ) {
+            ctx = ctx.set_lvalue(false);
+        }
+
         if self.tcfg.translate_const_macros {
             if let Some(converted) = self.convert_macro_expansion(ctx, expr_id)? {
                 return Ok(converted);
@@ -3311,7 +3626,7 @@ impl<'c> Translation<'c> {
                     .ok_or_else(|| format_err!("name not declared: '{}'", varname))?;
 
                 // Import the referenced global decl into our submodule
-                if self.tcfg.reorganize_definitions {
+                if self.tcfg.headers_in_submodules() {
                     if let Some(cur_file) = self.cur_file.borrow().as_ref() {
                         self.add_import(*cur_file, decl_id, &rustname);
                         // match decl {
@@ -3462,9 +3777,22 @@ impl<'c> Translation<'c> {
                     CastKind::FunctionToPointerDecay | CastKind::BuiltinFnToFnPtr => {
                         ctx.needs_address = true;
                     }
+                    // An array decaying to a pointer to mutable elements is borrowed mutably
+                    // by `as_mut_ptr`, so an element of an outer array it is part of must be too
+                    CastKind::ArrayToPointerDecay if !ctx.is_static => {
+                        let pointee_is_const = self
+                            .ast_context
+                            .get_pointee_qual_type(ty.ctype)
+                            .map_or(true, |pointee| pointee.qualifiers.is_const);
+                        ctx = ctx.set_lvalue(!pointee_is_const);
+                    }
                     _ => {}
                 }
 
+                if let Some(val) = self.convert_atomic_read(ctx, kind, expr)? {
+                    return Ok(val);
+                }
+
                 let source_ty = self.ast_context[expr]
                     .kind
                     .get_qual_type()
@@ -3667,19 +3995,48 @@ impl<'c> Translation<'c> {
                             ref other => panic!("Unexpected array type {:?}", other),
                         };
 
+                        // Unchecked indexing is not available in constant expressions
+                        let unchecked = self.tcfg.unchecked_array_indexing
+                            && var_elt_type_id.is_none()
+                            && !ctx.is_static
+                            && !ctx.is_const;
+
                         let lhs = self.convert_expr(ctx.used(), arr)?;
-                        Ok(lhs.map(|lhs| {
+                        let mut val = lhs.map(|lhs| {
                             // stmts.extend(lhs.stmts_mut());
                             // is_unsafe = is_unsafe || lhs.is_unsafe();
 
                             // Don't dereference the offset if we're still within the variable portion
                             if let Some(elt_type_id) = var_elt_type_id {
                                 let mul = self.compute_size_of_expr(elt_type_id);
-                                pointer_offset(lhs, rhs, mul, false, true)
+                                pointer_offset(
+                                    lhs,
+                                    rhs,
+                                    mul,
+                                    false,
+                                    true,
+                                    self.signed_overflow(ctx),
+                                )
+                            } else if unchecked {
+                                // Only borrow the array mutably if the element is written to
+                                let method = if ctx.is_lvalue() {
+                                    "get_unchecked_mut"
+                                } else {
+                                    "get_unchecked"
+                                };
+                                let index = cast_int(rhs, "usize", false);
+                                mk().unary_expr(
+                                    UnOp::Deref(Default::default()),
+                                    mk().method_call_expr(lhs, method, vec![index]),
+                                )
                             } else {
                                 mk().index_expr(lhs, cast_int(rhs, "usize", false))
                             }
-                        }))
+                        });
+                        if unchecked {
+                            val.set_unsafe();
+                        }
+                        Ok(val)
                     } else {
                         // LHS must be ref decayed for the offset method call's self param
                         let lhs = self.convert_expr(ctx.used().decay_ref(), *lhs)?;
@@ -3705,13 +4062,26 @@ impl<'c> Translation<'c> {
                                 };
 
                             let mul = self.compute_size_of_expr(pointee_type_id.ctype);
-                            Ok(pointer_offset(lhs, rhs, mul, false, true))
+                            Ok(pointer_offset(
+                                lhs,
+                                rhs,
+                                mul,
+                                false,
+                                true,
+                                self.signed_overflow(ctx),
+                            ))
                         })
                     }
                 })
             }
 
             Call(call_expr_ty, func, ref args) => {
+                if self.tcfg.translate_printf && ctx.is_unused() {
+                    if let Some(call) = self.convert_printf_call(ctx, func, args)? {
+                        return Ok(call);
+                    }
+                }
+
                 let fn_ty =
                     self.ast_context
                         .get_pointee_qual_type(
@@ -3811,7 +4181,8 @@ impl<'c> Translation<'c> {
                                 // Convert it directly into `x.field`
                                 self.convert_expr(ctx, subexpr_id)?
                             } else {
-                                let val = self.convert_expr(ctx, expr)?;
+                                // The pointer itself is only read
+                                let val = self.convert_expr(ctx.set_lvalue(false), expr)?;
                                 val.map(|v| mk().unary_expr(UnOp::Deref(Default::default()), v))
                             }
                         }
@@ -4292,9 +4663,13 @@ impl<'c> Translation<'c> {
 
             CastKind::LValueToRValue | CastKind::ToVoid | CastKind::ConstCast => Ok(val),
 
-            CastKind::FunctionToPointerDecay | CastKind::BuiltinFnToFnPtr => {
-                Ok(val.map(|x| mk().call_expr(mk().ident_expr("Some"), vec![x])))
-            }
+            CastKind::FunctionToPointerDecay | CastKind::BuiltinFnToFnPtr => val.result_map(|x| {
+                let x = match expr {
+                    Some(expr) => self.cast_safe_fn(expr, ty.ctype, x)?,
+                    None => x,
+                };
+                Ok(mk().call_expr(mk().ident_expr("Some"), vec![x]))
+            }),
 
             CastKind::ArrayToPointerDecay => {
                 // Because va_list is sometimes defined as a single-element
@@ -4412,6 +4787,11 @@ impl<'c> Translation<'c> {
             CastKind::VectorSplat => Err(TranslationError::generic(
                 "TODO vector splat casts not supported",
             )),
+
+            CastKind::NonAtomicToAtomic => val.result_map(|x| self.atomic_new(ty.ctype, x)),
+            CastKind::AtomicToNonAtomic => {
+                Ok(val.map(|x| mk().method_call_expr(x, "into_inner", vec![])))
+            }
         }
     }
 
@@ -4544,6 +4924,12 @@ impl<'c> Translation<'c> {
             self.null_ptr(resolved_ty_id, is_static)
                 .map(WithStmts::new_val)
         } else if let &CTypeKind::ConstantArray(elt, sz) = resolved_ty {
+            // Atomics are not `Copy`, so they cannot be repeated
+            if self.ast_context.contains_atomic(elt) {
+                return Ok(self
+                    .implicit_default_expr(elt, is_static)?
+                    .map(|elt| mk().array_expr(vec![elt; sz])));
+            }
             let sz = mk().lit_expr(mk().int_unsuffixed_lit(sz as u128));
             Ok(self
                 .implicit_default_expr(elt, is_static)?
@@ -4565,6 +4951,9 @@ impl<'c> Translation<'c> {
                 .map(|val| vec_expr(val, count)))
         } else if let &CTypeKind::Vector(CQualTypeId { ctype, .. }, len) = resolved_ty {
             self.implicit_vector_default(ctype, len, is_static)
+        } else if let &CTypeKind::Atomic(value_ty) = resolved_ty {
+            self.implicit_default_expr(value_ty.ctype, is_static)?
+                .result_map(|val| self.atomic_new(resolved_ty_id, val))
         } else {
             Err(format_err!("Unsupported default initializer: {:?}", resolved_ty).into())
         }
@@ -4798,10 +5187,13 @@ impl<'c> Translation<'c> {
     fn insert_item(&self, mut item: Box<Item>, decl: &CDecl) {
         let decl_file_id = self.ast_context.file_id(decl);
 
-        if self.tcfg.reorganize_definitions {
-            self.use_feature("register_tool");
-            let attrs = item_attrs(&mut item).expect("no attrs field on unexpected item variant");
-            add_src_loc_attr(attrs, &decl.loc.as_ref().map(|x| x.begin()));
+        if self.tcfg.headers_in_submodules() {
+            if self.tcfg.reorganize_definitions {
+                self.use_feature("register_tool");
+                let attrs =
+                    item_attrs(&mut item).expect("no attrs field on unexpected item variant");
+                add_src_loc_attr(attrs, &decl.loc.as_ref().map(|x| x.begin()));
+            }
             let mut item_stores = self.items.borrow_mut();
             let items = item_stores
                 .entry(decl_file_id.unwrap())
@@ -4818,11 +5210,13 @@ impl<'c> Translation<'c> {
     fn insert_foreign_item(&self, mut item: ForeignItem, decl: &CDecl) {
         let decl_file_id = self.ast_context.file_id(decl);
 
-        if self.tcfg.reorganize_definitions {
-            self.use_feature("register_tool");
-            let attrs = foreign_item_attrs(&mut item)
-                .expect("no attrs field on unexpected foreign item variant");
-            add_src_loc_attr(attrs, &decl.loc.as_ref().map(|x| x.begin()));
+        if self.tcfg.headers_in_submodules() {
+            if self.tcfg.reorganize_definitions {
+                self.use_feature("register_tool");
+                let attrs = foreign_item_attrs(&mut item)
+                    .expect("no attrs field on unexpected foreign item variant");
+                add_src_loc_attr(attrs, &decl.loc.as_ref().map(|x| x.begin()));
+            }
             let mut items = self.items.borrow_mut();
             let mod_block_items = items
                 .entry(decl_file_id.unwrap())
@@ -4887,6 +5281,7 @@ impl<'c> Translation<'c> {
             | VariableArray(ctype, _)
             | Reference(CQualTypeId { ctype, .. })
             | BlockPointer(CQualTypeId { ctype, .. })
+            | Atomic(CQualTypeId { ctype, .. })
             | TypeOf(ctype)
             | Complex(ctype) => self.import_type(*ctype, decl_file_id),
             Enum(decl_id) | Typedef(decl_id) | Union(decl_id) | Struct(decl_id) => {
@@ -107,7 +107,7 @@ impl<'c> Translation<'c> {
             .get_qual_type()
             .ok_or_else(|| format_err!("bad reference type"))?;
         let read = |write| self.read(reference_ty, write);
-        let reference = self.convert_expr(ctx.used(), reference)?;
+        let reference = self.convert_expr(ctx.used().set_lvalue(true), reference)?;
         reference.and_then(|reference| {
             if !uses_read && is_lvalue(&reference) {
                 Ok(WithStmts::new_val(NamedReference {
@@ -146,6 +146,7 @@ impl<'c> Translation<'c> {
                         self.convert_expr(rhs_ctx, rhs)?.result_map(|rhs_val| {
                             let expr_ids = Some((lhs, rhs));
                             self.convert_binary_operator(
+                                ctx,
                                 op,
                                 ty,
                                 type_id.ctype,
@@ -164,6 +165,7 @@ impl<'c> Translation<'c> {
 
     fn convert_assignment_operator_aux(
         &self,
+        ctx: ExprContext,
         bin_op_kind: BinOp,
         bin_op: c_ast::BinOp,
         read: Box<Expr>,
@@ -202,6 +204,7 @@ impl<'c> Translation<'c> {
             };
             let ty = self.convert_type(compute_res_ty.ctype)?;
             let val = self.convert_binary_operator(
+                ctx,
                 bin_op,
                 ty,
                 compute_res_ty.ctype,
@@ -243,6 +246,14 @@ impl<'c> Translation<'c> {
         compute_type: Option<CQualTypeId>,
         result_type: Option<CQualTypeId>,
     ) -> TranslationResult<WithStmts<Box<Expr>>> {
+        let is_atomic = self.is_atomic_lvalue(lhs);
+        // The value stored to an atomic is the operand of its conversion to the atomic type
+        let rhs = match self.ast_context[rhs].kind {
+            CExprKind::ImplicitCast(_, expr, CastKind::NonAtomicToAtomic, _, _) if is_atomic => {
+                expr
+            }
+            _ => rhs,
+        };
         let rhs_type_id = self
             .ast_context
             .index(rhs)
@@ -250,6 +261,18 @@ impl<'c> Translation<'c> {
             .get_qual_type()
             .ok_or_else(|| format_err!("bad assignment rhs type"))?;
         let rhs_translation = self.convert_expr(ctx.used(), rhs)?;
+        if is_atomic {
+            return self.convert_atomic_assignment(
+                ctx,
+                op,
+                lhs,
+                rhs_translation,
+                rhs_type_id,
+                compute_type,
+                result_type,
+                false,
+            );
+        }
         self.convert_assignment_operator_with_rhs(
             ctx,
             op,
@@ -324,12 +347,17 @@ impl<'c> Translation<'c> {
             _ => None,
         };
 
-        let is_unsigned_arith = match op {
+        // Arithmetic translated into method calls has no compound assignment
+        let is_method_arith = match op {
             c_ast::BinOp::AssignAdd
             | c_ast::BinOp::AssignSubtract
             | c_ast::BinOp::AssignMultiply
             | c_ast::BinOp::AssignDivide
-            | c_ast::BinOp::AssignModulus => compute_type_kind.is_unsigned_integral_type(),
+            | c_ast::BinOp::AssignModulus => {
+                compute_type_kind.is_unsigned_integral_type()
+                    || (compute_type_kind.is_signed_integral_type()
+                        && self.signed_overflow(ctx) != SignedOverflow::Plain)
+            }
             _ => false,
         };
 
@@ -337,7 +365,7 @@ impl<'c> Translation<'c> {
             || ctx.is_used()
             || pointer_lhs.is_some()
             || is_volatile_compound_assign
-            || is_unsigned_arith
+            || is_method_arith
         {
             self.name_reference_write_read(ctx, lhs)?
         } else {
@@ -365,7 +393,7 @@ impl<'c> Translation<'c> {
                         )?),
 
                         // Anything volatile needs to be desugared into explicit reads and writes
-                        op if is_volatile || is_unsigned_arith => {
+                        op if is_volatile || is_method_arith => {
                             let mut is_unsafe = false;
                             let op = op
                                 .underlying_assignment()
@@ -373,6 +401,7 @@ impl<'c> Translation<'c> {
 
                             let val = if compute_lhs_type_id.ctype == initial_lhs_type_id.ctype {
                                 self.convert_binary_operator(
+                                    ctx,
                                     op,
                                     ty,
                                     qtype.ctype,
@@ -388,6 +417,7 @@ impl<'c> Translation<'c> {
                                 let lhs = mk().cast_expr(read.clone(), lhs_type.clone());
                                 let ty = self.convert_type(result_type_id.ctype)?;
                                 let val = self.convert_binary_operator(
+                                    ctx,
                                     op,
                                     ty,
                                     result_type_id.ctype,
@@ -427,12 +457,26 @@ impl<'c> Translation<'c> {
                         // Everything else
                         AssignAdd if pointer_lhs.is_some() => {
                             let mul = self.compute_size_of_expr(pointer_lhs.unwrap().ctype);
-                            let ptr = pointer_offset(write.clone(), rhs, mul, false, false);
+                            let ptr = pointer_offset(
+                                write.clone(),
+                                rhs,
+                                mul,
+                                false,
+                                false,
+                                self.signed_overflow(ctx),
+                            );
                             WithStmts::new_val(mk().assign_expr(write, ptr))
                         }
                         AssignSubtract if pointer_lhs.is_some() => {
                             let mul = self.compute_size_of_expr(pointer_lhs.unwrap().ctype);
-                            let ptr = pointer_offset(write.clone(), rhs, mul, true, false);
+                            let ptr = pointer_offset(
+                                write.clone(),
+                                rhs,
+                                mul,
+                                true,
+                                false,
+                                self.signed_overflow(ctx),
+                            );
                             WithStmts::new_val(mk().assign_expr(write, ptr))
                         }
 
@@ -447,6 +491,7 @@ impl<'c> Translation<'c> {
                                     mul,
                                     op == AssignSubtract,
                                     false,
+                                    self.signed_overflow(ctx),
                                 );
                                 WithStmts::new_val(mk().assign_expr(write, ptr))
                             } else {
@@ -468,6 +513,7 @@ impl<'c> Translation<'c> {
                                     _ => panic!("Cannot convert non-assignment operator"),
                                 };
                                 self.convert_assignment_operator_aux(
+                                    ctx,
                                     bin_op_kind,
                                     bin_op,
                                     read.clone(),
@@ -490,10 +536,21 @@ impl<'c> Translation<'c> {
         })
     }
 
+    /// The translation of signed integer arithmetic in `ctx`. Statics and
+    /// constants are evaluated at compile time, which already rejects
+    /// overflow, and cannot call `Option::expect`.
+    pub(crate) fn signed_overflow(&self, ctx: ExprContext) -> SignedOverflow {
+        match self.tcfg.signed_overflow {
+            SignedOverflow::Checked if ctx.is_static || ctx.is_const => SignedOverflow::Plain,
+            overflow => overflow,
+        }
+    }
+
     /// Translate a non-assignment binary operator. It is expected that the `lhs` and `rhs`
     /// arguments be usable as rvalues.
-    fn convert_binary_operator(
+    pub(crate) fn convert_binary_operator(
         &self,
+        ctx: ExprContext,
         op: c_ast::BinOp,
         ty: Box<Type>,
         ctype: CTypeId,
@@ -508,14 +565,25 @@ impl<'c> Translation<'c> {
             .index(ctype)
             .kind
             .is_unsigned_integral_type();
+        let is_signed_integral_type = self
+            .ast_context
+            .resolve_type(ctype)
+            .kind
+            .is_signed_integral_type();
+        let overflow = self.signed_overflow(ctx);
 
         match op {
-            c_ast::BinOp::Add => self.convert_addition(lhs_type, rhs_type, lhs, rhs),
-            c_ast::BinOp::Subtract => self.convert_subtraction(ty, lhs_type, rhs_type, lhs, rhs),
+            c_ast::BinOp::Add => self.convert_addition(ctx, lhs_type, rhs_type, lhs, rhs),
+            c_ast::BinOp::Subtract => {
+                self.convert_subtraction(ctx, ty, lhs_type, rhs_type, lhs, rhs)
+            }
 
             c_ast::BinOp::Multiply if is_unsigned_integral_type => {
                 Ok(mk().method_call_expr(lhs, mk().path_segment("wrapping_mul"), vec![rhs]))
             }
+            c_ast::BinOp::Multiply if is_signed_integral_type => {
+                Ok(overflow.binary_expr(BinOp::Mul(Default::default()), lhs, rhs))
+            }
             c_ast::BinOp::Multiply => {
                 Ok(mk().binary_expr(BinOp::Mul(Default::default()), lhs, rhs))
             }
@@ -523,11 +591,17 @@ impl<'c> Translation<'c> {
             c_ast::BinOp::Divide if is_unsigned_integral_type => {
                 Ok(mk().method_call_expr(lhs, mk().path_segment("wrapping_div"), vec![rhs]))
             }
+            c_ast::BinOp::Divide if is_signed_integral_type => {
+                Ok(overflow.binary_expr(BinOp::Div(Default::default()), lhs, rhs))
+            }
             c_ast::BinOp::Divide => Ok(mk().binary_expr(BinOp::Div(Default::default()), lhs, rhs)),
 
             c_ast::BinOp::Modulus if is_unsigned_integral_type => {
                 Ok(mk().method_call_expr(lhs, mk().path_segment("wrapping_rem"), vec![rhs]))
             }
+            c_ast::BinOp::Modulus if is_signed_integral_type => {
+                Ok(overflow.binary_expr(BinOp::Rem(Default::default()), lhs, rhs))
+            }
             c_ast::BinOp::Modulus => Ok(mk().binary_expr(BinOp::Rem(Default::default()), lhs, rhs)),
 
             c_ast::BinOp::BitXor => {
@@ -617,6 +691,7 @@ impl<'c> Translation<'c> {
 
     fn convert_addition(
         &self,
+        ctx: ExprContext,
         lhs_type_id: CQualTypeId,
         rhs_type_id: CQualTypeId,
         lhs: Box<Expr>,
@@ -627,12 +702,29 @@ impl<'c> Translation<'c> {
 
         if let &CTypeKind::Pointer(pointee) = lhs_type {
             let mul = self.compute_size_of_expr(pointee.ctype);
-            Ok(pointer_offset(lhs, rhs, mul, false, false))
+            Ok(pointer_offset(
+                lhs,
+                rhs,
+                mul,
+                false,
+                false,
+                self.signed_overflow(ctx),
+            ))
         } else if let &CTypeKind::Pointer(pointee) = rhs_type {
             let mul = self.compute_size_of_expr(pointee.ctype);
-            Ok(pointer_offset(rhs, lhs, mul, false, false))
+            Ok(pointer_offset(
+                rhs,
+                lhs,
+                mul,
+                false,
+                false,
+                self.signed_overflow(ctx),
+            ))
         } else if lhs_type.is_unsigned_integral_type() {
             Ok(mk().method_call_expr(lhs, mk().path_segment("wrapping_add"), vec![rhs]))
+        } else if lhs_type.is_signed_integral_type() {
+            let add = BinOp::Add(Default::default());
+            Ok(self.signed_overflow(ctx).binary_expr(add, lhs, rhs))
         } else {
             Ok(mk().binary_expr(BinOp::Add(Default::default()), lhs, rhs))
         }
@@ -640,6 +732,7 @@ impl<'c> Translation<'c> {
 
     fn convert_subtraction(
         &self,
+        ctx: ExprContext,
         ty: Box<Type>,
         lhs_type_id: CQualTypeId,
         rhs_type_id: CQualTypeId,
@@ -660,9 +753,19 @@ impl<'c> Translation<'c> {
             Ok(mk().cast_expr(offset, ty))
         } else if let &CTypeKind::Pointer(pointee) = lhs_type {
             let mul = self.compute_size_of_expr(pointee.ctype);
-            Ok(pointer_offset(lhs, rhs, mul, true, false))
+            Ok(pointer_offset(
+                lhs,
+                rhs,
+                mul,
+                true,
+                false,
+                self.signed_overflow(ctx),
+            ))
         } else if lhs_type.is_unsigned_integral_type() {
             Ok(mk().method_call_expr(lhs, mk().path_segment("wrapping_sub"), vec![rhs]))
+        } else if lhs_type.is_signed_integral_type() {
+            let sub = BinOp::Sub(Default::default());
+            Ok(self.signed_overflow(ctx).binary_expr(sub, lhs, rhs))
         } else {
             Ok(mk().binary_expr(BinOp::Sub(Default::default()), lhs, rhs))
         }
@@ -786,7 +889,16 @@ impl<'c> Translation<'c> {
                     } else {
                         BinOp::Sub(Default::default())
                     };
-                    mk().binary_expr(k, read, one)
+                    if self
+                        .ast_context
+                        .resolve_type(ty.ctype)
+                        .kind
+                        .is_signed_integral_type()
+                    {
+                        self.signed_overflow(ctx).binary_expr(k, read, one)
+                    } else {
+                        mk().binary_expr(k, read, one)
+                    }
                 };
 
                 // *p = *p + rhs
@@ -836,10 +948,14 @@ impl<'c> Translation<'c> {
                 // In this translation, there are only pointers to functions and
                 // & becomes a no-op when applied to a function.
 
+                let arg_id = arg;
                 let arg = self.convert_expr(ctx.used().set_needs_address(true), arg)?;
 
                 if self.ast_context.is_function_pointer(ctype) {
-                    Ok(arg.map(|x| mk().call_expr(mk().ident_expr("Some"), vec![x])))
+                    arg.result_map(|x| {
+                        let x = self.cast_safe_fn(arg_id, ctype, x)?;
+                        Ok(mk().call_expr(mk().ident_expr("Some"), vec![x]))
+                    })
                 } else {
                     let pointee_ty =
                         self.ast_context
@@ -885,6 +1001,14 @@ impl<'c> Translation<'c> {
                     })
                 }
             }
+            c_ast::UnOp::PreIncrement
+            | c_ast::UnOp::PreDecrement
+            | c_ast::UnOp::PostIncrement
+            | c_ast::UnOp::PostDecrement
+                if self.is_atomic_lvalue(arg) =>
+            {
+                self.convert_atomic_increment(ctx, name, arg)
+            }
             c_ast::UnOp::PreIncrement => self.convert_pre_increment(ctx, cqual_type, true, arg),
             c_ast::UnOp::PreDecrement => self.convert_pre_increment(ctx, cqual_type, false, arg),
             c_ast::UnOp::PostIncrement => self.convert_post_increment(ctx, cqual_type, true, arg),
@@ -925,6 +1049,11 @@ impl<'c> Translation<'c> {
 
                 if resolved_ctype.kind.is_unsigned_integral_type() {
                     Ok(val.map(wrapping_neg_expr))
+                } else if resolved_ctype.kind.is_signed_integral_type()
+                    // Negating a literal, which is never negative, cannot overflow
+                    && !matches!(self.ast_context[arg].kind, CExprKind::Literal(..))
+                {
+                    Ok(val.map(|v| self.signed_overflow(ctx).neg_expr(v)))
                 } else {
                     Ok(val.map(neg_expr))
                 }
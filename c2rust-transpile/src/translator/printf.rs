@@ -0,0 +1,275 @@
+//! Translation of `printf`-family calls with literal format strings into the
+//! formatting macros of the `c2rust-printf` crate, which reproduce the output
+//! of each C conversion specification byte for byte.
+
+use super::*;
+use c2rust_printf::{Amount, Conversion, Piece, Pieces, Spec};
+use syn::__private::ToTokens;
+
+/// Where a `printf`-family call writes its output.
+enum PrintfDest {
+    Stdout,
+    Stderr,
+    /// `sprintf` and `snprintf`: the destination buffer and, for
+    /// `snprintf`, its size.
+    Buffer(CExprId, Option<CExprId>),
+}
+
+/// The arguments consumed by a single conversion specification.
+struct PrintfArg<'a> {
+    spec_text: &'a str,
+    width: Option<CExprId>,
+    precision: Option<CExprId>,
+    value: CExprId,
+}
+
+impl<'c> Translation<'c> {
+    /// Match a call to an external `printf`, `fprintf` to `stdout` or
+    /// `stderr`, `sprintf`, or `snprintf` with a literal format string.
+    fn match_printf_call<'a>(
+        &self,
+        func: CExprId,
+        args: &'a [CExprId],
+    ) -> Option<(PrintfDest, String, &'a [CExprId])> {
+        let func = match self.ast_context[func].kind {
+            CExprKind::ImplicitCast(_, fexp, CastKind::FunctionToPointerDecay, _, _) => fexp,
+            _ => return None,
+        };
+        let name = match self.ast_context[func].kind {
+            CExprKind::DeclRef(_, decl_id, _) => match self.ast_context[decl_id].kind {
+                CDeclKind::Function {
+                    ref name,
+                    body: None,
+                    ..
+                } => name.as_str(),
+                _ => return None,
+            },
+            _ => return None,
+        };
+
+        let (dest, format_idx) = match (name, args) {
+            ("printf", _) => (PrintfDest::Stdout, 0),
+            ("fprintf", [stream, ..]) => {
+                let (_, stream) = self.ast_context.resolve_expr(*stream);
+                let stream = match *stream {
+                    CExprKind::DeclRef(_, decl_id, _) => match self.ast_context[decl_id].kind {
+                        CDeclKind::Variable { ref ident, .. } => ident.as_str(),
+                        _ => return None,
+                    },
+                    _ => return None,
+                };
+                match stream {
+                    // `__stdoutp` and `__stderrp` are used on macOS
+                    "stdout" | "__stdoutp" => (PrintfDest::Stdout, 1),
+                    "stderr" | "__stderrp" => (PrintfDest::Stderr, 1),
+                    _ => return None,
+                }
+            }
+            ("sprintf", [buf, ..]) => (PrintfDest::Buffer(*buf, None), 1),
+            ("snprintf", [buf, size, ..]) => (PrintfDest::Buffer(*buf, Some(*size)), 2),
+            _ => return None,
+        };
+
+        let format = match self.ast_context.resolve_expr(*args.get(format_idx)?).1 {
+            CExprKind::Literal(_, CLiteral::String(bytes, 1)) => {
+                // Formatting stops at the first NUL
+                let len = bytes.iter().position(|&b| b == 0).unwrap_or(bytes.len());
+                String::from_utf8(bytes[..len].to_vec()).ok()?
+            }
+            _ => return None,
+        };
+
+        Some((dest, format, &args[format_idx + 1..]))
+    }
+
+    /// Check that the C type of `expr` can be formatted by `conversion`.
+    fn is_printf_arg_compatible(&self, conversion: Conversion, expr: CExprId) -> bool {
+        use CTypeKind::*;
+        let ty = match self.ast_context[expr].kind.get_type() {
+            Some(ty) => ty,
+            None => return false,
+        };
+        match self.ast_context.resolve_type(ty).kind {
+            Char | SChar | UChar | Short | UShort | Int | UInt | Long | ULong | LongLong
+            | ULongLong => conversion.is_integer(),
+            Float | Double => matches!(conversion, Conversion::Float { .. }),
+            // Function pointers are translated as `Option<fn>`
+            Pointer(pointee) => {
+                matches!(conversion, Conversion::Str | Conversion::Pointer)
+                    && !matches!(
+                        self.ast_context.resolve_type(pointee.ctype).kind,
+                        Function(..)
+                    )
+            }
+            _ => false,
+        }
+    }
+
+    /// Split the arguments of a `printf`-family call among the conversion
+    /// specifications of its format string, checking that each argument has
+    /// a type the specification can format.
+    fn match_printf_args<'a>(
+        &self,
+        specs: &[(Spec, &'a str)],
+        args: &[CExprId],
+    ) -> Option<Vec<PrintfArg<'a>>> {
+        let num_args = specs.iter().map(|(spec, _)| spec.num_args()).sum::<usize>();
+        if num_args != args.len() {
+            return None;
+        }
+
+        let mut args = args.iter().copied();
+        let mut printf_args = vec![];
+        for &(spec, spec_text) in specs {
+            // `*` widths and precisions are `int` arguments preceding the value
+            let mut amount_arg = |amount: Option<Amount>| match amount {
+                Some(Amount::Arg) => args
+                    .next()
+                    .filter(|&arg| self.is_printf_int_arg(arg))
+                    .map(Some),
+                _ => Some(None),
+            };
+            let width = amount_arg(spec.width)?;
+            let precision = amount_arg(spec.precision)?;
+            let value = args
+                .next()
+                .filter(|&arg| self.is_printf_arg_compatible(spec.conversion, arg))?;
+            printf_args.push(PrintfArg {
+                spec_text,
+                width,
+                precision,
+                value,
+            });
+        }
+        Some(printf_args)
+    }
+
+    fn is_printf_int_arg(&self, expr: CExprId) -> bool {
+        self.ast_context[expr].kind.get_type().map_or(false, |ty| {
+            matches!(self.ast_context.resolve_type(ty).kind, CTypeKind::Int)
+        })
+    }
+
+    /// Translate a call to a `printf`-family function whose result is unused
+    /// into `print_c!`, `eprint_c!` or `write_c!`, which write the literal
+    /// text and formatted arguments as raw bytes. Returns `None`, leaving the
+    /// call to be translated as is, if the format string is not a literal or
+    /// uses a conversion that cannot be reproduced.
+    pub fn convert_printf_call(
+        &self,
+        ctx: ExprContext,
+        func: CExprId,
+        args: &[CExprId],
+    ) -> TranslationResult<Option<WithStmts<Box<Expr>>>> {
+        let (dest, format, args) = match self.match_printf_call(func, args) {
+            Some(call) => call,
+            None => return Ok(None),
+        };
+        // `print!` and `eprint!` are only available with `std`
+        if self.tcfg.emit_no_std && !matches!(dest, PrintfDest::Buffer(..)) {
+            return Ok(None);
+        }
+
+        // The literal text before, between and after the conversion specifications
+        let mut texts = vec![];
+        let mut specs = vec![];
+        let mut text = vec![];
+        for piece in Pieces::new(&format) {
+            match piece {
+                Ok(Piece::Text(s)) => text.extend_from_slice(s.as_bytes()),
+                Ok(Piece::Percent) => text.push(b'%'),
+                Ok(Piece::Spec(spec, spec_text)) => {
+                    texts.push(mem::take(&mut text));
+                    specs.push((spec, spec_text));
+                }
+                Err(_) => return Ok(None),
+            }
+        }
+        texts.push(text);
+        let printf_args = match self.match_printf_args(&specs, args) {
+            Some(printf_args) => printf_args,
+            None => return Ok(None),
+        };
+
+        self.use_crate(ExternCrate::C2RustPrintf);
+
+        let mut stmts = vec![];
+        let mut macro_args = vec![];
+        let mut convert = |expr: CExprId| -> TranslationResult<Box<Expr>> {
+            let mut expr = self.convert_expr(ctx.used().decay_ref(), expr)?;
+            stmts.append(expr.stmts_mut());
+            Ok(expr.into_value())
+        };
+
+        let mac_name = match dest {
+            PrintfDest::Stdout => "print_c",
+            PrintfDest::Stderr => "eprint_c",
+            PrintfDest::Buffer(buf, size) => {
+                self.with_cur_file_item_store(|item_store| {
+                    item_store.add_use(vec!["c2rust_printf".into()], "CBuffer");
+                });
+                let buf = convert(buf)?;
+                let size = match size {
+                    Some(size) => mk().cast_expr(convert(size)?, mk().path_ty(vec!["usize"])),
+                    None => mk().path_expr(vec!["usize", "MAX"]),
+                };
+                let buf = mk().call_expr(mk().path_expr(vec!["CBuffer", "new"]), vec![buf, size]);
+                macro_args.push(buf);
+                "write_c"
+            }
+        };
+
+        if !printf_args.is_empty() {
+            self.with_cur_file_item_store(|item_store| {
+                item_store.add_use(vec!["c2rust_printf".into()], "Formatted");
+            });
+        }
+        let mut texts = texts.into_iter();
+        for arg in printf_args {
+            let text = texts.next().unwrap_or_default();
+            if !text.is_empty() {
+                macro_args.push(mk().lit_expr(text));
+            }
+            // `Formatted::new("%5.2f", value).width(w).precision(p)`
+            let width = arg.width.map(&mut convert).transpose()?;
+            let precision = arg.precision.map(&mut convert).transpose()?;
+            let value = convert(arg.value)?;
+            let mut formatted = mk().call_expr(
+                mk().path_expr(vec!["Formatted", "new"]),
+                vec![mk().lit_expr(arg.spec_text), value],
+            );
+            if let Some(width) = width {
+                formatted = mk().method_call_expr(formatted, "width", vec![width]);
+            }
+            if let Some(precision) = precision {
+                formatted = mk().method_call_expr(formatted, "precision", vec![precision]);
+            }
+            macro_args.push(formatted);
+        }
+        let text = texts.next().unwrap_or_default();
+        if !text.is_empty() {
+            macro_args.push(mk().lit_expr(text));
+        }
+
+        let mut tokens = vec![];
+        for (i, arg) in macro_args.into_iter().enumerate() {
+            if i > 0 {
+                tokens.push(TokenTree::Punct(Punct::new(',', Alone)));
+            }
+            tokens.extend(arg.to_token_stream());
+        }
+        let mac = mk().mac_expr(mk().mac(
+            mk().path(vec!["c2rust_printf", mac_name]),
+            tokens.into_iter().collect::<TokenStream>(),
+            MacroDelimiter::Paren(Default::default()),
+        ));
+        stmts.push(mk().semi_stmt(mac));
+
+        let mut res = WithStmts::new(
+            stmts,
+            self.panic_or_err("printf call expression is not supposed to be used"),
+        );
+        res.set_unsafe();
+        Ok(Some(res))
+    }
+}
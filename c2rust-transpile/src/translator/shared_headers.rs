@@ -0,0 +1,342 @@
+//! Sharing of header declarations between translation units (`--dedup-headers`).
+//!
+//! Each header is translated into a submodule of the crate's `headers` module
+//! the first time a translation unit includes it. Later translation units
+//! import that submodule instead of emitting their own copy, unless their
+//! translation of the header differs, in which case they keep a private copy.
+
+use std::path::{Path, PathBuf};
+
+use crate::diagnostics::{diag, Diagnostic};
+use crate::rust_ast::item_store::PathedMultiImports;
+
+use super::*;
+use syn::__private::ToTokens;
+
+/// The contents of the crate's `headers` module, accumulated over the
+/// translation units of a crate.
+#[derive(Default)]
+pub struct SharedHeaders {
+    /// Module names of header files, kept consistent across translation units
+    pub(super) mod_names: IndexMap<String, PathBuf>,
+    modules: IndexMap<String, SharedHeader>,
+}
+
+#[derive(Default)]
+struct SharedHeader {
+    /// Named items by name, and unnamed items (e.g. impls) by their tokens
+    items: IndexMap<String, SharedItem<Box<Item>>>,
+    foreign_items: IndexMap<String, SharedItem<ForeignItem>>,
+    uses: PathedMultiImports,
+}
+
+struct SharedItem<T> {
+    item: T,
+    /// The translation unit the item was first translated in
+    origin: PathBuf,
+}
+
+/// Why a translation unit keeps its own copy of a header.
+enum Unshareable {
+    /// The header defines variables with internal linkage, which are distinct in
+    /// each translation unit
+    InternalLinkage,
+    /// The header refers to declarations of the translation unit itself
+    UsesMainFile,
+    /// The header refers to a header that is not shared
+    UsesUnshared(String),
+    /// The header translates differently than in another translation unit
+    Conflict { name: String, origin: PathBuf },
+}
+
+impl SharedHeaders {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.modules.is_empty()
+    }
+
+    /// Pretty-print the `headers` module.
+    pub fn into_string(self, tcfg: &TranspilerConfig) -> String {
+        let modules = self
+            .modules
+            .into_iter()
+            .map(|(mod_name, header)| {
+                let mut items = vec![];
+                if tcfg.emit_no_libc {
+                    items.push(
+                        mk().use_simple_item(mk().path(vec!["crate", "libc"]), None::<Ident>),
+                    );
+                }
+                items.extend(header.uses.into_items());
+                items.extend(header.items.into_values().map(|shared| shared.item));
+                if !header.foreign_items.is_empty() {
+                    let foreign_items = header
+                        .foreign_items
+                        .into_values()
+                        .map(|shared| shared.item)
+                        .collect();
+                    items.push(mk().extern_("C").foreign_items(foreign_items));
+                }
+                *mk().pub_().mod_item(mod_name, Some(mk().mod_(items)))
+            })
+            .collect();
+        pprust::to_string(|| syn::File {
+            shebang: None,
+            attrs: vec![],
+            items: modules,
+        })
+    }
+
+    /// Find an item of a translation unit's copy `store` of the header module
+    /// `mod_name` that an earlier translation unit translated differently.
+    /// Returns the name of the item and the earlier translation unit.
+    fn find_conflict(&self, mod_name: &str, store: &ItemStore) -> Option<(String, PathBuf)> {
+        let header = self.modules.get(mod_name)?;
+        store
+            .items()
+            .iter()
+            .filter_map(|item| {
+                let other = header.items.get(&item_key(item))?;
+                (other.item != *item).then(|| (item_key(item), other.origin.clone()))
+            })
+            .chain(store.foreign_items().iter().filter_map(|item| {
+                let (ident, _) = foreign_item_ident_vis(item)?;
+                let other = header.foreign_items.get(&ident.to_string())?;
+                (other.item != *item).then(|| (ident.to_string(), other.origin.clone()))
+            }))
+            .next()
+    }
+
+    /// Add the translation `store` of the header module `mod_name` by the
+    /// translation unit `origin`. Items that are already shared are kept as
+    /// they are.
+    fn add(&mut self, mod_name: &str, mut store: ItemStore, origin: &Path) {
+        let (items, foreign_items, uses) = store.drain();
+        let header = self.modules.entry(mod_name.to_owned()).or_default();
+        for item in items {
+            header
+                .items
+                .entry(item_key(&item))
+                .or_insert_with(|| SharedItem {
+                    item,
+                    origin: origin.to_owned(),
+                });
+        }
+        for item in foreign_items {
+            let ident = match foreign_item_ident_vis(&item) {
+                Some((ident, _)) => ident.to_string(),
+                None => continue,
+            };
+            header
+                .foreign_items
+                .entry(ident)
+                .or_insert_with(|| SharedItem {
+                    item,
+                    origin: origin.to_owned(),
+                });
+        }
+        for (path, leaves) in uses.iter() {
+            for leaf in leaves {
+                header.uses.get_mut(path.clone()).insert(leaf.as_str());
+            }
+        }
+    }
+}
+
+fn item_key(item: &Item) -> String {
+    match item_ident(item) {
+        Some(ident) => ident.to_string(),
+        None => item.to_token_stream().to_string(),
+    }
+}
+
+impl<'c> Translation<'c> {
+    /// Move the headers of this translation unit that can be shared with
+    /// other translation units into `shared`, and import them in place of
+    /// local submodules. Returns the module names of the shared headers.
+    pub(super) fn share_headers(
+        &self,
+        shared: &mut SharedHeaders,
+        main_file: &Path,
+        use_item_store: &mut ItemStore,
+    ) -> Vec<String> {
+        let mut item_stores = self.items.borrow_mut();
+
+        let mut mod_names = IndexMap::new();
+        for &file_id in item_stores.keys() {
+            if file_id != self.main_file {
+                let path = self.ast_context.get_file_path(file_id);
+                mod_names.insert(file_id, clean_path(&self.mod_names, path));
+            }
+        }
+        shared.mod_names = self.mod_names.borrow().clone();
+
+        let mut unshareable = IndexMap::new();
+        for (_, decl) in self.ast_context.iter_decls() {
+            if let CDeclKind::Variable {
+                has_static_duration: true,
+                is_externally_visible: false,
+                ..
+            } = decl.kind
+            {
+                match self.ast_context.file_id(decl) {
+                    Some(file_id) if mod_names.contains_key(&file_id) => {
+                        unshareable.insert(file_id, Unshareable::InternalLinkage);
+                    }
+                    _ => {}
+                }
+            }
+        }
+
+        for (&file_id, mod_name) in &mod_names {
+            if unshareable.contains_key(&file_id) {
+                continue;
+            }
+            let store = &item_stores[&file_id];
+            if store.uses().iter().any(|(path, _)| path.len() == 1) {
+                unshareable.insert(file_id, Unshareable::UsesMainFile);
+                continue;
+            }
+            if let Some((name, origin)) = shared.find_conflict(mod_name, store) {
+                unshareable.insert(file_id, Unshareable::Conflict { name, origin });
+            }
+        }
+
+        // Shared headers cannot refer to headers that are kept local
+        let header_uses = |file_id: &FileId| {
+            item_stores[file_id]
+                .uses()
+                .iter()
+                .filter_map(|(path, _)| match path.as_slice() {
+                    [sup, mod_name] if sup == "super" => Some(mod_name.clone()),
+                    _ => None,
+                })
+                .collect::<Vec<_>>()
+        };
+        loop {
+            let unshared = unshareable
+                .keys()
+                .map(|file_id| &mod_names[file_id])
+                .collect::<IndexSet<_>>();
+            let newly_unshared = mod_names
+                .keys()
+                .filter(|file_id| !unshareable.contains_key(*file_id))
+                .filter_map(|file_id| {
+                    let used = header_uses(file_id)
+                        .into_iter()
+                        .find(|mod_name| unshared.contains(mod_name))?;
+                    Some((*file_id, Unshareable::UsesUnshared(used)))
+                })
+                .collect::<Vec<_>>();
+            if newly_unshared.is_empty() {
+                break;
+            }
+            unshareable.extend(newly_unshared);
+        }
+
+        let mut shared_mod_names = vec![];
+        for (file_id, mod_name) in mod_names {
+            if let Some(reason) = unshareable.get(&file_id) {
+                if let Unshareable::Conflict { name, origin } = reason {
+                    let path = self.ast_context.get_file_path(file_id);
+                    diag!(
+                        Diagnostic::HeaderConflict,
+                        "`{}` from {} is translated differently in {} and {}; \
+                         keeping a private copy of the header in {}",
+                        name,
+                        path.map_or(mod_name.clone(), |path| path.display().to_string()),
+                        main_file.display(),
+                        origin.display(),
+                        main_file.display(),
+                    );
+                } else {
+                    let reason = match reason {
+                        Unshareable::InternalLinkage => {
+                            "it defines variables with internal linkage".to_string()
+                        }
+                        Unshareable::UsesMainFile => {
+                            "it refers to declarations of the translation unit".to_string()
+                        }
+                        Unshareable::UsesUnshared(other) => {
+                            format!("it refers to the unshared header module {}", other)
+                        }
+                        Unshareable::Conflict { .. } => unreachable!(),
+                    };
+                    info!(
+                        "Not sharing header module {} of {}: {}",
+                        mod_name,
+                        main_file.display(),
+                        reason
+                    );
+                }
+                continue;
+            }
+
+            let store = item_stores.shift_remove(&file_id).unwrap();
+            add_submodule_uses(
+                &mod_name,
+                store.items(),
+                store.foreign_items(),
+                use_item_store,
+            );
+            shared.add(&mod_name, store, main_file);
+            shared_mod_names.push(mod_name);
+        }
+        shared_mod_names
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The translation of a header `point.h` in a translation unit, with the
+    /// given type of the `x` field of its unnamed struct.
+    fn point_h(x_ty: &str) -> ItemStore {
+        let mut store = ItemStore::new();
+        let items = [
+            format!(
+                "pub struct C2RustUnnamed_point_h {{ pub x: {}, pub y: i32 }}",
+                x_ty
+            ),
+            "pub type point = C2RustUnnamed_point_h;".to_owned(),
+        ];
+        for item in items {
+            store.add_item(Box::new(syn::parse_str(&item).unwrap()));
+        }
+        store.add_foreign_item(syn::parse_str("pub fn norm(p: point) -> i32;").unwrap());
+        store
+    }
+
+    #[test]
+    fn same_header_is_shared() {
+        let mut shared = SharedHeaders::new();
+        shared.add("point_h", point_h("i32"), Path::new("a.c"));
+
+        let store = point_h("i32");
+        assert_eq!(shared.find_conflict("point_h", &store), None);
+        shared.add("point_h", store, Path::new("b.c"));
+        let header = &shared.modules["point_h"];
+        assert_eq!(header.items.len(), 2);
+        assert_eq!(header.foreign_items.len(), 1);
+        assert!(header
+            .items
+            .values()
+            .all(|shared| shared.origin == Path::new("a.c")));
+    }
+
+    #[test]
+    fn differing_header_conflicts() {
+        let mut shared = SharedHeaders::new();
+        shared.add("point_h", point_h("i32"), Path::new("a.c"));
+
+        let store = point_h("i64");
+        assert_eq!(
+            shared.find_conflict("point_h", &store),
+            Some(("C2RustUnnamed_point_h".to_owned(), PathBuf::from("a.c"))),
+        );
+    }
+}
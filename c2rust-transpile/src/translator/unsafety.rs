@@ -0,0 +1,492 @@
+//! Narrowing of `unsafe` to the code that needs it (`--minimize-unsafe`).
+//!
+//! Functions are only translated into `unsafe fn`s when they are C variadic
+//! functions, use one of their parameters holding raw pointers, whose validity
+//! their callers have to guarantee, or cast an integer to a pointer. The
+//! bodies of the other functions are checked for operations that need
+//! `unsafe`, such as raw pointer dereferences, calls to `unsafe` or foreign
+//! functions, uses of mutable or foreign statics, and union field reads, and
+//! only the statements containing them are wrapped in `unsafe` blocks. The check works on the translated Rust code, so anything
+//! it does not recognize as safe is assumed to need `unsafe`.
+
+use super::*;
+use crate::rust_ast::traverse::{traverse_expr_def, Traversal};
+use std::collections::HashSet;
+
+/// Methods called in translated code that are safe on any receiver
+const SAFE_METHODS: &[&str] = &[
+    "abs",
+    "as_mut_ptr",
+    "as_ptr",
+    "clone",
+    "count_ones",
+    "expect",
+    "into_inner",
+    "is_infinite",
+    "is_nan",
+    "is_none",
+    "is_null",
+    "is_sign_negative",
+    "is_sign_positive",
+    "is_some",
+    "leading_zeros",
+    "len",
+    "load",
+    "store",
+    "swap_bytes",
+    "trailing_zeros",
+    "unwrap",
+];
+
+/// Families of safe integer and atomic methods
+const SAFE_METHOD_PREFIXES: &[&str] = &[
+    "checked_",
+    "compare_exchange",
+    "fetch_",
+    "overflowing_",
+    "wrapping_",
+];
+
+/// Functions and constructors called in translated code that are safe
+const SAFE_CALLS: &[&str] = &["Some", "align_of", "size_of"];
+
+/// The declarations of a translation unit that decide whether code using them
+/// needs `unsafe`.
+#[derive(Default)]
+pub(super) struct UnsafeDecls {
+    /// Defined functions that are translated into safe functions
+    pub safe_fns: IndexSet<CDeclId>,
+    /// Variables with static or thread storage duration, which are translated
+    /// into `static mut`s or foreign statics
+    statics: IndexSet<CDeclId>,
+    /// Fields of unions
+    union_fields: Vec<(CRecordId, CFieldId)>,
+}
+
+/// Counts of the `unsafe` code in a translated module.
+#[derive(Default, Copy, Clone)]
+pub(super) struct UnsafeCounts {
+    /// Operations needing `unsafe`
+    pub ops: usize,
+    /// `unsafe` blocks in safe functions
+    pub blocks: usize,
+    /// Functions translated into `unsafe fn`s
+    pub unsafe_fns: usize,
+    /// All translated functions
+    pub fns: usize,
+}
+
+/// The Rust names of the `UnsafeDecls` for checking translated code.
+struct UnsafeNames {
+    statics: HashSet<String>,
+    union_fields: HashSet<String>,
+    safe_fns: HashSet<String>,
+}
+
+impl UnsafeNames {
+    /// Whether `expr` itself, not counting its subexpressions, needs `unsafe`.
+    fn is_unsafe_op(&self, expr: &Expr) -> bool {
+        let last_ident = |path: &Path| path.segments.last().map(|seg| seg.ident.to_string());
+        match *expr {
+            Expr::Unary(ExprUnary {
+                op: UnOp::Deref(_), ..
+            }) => true,
+            Expr::Call(ExprCall { ref func, .. }) => match **func {
+                Expr::Path(ExprPath { ref path, .. }) => !last_ident(path).map_or(false, |name| {
+                    SAFE_CALLS.contains(&name.as_str()) || self.safe_fns.contains(&name)
+                }),
+                _ => true,
+            },
+            Expr::MethodCall(ExprMethodCall { ref method, .. }) => {
+                let method = method.to_string();
+                !SAFE_METHODS.contains(&method.as_str())
+                    && !SAFE_METHOD_PREFIXES
+                        .iter()
+                        .any(|prefix| method.starts_with(prefix))
+            }
+            Expr::Path(ExprPath { ref path, .. }) => {
+                last_ident(path).map_or(false, |name| self.statics.contains(&name))
+            }
+            Expr::Field(ExprField {
+                member: Member::Named(ref field),
+                ..
+            }) => self.union_fields.contains(&field.to_string()),
+            // The expressions in macros are not checked
+            Expr::Macro(..) => true,
+            _ => false,
+        }
+    }
+
+    /// Count the operations in `expr` that need `unsafe`, optionally including
+    /// those already in `unsafe` blocks.
+    fn count_ops(&self, expr: &Expr, in_unsafe_blocks: bool) -> usize {
+        let mut counter = OpCounter {
+            names: self,
+            in_unsafe_blocks,
+            ops: 0,
+        };
+        counter.traverse_expr(expr.clone());
+        counter.ops
+    }
+
+    fn needs_unsafe(&self, expr: &Expr) -> bool {
+        self.count_ops(expr, false) > 0
+    }
+
+    /// Wrap `expr` in an `unsafe` block if it needs one.
+    fn wrap_expr(&self, expr: Box<Expr>, blocks: &mut usize) -> Box<Expr> {
+        if self.needs_unsafe(&expr) {
+            *blocks += 1;
+            mk().unsafe_block_expr(mk().unsafe_block(vec![mk().expr_stmt(expr)]))
+        } else {
+            expr
+        }
+    }
+
+    fn narrow_block(&self, mut block: Block, blocks: &mut usize) -> Block {
+        block.stmts = self.narrow_stmts(block.stmts, blocks);
+        block
+    }
+
+    /// Wrap the statements that need `unsafe` in `unsafe` blocks, looking into
+    /// the bodies of control flow statements, and grouping consecutive
+    /// statements into one block.
+    fn narrow_stmts(&self, stmts: Vec<Stmt>, blocks: &mut usize) -> Vec<Stmt> {
+        let mut narrowed = vec![];
+        let mut unsafe_stmts = vec![];
+        let flush = |narrowed: &mut Vec<Stmt>, unsafe_stmts: &mut Vec<Stmt>, blocks: &mut usize| {
+            if !unsafe_stmts.is_empty() {
+                *blocks += 1;
+                let block = mk().unsafe_block(mem::take(unsafe_stmts));
+                narrowed.push(mk().expr_stmt(mk().unsafe_block_expr(block)));
+            }
+        };
+
+        let len = stmts.len();
+        for (i, stmt) in stmts.into_iter().enumerate() {
+            match stmt {
+                Stmt::Local(mut local) => {
+                    flush(&mut narrowed, &mut unsafe_stmts, blocks);
+                    local.init = local
+                        .init
+                        .map(|(eq, init)| (eq, self.wrap_expr(init, blocks)));
+                    narrowed.push(Stmt::Local(local));
+                }
+                Stmt::Item(..) => {
+                    flush(&mut narrowed, &mut unsafe_stmts, blocks);
+                    narrowed.push(stmt);
+                }
+                Stmt::Semi(expr, semi) => match self.narrow_control_flow(Box::new(expr), blocks) {
+                    Ok(expr) => {
+                        flush(&mut narrowed, &mut unsafe_stmts, blocks);
+                        narrowed.push(Stmt::Semi(*expr, semi));
+                    }
+                    Err(expr) if self.needs_unsafe(&expr) => {
+                        unsafe_stmts.push(Stmt::Semi(*expr, semi));
+                    }
+                    Err(expr) => {
+                        flush(&mut narrowed, &mut unsafe_stmts, blocks);
+                        narrowed.push(Stmt::Semi(*expr, semi));
+                    }
+                },
+                Stmt::Expr(expr) => match self.narrow_control_flow(Box::new(expr), blocks) {
+                    Ok(expr) => {
+                        flush(&mut narrowed, &mut unsafe_stmts, blocks);
+                        narrowed.push(Stmt::Expr(*expr));
+                    }
+                    // The value of the block has to stay its last expression
+                    Err(expr) if i + 1 == len => {
+                        flush(&mut narrowed, &mut unsafe_stmts, blocks);
+                        narrowed.push(Stmt::Expr(*self.wrap_expr(expr, blocks)));
+                    }
+                    Err(expr) if self.needs_unsafe(&expr) => {
+                        unsafe_stmts.push(Stmt::Expr(*expr));
+                    }
+                    Err(expr) => {
+                        flush(&mut narrowed, &mut unsafe_stmts, blocks);
+                        narrowed.push(Stmt::Expr(*expr));
+                    }
+                },
+            }
+        }
+        flush(&mut narrowed, &mut unsafe_stmts, blocks);
+        narrowed
+    }
+
+    /// Narrow `unsafe` within the conditions and bodies of a control flow
+    /// expression, or give back any other expression.
+    fn narrow_control_flow(
+        &self,
+        expr: Box<Expr>,
+        blocks: &mut usize,
+    ) -> Result<Box<Expr>, Box<Expr>> {
+        let expr = match *expr {
+            Expr::If(mut e) => {
+                e.cond = self.wrap_expr(e.cond, blocks);
+                e.then_branch = self.narrow_block(e.then_branch, blocks);
+                e.else_branch = e.else_branch.map(|(else_, expr)| {
+                    let expr = self
+                        .narrow_control_flow(expr, blocks)
+                        .unwrap_or_else(|expr| self.wrap_expr(expr, blocks));
+                    (else_, expr)
+                });
+                Expr::If(e)
+            }
+            Expr::While(mut e) => {
+                e.cond = self.wrap_expr(e.cond, blocks);
+                e.body = self.narrow_block(e.body, blocks);
+                Expr::While(e)
+            }
+            Expr::Loop(mut e) => {
+                e.body = self.narrow_block(e.body, blocks);
+                Expr::Loop(e)
+            }
+            Expr::ForLoop(mut e) => {
+                e.expr = self.wrap_expr(e.expr, blocks);
+                e.body = self.narrow_block(e.body, blocks);
+                Expr::ForLoop(e)
+            }
+            Expr::Block(mut e) => {
+                e.block = self.narrow_block(e.block, blocks);
+                Expr::Block(e)
+            }
+            Expr::Match(mut e) => {
+                e.expr = self.wrap_expr(e.expr, blocks);
+                e.arms = e
+                    .arms
+                    .into_iter()
+                    .map(|mut arm| {
+                        arm.guard = arm
+                            .guard
+                            .map(|(if_, guard)| (if_, self.wrap_expr(guard, blocks)));
+                        arm.body = self
+                            .narrow_control_flow(arm.body, blocks)
+                            .unwrap_or_else(|body| self.wrap_expr(body, blocks));
+                        arm
+                    })
+                    .collect();
+                Expr::Match(e)
+            }
+            // Already `unsafe`
+            Expr::Unsafe(..) => return Ok(expr),
+            _ => return Err(expr),
+        };
+        Ok(Box::new(expr))
+    }
+}
+
+/// Counts the operations needing `unsafe` in an expression.
+struct OpCounter<'a> {
+    names: &'a UnsafeNames,
+    in_unsafe_blocks: bool,
+    ops: usize,
+}
+
+impl Traversal for OpCounter<'_> {
+    fn traverse_expr(&mut self, e: Expr) -> Expr {
+        if matches!(e, Expr::Unsafe(..)) && !self.in_unsafe_blocks {
+            return e;
+        }
+        if self.names.is_unsafe_op(&e) {
+            self.ops += 1;
+        }
+        traverse_expr_def(self, e)
+    }
+
+    fn traverse_item(&mut self, i: Item) -> Item {
+        i
+    }
+}
+
+impl<'c> Translation<'c> {
+    /// Find the safe functions and the declarations whose uses need `unsafe`.
+    pub(super) fn collect_unsafe_decls(&self) -> UnsafeDecls {
+        let mut decls = UnsafeDecls::default();
+        for (&decl_id, decl) in self.ast_context.iter_decls() {
+            match decl.kind {
+                CDeclKind::Function {
+                    typ,
+                    body: Some(body),
+                    ref parameters,
+                    ..
+                } => {
+                    let is_variadic = matches!(
+                        self.ast_context.resolve_type(typ).kind,
+                        CTypeKind::Function(_, _, true, _, _)
+                    );
+                    if !is_variadic && !self.needs_unsafe_fn(parameters, body) {
+                        decls.safe_fns.insert(decl_id);
+                    }
+                }
+                CDeclKind::Variable {
+                    has_static_duration,
+                    has_thread_duration,
+                    ..
+                } if has_static_duration || has_thread_duration => {
+                    decls.statics.insert(decl_id);
+                }
+                CDeclKind::Union {
+                    fields: Some(ref fields),
+                    ..
+                } => {
+                    decls
+                        .union_fields
+                        .extend(fields.iter().map(|&field_id| (decl_id, field_id)));
+                }
+                _ => {}
+            }
+        }
+        decls
+    }
+
+    /// Whether a function body refers to any of its parameters that hold raw
+    /// pointers, which it may dereference or pass on to code that does, or
+    /// forms a pointer from an integer.
+    fn needs_unsafe_fn(&self, parameters: &[CParamId], body: CStmtId) -> bool {
+        let pointer_params = parameters
+            .iter()
+            .filter(|&&param_id| match self.ast_context[param_id].kind {
+                CDeclKind::Variable { typ, .. } => self.ast_context.contains_raw_pointer(typ.ctype),
+                _ => false,
+            })
+            .collect::<IndexSet<_>>();
+        DFExpr::new(&self.ast_context, body.into())
+            .filter_map(SomeId::expr)
+            .any(|expr_id| match self.ast_context[expr_id].kind {
+                CExprKind::DeclRef(_, decl_id, _) => pointer_params.contains(&decl_id),
+                CExprKind::ImplicitCast(_, _, CastKind::IntegralToPointer, _, _)
+                | CExprKind::ExplicitCast(_, _, CastKind::IntegralToPointer, _, _) => true,
+                _ => false,
+            })
+    }
+
+    fn unsafe_names(&self) -> UnsafeNames {
+        let renamer = self.renamer.borrow();
+        let type_converter = self.type_converter.borrow();
+        let decls = &self.unsafe_decls;
+        UnsafeNames {
+            statics: decls
+                .statics
+                .iter()
+                .filter_map(|decl_id| renamer.get(decl_id))
+                .collect(),
+            union_fields: decls
+                .union_fields
+                .iter()
+                .filter_map(|&(record_id, field_id)| {
+                    type_converter.resolve_field_name(Some(record_id), field_id)
+                })
+                .collect(),
+            safe_fns: decls
+                .safe_fns
+                .iter()
+                .filter_map(|decl_id| renamer.get(decl_id))
+                .collect(),
+        }
+    }
+
+    /// Narrow the `unsafe` code in the body of a function, recording it in the
+    /// counts for the module.
+    pub(super) fn minimize_unsafe(&self, block: Block, is_unsafe_fn: bool) -> Block {
+        let names = self.unsafe_names();
+        let mut counts = self.unsafe_counts.get();
+        counts.fns += 1;
+        counts.ops += names.count_ops(&mk().block_expr(block.clone()), true);
+        let block = if is_unsafe_fn {
+            counts.unsafe_fns += 1;
+            block
+        } else {
+            names.narrow_block(block, &mut counts.blocks)
+        };
+        self.unsafe_counts.set(counts);
+        block
+    }
+
+    /// Cast a reference to a safe function to the `unsafe` function pointer
+    /// type that C function pointers are translated into.
+    pub(super) fn cast_safe_fn(
+        &self,
+        fn_expr: CExprId,
+        fn_ptr_ty: CTypeId,
+        val: Box<Expr>,
+    ) -> TranslationResult<Box<Expr>> {
+        let is_safe_fn = match self.ast_context[fn_expr].kind {
+            CExprKind::DeclRef(_, decl_id, _) => self.unsafe_decls.safe_fns.contains(&decl_id),
+            _ => false,
+        };
+        let fn_ty = match self.ast_context.get_pointee_qual_type(fn_ptr_ty) {
+            Some(fn_ty) if is_safe_fn => fn_ty,
+            _ => return Ok(val),
+        };
+        Ok(mk().cast_expr(val, self.convert_type(fn_ty.ctype)?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use syn::__private::ToTokens;
+
+    fn narrow(block: &str) -> (String, usize) {
+        let names = UnsafeNames {
+            statics: ["COUNT".to_owned()].into_iter().collect(),
+            union_fields: ["bytes".to_owned()].into_iter().collect(),
+            safe_fns: ["square".to_owned()].into_iter().collect(),
+        };
+        let block = syn::parse_str::<Block>(block).unwrap();
+        let mut blocks = 0;
+        let block = names.narrow_block(block, &mut blocks);
+        (block.to_token_stream().to_string(), blocks)
+    }
+
+    fn tokens(block: &str) -> String {
+        syn::parse_str::<Block>(block)
+            .unwrap()
+            .to_token_stream()
+            .to_string()
+    }
+
+    #[test]
+    fn safe_statements_stay_safe() {
+        let block = "{ let mut x = square(2).wrapping_add(1); x += 1; x }";
+        assert_eq!(narrow(block), (tokens(block), 0));
+    }
+
+    #[test]
+    fn unsafe_statements_are_wrapped() {
+        let (narrowed, blocks) = narrow(
+            "{
+                let x = *p;
+                COUNT += 1;
+                COUNT += x;
+                let y = square(x);
+                if *p != 0 {
+                    y = b.bytes[0];
+                } else {
+                    y = 1;
+                }
+                f(y)
+            }",
+        );
+        let expected = tokens(
+            "{
+                let x = unsafe { *p };
+                unsafe {
+                    COUNT += 1;
+                    COUNT += x;
+                }
+                let y = square(x);
+                if unsafe { *p != 0 } {
+                    unsafe {
+                        y = b.bytes[0];
+                    }
+                } else {
+                    y = 1;
+                }
+                unsafe { f(y) }
+            }",
+        );
+        assert_eq!(narrowed, expected);
+        assert_eq!(blocks, 5);
+    }
+}
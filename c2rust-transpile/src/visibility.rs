@@ -0,0 +1,208 @@
+//! Inference of the functions that are only used within a translated
+//! executable (`--infer-visibility`).
+//!
+//! By default, every function with external linkage becomes a `#[no_mangle]
+//! pub unsafe extern "C" fn`, as it may be called from outside of the crate.
+//! An executable has no callers outside of its link command, though, so its
+//! functions that are only called directly from its own translation units can
+//! be translated as `pub(crate) unsafe fn`s with the Rust ABI, which the other
+//! modules import instead of declaring them `extern "C"`.
+
+use indexmap::{IndexMap, IndexSet};
+
+use crate::c_ast::*;
+use crate::convert_type::RESERVED_NAMES;
+
+/// How a translation unit defines and uses functions with external linkage.
+#[derive(Debug, Default)]
+pub struct LinkSummary {
+    /// Functions defined in the translation unit
+    defined: IndexSet<String>,
+    /// Functions used in the translation unit but defined elsewhere
+    imported: IndexSet<String>,
+    /// Functions that have to keep the C ABI and symbol name, e.g. because
+    /// their address is taken or they are variadic
+    pinned: IndexSet<String>,
+}
+
+impl LinkSummary {
+    pub fn new(ast_context: &TypedAstContext) -> Self {
+        let mut summary = Self::default();
+
+        for (&decl_id, decl) in ast_context.iter_decls() {
+            let (name, body, typ, is_global, is_inline, attrs) = match decl.kind {
+                CDeclKind::Function {
+                    ref name,
+                    body,
+                    typ,
+                    is_global,
+                    is_inline,
+                    ref attrs,
+                    ..
+                } => (name, body, typ, is_global, is_inline, attrs),
+                _ => continue,
+            };
+            if !is_global {
+                continue;
+            }
+            if body.is_some() {
+                summary.defined.insert(name.clone());
+            }
+
+            let (is_variadic, has_proto) = match ast_context.resolve_type(typ).kind {
+                CTypeKind::Function(_, _, is_variadic, _, has_proto) => (is_variadic, has_proto),
+                _ => (false, false),
+            };
+            // Variadic functions and calls through declarations without a
+            // prototype need the C ABI; inline functions, `main`, and functions
+            // with linkage attributes need their symbol
+            let is_pinned = is_variadic
+                || !has_proto
+                || is_inline
+                || ast_context.c_main == Some(decl_id)
+                || attrs.iter().any(|attr| {
+                    matches!(
+                        attr,
+                        Attribute::Alias(_)
+                            | Attribute::Section(_)
+                            | Attribute::Used
+                            | Attribute::Visibility(_)
+                    )
+                });
+            if is_pinned {
+                summary.pinned.insert(name.clone());
+            }
+            for attr in attrs {
+                if let Attribute::Alias(aliasee) = attr {
+                    summary.pinned.insert(aliasee.clone());
+                }
+            }
+        }
+
+        // Functions referred to other than as the callee of a call have their
+        // address taken, e.g. as callbacks
+        let callees = ast_context
+            .iter_exprs()
+            .filter_map(|(_, expr)| match expr.kind {
+                CExprKind::Call(_, callee, _) => Some(callee_decl_ref(ast_context, callee)),
+                _ => None,
+            })
+            .collect::<IndexSet<_>>();
+        for (expr_id, expr) in ast_context.iter_exprs() {
+            let decl_id = match expr.kind {
+                CExprKind::DeclRef(_, decl_id, _) => decl_id,
+                _ => continue,
+            };
+            let name = match ast_context[decl_id].kind {
+                CDeclKind::Function {
+                    ref name,
+                    is_global: true,
+                    ..
+                } => name,
+                _ => continue,
+            };
+            if !summary.defined.contains(name) {
+                summary.imported.insert(name.clone());
+            }
+            if !callees.contains(expr_id) {
+                summary.pinned.insert(name.clone());
+            }
+        }
+
+        summary
+    }
+}
+
+/// The `DeclRef` a call expression calls through, looking through the decay
+/// of the function to a pointer.
+fn callee_decl_ref(ast_context: &TypedAstContext, mut expr_id: CExprId) -> CExprId {
+    loop {
+        match ast_context[expr_id].kind {
+            CExprKind::ImplicitCast(_, subexpr, CastKind::FunctionToPointerDecay, _, _)
+            | CExprKind::Paren(_, subexpr) => expr_id = subexpr,
+            _ => return expr_id,
+        }
+    }
+}
+
+/// Names that the translator reserves for functions in addition to
+/// `RESERVED_NAMES`.
+const PRELUDE_NAMES: [&str; 6] = ["main", "drop", "Some", "None", "Ok", "Err"];
+
+/// A function that is only visible within its crate.
+#[derive(Debug, Clone)]
+pub struct InternalFn {
+    /// Path of the module defining the function, relative to the crate root
+    pub module_path: Vec<String>,
+    /// Name of the function in that module
+    pub ident: String,
+}
+
+/// The internal functions of a crate, by C name.
+#[derive(Debug, Default)]
+pub struct InternalFns(IndexMap<String, InternalFn>);
+
+impl InternalFns {
+    pub fn get(&self, name: &str) -> Option<&InternalFn> {
+        self.0.get(name)
+    }
+
+    /// Infer the internal functions of an executable from the summaries of
+    /// its translation units, along with the path of the module each one is
+    /// translated to. `external` names the functions that are used by other
+    /// link targets. `fn_ident` gives the Rust name of a function.
+    pub fn infer<'a>(
+        units: impl IntoIterator<Item = (&'a LinkSummary, Vec<String>)>,
+        external: &IndexSet<String>,
+        fn_ident: impl Fn(&str) -> String,
+    ) -> Self {
+        let mut definitions = IndexMap::<&str, Vec<Vec<String>>>::new();
+        let mut pinned = IndexSet::new();
+        for (summary, module_path) in units {
+            for name in &summary.defined {
+                definitions
+                    .entry(name)
+                    .or_default()
+                    .push(module_path.clone());
+            }
+            pinned.extend(summary.pinned.iter().map(String::as_str));
+        }
+
+        let fns = definitions
+            .into_iter()
+            .filter_map(|(name, mut module_paths)| {
+                // Functions defined more than once are left for the linker to
+                // resolve, and reserved names would be renamed by the translator
+                let ident = fn_ident(name);
+                if module_paths.len() != 1
+                    || pinned.contains(name)
+                    || external.contains(name)
+                    || RESERVED_NAMES.contains(&ident.as_str())
+                    || PRELUDE_NAMES.contains(&ident.as_str())
+                {
+                    return None;
+                }
+                let internal = InternalFn {
+                    module_path: module_paths.pop().unwrap(),
+                    ident,
+                };
+                Some((name.to_owned(), internal))
+            })
+            .collect();
+        InternalFns(fns)
+    }
+}
+
+/// The functions used by a link target but not defined by it.
+pub fn imported_fns(units: &[LinkSummary]) -> IndexSet<String> {
+    let defined = units
+        .iter()
+        .flat_map(|summary| &summary.defined)
+        .collect::<IndexSet<_>>();
+    units
+        .iter()
+        .flat_map(|summary| &summary.imported)
+        .filter(|name| !defined.contains(name))
+        .cloned()
+        .collect()
+}
@@ -3,7 +3,7 @@ use log::LevelFilter;
 use regex::Regex;
 use std::path::{Path, PathBuf};
 
-use c2rust_transpile::{Diagnostic, ReplaceMode, TranspilerConfig};
+use c2rust_transpile::{Diagnostic, ReplaceMode, SignedOverflow, TranspilerConfig};
 
 #[derive(Debug, Parser)]
 #[clap(
@@ -36,6 +36,16 @@ struct Args {
     #[clap(long)]
     debug_ast_exporter: bool,
 
+    /// Save the Clang AST of each input file to DIR, so that it can be translated later with
+    /// --from-cbor on a machine without a matching clang installation
+    #[clap(long, value_name = "DIR")]
+    dump_cbor: Option<PathBuf>,
+
+    /// Translate the Clang ASTs saved to DIR by --dump-cbor instead of running clang. The input
+    /// files are still taken from compile_commands.json, but need not exist
+    #[clap(long, value_name = "DIR", conflicts_with = "dump-cbor")]
+    from_cbor: Option<PathBuf>,
+
     /// Verbose mode
     #[clap(short = 'v', long)]
     verbose: bool,
@@ -128,6 +138,19 @@ struct Args {
     #[clap(short = 'r', long)]
     reorganize_definitions: bool,
 
+    /// Translate each declaration from a header once, into a shared `headers` module that the
+    /// translated files import, instead of repeating it in every file that includes the header.
+    /// Headers that translate differently between files are kept in those files, with a
+    /// warning (implies --emit-build-files)
+    #[clap(long)]
+    dedup_headers: bool,
+
+    /// Translate the functions of an executable that are only called directly from within it as
+    /// `pub(crate)` Rust functions instead of `#[no_mangle] pub extern "C"` ones, importing them
+    /// where they are declared. Only applies to the crates emitted for link commands
+    #[clap(long, conflicts_with_all = &["reorganize-definitions", "dedup-headers"])]
+    infer_visibility: bool,
+
     /// Extra arguments to pass to clang frontend during parsing the input C file
     #[clap(multiple = true)]
     extra_clang_args: Vec<String>,
@@ -140,6 +163,64 @@ struct Args {
     #[clap(long)]
     emit_no_std: bool,
 
+    /// Emit code that does not depend on the libc crate or link a C library. C library
+    /// functions are provided by a generated `libc` module, with platform-specific ones
+    /// implemented in `libc_hooks.rs`. Implies --emit-no-std and --emit-build-files
+    #[clap(long)]
+    emit_no_libc: bool,
+
+    /// Translate calls to printf, fprintf(stdout/stderr), sprintf and snprintf with literal
+    /// format strings into Rust formatting macros. Translated calls do not share libc's stdio
+    /// buffers, so output may be reordered relative to calls that are left untranslated.
+    #[clap(long)]
+    translate_printf: bool,
+
+    /// Translate main into a `c_main(args: &[&CStr]) -> i32` function that can be called from
+    /// Rust, and a `main` that passes it the process arguments without requiring them to be
+    /// UTF-8 and returns its result as an `ExitCode`, so that destructors run. Unix only
+    #[clap(long)]
+    safe_main: bool,
+
+    /// Emit compile-time assertions that the size and alignment of each translated struct and
+    /// union, and the offsets of its fields, match the layout computed by clang
+    #[clap(long)]
+    emit_layout_asserts: bool,
+
+    /// Only emit bindings to the C code: types, constants, const macros, and `extern "C"`
+    /// declarations of its externally visible functions and variables, leaving their
+    /// definitions in C. Implies --translate-const-macros
+    #[clap(long, conflicts_with = "binary")]
+    bindings_only: bool,
+
+    /// Rename types to UpperCamelCase, functions, local variables, and fields to snake_case, and
+    /// constants and statics to SCREAMING_SNAKE_CASE, keeping the C symbol names of functions and
+    /// variables with `#[export_name]` and `#[link_name]`
+    #[clap(long)]
+    rust_naming_conventions: bool,
+
+    /// Translate switch statements whose cases do not fall through, or only fall through into a
+    /// last case, directly into a `match`. Switches that need the general CFG translation are
+    /// reported as `switch-cfg` warnings (enable with -Wswitch-cfg)
+    #[clap(long)]
+    switch_to_match: bool,
+
+    /// Index fixed-size arrays with `get_unchecked` and `get_unchecked_mut` instead of
+    /// bounds-checked indexing, trusting the C code to stay within their bounds
+    #[clap(long)]
+    unchecked_array_indexing: bool,
+
+    /// Translate functions into `unsafe fn`s only if they use a raw pointer parameter or are
+    /// variadic, wrapping the statements of the other functions that need `unsafe` in `unsafe`
+    /// blocks, and log the number of unsafe operations in each module at the `info` level
+    #[clap(long)]
+    minimize_unsafe: bool,
+
+    /// How to translate signed integer arithmetic, whose overflow is undefined behavior in C:
+    /// with operators, which panic on overflow in debug builds only, or with `wrapping_*` or
+    /// `checked_*` methods, which wrap around or panic on overflow in all builds
+    #[clap(long, value_enum, default_value_t = SignedOverflowMode::Plain)]
+    signed_overflow: SignedOverflowMode,
+
     /// Disable running refactoring tool after translation
     #[clap(long)]
     disable_refactoring: bool,
@@ -164,6 +245,14 @@ enum InvalidCodes {
     CompileError,
 }
 
+#[derive(Debug, PartialEq, Eq, ValueEnum, Clone)]
+#[clap(rename_all = "snake_case")]
+enum SignedOverflowMode {
+    Plain,
+    Wrapping,
+    Checked,
+}
+
 fn main() {
     let args = Args::parse();
 
@@ -177,6 +266,8 @@ fn main() {
         dump_cfg_liveness: args.dump_cfgs_liveness,
         dump_structures: args.dump_structures,
         debug_ast_exporter: args.debug_ast_exporter,
+        dump_cbor: args.dump_cbor,
+        from_cbor: args.from_cbor,
         verbose: args.verbose,
 
         incremental_relooper: !args.no_incremental_relooper,
@@ -198,6 +289,19 @@ fn main() {
 
         translate_const_macros: args.translate_const_macros,
         translate_fn_macros: args.translate_fn_macros,
+        translate_printf: args.translate_printf,
+        safe_main: args.safe_main,
+        emit_layout_asserts: args.emit_layout_asserts,
+        bindings_only: args.bindings_only,
+        rust_naming_conventions: args.rust_naming_conventions,
+        switch_to_match: args.switch_to_match,
+        unchecked_array_indexing: args.unchecked_array_indexing,
+        minimize_unsafe: args.minimize_unsafe,
+        signed_overflow: match args.signed_overflow {
+            SignedOverflowMode::Plain => SignedOverflow::Plain,
+            SignedOverflowMode::Wrapping => SignedOverflow::Wrapping,
+            SignedOverflowMode::Checked => SignedOverflow::Checked,
+        },
         disable_refactoring: args.disable_refactoring,
         preserve_unused_functions: args.preserve_unused_functions,
 
@@ -207,6 +311,8 @@ fn main() {
         overwrite_existing: args.overwrite_existing,
         reduce_type_annotations: args.reduce_type_annotations,
         reorganize_definitions: args.reorganize_definitions,
+        dedup_headers: args.dedup_headers,
+        infer_visibility: args.infer_visibility,
         emit_modules: args.emit_modules,
         emit_build_files: args.emit_build_files,
         output_dir: args.output_dir,
@@ -214,9 +320,23 @@ fn main() {
         panic_on_translator_failure: args.invalid_code == InvalidCodes::Panic,
         replace_unsupported_decls: ReplaceMode::Extern,
         emit_no_std: args.emit_no_std,
+        emit_no_libc: args.emit_no_libc,
         enabled_warnings: args.warn.into_iter().collect(),
         log_level: args.log_level,
     };
+    // emit-no-libc implies emit-no-std, and emit-build-files for the `libc` module
+    if tcfg.emit_no_libc {
+        tcfg.emit_no_std = true;
+        tcfg.emit_build_files = true;
+    }
+    // dedup-headers implies emit-build-files for the `headers` module
+    if tcfg.dedup_headers {
+        tcfg.emit_build_files = true;
+    }
+    // bindings-only implies translate-const-macros, as macros are part of C APIs
+    if tcfg.bindings_only {
+        tcfg.translate_const_macros = true;
+    }
     // binaries imply emit-build-files
     if !tcfg.binaries.is_empty() {
         tcfg.emit_build_files = true
@@ -41,6 +41,7 @@ CRATES = [
     c.AST_BUILDER_CRATE_DIR,
     c.AST_EXPORTER_CRATE_DIR,
     # c.BITFIELDS_CRATE_DIR,
+    c.PRINTF_CRATE_DIR,
     c.TRANSPILE_CRATE_DIR,
     # c.REFACTOR_CRATE_DIR,
     c.C2RUST_DIR,
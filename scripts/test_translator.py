@@ -6,6 +6,7 @@ from pathlib import Path
 import sys
 import logging
 import argparse
+import bencode
 import re
 
 from common import (
@@ -70,8 +71,20 @@ class CFile:
         self.disable_incremental_relooper = "disable_incremental_relooper" in flags
         self.disallow_current_block = "disallow_current_block" in flags
         self.translate_const_macros = "translate_const_macros" in flags
+        self.translate_printf = "translate_printf" in flags
+        self.emit_layout_asserts = "emit_layout_asserts" in flags
+        self.bindings_only = "bindings_only" in flags
+        self.rust_naming_conventions = "rust_naming_conventions" in flags
+        self.switch_to_match = "switch_to_match" in flags
+        self.unchecked_array_indexing = "unchecked_array_indexing" in flags
+        self.minimize_unsafe = "minimize_unsafe" in flags
+        self.signed_overflow_checked = "signed_overflow_checked" in flags
+        self.signed_overflow_wrapping = "signed_overflow_wrapping" in flags
         self.reorganize_definitions = "reorganize_definitions" in flags
         self.emit_build_files = "emit_build_files" in flags
+        self.emit_no_libc = "emit_no_libc" in flags
+        self.safe_main = "safe_main" in flags
+        self.infer_visibility = "infer_visibility" in flags
 
     def translate(self, cc_db: str, ld_lib_path: str, extra_args: List[str] = []) -> RustFile:
         extensionless_file, _ = os.path.splitext(self.path)
@@ -92,10 +105,34 @@ class CFile:
             args.append("--fail-on-multiple")
         if self.translate_const_macros:
             args.append("--translate-const-macros")
+        if self.translate_printf:
+            args.append("--translate-printf")
+        if self.emit_layout_asserts:
+            args.append("--emit-layout-asserts")
+        if self.bindings_only:
+            args.append("--bindings-only")
+        if self.rust_naming_conventions:
+            args.append("--rust-naming-conventions")
+        if self.switch_to_match:
+            args.append("--switch-to-match")
+        if self.unchecked_array_indexing:
+            args.append("--unchecked-array-indexing")
+        if self.minimize_unsafe:
+            args.append("--minimize-unsafe")
+        if self.signed_overflow_checked:
+            args.extend(["--signed-overflow", "checked"])
+        if self.signed_overflow_wrapping:
+            args.extend(["--signed-overflow", "wrapping"])
         if self.reorganize_definitions:
             args.append("--reorganize-definitions")
         if self.emit_build_files:
             args.append("--emit-build-files")
+        if self.emit_no_libc:
+            args.append("--emit-no-libc")
+        if self.safe_main:
+            args.append("--safe-main")
+        if self.infer_visibility:
+            args.append("--infer-visibility")
 
         if self.log_level == 'DEBUG':
             args.append("--log-level=debug")
@@ -315,20 +352,40 @@ class TestDirectory:
         if message:
             sys.stdout.write(message)
 
-    def _generate_cc_db(self, c_file_path: str) -> None:
-        directory, cfile = os.path.split(c_file_path)
+    def _generate_cc_db(self, c_file: CFile) -> None:
+        directory, cfile = os.path.split(c_file.path)
 
         target_args = '"-target", "{}", '.format(self.target) if self.target else ""
 
+        # Visibility is only inferred for executables, so link the file into one
+        output = ""
+        link_command = ""
+        if c_file.infer_visibility:
+            exe, _ = os.path.splitext(cfile)
+            output = ',\n            "output": "{}.o"'.format(exe)
+            link_cmd = bencode.bencode({
+                "inputs": [exe + ".o"],
+                "libs": [],
+                "lib_dirs": [],
+                "type": "exe",
+            })
+            link_command = """,
+          {{
+            "arguments": [ "cc", "-o", "{0}", "{0}.o" ],
+            "directory": "{1}",
+            "file": "/c2rust/link/{2}",
+            "output": "{0}"
+          }}""".format(exe, directory, link_cmd)
+
         compile_commands = """ \
         [
           {{
             "arguments": [ "cc", "-D_FORTIFY_SOURCE=0",{3} "-c", {2}"{0}" ],
             "directory": "{1}",
-            "file": "{0}"
-          }}
+            "file": "{0}"{4}
+          }}{5}
         ]
-        """.format(cfile, directory, target_args, self.clang_resource_dir)
+        """.format(cfile, directory, target_args, self.clang_resource_dir, output, link_command)
 
         cc_db = os.path.join(directory, "compile_commands.json")
 
@@ -416,7 +473,7 @@ class TestDirectory:
             # Run the step
             self.print_status(Colors.WARNING, "RUNNING", description)
 
-            self._generate_cc_db(c_file.path)
+            self._generate_cc_db(c_file)
 
             try:
                 logging.debug("translating %s", c_file_short)
@@ -438,6 +495,14 @@ class TestDirectory:
                 self.generated_files["rust_src"].append(self.full_path + "/src/build.rs")
                 self.generated_files["rust_src"].append(self.full_path + "/src/c2rust-lib.rs")
                 self.generated_files["rust_src"].append(self.full_path + "/src/rust-toolchain.toml")
+            if c_file.emit_no_libc:
+                # `--emit-no-libc` implies `--emit-build-files`, and translated code refers to
+                # the generated `libc` module, which needs `libc_hooks` in turn
+                for build_file in ["Cargo.toml", "build.rs", "c2rust-lib.rs", "rust-toolchain.toml",
+                                   "libc.rs", "libc_hooks.rs"]:
+                    self.generated_files["rust_src"].append(self.full_path + "/src/" + build_file)
+                rust_file_builder.add_mod(RustMod("libc", RustVisibility.Public))
+                rust_file_builder.add_mod(RustMod("libc_hooks", RustVisibility.Public))
 
             _, rust_file_short = os.path.split(translated_rust_file.path)
             extensionless_rust_file, _ = os.path.splitext(rust_file_short)
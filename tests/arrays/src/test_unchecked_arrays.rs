@@ -0,0 +1,35 @@
+use crate::unchecked_arrays::{rust_count_duplicates, rust_fill_grid, rust_weighted_histogram};
+use libc::{c_int, c_uchar};
+
+#[link(name = "test")]
+extern "C" {
+    fn weighted_histogram(_: *const c_uchar, _: c_int) -> c_int;
+    fn count_duplicates() -> c_int;
+    fn fill_grid() -> c_int;
+}
+
+pub fn test_weighted_histogram() {
+    let data = b"the quick brown fox jumps over the lazy dog";
+    let len = data.len() as c_int;
+
+    let val = unsafe { weighted_histogram(data.as_ptr(), len) };
+    let rust_val = unsafe { rust_weighted_histogram(data.as_ptr(), len) };
+
+    assert_eq!(val, rust_val);
+}
+
+pub fn test_count_duplicates() {
+    let val = unsafe { count_duplicates() };
+    let rust_val = unsafe { rust_count_duplicates() };
+
+    assert_eq!(val, rust_val);
+    assert_eq!(val, 2);
+}
+
+pub fn test_fill_grid() {
+    let val = unsafe { fill_grid() };
+    let rust_val = unsafe { rust_fill_grid() };
+
+    assert_eq!(val, rust_val);
+    assert_eq!(val, 14);
+}
@@ -0,0 +1,66 @@
+//! unchecked_array_indexing
+
+#include <string.h>
+
+struct histogram {
+    int counts[8];
+    int total;
+};
+
+static const int weights[4] = {1, 2, 3, 4};
+
+int weighted_histogram(const unsigned char *data, int len) {
+    struct histogram h = {{0}, 0};
+    int grid[2][4] = {{0}};
+
+    for (int i = 0; i < len; i++) {
+        h.counts[data[i] % 8] += weights[i % 4];
+        h.total++;
+        grid[i % 2][data[i] % 4] = h.counts[data[i] % 8];
+    }
+
+    int sum = h.total;
+    for (int i = 0; i < 8; i++) {
+        sum += h.counts[i] * (i + 1);
+    }
+    for (int i = 0; i < 2; i++) {
+        for (int j = 0; j < 4; j++) {
+            sum += grid[i][j];
+        }
+    }
+    return sum;
+}
+
+int count_duplicates(void) {
+    int values[6] = {3, 1, 4, 1, 5, 3};
+    int duplicates = 0;
+
+    for (int i = 0; i < 6; i++) {
+        for (int j = i + 1; j < 6; j++) {
+            // Elements that are only read are indexed without borrowing `values` mutably
+            if (values[i] == values[j]) {
+                duplicates++;
+            }
+        }
+    }
+    return duplicates;
+}
+
+int fill_grid(void) {
+    int grid[3][4];
+    int sum = 0;
+
+    // Rows that decay to pointers to mutable elements borrow `grid` mutably
+    for (int i = 0; i < 3; i++) {
+        memset(grid[i], 0, sizeof(grid[i]));
+        int *row = grid[i];
+        row[i] = i + 1;
+    }
+    for (int i = 0; i < 3; i++) {
+        const int *row = grid[i];
+        for (int j = 0; j < 4; j++) {
+            sum += row[j] * (j + 1);
+        }
+    }
+    return sum;
+}
@@ -68,6 +68,51 @@ int six(void) {
     return out;
 }
 
+// Named operands, and a memory operand whose size comes from the mnemonic
+int add_named(int x, int y) {
+    int sum = x;
+    asm("addl %[y], %[sum]"
+        : [sum] "+m" (sum)
+        : [y] "r" (y));
+    return sum;
+}
+
+// Immediate operands are translated to `const` operands
+int add_imm(int x) {
+    asm("addl %1, %0"
+        : "+r" (x)
+        : "i" (40));
+    return x;
+}
+
+// Operand modifiers select sub-registers
+int low_byte(int x) {
+    int out = 0;
+    asm("movb %b1, %b0"
+        : "+r" (out)
+        : "r" (x));
+    return out;
+}
+
+int store_via_memory(int x) {
+    int y;
+    asm volatile("movl %1, %0"
+                 : "=m" (y)
+                 : "r" (x)
+                 : "memory");
+    return y;
+}
+
+// Non-volatile, but the "memory" clobber means it is not `pure`
+int copy_with_clobber(int x) {
+    int y;
+    asm("movl %1, %0"
+        : "=r" (y)
+        : "r" (x)
+        : "memory");
+    return y;
+}
+
 void entry(const unsigned int buffer_size, int buffer[const])
 {
     int i = 0;
@@ -78,4 +123,9 @@ void entry(const unsigned int buffer_size, int buffer[const])
     buffer[i++] = mul2(6);
     buffer[i++] = mul2_2(6);
     buffer[i++] = six();
+    buffer[i++] = add_named(20, 22);
+    buffer[i++] = add_imm(2);
+    buffer[i++] = low_byte(0x1234);
+    buffer[i++] = store_via_memory(7);
+    buffer[i++] = copy_with_clobber(9);
 }
@@ -1,4 +1,4 @@
-//! extern_crate_c2rust_asm_casts
+//! extern_crate_c2rust_asm_casts, feature_asm_const
 
 use crate::asm::rust_entry;
 use libc::{c_int, c_uint};
@@ -8,12 +8,12 @@ extern "C" {
     fn entry(_: c_uint, _: *mut c_int);
 }
 
-const BUFFER_SIZE: usize = 6;
+const BUFFER_SIZE: usize = 11;
 
 pub fn test_buffer() {
     let mut buffer = [0; BUFFER_SIZE];
     let mut rust_buffer = [0; BUFFER_SIZE];
-    let expected_buffer = [243025, 65070, 51450, 12, 12, 6];
+    let expected_buffer = [243025, 65070, 51450, 12, 12, 6, 42, 42, 52, 7, 9];
 
     unsafe {
         entry(BUFFER_SIZE as u32, buffer.as_mut_ptr());
@@ -0,0 +1,58 @@
+#include <stdatomic.h>
+#include <stdbool.h>
+
+struct counter {
+    atomic_int value;
+    int hits;
+};
+
+static _Atomic long total;
+
+void c11_atomics(const unsigned buffer_size, int buffer[const])
+{
+    int i = 0;
+    atomic_int x = 34;
+    struct counter c = { 0 };
+
+    buffer[i++] = atomic_fetch_add(&x, 55);                             buffer[i++] = x;
+    buffer[i++] = atomic_fetch_sub_explicit(&x, 17, memory_order_relaxed); buffer[i++] = x;
+    buffer[i++] = atomic_fetch_or(&x, 128);                             buffer[i++] = x;
+    buffer[i++] = atomic_fetch_xor(&x, 0xA5);                           buffer[i++] = x;
+    buffer[i++] = atomic_fetch_and(&x, 0xAA);                           buffer[i++] = x;
+    buffer[i++] = atomic_exchange(&x, 7);                               buffer[i++] = x;
+
+    atomic_store_explicit(&x, 12, memory_order_release);
+    buffer[i++] = atomic_load_explicit(&x, memory_order_acquire);
+
+    int expected = 12;
+    buffer[i++] = atomic_compare_exchange_strong(&x, &expected, 99); buffer[i++] = expected;
+    buffer[i++] = atomic_compare_exchange_strong(&x, &expected, 5);  buffer[i++] = expected;
+    buffer[i++] = x;
+
+    x += 3;      buffer[i++] = x;
+    x -= 1;      buffer[i++] = x;
+    x *= 4;      buffer[i++] = x;
+    x |= 0x100;  buffer[i++] = x;
+    buffer[i++] = x++;
+    buffer[i++] = ++x;
+    buffer[i++] = x--;
+    buffer[i++] = --x;
+    buffer[i++] = (x = 41);
+
+    c.value = 10;
+    c.value += 5;
+    c.hits++;
+    buffer[i++] = c.value;
+    buffer[i++] = c.hits;
+
+    total += 1000;
+    total -= 1;
+    buffer[i++] = (int)total;
+
+    atomic_char ch = 'a';
+    buffer[i++] = atomic_fetch_add(&ch, 1);
+    buffer[i++] = atomic_load(&ch);
+
+    atomic_thread_fence(memory_order_seq_cst);
+    atomic_signal_fence(memory_order_acq_rel);
+}
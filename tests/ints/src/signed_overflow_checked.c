@@ -0,0 +1,23 @@
+//! signed_overflow_checked
+
+int checked_arith(int a, int b) {
+    int x = a * b - a / (b | 1) + a % (b | 1);
+    x += a;
+    x -= b;
+    x *= 3;
+    x /= 2;
+    x %= 1000;
+    x++;
+    --x;
+    return -x;
+}
+
+long checked_offsets(const long *values, int n) {
+    const long *end = values + n;
+    long total = 0;
+
+    for (int i = 1; i <= n; i += 2) {
+        total += *(end - i);
+    }
+    return total + *(end - n);
+}
@@ -0,0 +1,14 @@
+//! signed_overflow_wrapping
+
+int wrapping_add(int a, int b) {
+    return a + b;
+}
+
+int wrapping_increment(int a) {
+    a++;
+    return a;
+}
+
+int wrapping_negate(int a) {
+    return -a;
+}
@@ -0,0 +1,42 @@
+use crate::signed_overflow_checked::{rust_checked_arith, rust_checked_offsets};
+use crate::signed_overflow_wrapping::{
+    rust_wrapping_add, rust_wrapping_increment, rust_wrapping_negate,
+};
+use libc::{c_int, c_long};
+
+#[link(name = "test")]
+extern "C" {
+    fn checked_arith(_: c_int, _: c_int) -> c_int;
+    fn checked_offsets(_: *const c_long, _: c_int) -> c_long;
+}
+
+pub fn test_checked_arith() {
+    for (a, b) in [(0, 0), (7, 3), (-12, 5), (1000, -31), (-4096, -17)] {
+        let rust_val = unsafe { rust_checked_arith(a, b) };
+        let val = unsafe { checked_arith(a, b) };
+
+        assert_eq!(val, rust_val);
+    }
+}
+
+pub fn test_checked_offsets() {
+    let values: [c_long; 7] = [1, 2, 3, 4, 5, 6, 7];
+
+    for n in 1..=7 {
+        let rust_val = unsafe { rust_checked_offsets(values.as_ptr(), n) };
+        let val = unsafe { checked_offsets(values.as_ptr(), n) };
+
+        assert_eq!(val, rust_val);
+    }
+}
+
+pub fn test_wrapping() {
+    // Overflow is undefined behavior in C, so only the translations are run
+    unsafe {
+        assert_eq!(rust_wrapping_add(c_int::MAX, 1), c_int::MIN);
+        assert_eq!(rust_wrapping_add(-2, 1), -1);
+        assert_eq!(rust_wrapping_increment(c_int::MAX), c_int::MIN);
+        assert_eq!(rust_wrapping_negate(c_int::MIN), c_int::MIN);
+        assert_eq!(rust_wrapping_negate(5), -5);
+    }
+}
@@ -0,0 +1,23 @@
+//! bindings_only
+
+#define SCALE 3
+
+struct point {
+    int x, y;
+};
+
+enum direction { NORTH, EAST, SOUTH, WEST };
+
+int counter = 5;
+
+static int helper(int x) { return x * SCALE; }
+
+int scale_point(struct point *p) {
+    p->x = helper(p->x);
+    p->y = helper(p->y);
+    return ++counter;
+}
+
+enum direction turn_right(enum direction d) {
+    return (enum direction)((d + 1) % 4);
+}
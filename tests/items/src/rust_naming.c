@@ -0,0 +1,22 @@
+//! rust_naming_conventions, translate_const_macros
+
+#define MaxRetries 3
+
+typedef struct HTTP_Request {
+    int StatusCode;
+    int retryCount;
+} HTTP_Request;
+
+enum RequestKind { KindGet, KindPost };
+
+int TotalRequests = 0;
+
+static int ClampRetries(int RetryCount) {
+    return RetryCount > MaxRetries ? MaxRetries : RetryCount;
+}
+
+int HandleRequest(HTTP_Request *Req, enum RequestKind Kind) {
+    int Retries = ClampRetries(Req->retryCount);
+    TotalRequests += 1;
+    return Req->StatusCode + Retries + (Kind == KindPost);
+}
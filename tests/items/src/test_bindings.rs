@@ -0,0 +1,13 @@
+use crate::bindings::{counter, point, scale_point, turn_right, EAST, NORTH, SCALE, WEST};
+
+// The bindings call the C definitions in the test library
+pub fn test_bindings() {
+    let mut p = point { x: 1, y: 2 };
+    let count = unsafe { scale_point(&mut p) };
+
+    assert_eq!((p.x, p.y), (SCALE as i32, 2 * SCALE as i32));
+    assert_eq!(count, 6);
+    assert_eq!(unsafe { counter }, 6);
+    assert_eq!(unsafe { turn_right(NORTH) }, EAST);
+    assert_eq!(unsafe { turn_right(WEST) }, NORTH);
+}
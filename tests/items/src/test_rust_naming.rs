@@ -0,0 +1,31 @@
+use crate::rust_naming::{
+    rust_handle_request, HttpRequest, RequestKind, KIND_GET, KIND_POST, MAX_RETRIES,
+    RUST_TOTAL_REQUESTS,
+};
+use std::os::raw::c_int;
+
+#[link(name = "test")]
+extern "C" {
+    fn HandleRequest(req: *mut HttpRequest, kind: RequestKind) -> c_int;
+}
+
+// The renamed translation keeps the symbol name of the C function
+extern "C" {
+    fn rust_HandleRequest(req: *mut HttpRequest, kind: RequestKind) -> c_int;
+}
+
+pub fn test_rust_naming() {
+    let mut req = HttpRequest {
+        status_code: 200,
+        retry_count: 5,
+    };
+
+    let c_result = unsafe { HandleRequest(&mut req, KIND_POST) };
+    let rust_result = unsafe { rust_handle_request(&mut req, KIND_POST) };
+    assert_eq!(c_result, 200 + MAX_RETRIES + 1);
+    assert_eq!(rust_result, c_result);
+
+    let exported_result = unsafe { rust_HandleRequest(&mut req, KIND_GET) };
+    assert_eq!(exported_result, 200 + MAX_RETRIES);
+    assert_eq!(unsafe { RUST_TOTAL_REQUESTS }, 2);
+}
@@ -0,0 +1,25 @@
+use crate::visibility::{rust_add_one, rust_square, rust_square_plus_one};
+
+use libc::c_int;
+
+#[link(name = "test")]
+extern "C" {
+    fn square_plus_one(_: c_int) -> c_int;
+}
+
+pub fn test_visibility() {
+    let src = include_str!("visibility.rs");
+
+    // int add_one(int x)
+    assert!(src.contains("pub(crate) unsafe fn rust_add_one("));
+    assert!(!src.contains("extern \"C\" fn rust_add_one("));
+
+    // int square(int x)
+    assert!(src.contains("#[no_mangle]\npub unsafe extern \"C\" fn rust_square("));
+
+    unsafe {
+        assert_eq!(rust_add_one(1), 2);
+        assert_eq!(rust_square(3), 9);
+        assert_eq!(rust_square_plus_one(3), square_plus_one(3));
+    }
+}
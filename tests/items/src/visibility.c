@@ -0,0 +1,19 @@
+//! infer_visibility
+
+// Only called directly, so it becomes a `pub(crate) unsafe fn`
+int add_one(int x) {
+    return x + 1;
+}
+
+// Its address escapes, so it keeps the C ABI and stays `pub`
+int square(int x) {
+    return x * x;
+}
+
+int apply(int (*f)(int), int x) {
+    return f(x);
+}
+
+int square_plus_one(int x) {
+    return add_one(apply(square, x));
+}
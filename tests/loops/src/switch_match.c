@@ -0,0 +1,66 @@
+//! switch_to_match
+
+enum color { RED, GREEN, BLUE };
+
+int classify(int c) {
+    int kind = 0;
+    switch (c) {
+        case '0':
+        case '1':
+        case '2':
+        case '3':
+            kind = 1;
+            break;
+        case ' ':
+        case '\t':
+            kind = 2;
+            break;
+        default:
+            kind = 3;
+            break;
+        case -1:
+            kind = -1;
+            break;
+    }
+    return kind;
+}
+
+int color_value(enum color c) {
+    switch (c) {
+        case RED:
+            return 10;
+        case GREEN: {
+            int x = 20;
+            for (int i = 0; i < 3; i++) {
+                if (i == 1)
+                    continue;
+                x += i;
+            }
+            return x;
+        }
+        case BLUE:
+            break;
+    }
+    return 0;
+}
+
+int fallthrough(int n) {
+    int total = 0;
+    switch (n) {
+        case 0:
+            total += 1;
+            break;
+        case 1:
+            total += 10;
+            __attribute__((fallthrough));
+        case 2:
+            total += 100;
+            break;
+        case 5:
+            total += 1000;
+            /* fallthrough */
+        default:
+            total += 10000;
+    }
+    return total;
+}
@@ -0,0 +1,42 @@
+use crate::switch_match::{color, rust_classify, rust_color_value, rust_fallthrough};
+use libc::c_int;
+
+#[link(name = "test")]
+extern "C" {
+    fn classify(_: c_int) -> c_int;
+    fn color_value(_: color) -> c_int;
+    fn fallthrough(_: c_int) -> c_int;
+}
+
+pub fn test_classify() {
+    let chars = "0234 \ta".chars().map(|c| c as c_int);
+    for c in [-2, -1, 0].iter().copied().chain(chars) {
+        let val = unsafe { classify(c) };
+        let rust_val = unsafe { rust_classify(c) };
+
+        assert_eq!(val, rust_val);
+    }
+    assert_eq!(unsafe { rust_classify('2' as c_int) }, 1);
+    assert_eq!(unsafe { rust_classify(-1) }, -1);
+}
+
+pub fn test_color_value() {
+    for c in 0..3 {
+        let val = unsafe { color_value(c) };
+        let rust_val = unsafe { rust_color_value(c) };
+
+        assert_eq!(val, rust_val);
+    }
+    assert_eq!(unsafe { rust_color_value(1) }, 22);
+}
+
+pub fn test_fallthrough() {
+    for n in 0..7 {
+        let val = unsafe { fallthrough(n) };
+        let rust_val = unsafe { rust_fallthrough(n) };
+
+        assert_eq!(val, rust_val);
+    }
+    assert_eq!(unsafe { rust_fallthrough(1) }, 110);
+    assert_eq!(unsafe { rust_fallthrough(5) }, 11000);
+}
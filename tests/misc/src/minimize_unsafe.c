@@ -0,0 +1,52 @@
+//! minimize_unsafe
+
+static int calls = 0;
+
+struct slot {
+    int *value;
+};
+
+union bits {
+    unsigned int word;
+    unsigned char bytes[4];
+};
+
+static int square(int x) {
+    return x * x;
+}
+
+int sum_squares(int n) {
+    int squares[8] = {0};
+    int total = 0;
+
+    calls++;
+    for (int i = 0; i < n && i < 8; i++) {
+        squares[i] = square(i);
+    }
+    for (int i = 0; i < 8; i++) {
+        total += squares[i];
+    }
+    return total;
+}
+
+unsigned int low_byte(unsigned int word) {
+    union bits b;
+    b.word = word;
+    return b.bytes[0] == (word & 0xff) ? b.bytes[0] : word & 0xff;
+}
+
+int scale(int *values, int len, int factor) {
+    for (int i = 0; i < len; i++) {
+        values[i] *= factor;
+    }
+    return calls;
+}
+
+int store(struct slot s, int value) {
+    *s.value = value;
+    return value;
+}
+
+int load(unsigned long address) {
+    return *(int *)address;
+}
@@ -0,0 +1,20 @@
+//! translate_printf
+
+#include <stdio.h>
+#include <string.h>
+
+void format_buffer(unsigned buffer_size, char buffer[]) {
+    char *p = buffer;
+
+    sprintf(p, "%5.2f|%-10s|%x", 3.14159, "left", -42);
+    p += strlen(p);
+    snprintf(p, 8, "%08.3e %+d", 12345.678, 7);
+    p += strlen(p);
+    sprintf(p, "%*.*s|%#o|%%|%c", 6, 3, "abcdef", 8, 'z');
+    p += strlen(p);
+    sprintf(p, "%g %g %G|%p", 0.0001, 1e-5, 123456789.0, (void *)0);
+}
+
+void format_bytes(unsigned buffer_size, char buffer[]) {
+    snprintf(buffer, buffer_size, "%c|%s|%3c", 0xff, "caf\xe9", 'x');
+}
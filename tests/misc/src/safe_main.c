@@ -0,0 +1,18 @@
+//! safe_main
+
+#include <stddef.h>
+#include <string.h>
+
+// Returns the total length of the arguments, clearing each one after it is
+// counted so that tests can check that main only sees copies of them
+int main(int argc, char *argv[]) {
+    if (argv[argc] != NULL)
+        return -1;
+
+    int total = 0;
+    for (int i = 0; i < argc; i++) {
+        total += strlen(argv[i]);
+        argv[i][0] = '\0';
+    }
+    return total;
+}
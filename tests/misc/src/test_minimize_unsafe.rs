@@ -0,0 +1,70 @@
+use crate::minimize_unsafe::{
+    rust_load, rust_low_byte, rust_scale, rust_store, rust_sum_squares, slot,
+};
+use libc::{c_int, c_uint, c_ulong};
+
+#[link(name = "test")]
+extern "C" {
+    fn sum_squares(_: c_int) -> c_int;
+    fn low_byte(_: c_uint) -> c_uint;
+    fn scale(_: *mut c_int, _: c_int, _: c_int) -> c_int;
+    fn store(_: slot, _: c_int) -> c_int;
+    fn load(_: c_ulong) -> c_int;
+}
+
+pub fn test_safe_functions() {
+    for n in 0..10 {
+        // Functions without pointer parameters are translated into safe functions
+        let rust_val = rust_sum_squares(n);
+        let val = unsafe { sum_squares(n) };
+
+        assert_eq!(val, rust_val);
+    }
+
+    let rust_val = rust_low_byte(0x1234_5678);
+    let val = unsafe { low_byte(0x1234_5678) };
+
+    assert_eq!(val, rust_val);
+    assert_eq!(rust_val, 0x78);
+}
+
+pub fn test_unsafe_functions() {
+    let mut values = [1, 2, 3, 4];
+    let mut rust_values = values;
+
+    let calls = unsafe { scale(values.as_mut_ptr(), 4, 3) };
+    let rust_calls = unsafe { rust_scale(rust_values.as_mut_ptr(), 4, 3) };
+
+    assert_eq!(values, rust_values);
+    assert_eq!(calls, rust_calls);
+}
+
+pub fn test_pointers_in_params() {
+    let mut value = 0;
+    let mut rust_value = 0;
+
+    let c_slot = slot { value: &mut value };
+    let rust_slot = slot {
+        value: &mut rust_value,
+    };
+
+    let ret = unsafe { store(c_slot, 5) };
+    let rust_ret = unsafe { rust_store(rust_slot, 5) };
+
+    assert_eq!(value, rust_value);
+    assert_eq!(ret, rust_ret);
+
+    let address = &value as *const c_int as c_ulong;
+    let ret = unsafe { load(address) };
+    let rust_ret = unsafe { rust_load(address) };
+
+    assert_eq!(ret, rust_ret);
+    assert_eq!(rust_ret, 5);
+
+    // Functions that take a pointer inside a struct or form one from an
+    // integer are translated into `unsafe` functions
+    let src = include_str!("minimize_unsafe.rs");
+    assert!(src.contains("pub unsafe extern \"C\" fn rust_store("));
+    assert!(src.contains("pub unsafe extern \"C\" fn rust_load("));
+    assert!(src.contains("pub extern \"C\" fn rust_sum_squares("));
+}
@@ -0,0 +1,40 @@
+//! extern_crate_c2rust_printf
+
+use crate::printf::{rust_format_buffer, rust_format_bytes};
+use libc::{c_char, c_uint};
+
+#[link(name = "test")]
+extern "C" {
+    fn format_buffer(_: c_uint, _: *mut c_char);
+    fn format_bytes(_: c_uint, _: *mut c_char);
+}
+
+const BUFFER_SIZE: usize = 128;
+
+pub fn test_format_buffer() {
+    let mut buffer = [0; BUFFER_SIZE];
+    let mut rust_buffer = [0; BUFFER_SIZE];
+    let expected = b" 3.14|left      |ffffffd61.235e+   abc|010|%|z0.0001 1e-05 1.23457E+08|(nil)";
+
+    unsafe {
+        format_buffer(BUFFER_SIZE as c_uint, buffer.as_mut_ptr());
+        rust_format_buffer(BUFFER_SIZE as c_uint, rust_buffer.as_mut_ptr());
+    }
+
+    assert_eq!(buffer, rust_buffer);
+    assert_eq!(&buffer[..expected.len()], expected.map(|b| b as c_char));
+}
+
+pub fn test_format_bytes() {
+    let mut buffer = [0; BUFFER_SIZE];
+    let mut rust_buffer = [0; BUFFER_SIZE];
+    let expected = b"\xff|caf\xe9|  x\0";
+
+    unsafe {
+        format_bytes(BUFFER_SIZE as c_uint, buffer.as_mut_ptr());
+        rust_format_bytes(BUFFER_SIZE as c_uint, rust_buffer.as_mut_ptr());
+    }
+
+    assert_eq!(buffer, rust_buffer);
+    assert_eq!(&buffer[..expected.len()], expected.map(|b| b as c_char));
+}
@@ -0,0 +1,14 @@
+use crate::safe_main::c_main;
+
+use std::ffi::CStr;
+
+pub fn test_c_main() {
+    let prog = CStr::from_bytes_with_nul(b"prog\0").unwrap();
+    let arg = CStr::from_bytes_with_nul(b"\xff\xfe\0").unwrap();
+    let args = [prog, arg];
+
+    assert_eq!(c_main(&args), 6);
+    assert_eq!(c_main(&args), 6);
+    assert_eq!(args[0].to_bytes(), b"prog");
+    assert_eq!(c_main(&[]), 0);
+}
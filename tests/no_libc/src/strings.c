@@ -0,0 +1,12 @@
+//! emit_no_libc
+
+#include <string.h>
+
+unsigned long string_length(const char *s) {
+    return strlen(s);
+}
+
+int compare_strings(const char *a, const char *b) {
+    int cmp = strcmp(a, b);
+    return cmp < 0 ? -1 : cmp > 0;
+}
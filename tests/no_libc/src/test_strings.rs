@@ -0,0 +1,32 @@
+use crate::strings::{rust_compare_strings, rust_string_length};
+
+use core::ffi::{c_char, c_int, c_ulong};
+
+#[link(name = "test")]
+extern "C" {
+    fn string_length(_: *const c_char) -> c_ulong;
+    fn compare_strings(_: *const c_char, _: *const c_char) -> c_int;
+}
+
+const HELLO: &[u8] = b"hello\0";
+const WORLD: &[u8] = b"world\0";
+
+pub fn test_string_length() {
+    let s = HELLO.as_ptr() as *const c_char;
+
+    unsafe {
+        assert_eq!(string_length(s), 5);
+        assert_eq!(rust_string_length(s), 5);
+    }
+}
+
+pub fn test_compare_strings() {
+    let (hello, world) = (HELLO.as_ptr() as *const c_char, WORLD.as_ptr() as *const c_char);
+
+    unsafe {
+        for (a, b) in [(hello, world), (world, hello), (hello, hello)] {
+            assert_eq!(compare_strings(a, b), rust_compare_strings(a, b));
+        }
+        assert_eq!(rust_compare_strings(hello, world), -1);
+    }
+}
@@ -0,0 +1,70 @@
+//! emit_layout_asserts
+
+#include <stddef.h>
+
+struct padded {
+    char c;
+    long l;
+    short s;
+};
+
+struct __attribute__((packed)) packed {
+    char c;
+    int i;
+    short s;
+};
+
+#pragma pack(push, 2)
+struct pragma_packed {
+    char c;
+    long l;
+};
+#pragma pack(pop)
+
+struct __attribute__((aligned(16))) over_aligned {
+    char c;
+    int i;
+};
+
+struct with_bitfields {
+    char c;
+    unsigned a : 3;
+    unsigned b : 7;
+    int i;
+};
+
+struct nested {
+    struct padded p;
+    struct {
+        char x;
+        double y;
+    } anon;
+    union {
+        int i;
+        char bytes[6];
+    };
+};
+
+union mixed {
+    char c;
+    double d;
+    struct padded p;
+};
+
+struct flexible {
+    int len;
+    char data[];
+};
+
+size_t layout_sizes(size_t *sizes) {
+    size_t i = 0;
+    sizes[i++] = sizeof(struct padded);
+    sizes[i++] = sizeof(struct packed);
+    sizes[i++] = sizeof(struct pragma_packed);
+    sizes[i++] = sizeof(struct over_aligned);
+    sizes[i++] = sizeof(struct with_bitfields);
+    sizes[i++] = sizeof(struct nested);
+    sizes[i++] = sizeof(union mixed);
+    sizes[i++] = sizeof(struct flexible);
+    return i;
+}
@@ -0,0 +1,24 @@
+//! feature_const_ptr_offset_from
+
+use crate::layout_asserts::rust_layout_sizes;
+use libc::size_t;
+
+#[link(name = "test")]
+extern "C" {
+    fn layout_sizes(_: *mut size_t) -> size_t;
+}
+
+const BUFFER_SIZE: usize = 8;
+
+// The layout assertions themselves are checked when this crate is built
+pub fn test_layout_sizes() {
+    let mut buffer = [0; BUFFER_SIZE];
+    let mut rust_buffer = [0; BUFFER_SIZE];
+
+    let count = unsafe { layout_sizes(buffer.as_mut_ptr()) };
+    let rust_count = unsafe { rust_layout_sizes(rust_buffer.as_mut_ptr()) };
+
+    assert_eq!(count, BUFFER_SIZE as size_t);
+    assert_eq!(rust_count, count);
+    assert_eq!(buffer, rust_buffer);
+}